@@ -1,3 +1,12 @@
 fn main() {
+    // fingerprint of the bundled catalog; db_init skips the builtin sync while it matches.
+    // blake3 rather than std's hasher, whose output may change between Rust releases
+    println!("cargo:rerun-if-changed=data/catalog.json");
+    let catalog = std::fs::read("data/catalog.json").expect("data/catalog.json is missing");
+    println!(
+        "cargo:rustc-env=BUILTIN_CATALOG_HASH={}",
+        blake3::hash(&catalog).to_hex()
+    );
+
     tauri_build::build()
 }
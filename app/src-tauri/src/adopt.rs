@@ -0,0 +1,218 @@
+use crate::aliases;
+use crate::authors;
+use crate::commands::{self, load_settings, normalize_path_string};
+use crate::db;
+use crate::diskspace;
+use crate::errors::AppError;
+use crate::history;
+use crate::infer;
+use crate::install;
+use crate::layouts::{self, Exclusions};
+use crate::manifest;
+use crate::roots;
+use crate::types::LibraryLayout;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Author given to mods found in the game folder.
+pub const ADOPTED_AUTHOR: &str = "adopted";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdoptSkip {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AdoptReport {
+    pub adopted: Vec<i64>,
+    pub skipped: Vec<AdoptSkip>,
+}
+
+fn copy_dir(src: &Path, dest: &Path) -> SResult<()> {
+    for entry in WalkDir::new(src).min_depth(1) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let rel = entry.path().strip_prefix(src).map_err(|e| e.to_string())?;
+        let target = dest.join(rel);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create '{}': {}", target.display(), e))?;
+        } else if entry.file_type().is_file() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+            }
+            fs::copy(entry.path(), &target)
+                .map_err(|e| format!("Failed to copy '{}': {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn known_targets(conn: &Connection) -> SResult<HashSet<String>> {
+    let mut stmt = conn
+        .prepare("SELECT target_path FROM mods WHERE installed = 1 AND target_path IS NOT NULL")?;
+    let rows = stmt.query_map([], |r| r.get::<_, String>(0))?;
+    let mut out = HashSet::new();
+    for row in rows {
+        out.insert(normalize_path_string(&row?));
+    }
+    Ok(out)
+}
+
+// copies one game folder into the library and registers it as an installed mod; the copy
+// runs without the writer lock and nothing of it is left behind when a step fails
+fn adopt_one(
+    conn: &mut Connection,
+    found: &Path,
+    parent: &Path,
+    lib_root: &str,
+    min_free_mb: u64,
+    now: &str,
+) -> SResult<i64> {
+    let name = found
+        .file_name()
+        .ok_or_else(|| format!("'{}' has no folder name", found.display()))?;
+    let dest = parent.join(name);
+    if dest.exists() {
+        return Err(format!("'{}' already exists in the library", dest.display()).into());
+    }
+    let stat = manifest::folder_stat(found, &manifest::Ignores::default());
+    diskspace::ensure(parent, stat.size_bytes, min_free_mb)?;
+    let staging = parent.join(format!(".{}.adopting", name.to_string_lossy()));
+    let _ = fs::remove_dir_all(&staging);
+    let copied = copy_dir(found, &staging).and_then(|_| {
+        fs::rename(&staging, &dest).map_err(|e| {
+            AppError::from(format!(
+                "Failed to move '{}' to '{}': {}",
+                staging.display(),
+                dest.display(),
+                e
+            ))
+        })
+    });
+    if let Err(e) = copied {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    let registered = register(conn, found, &dest, lib_root, now);
+    if registered.is_err() {
+        let _ = fs::remove_dir_all(&dest);
+    }
+    registered
+}
+
+fn register(
+    conn: &mut Connection,
+    found: &Path,
+    dest: &Path,
+    lib_root: &str,
+    now: &str,
+) -> SResult<i64> {
+    let draft = commands::draft_for_folder(
+        dest,
+        Some(ADOPTED_AUTHOR.to_string()),
+        None,
+        &infer::Catalog::load(conn)?,
+        &aliases::type_aliases(conn)?,
+    );
+    let target_path = normalize_path_string(&found.to_string_lossy());
+    let _writer = db::write_lock();
+    let tx = conn.transaction()?;
+    let mod_id = commands::upsert_draft(&tx, &draft, &draft.folder_path, now)?;
+    tx.execute(
+        r#"
+        UPDATE mods SET installed = 1, installed_at = ?2, target_path = ?3, updated_at = ?2
+        WHERE id = ?1
+        "#,
+        params![mod_id, now, target_path],
+    )?;
+    manifest::ensure(&tx, mod_id, dest, false, now)?;
+    install::record_event(&tx, mod_id, "adopt", Some(&target_path), None, now)?;
+    roots::register(&tx, lib_root)?;
+    roots::assign(&tx)?;
+    authors::sync(&tx, now)?;
+    history::log(
+        &tx,
+        "import",
+        &format!("Adopted '{}' from the game folder", draft.display_name),
+        &[(mod_id, None)],
+        now,
+    )?;
+    tx.commit()?;
+    Ok(mod_id)
+}
+
+/// Finds mods installed by hand in the game mods folder (folders with Spine assets that no
+/// mod is installed at), copies each into `<first library dir>/adopted/` and registers it
+/// as installed there, with inferred character and type. One failing folder does not stop
+/// the rest.
+pub fn adopt_existing(conn: &mut Connection, now: &str) -> SResult<AdoptReport> {
+    let settings = load_settings(conn)?;
+    let game_dir = settings
+        .game_mods_dir
+        .clone()
+        .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+    let Some(lib) = settings.library_dirs.iter().find(|d| d.enabled) else {
+        return Err(AppError::not_configured("library_dirs"));
+    };
+    let parent: PathBuf = match lib.layout {
+        LibraryLayout::Flat => PathBuf::from(&lib.path),
+        _ => Path::new(&lib.path).join(ADOPTED_AUTHOR),
+    };
+
+    let mut walk_errors = Vec::new();
+    let found = layouts::discover(
+        Path::new(&game_dir),
+        LibraryLayout::Auto,
+        &Exclusions::default(),
+        &mut walk_errors,
+    );
+    for err in walk_errors.iter() {
+        warn!("[adopt] {}", err);
+    }
+    let known = known_targets(conn)?;
+
+    let mut report = AdoptReport::default();
+    for entry in found {
+        let path = normalize_path_string(&entry.path.to_string_lossy());
+        // the folder or one of its parents is where a library mod is installed
+        if known
+            .iter()
+            .any(|t| Path::new(&path).starts_with(Path::new(t)))
+        {
+            continue;
+        }
+        match adopt_one(
+            conn,
+            &entry.path,
+            &parent,
+            &lib.path,
+            settings.min_free_space_mb,
+            now,
+        ) {
+            Ok(id) => report.adopted.push(id),
+            Err(reason) => {
+                warn!("[adopt] skipped '{}': {}", path, reason);
+                report.skipped.push(AdoptSkip {
+                    path,
+                    reason: reason.to_string(),
+                });
+            }
+        }
+    }
+    info!(
+        "[adopt] adopted={} skipped={}",
+        report.adopted.len(),
+        report.skipped.len()
+    );
+    Ok(report)
+}
@@ -0,0 +1,400 @@
+use crate::commands::load_settings;
+use crate::errors::AppError;
+use crate::platform;
+use crate::types::{DraftMod, ModType};
+use deunicode::deunicode;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+const DICTIONARY_VERSION: u32 = 1;
+
+pub const DEFAULT_TYPE_ALIASES: &[(&str, &str)] = &[
+    // gameplay "idle" equivalents
+    ("idle", "idle"),
+    ("standing", "idle"),
+    ("stand", "idle"),
+    ("idleanim", "idle"),
+    ("loop", "idle"),
+    ("lobby", "idle"),
+    ("illustration", "idle"),
+    ("illust", "idle"),
+    // cutscenes
+    ("burst", "cutscene"),
+    ("cutscene", "cutscene"),
+    ("cut", "cutscene"),
+    ("cs", "cutscene"),
+    ("skillcut", "cutscene"),
+    ("stkillcut", "cutscene"),
+    ("skullcut", "cutscene"),
+    ("skillcit", "cutscene"),
+    ("specialillustration", "cutscene"),
+    ("specialillust", "cutscene"),
+    // history
+    ("history", "history"),
+    ("story", "history"),
+    ("plot", "history"),
+    // date
+    ("date", "date"),
+    ("dating", "date"),
+    // Minigame content
+    ("minigame", "minigame"),
+    // Different characters
+    ("swap", "swap"),
+    // battle animations
+    ("battle", "battle"),
+    ("combat", "battle"),
+];
+
+pub const DEFAULT_AUTHOR_ALIASES: &[(&str, &str)] = &[
+    ("mrmiagi", "MrMiagi"),
+    // Add more aliases here as they become known
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeAlias {
+    pub alias: String,
+    pub mod_type: ModType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorAlias {
+    pub alias: String,
+    pub author: String,
+}
+
+/// Catalog alias keyed by slug so dictionaries are portable between databases.
+/// `costume_slug` is empty for character aliases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogAlias {
+    pub character_slug: String,
+    #[serde(default)]
+    pub costume_slug: Option<String>,
+    pub alias: String,
+}
+
+/// Shareable alias dictionary file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AliasDictionary {
+    pub version: u32,
+    #[serde(default)]
+    pub type_aliases: Vec<TypeAlias>,
+    #[serde(default)]
+    pub author_aliases: Vec<AuthorAlias>,
+    #[serde(default)]
+    pub catalog_aliases: Vec<CatalogAlias>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AliasImportReport {
+    pub type_aliases: usize,
+    pub author_aliases: usize,
+    pub catalog_aliases: usize,
+    pub skipped: usize,
+}
+
+/// Aliases are matched against folder names reduced to lowercase ASCII alphanumerics.
+pub fn normalize_alias(alias: &str) -> String {
+    deunicode(&alias.to_lowercase())
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// Inserts the built-in aliases without overriding user edits.
+pub fn seed_defaults(conn: &Connection) -> SResult<()> {
+    for (alias, ty) in DEFAULT_TYPE_ALIASES.iter() {
+        conn.execute(
+            "INSERT OR IGNORE INTO type_aliases (alias, mod_type) VALUES (?1, ?2)",
+            params![alias, ty],
+        )?;
+    }
+    for (alias, author) in DEFAULT_AUTHOR_ALIASES.iter() {
+        conn.execute(
+            "INSERT OR IGNORE INTO author_aliases (alias, author) VALUES (?1, ?2)",
+            params![alias, author],
+        )?;
+    }
+    Ok(())
+}
+
+fn pairs(conn: &Connection, sql: &str) -> SResult<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+/// (alias, mod_type) pairs used by type inference.
+pub fn type_aliases(conn: &Connection) -> SResult<Vec<(String, String)>> {
+    pairs(
+        conn,
+        "SELECT alias, mod_type FROM type_aliases ORDER BY alias",
+    )
+}
+
+/// (alias, canonical author) pairs used by author inference.
+pub fn author_aliases(conn: &Connection) -> SResult<Vec<(String, String)>> {
+    pairs(
+        conn,
+        "SELECT alias, author FROM author_aliases ORDER BY alias",
+    )
+}
+
+fn catalog_aliases(conn: &Connection) -> SResult<Vec<CatalogAlias>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT ch.slug, NULL, a.alias_text
+            FROM aliases a JOIN characters ch ON a.entity_type = 'character' AND ch.id = a.entity_id
+            UNION ALL
+            SELECT ch.slug, co.slug, a.alias_text
+            FROM aliases a
+            JOIN costumes co ON a.entity_type = 'costume' AND co.id = a.entity_id
+            JOIN characters ch ON ch.id = co.character_id
+            ORDER BY 1, 2, 3
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(CatalogAlias {
+            character_slug: r.get(0)?,
+            costume_slug: r.get(1)?,
+            alias: r.get(2)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+pub fn export_to_path(conn: &Connection, path: &Path) -> SResult<AliasDictionary> {
+    let dict = AliasDictionary {
+        version: DICTIONARY_VERSION,
+        type_aliases: type_aliases(conn)?
+            .into_iter()
+            .map(|(alias, ty)| TypeAlias {
+                alias,
+                mod_type: ModType::from_str(&ty),
+            })
+            .collect(),
+        author_aliases: author_aliases(conn)?
+            .into_iter()
+            .map(|(alias, author)| AuthorAlias { alias, author })
+            .collect(),
+        catalog_aliases: catalog_aliases(conn)?,
+    };
+    let json = serde_json::to_string_pretty(&dict)?;
+    std::fs::write(path, json)
+        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    Ok(dict)
+}
+
+/// Merges a dictionary into the DB. Type/author aliases from the file win over existing
+/// ones; catalog aliases whose slug is unknown locally are skipped.
+pub fn import_from_path(conn: &mut Connection, path: &Path) -> SResult<AliasImportReport> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let dict: AliasDictionary = serde_json::from_str(&raw)?;
+    if dict.version > DICTIONARY_VERSION {
+        return Err(format!(
+            "Alias dictionary version {} is newer than supported ({})",
+            dict.version, DICTIONARY_VERSION
+        )
+        .into());
+    }
+
+    let tx = conn.transaction()?;
+    let mut report = AliasImportReport {
+        type_aliases: 0,
+        author_aliases: 0,
+        catalog_aliases: 0,
+        skipped: 0,
+    };
+
+    for entry in dict.type_aliases.iter() {
+        let alias = normalize_alias(&entry.alias);
+        if alias.is_empty() {
+            report.skipped += 1;
+            continue;
+        }
+        tx.execute(
+            r#"
+            INSERT INTO type_aliases (alias, mod_type) VALUES (?1, ?2)
+            ON CONFLICT(alias) DO UPDATE SET mod_type = excluded.mod_type
+            "#,
+            params![alias, entry.mod_type.as_str()],
+        )?;
+        report.type_aliases += 1;
+    }
+
+    for entry in dict.author_aliases.iter() {
+        let alias = normalize_alias(&entry.alias);
+        let author = entry.author.trim();
+        if alias.is_empty() || author.is_empty() {
+            report.skipped += 1;
+            continue;
+        }
+        tx.execute(
+            r#"
+            INSERT INTO author_aliases (alias, author) VALUES (?1, ?2)
+            ON CONFLICT(alias) DO UPDATE SET author = excluded.author
+            "#,
+            params![alias, author],
+        )?;
+        report.author_aliases += 1;
+    }
+
+    for entry in dict.catalog_aliases.iter() {
+        let target: Option<(&str, i64)> = match entry.costume_slug.as_deref() {
+            None | Some("") => tx
+                .query_row(
+                    "SELECT id FROM characters WHERE slug = ?1",
+                    params![entry.character_slug],
+                    |r| r.get(0),
+                )
+                .optional()?
+                .map(|id| ("character", id)),
+            Some(costume_slug) => tx
+                .query_row(
+                    r#"
+                    SELECT co.id FROM costumes co JOIN characters ch ON ch.id = co.character_id
+                    WHERE ch.slug = ?1 AND co.slug = ?2
+                    "#,
+                    params![entry.character_slug, costume_slug],
+                    |r| r.get(0),
+                )
+                .optional()?
+                .map(|id| ("costume", id)),
+        };
+        match target {
+            Some((entity_type, id)) => {
+                crate::types::upsert_alias(&tx, entity_type, id, &entry.alias)?;
+                report.catalog_aliases += 1;
+            }
+            None => report.skipped += 1,
+        }
+    }
+
+    tx.commit()?;
+    Ok(report)
+}
+
+/// A folder under a library root whose mods have no known author, see
+/// `author_alias_suggestions`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorAliasSuggestion {
+    pub folder_name: String,
+    /// What an alias for the folder would be stored as.
+    pub alias: String,
+    pub mod_count: usize,
+    pub mod_ids: Vec<i64>,
+}
+
+fn is_unknown(author: &str) -> bool {
+    let author = author.trim();
+    author.is_empty() || author.eq_ignore_ascii_case("unknown")
+}
+
+// an existing alias already gives the folder an author
+fn resolves(known: &[(String, String)], alias: &str) -> bool {
+    known.iter().any(|(a, _)| alias.contains(a.as_str()))
+}
+
+/// Remembers the author the user gave mods whose author folder matched no alias, so the
+/// next scan of the folder resolves it. A folder is learned only when all its `drafts`
+/// agree on one author. Returns the aliases added.
+pub fn learn_author_aliases(conn: &Connection, drafts: &[DraftMod]) -> SResult<Vec<AuthorAlias>> {
+    let known = author_aliases(conn)?;
+    let mut by_folder: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for draft in drafts {
+        let Some(folder_name) = Path::new(&draft.folder_path)
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        let author = draft.author.as_deref().unwrap_or_default().trim();
+        by_folder
+            .entry(folder_name)
+            .or_default()
+            .insert(author.to_string());
+    }
+
+    let mut learned = Vec::new();
+    for (folder_name, authors) in by_folder {
+        let alias = normalize_alias(&folder_name);
+        let mut authors = authors.into_iter();
+        let (Some(author), None) = (authors.next(), authors.next()) else {
+            continue;
+        };
+        if alias.is_empty() || is_unknown(&author) || resolves(&known, &alias) {
+            continue;
+        }
+        let added = conn.execute(
+            "INSERT OR IGNORE INTO author_aliases (alias, author) VALUES (?1, ?2)",
+            params![alias, author],
+        )?;
+        if added > 0 {
+            info!(
+                "[aliases] learned author folder '{}' -> '{}'",
+                folder_name, author
+            );
+            learned.push(AuthorAlias { alias, author });
+        }
+    }
+    Ok(learned)
+}
+
+/// Author folders of mods without a known author that no alias resolves, most mods first.
+/// Mods directly below a library root have no author folder and are left out.
+pub fn author_alias_suggestions(conn: &Connection) -> SResult<Vec<AuthorAliasSuggestion>> {
+    let roots: HashSet<String> = load_settings(conn)?
+        .library_dirs
+        .iter()
+        .map(|d| platform::path_key(&d.path))
+        .collect();
+    let known = author_aliases(conn)?;
+    let mods: Vec<(i64, String, Option<String>)> = {
+        let mut stmt = conn.prepare("SELECT id, folder_path, author FROM mods ORDER BY id")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut by_alias: BTreeMap<String, AuthorAliasSuggestion> = BTreeMap::new();
+    for (id, folder_path, author) in mods {
+        if !is_unknown(author.as_deref().unwrap_or_default()) {
+            continue;
+        }
+        let Some(parent) = Path::new(&folder_path).parent() else {
+            continue;
+        };
+        let Some(folder_name) = parent.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        if roots.contains(&platform::path_key(&parent.to_string_lossy())) {
+            continue;
+        }
+        let alias = normalize_alias(&folder_name);
+        if alias.is_empty() || resolves(&known, &alias) {
+            continue;
+        }
+        let entry = by_alias
+            .entry(alias.clone())
+            .or_insert_with(|| AuthorAliasSuggestion {
+                folder_name,
+                alias,
+                mod_count: 0,
+                mod_ids: Vec::new(),
+            });
+        entry.mod_count += 1;
+        entry.mod_ids.push(id);
+    }
+    let mut out: Vec<_> = by_alias.into_values().collect();
+    out.sort_by(|a, b| {
+        b.mod_count
+            .cmp(&a.mod_count)
+            .then_with(|| a.folder_name.cmp(&b.folder_name))
+    });
+    Ok(out)
+}
@@ -0,0 +1,122 @@
+use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::time::Duration;
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/OscarNE/brown-dust-2-mods-handler/releases/latest";
+// release notes beyond this are cut; the page has the rest
+const MAX_RESPONSE_BYTES: u64 = 1024 * 1024;
+const TIMEOUT: Duration = Duration::from_secs(10);
+// installer assets, in order of preference
+const INSTALLER_EXTENSIONS: &[&str] = &[".msi", "-setup.exe", ".exe"];
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// The latest GitHub release compared with the running version.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppUpdate {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub name: Option<String>,
+    /// Markdown, as written on the release.
+    pub notes: Option<String>,
+    pub published_at: Option<String>,
+    pub release_url: String,
+    /// The installer asset; the release page when there is none.
+    pub download_url: String,
+}
+
+// "v1.2.10" -> [1, 2, 10]; a pre-release suffix ("1.3.0-beta.1") is ignored
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|p| p.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Whether `latest` is a higher version than `current`; "1.2" equals "1.2.0".
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    let (mut a, mut b) = (version_parts(latest), version_parts(current));
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    a > b
+}
+
+fn installer_url(release: &Release) -> Option<String> {
+    INSTALLER_EXTENSIONS.iter().find_map(|ext| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name.to_lowercase().ends_with(ext))
+            .map(|a| a.browser_download_url.clone())
+    })
+}
+
+/// Asks the GitHub releases API for the latest release of the app.
+pub fn check(current_version: &str) -> SResult<AppUpdate> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into();
+    let resp = agent
+        .get(LATEST_RELEASE_URL)
+        .header("Accept", "application/vnd.github+json")
+        // GitHub rejects API requests without one
+        .header(
+            "User-Agent",
+            &format!("brown-dust-2-mods-handler/{}", current_version),
+        )
+        .call()
+        .map_err(|e| AppError::network(LATEST_RELEASE_URL, e))?;
+    let mut raw = String::new();
+    resp.into_body()
+        .into_reader()
+        .take(MAX_RESPONSE_BYTES)
+        .read_to_string(&mut raw)
+        .map_err(|e| AppError::network(LATEST_RELEASE_URL, e))?;
+    let release: Release =
+        serde_json::from_str(&raw).map_err(|e| format!("Unexpected release info: {}", e))?;
+
+    let latest_version = release.tag_name.trim_start_matches(['v', 'V']).to_string();
+    let update_available = is_newer(&latest_version, current_version);
+    info!(
+        "[appupdate] current={} latest={} update_available={}",
+        current_version, latest_version, update_available
+    );
+    Ok(AppUpdate {
+        current_version: current_version.to_string(),
+        update_available,
+        download_url: installer_url(&release).unwrap_or_else(|| release.html_url.clone()),
+        latest_version,
+        name: release.name,
+        notes: release.body,
+        published_at: release.published_at,
+        release_url: release.html_url,
+    })
+}
@@ -0,0 +1,138 @@
+use crate::diskspace;
+use crate::errors::AppError;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.eq_ignore_ascii_case("zip"))
+        .unwrap_or(false)
+}
+
+// "<stem>", then "<stem> (2)", "<stem> (3)"... so an extraction never merges into an
+// existing mod folder
+fn free_folder(parent: &Path, stem: &str) -> PathBuf {
+    let mut candidate = parent.join(stem);
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = parent.join(format!("{} ({})", stem, n));
+        n += 1;
+    }
+    candidate
+}
+
+/// Extracts a zip archive into a new folder under `parent` named after the archive and
+/// returns it. Archives wrapping everything in a single top-level folder are unwrapped.
+/// Nothing is written when the unpacked files would leave less than `min_free_mb` free.
+pub fn extract(archive_path: &Path, parent: &Path, min_free_mb: u64) -> SResult<PathBuf> {
+    if !is_supported(archive_path) {
+        return Err(format!(
+            "Unsupported archive '{}' (only .zip is supported)",
+            archive_path.display()
+        )
+        .into());
+    }
+    let file = File::open(archive_path)
+        .map_err(|e| format!("Failed to open '{}': {}", archive_path.display(), e))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read '{}': {}", archive_path.display(), e))?;
+
+    // entries are only written below their sanitized (enclosed) name
+    let mut entries: Vec<(usize, PathBuf)> = Vec::new();
+    let mut unpacked_size = 0u64;
+    for i in 0..zip.len() {
+        let entry = zip.by_index(i).map_err(|e| e.to_string())?;
+        match entry.enclosed_name() {
+            Some(name) if !entry.is_dir() => {
+                unpacked_size += entry.size();
+                entries.push((i, name));
+            }
+            Some(_) => {}
+            None => warn!(
+                "[archive] skipping unsafe entry '{}' in '{}'",
+                entry.name(),
+                archive_path.display()
+            ),
+        }
+    }
+    if entries.is_empty() {
+        return Err(format!("Archive '{}' is empty", archive_path.display()).into());
+    }
+    diskspace::ensure(parent, unpacked_size, min_free_mb)?;
+
+    let first_component = |p: &PathBuf| p.components().next().map(|c| c.as_os_str().to_owned());
+    let wrapper = first_component(&entries[0].1).filter(|top| {
+        entries
+            .iter()
+            .all(|(_, p)| p.components().count() > 1 && first_component(p).as_ref() == Some(top))
+    });
+
+    let stem = match wrapper.as_ref() {
+        Some(top) => top.to_string_lossy().to_string(),
+        None => archive_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "archive".to_string()),
+    };
+    let dest = free_folder(parent, &stem);
+    fs::create_dir_all(&dest)?;
+
+    for (i, name) in entries {
+        let rel = match wrapper.as_ref() {
+            Some(top) => name.strip_prefix(top).unwrap_or(&name).to_path_buf(),
+            None => name,
+        };
+        let out_path = dest.join(rel);
+        if let Some(dir) = out_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+        let mut out = File::create(&out_path)
+            .map_err(|e| format!("Failed to create '{}': {}", out_path.display(), e))?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to extract '{}': {}", out_path.display(), e))?;
+    }
+    info!(
+        "[archive] extracted '{}' -> '{}'",
+        archive_path.display(),
+        dest.display()
+    );
+    Ok(dest)
+}
+
+/// Writes a zip at `dest` holding every file in `files` (source, name in the archive)
+/// plus the in-memory `extra` entries. Names use '/' separators.
+pub fn pack(dest: &Path, files: &[(PathBuf, String)], extra: &[(String, Vec<u8>)]) -> SResult<()> {
+    if let Some(dir) = dest.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let out =
+        File::create(dest).map_err(|e| format!("Failed to create '{}': {}", dest.display(), e))?;
+    let mut zip = zip::ZipWriter::new(out);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (src, name) in files {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| e.to_string())?;
+        let mut input =
+            File::open(src).map_err(|e| format!("Failed to read '{}': {}", src.display(), e))?;
+        io::copy(&mut input, &mut zip)
+            .map_err(|e| format!("Failed to pack '{}': {}", src.display(), e))?;
+    }
+    for (name, bytes) in extra {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| e.to_string())?;
+        io::Write::write_all(&mut zip, bytes)?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    info!(
+        "[archive] packed {} files into '{}'",
+        files.len() + extra.len(),
+        dest.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use crate::catalog::slugify;
+use crate::errors::AppError;
+use crate::manifest;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// One file of the community mapping: costumes by catalog slug, with the names the game
+/// uses for their assets. A bare array of entries is accepted too.
+#[derive(Debug, Deserialize)]
+struct AssetMapFile {
+    costumes: Vec<AssetMapEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetMapEntry {
+    character: String,
+    costume: String,
+    #[serde(default)]
+    asset_ids: Vec<String>,
+    #[serde(default)]
+    cutscene_codes: Vec<String>,
+}
+
+/// A game identifier of a costume.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostumeAsset {
+    pub kind: String, // 'asset' | 'cutscene'
+    pub asset_id: String,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AssetImportReport {
+    pub costumes: usize,
+    pub asset_ids: usize,
+    /// "character/costume" entries with no catalog costume.
+    pub unmatched: Vec<String>,
+}
+
+/// Another installed mod overriding a game asset the mod also replaces.
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetConflict {
+    pub asset_id: String,
+    pub mod_id: i64,
+    pub display_name: String,
+}
+
+fn parse(raw: &str) -> SResult<Vec<AssetMapEntry>> {
+    let trimmed = raw.trim();
+    if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed)
+            .map_err(|e| format!("Invalid asset mapping: {}", e))
+            .map_err(AppError::from)
+    } else {
+        serde_json::from_str::<AssetMapFile>(trimmed)
+            .map(|f| f.costumes)
+            .map_err(|e| format!("Invalid asset mapping: {}", e))
+            .map_err(AppError::from)
+    }
+}
+
+// catalog costumes by (character slug, costume slug)
+fn costume_ids(conn: &Connection) -> SResult<HashMap<(String, String), i64>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT ch.slug, co.slug, co.id FROM costumes co JOIN characters ch ON ch.id = co.character_id",
+        )?;
+    let rows = stmt.query_map([], |r| Ok(((r.get(0)?, r.get(1)?), r.get(2)?)))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Imports a mapping file. The ids of every costume it names are replaced; costumes it
+/// leaves out keep theirs.
+pub fn import_from_path(conn: &mut Connection, path: &Path) -> SResult<AssetImportReport> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let entries = parse(&raw)?;
+    let source = path.file_name().map(|n| n.to_string_lossy().to_string());
+    let known = costume_ids(conn)?;
+
+    let tx = conn.transaction()?;
+    let mut report = AssetImportReport::default();
+    for entry in entries {
+        // slugs as the catalog writes them; names are accepted for hand-written files
+        let key = (slugify(&entry.character), slugify(&entry.costume));
+        let Some(&costume_id) = known.get(&key) else {
+            report
+                .unmatched
+                .push(format!("{}/{}", entry.character, entry.costume));
+            continue;
+        };
+        tx.execute(
+            "DELETE FROM costume_assets WHERE costume_id = ?1",
+            [costume_id],
+        )?;
+        let ids = entry
+            .asset_ids
+            .iter()
+            .map(|id| ("asset", id))
+            .chain(entry.cutscene_codes.iter().map(|id| ("cutscene", id)));
+        for (kind, asset_id) in ids {
+            let asset_id = asset_id.trim();
+            if asset_id.is_empty() {
+                continue;
+            }
+            report.asset_ids += tx.execute(
+                r#"
+                    INSERT OR IGNORE INTO costume_assets (costume_id, kind, asset_id, source)
+                    VALUES (?1, ?2, ?3, ?4)
+                    "#,
+                params![costume_id, kind, asset_id, source],
+            )?;
+        }
+        report.costumes += 1;
+    }
+    tx.commit()?;
+    if !report.unmatched.is_empty() {
+        warn!(
+            "[assets] {} mapping entries match no catalog costume",
+            report.unmatched.len()
+        );
+    }
+    info!(
+        "[assets] imported costumes={} asset_ids={}",
+        report.costumes, report.asset_ids
+    );
+    Ok(report)
+}
+
+/// The game identifiers of a costume, asset ids first.
+pub fn costume_asset_ids(conn: &Connection, costume_id: i64) -> SResult<Vec<CostumeAsset>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT kind, asset_id, source FROM costume_assets WHERE costume_id = ?1
+            ORDER BY kind = 'cutscene', asset_id
+            "#,
+    )?;
+    let rows = stmt.query_map([costume_id], |r| {
+        Ok(CostumeAsset {
+            kind: r.get(0)?,
+            asset_id: r.get(1)?,
+            source: r.get(2)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// The first asset id of a costume, for the `{asset}` install path placeholder.
+pub fn primary_asset_id(conn: &Connection, costume_id: i64) -> SResult<Option<String>> {
+    Ok(costume_asset_ids(conn, costume_id)?
+        .into_iter()
+        .find(|a| a.kind == "asset")
+        .map(|a| a.asset_id))
+}
+
+fn all_asset_ids(conn: &Connection) -> SResult<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT lower(asset_id) FROM costume_assets")?;
+    let rows = stmt.query_map([], |r| r.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+// the known ids a mod replaces: its costume's, plus any its file names carry (mods
+// tagged with the wrong costume, or none)
+fn asset_ids_of(conn: &Connection, mod_id: i64, known: &[String]) -> SResult<BTreeSet<String>> {
+    let costume_id: Option<i64> =
+        conn.query_row("SELECT costume_id FROM mods WHERE id = ?1", [mod_id], |r| {
+            r.get(0)
+        })?;
+    let mut ids: BTreeSet<String> = match costume_id {
+        Some(costume_id) => costume_asset_ids(conn, costume_id)?
+            .into_iter()
+            .map(|a| a.asset_id.to_lowercase())
+            .collect(),
+        None => BTreeSet::new(),
+    };
+    for file in manifest::load(conn, mod_id)? {
+        let name = file.rel_path.to_lowercase();
+        ids.extend(
+            known
+                .iter()
+                .filter(|id| name.contains(id.as_str()))
+                .cloned(),
+        );
+    }
+    Ok(ids)
+}
+
+/// Installed mods (other than `mod_id`) that replace a game asset `mod_id` replaces too.
+/// Empty when no mapping has been imported.
+pub fn conflicts(conn: &Connection, mod_id: i64) -> SResult<Vec<AssetConflict>> {
+    let known = all_asset_ids(conn)?;
+    if known.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ids = asset_ids_of(conn, mod_id, &known)?;
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let installed: Vec<(i64, String)> = {
+        let mut stmt =
+            conn.prepare("SELECT id, display_name FROM mods WHERE installed = 1 AND id != ?1")?;
+        let rows = stmt.query_map([mod_id], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut out = Vec::new();
+    for (other, display_name) in installed {
+        for asset_id in asset_ids_of(conn, other, &known)?.intersection(&ids) {
+            out.push(AssetConflict {
+                asset_id: asset_id.clone(),
+                mod_id: other,
+                display_name: display_name.clone(),
+            });
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,236 @@
+use crate::errors::AppError;
+use crate::manifest;
+use crate::roots;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tracing::warn;
+
+pub type SResult<T> = Result<T, AppError>;
+
+// same bar as duplicate detection: a moved folder keeps (almost) all of its files
+const HASH_MATCH_THRESHOLD: f32 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// Point the row at the candidate folder and drop the candidate's fresh row.
+    Relink,
+    MarkMissing,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditCandidate {
+    pub mod_id: i64,
+    pub folder_path: String,
+    pub matched_by: String, // "hash" | "name"
+    pub similarity: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub folder_path: String,
+    pub installed: bool,
+    pub candidate: Option<AuditCandidate>,
+    pub suggested_action: AuditAction,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditResolution {
+    pub mod_id: i64,
+    pub action: AuditAction,
+    pub target_mod_id: Option<i64>, // required for relink
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditApplyReport {
+    pub relinked: usize,
+    pub marked_missing: usize,
+    pub deleted: usize,
+}
+
+struct Row {
+    id: i64,
+    display_name: String,
+    folder_path: String,
+    installed: bool,
+}
+
+fn folder_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn hash_sets(conn: &Connection) -> SResult<HashMap<i64, HashSet<String>>> {
+    let mut sets: HashMap<i64, HashSet<String>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT mod_id, hash FROM mod_files")?;
+    let rows = stmt.query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (id, hash) = row?;
+        sets.entry(id).or_default().insert(hash);
+    }
+    Ok(sets)
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let common = a.intersection(b).count();
+    let union = a.len() + b.len() - common;
+    if union == 0 {
+        0.0
+    } else {
+        common as f32 / union as f32
+    }
+}
+
+/// Finds mods whose folder is gone and proposes a resolution for each: relink to a folder
+/// with the same content (or, failing that, the same name), otherwise mark it missing.
+/// Builds manifests for present folders that lack one so content matching can work.
+pub fn run(conn: &Connection, now: &str) -> SResult<Vec<AuditEntry>> {
+    let rows: Vec<Row> = {
+        let mut stmt =
+            conn.prepare("SELECT id, display_name, folder_path, installed FROM mods ORDER BY id")?;
+        let rows = stmt.query_map([], |r| {
+            Ok(Row {
+                id: r.get(0)?,
+                display_name: r.get(1)?,
+                folder_path: r.get(2)?,
+                installed: r.get::<_, i64>(3)? != 0,
+            })
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let (missing, present): (Vec<Row>, Vec<Row>) = rows
+        .into_iter()
+        .partition(|r| !Path::new(&r.folder_path).is_dir());
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    for row in present.iter() {
+        if let Err(err) = manifest::ensure(conn, row.id, Path::new(&row.folder_path), false, now) {
+            warn!("[audit] manifest failed for mod id={} err={}", row.id, err);
+        }
+    }
+    let sets = hash_sets(conn)?;
+    let empty = HashSet::new();
+
+    let mut taken: HashSet<i64> = HashSet::new();
+    let mut out = Vec::new();
+    for row in missing {
+        let own = sets.get(&row.id).unwrap_or(&empty);
+        let by_hash = present
+            .iter()
+            .filter(|p| !taken.contains(&p.id) && !own.is_empty())
+            .map(|p| (p, jaccard(own, sets.get(&p.id).unwrap_or(&empty))))
+            .filter(|(_, score)| *score >= HASH_MATCH_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(p, score)| AuditCandidate {
+                mod_id: p.id,
+                folder_path: p.folder_path.clone(),
+                matched_by: "hash".into(),
+                similarity: Some(score),
+            });
+        let candidate = by_hash.or_else(|| {
+            let name = folder_name(&row.folder_path);
+            present
+                .iter()
+                .find(|p| !taken.contains(&p.id) && folder_name(&p.folder_path) == name)
+                .map(|p| AuditCandidate {
+                    mod_id: p.id,
+                    folder_path: p.folder_path.clone(),
+                    matched_by: "name".into(),
+                    similarity: None,
+                })
+        });
+        if let Some(c) = candidate.as_ref() {
+            taken.insert(c.mod_id);
+        }
+        out.push(AuditEntry {
+            mod_id: row.id,
+            display_name: row.display_name,
+            folder_path: row.folder_path,
+            installed: row.installed,
+            suggested_action: if candidate.is_some() {
+                AuditAction::Relink
+            } else {
+                AuditAction::MarkMissing
+            },
+            candidate,
+        });
+    }
+    Ok(out)
+}
+
+/// Applies the chosen resolutions in one transaction. A relink keeps the old row (and its
+/// curated metadata) and takes over the target row's folder and manifest.
+pub fn apply(
+    conn: &mut Connection,
+    resolutions: &[AuditResolution],
+    now: &str,
+) -> SResult<AuditApplyReport> {
+    let tx = conn.transaction()?;
+    let mut report = AuditApplyReport::default();
+    for res in resolutions {
+        match res.action {
+            AuditAction::Relink => {
+                let target = res
+                    .target_mod_id
+                    .ok_or_else(|| format!("Relink of mod id={} needs a target", res.mod_id))?;
+                if target == res.mod_id {
+                    continue;
+                }
+                let folder_path: String = tx
+                    .query_row(
+                        "SELECT folder_path FROM mods WHERE id = ?1",
+                        [target],
+                        |r| r.get(0),
+                    )
+                    .map_err(|e| format!("Relink target id={}: {}", target, e))?;
+                tx.execute(
+                    r#"
+                    UPDATE mods SET
+                      manifest_built_at = t.manifest_built_at,
+                      size_bytes = t.size_bytes,
+                      file_count = t.file_count,
+                      root_id = NULL,
+                      rel_path = NULL,
+                      missing_at = NULL,
+                      updated_at = ?3
+                    FROM (SELECT manifest_built_at, size_bytes, file_count FROM mods WHERE id = ?2) AS t
+                    WHERE mods.id = ?1
+                    "#,
+                    params![res.mod_id, target, now],
+                )?;
+                tx.execute("DELETE FROM mod_files WHERE mod_id = ?1", [res.mod_id])?;
+                tx.execute(
+                    "UPDATE mod_files SET mod_id = ?1 WHERE mod_id = ?2",
+                    params![res.mod_id, target],
+                )?;
+                tx.execute("DELETE FROM mods WHERE id = ?1", [target])?;
+                tx.execute(
+                    "UPDATE mods SET folder_path = ?2 WHERE id = ?1",
+                    params![res.mod_id, folder_path],
+                )?;
+                report.relinked += 1;
+            }
+            AuditAction::MarkMissing => {
+                report.marked_missing += tx.execute(
+                    "UPDATE mods SET missing_at = ?2 WHERE id = ?1",
+                    params![res.mod_id, now],
+                )?;
+            }
+            AuditAction::Delete => {
+                report.deleted += tx.execute("DELETE FROM mods WHERE id = ?1", [res.mod_id])?;
+            }
+        }
+    }
+    roots::assign(&tx)?;
+    tx.commit()?;
+    Ok(report)
+}
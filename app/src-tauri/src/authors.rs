@@ -0,0 +1,234 @@
+use crate::aliases;
+use crate::errors::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+// placeholder written by author inference; never promoted to an author row
+const UNKNOWN_AUTHOR: &str = "unknown";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Author {
+    pub id: i64,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub homepage_url: Option<String>,
+    pub kofi_url: Option<String>,
+    pub mod_count: usize,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuthorPatch {
+    pub name: Option<String>,
+    pub homepage_url: Option<String>,
+    pub kofi_url: Option<String>,
+}
+
+fn by_key(conn: &Connection, key: &str) -> SResult<Option<(i64, String)>> {
+    conn.query_row("SELECT id, name FROM authors WHERE key = ?1", [key], |r| {
+        Ok((r.get(0)?, r.get(1)?))
+    })
+    .optional()
+    .map_err(AppError::from)
+}
+
+/// Finds the author an author string refers to (case, punctuation and known aliases are
+/// ignored), creating it on first sight. Returns (id, canonical name).
+pub fn resolve(conn: &Connection, name: &str, now: &str) -> SResult<Option<(i64, String)>> {
+    let name = name.trim();
+    let key = aliases::normalize_alias(name);
+    if key.is_empty() || key == UNKNOWN_AUTHOR {
+        return Ok(None);
+    }
+    if let Some(found) = by_key(conn, &key)? {
+        return Ok(Some(found));
+    }
+    let aliased: Option<String> = conn
+        .query_row(
+            "SELECT author FROM author_aliases WHERE alias = ?1",
+            [&key],
+            |r| r.get(0),
+        )
+        .optional()?;
+    let (key, name) = match aliased {
+        Some(canonical) => {
+            let canonical_key = aliases::normalize_alias(&canonical);
+            if let Some(found) = by_key(conn, &canonical_key)? {
+                return Ok(Some(found));
+            }
+            (canonical_key, canonical)
+        }
+        None => (key, name.to_string()),
+    };
+    conn.execute(
+        "INSERT INTO authors (name, key, created_at) VALUES (?1, ?2, ?3)",
+        params![name, key, now],
+    )?;
+    Ok(Some((conn.last_insert_rowid(), name)))
+}
+
+/// Links mods whose author string is not (or no longer) attached to an author row and
+/// rewrites the string to the canonical name.
+pub fn sync(conn: &Connection, now: &str) -> SResult<usize> {
+    let pending: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT m.id, m.author FROM mods m LEFT JOIN authors a ON a.id = m.author_id
+                WHERE m.author IS NOT NULL AND (a.id IS NULL OR a.name != m.author)
+                "#,
+        )?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut linked = 0usize;
+    for (mod_id, author) in pending {
+        match resolve(conn, &author, now)? {
+            Some((author_id, name)) => {
+                linked += conn.execute(
+                    "UPDATE mods SET author_id = ?2, author = ?3 WHERE id = ?1",
+                    params![mod_id, author_id, name],
+                )?;
+            }
+            None => {
+                conn.execute("UPDATE mods SET author_id = NULL WHERE id = ?1", [mod_id])?;
+            }
+        }
+    }
+    if linked > 0 {
+        info!("[authors] linked {} mods", linked);
+    }
+    Ok(linked)
+}
+
+pub fn list(conn: &Connection) -> SResult<Vec<Author>> {
+    let mut authors: Vec<Author> = {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT a.id, a.name, a.homepage_url, a.kofi_url, COUNT(m.id)
+                FROM authors a LEFT JOIN mods m ON m.author_id = a.id
+                GROUP BY a.id ORDER BY LOWER(a.name)
+                "#,
+        )?;
+        let rows = stmt.query_map([], |r| {
+            Ok(Author {
+                id: r.get(0)?,
+                name: r.get(1)?,
+                aliases: Vec::new(),
+                homepage_url: r.get(2)?,
+                kofi_url: r.get(3)?,
+                mod_count: r.get::<_, i64>(4)? as usize,
+            })
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let all_aliases = aliases::author_aliases(conn)?;
+    for author in authors.iter_mut() {
+        let key = aliases::normalize_alias(&author.name);
+        author.aliases = all_aliases
+            .iter()
+            .filter(|(alias, canonical)| {
+                *alias != key && aliases::normalize_alias(canonical) == key
+            })
+            .map(|(alias, _)| alias.clone())
+            .collect();
+    }
+    Ok(authors)
+}
+
+fn name_of(conn: &Connection, id: i64) -> SResult<String> {
+    conn.query_row("SELECT name FROM authors WHERE id = ?1", [id], |r| r.get(0))
+        .optional()?
+        .ok_or_else(|| AppError::not_found("Author", id))
+}
+
+/// Folds `source_ids` into `target_id`: their mods move over, their names become aliases
+/// of the target (so future imports resolve to it) and missing profile links are kept.
+pub fn merge(conn: &Connection, target_id: i64, source_ids: &[i64]) -> SResult<usize> {
+    let target = name_of(conn, target_id)?;
+    let mut moved = 0usize;
+    for source_id in source_ids.iter().filter(|id| **id != target_id) {
+        let source = name_of(conn, *source_id)?;
+        conn.execute(
+            r#"
+            INSERT INTO author_aliases (alias, author) VALUES (?1, ?2)
+            ON CONFLICT(alias) DO UPDATE SET author = excluded.author
+            "#,
+            params![aliases::normalize_alias(&source), target],
+        )?;
+        conn.execute(
+            "UPDATE author_aliases SET author = ?2 WHERE author = ?1",
+            params![source, target],
+        )?;
+        moved += conn.execute(
+            "UPDATE mods SET author_id = ?2, author = ?3 WHERE author_id = ?1",
+            params![source_id, target_id, target],
+        )?;
+        conn.execute(
+            r#"
+            UPDATE authors SET
+              homepage_url = COALESCE(homepage_url, s.homepage_url),
+              kofi_url = COALESCE(kofi_url, s.kofi_url)
+            FROM (SELECT homepage_url, kofi_url FROM authors WHERE id = ?2) AS s
+            WHERE authors.id = ?1
+            "#,
+            params![target_id, source_id],
+        )?;
+        conn.execute("DELETE FROM authors WHERE id = ?1", [source_id])?;
+        info!(
+            "[authors] merged '{}' (id={}) into '{}' (id={})",
+            source, source_id, target, target_id
+        );
+    }
+    Ok(moved)
+}
+
+/// Ids of the mods credited to an author.
+pub fn mod_ids(conn: &Connection, id: i64) -> SResult<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT id FROM mods WHERE author_id = ?1 ORDER BY id")?;
+    let rows = stmt.query_map([id], |r| r.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Renames an author (mods follow) and/or sets its profile links; an empty link clears it.
+pub fn update(conn: &Connection, id: i64, patch: &AuthorPatch) -> SResult<()> {
+    let current = name_of(conn, id)?;
+    if let Some(name) = patch.name.as_deref().map(str::trim) {
+        let key = aliases::normalize_alias(name);
+        if key.is_empty() {
+            return Err(AppError::invalid("Author name is empty"));
+        }
+        if let Some((other, other_name)) = by_key(conn, &key)? {
+            if other != id {
+                return Err(
+                    format!("Author '{}' already exists, merge instead", other_name).into(),
+                );
+            }
+        }
+        conn.execute(
+            "UPDATE authors SET name = ?2, key = ?3 WHERE id = ?1",
+            params![id, name, key],
+        )?;
+        conn.execute(
+            "UPDATE author_aliases SET author = ?2 WHERE author = ?1",
+            params![current, name],
+        )?;
+        conn.execute(
+            "UPDATE mods SET author = ?2 WHERE author_id = ?1",
+            params![id, name],
+        )?;
+    }
+    let link = |v: &Option<String>| v.as_deref().map(str::trim).map(str::to_string);
+    conn.execute(
+        r#"
+        UPDATE authors SET
+          homepage_url = CASE WHEN ?2 IS NULL THEN homepage_url ELSE NULLIF(?2, '') END,
+          kofi_url = CASE WHEN ?3 IS NULL THEN kofi_url ELSE NULLIF(?3, '') END
+        WHERE id = ?1
+        "#,
+        params![id, link(&patch.homepage_url), link(&patch.kofi_url)],
+    )?;
+    Ok(())
+}
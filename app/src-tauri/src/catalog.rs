@@ -1,8 +1,26 @@
 use crate::db;
-use crate::types::{CatalogCharacter, CatalogReport};
-use std::path::Path;
+use crate::errors::AppError;
+use crate::targets::{self, InstallTarget};
+use crate::types::{
+    CatalogCharacter, CatalogDiff, CatalogDiffEntry, CatalogInstallTarget, CatalogMergeReport,
+    CatalogReport, CostumePreview, CostumeReference, ImageFetchReport, OrphanedMod,
+};
+use deunicode::deunicode;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
 
-pub type SResult<T> = Result<T, String>;
+pub type SResult<T> = Result<T, AppError>;
+
+// set by build.rs from data/catalog.json
+const BUILTIN_HASH: &str = env!("BUILTIN_CATALOG_HASH");
+const BUILTIN_HASH_KEY: &str = "builtin_catalog_hash";
+// app version that last applied the bundled catalog: a new version syncs again even with
+// the same catalog, as the sync itself may have changed
+const BUILTIN_VERSION_KEY: &str = "builtin_catalog_app_version";
 
 #[derive(Debug, serde::Deserialize)]
 struct CatalogWrapper {
@@ -12,17 +30,16 @@ struct CatalogWrapper {
 fn load_from_str(data: &str) -> SResult<Vec<CatalogCharacter>> {
     let trimmed = data.trim();
     if trimmed.starts_with('[') {
-        let parsed: Vec<CatalogCharacter> =
-            serde_json::from_str(trimmed).map_err(|e| e.to_string())?;
+        let parsed: Vec<CatalogCharacter> = serde_json::from_str(trimmed)?;
         Ok(parsed)
     } else {
-        let wrapper: CatalogWrapper = serde_json::from_str(trimmed).map_err(|e| e.to_string())?;
+        let wrapper: CatalogWrapper = serde_json::from_str(trimmed)?;
         Ok(wrapper.characters)
     }
 }
 
 fn load_from_path(path: &Path) -> SResult<Vec<CatalogCharacter>> {
-    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let raw = std::fs::read_to_string(path)?;
     load_from_str(&raw)
 }
 
@@ -31,49 +48,898 @@ fn load_builtin() -> SResult<Vec<CatalogCharacter>> {
     load_from_str(raw)
 }
 
-pub fn sync_from_path(path: &Path) -> SResult<CatalogReport> {
+pub fn sync_from_path(conn: &mut Connection, path: &Path) -> SResult<CatalogReport> {
     let items = load_from_path(path)?;
-    sync_records(items)
+    let tx = conn.transaction()?;
+    let report = sync_records(&tx, items)?;
+    tx.commit()?;
+    Ok(report)
 }
 
-pub fn sync_builtin() -> SResult<CatalogReport> {
+pub fn sync_builtin(conn: &mut Connection) -> SResult<CatalogReport> {
     let items = load_builtin()?;
-    sync_records(items)
+    let tx = conn.transaction()?;
+    let report = sync_records(&tx, items)?;
+    for (key, value) in [
+        (BUILTIN_HASH_KEY, BUILTIN_HASH),
+        (BUILTIN_VERSION_KEY, env!("CARGO_PKG_VERSION")),
+    ] {
+        tx.execute(
+            r#"
+            INSERT INTO settings(key, value_json) VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value_json = excluded.value_json
+            "#,
+            params![key, serde_json::to_string(value).unwrap_or_default()],
+        )?;
+    }
+    tx.commit()?;
+    Ok(report)
+}
+
+fn synced_value(conn: &Connection, key: &str) -> SResult<Option<String>> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT value_json FROM settings WHERE key = ?1",
+            [key],
+            |r| r.get(0),
+        )
+        .optional()?;
+    Ok(json.and_then(|json| serde_json::from_str::<String>(&json).ok()))
+}
+
+/// Startup variant of `sync_builtin`: does nothing (returns `None`) when this app version
+/// already synced the bundled catalog of this build.
+pub fn sync_builtin_if_changed(conn: &mut Connection) -> SResult<Option<CatalogReport>> {
+    if synced_value(conn, BUILTIN_HASH_KEY)?.as_deref() == Some(BUILTIN_HASH)
+        && synced_value(conn, BUILTIN_VERSION_KEY)?.as_deref() == Some(env!("CARGO_PKG_VERSION"))
+    {
+        return Ok(None);
+    }
+    sync_builtin(conn).map(Some)
+}
+
+/// Hash of the bundled catalog of this build, as `db_init` compares it.
+pub fn builtin_hash() -> &'static str {
+    BUILTIN_HASH
+}
+
+/// Computes what importing the catalog at `path` would change, without writing anything.
+pub fn diff_from_path(conn: &Connection, path: &Path) -> SResult<CatalogDiff> {
+    let items = load_from_path(path)?;
+    diff_records(conn, items)
+}
+
+/// Applies a previously computed diff. Entries listed as missing are only deleted when
+/// `remove_missing` is set; linked mods keep their row but lose the assignment. Both
+/// happen in one transaction, so a failed removal leaves the catalog as it was.
+pub fn apply_diff(
+    conn: &mut Connection,
+    diff: CatalogDiff,
+    remove_missing: bool,
+) -> SResult<CatalogReport> {
+    let tx = conn.transaction()?;
+    let mut report = sync_records(&tx, diff.source)?;
+    if !remove_missing {
+        tx.commit()?;
+        return Ok(report);
+    }
+
+    for entry in diff.missing_costumes.iter() {
+        let Some(costume_slug) = entry.costume_slug.as_deref() else {
+            continue;
+        };
+        let id: Option<i64> = tx
+            .query_row(
+                r#"
+                SELECT co.id FROM costumes co
+                JOIN characters ch ON ch.id = co.character_id
+                WHERE ch.slug = ?1 AND co.slug = ?2
+                "#,
+                params![entry.character_slug, costume_slug],
+                |r| r.get(0),
+            )
+            .optional()?;
+        if let Some(id) = id {
+            tx.execute(
+                "DELETE FROM aliases WHERE entity_type = 'costume' AND entity_id = ?1",
+                params![id],
+            )?;
+            tx.execute("DELETE FROM costumes WHERE id = ?1", params![id])?;
+            report.removed_costumes += 1;
+        }
+    }
+    for entry in diff.missing_characters.iter() {
+        let id: Option<i64> = tx
+            .query_row(
+                "SELECT id FROM characters WHERE slug = ?1",
+                params![entry.character_slug],
+                |r| r.get(0),
+            )
+            .optional()?;
+        if let Some(id) = id {
+            tx.execute(
+                r#"
+                DELETE FROM aliases
+                WHERE (entity_type = 'character' AND entity_id = ?1)
+                   OR (entity_type = 'costume'
+                       AND entity_id IN (SELECT id FROM costumes WHERE character_id = ?1))
+                "#,
+                params![id],
+            )?;
+            tx.execute("DELETE FROM characters WHERE id = ?1", params![id])?;
+            report.removed_characters += 1;
+        }
+    }
+    tx.commit()?;
+
+    Ok(report)
+}
+
+fn diff_records(conn: &Connection, items: Vec<CatalogCharacter>) -> SResult<CatalogDiff> {
+    // existing catalog keyed by slug: character slug -> (id, display_name)
+    let mut chars: HashMap<String, (i64, String)> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, slug, display_name FROM characters")?;
+    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?;
+    for row in rows {
+        let (id, slug, name): (i64, String, String) = row?;
+        chars.insert(slug, (id, name));
+    }
+
+    // (character slug, costume slug) -> (id, display_name)
+    let mut costumes: HashMap<(String, String), (i64, String)> = HashMap::new();
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT co.id, ch.slug, co.slug, co.display_name
+            FROM costumes co JOIN characters ch ON ch.id = co.character_id
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))?;
+    for row in rows {
+        let (id, ch_slug, slug, name): (i64, String, String, String) = row?;
+        costumes.insert((ch_slug, slug), (id, name));
+    }
+
+    let count_mods = |column: &str, id: i64| -> SResult<usize> {
+        let sql = format!("SELECT COUNT(*) FROM mods WHERE {} = ?1", column);
+        conn.query_row(&sql, params![id], |r| r.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .map_err(AppError::from)
+    };
+
+    let mut diff = CatalogDiff {
+        new_characters: Vec::new(),
+        renamed_characters: Vec::new(),
+        missing_characters: Vec::new(),
+        new_costumes: Vec::new(),
+        renamed_costumes: Vec::new(),
+        missing_costumes: Vec::new(),
+        unchanged: 0,
+        source: Vec::new(),
+    };
+    let mut seen_chars = HashSet::new();
+    let mut seen_costumes = HashSet::new();
+
+    for ch in items.iter() {
+        seen_chars.insert(ch.slug.clone());
+        match chars.get(&ch.slug) {
+            None => diff.new_characters.push(CatalogDiffEntry {
+                character_slug: ch.slug.clone(),
+                costume_slug: None,
+                display_name: ch.display_name.clone(),
+                previous_display_name: None,
+                linked_mods: 0,
+            }),
+            Some((id, name)) if name != &ch.display_name => {
+                diff.renamed_characters.push(CatalogDiffEntry {
+                    character_slug: ch.slug.clone(),
+                    costume_slug: None,
+                    display_name: ch.display_name.clone(),
+                    previous_display_name: Some(name.clone()),
+                    linked_mods: count_mods("character_id", *id)?,
+                })
+            }
+            Some(_) => diff.unchanged += 1,
+        }
+
+        for costume in ch.costumes.iter() {
+            let key = (ch.slug.clone(), costume.slug.clone());
+            seen_costumes.insert(key.clone());
+            match costumes.get(&key) {
+                None => diff.new_costumes.push(CatalogDiffEntry {
+                    character_slug: ch.slug.clone(),
+                    costume_slug: Some(costume.slug.clone()),
+                    display_name: costume.display_name.clone(),
+                    previous_display_name: None,
+                    linked_mods: 0,
+                }),
+                Some((id, name)) if name != &costume.display_name => {
+                    diff.renamed_costumes.push(CatalogDiffEntry {
+                        character_slug: ch.slug.clone(),
+                        costume_slug: Some(costume.slug.clone()),
+                        display_name: costume.display_name.clone(),
+                        previous_display_name: Some(name.clone()),
+                        linked_mods: count_mods("costume_id", *id)?,
+                    })
+                }
+                Some(_) => diff.unchanged += 1,
+            }
+        }
+    }
+
+    for (slug, (id, name)) in chars.iter() {
+        if !seen_chars.contains(slug) {
+            diff.missing_characters.push(CatalogDiffEntry {
+                character_slug: slug.clone(),
+                costume_slug: None,
+                display_name: name.clone(),
+                previous_display_name: None,
+                linked_mods: count_mods("character_id", *id)?,
+            });
+        }
+    }
+    for ((ch_slug, slug), (id, name)) in costumes.iter() {
+        if !seen_costumes.contains(&(ch_slug.clone(), slug.clone())) {
+            diff.missing_costumes.push(CatalogDiffEntry {
+                character_slug: ch_slug.clone(),
+                costume_slug: Some(slug.clone()),
+                display_name: name.clone(),
+                previous_display_name: None,
+                linked_mods: count_mods("costume_id", *id)?,
+            });
+        }
+    }
+    diff.missing_characters
+        .sort_by(|a, b| a.character_slug.cmp(&b.character_slug));
+    diff.missing_costumes.sort_by(|a, b| {
+        (&a.character_slug, &a.costume_slug).cmp(&(&b.character_slug, &b.costume_slug))
+    });
+
+    diff.source = items;
+    Ok(diff)
 }
 
-fn sync_records(items: Vec<CatalogCharacter>) -> SResult<CatalogReport> {
-    let mut conn = db::open_db().map_err(|e| e.to_string())?;
-    conn.pragma_update(None, "foreign_keys", "ON")
-        .map_err(|e| e.to_string())?;
-    let tx = conn.transaction().map_err(|e| e.to_string())?;
+fn store_install_targets(
+    conn: &Connection,
+    character_id: i64,
+    costume_id: Option<i64>,
+    items: &[CatalogInstallTarget],
+) -> SResult<()> {
+    for item in items {
+        targets::set(
+            conn,
+            &InstallTarget {
+                character_id,
+                costume_id,
+                mod_type: item.mod_type,
+                path_pattern: item.path.clone(),
+            },
+        )?;
+    }
+    Ok(())
+}
 
+/// Upserts `items`; the caller owns the transaction.
+fn sync_records(tx: &Transaction, items: Vec<CatalogCharacter>) -> SResult<CatalogReport> {
     let mut chars_count = 0usize;
     let mut costs_count = 0usize;
 
     for ch in items {
-        let ch_id = crate::types::upsert_character(&tx, &ch.slug, &ch.display_name)
-            .map_err(|e| e.to_string())?;
+        let ch_id = crate::types::upsert_character(
+            tx,
+            &ch.slug,
+            &ch.display_name,
+            ch.image_url.as_deref(),
+        )?;
         chars_count += 1;
         for alias in ch.aliases.iter() {
-            crate::types::upsert_alias(&tx, "character", ch_id, alias)
-                .map_err(|e| e.to_string())?;
+            crate::types::upsert_alias(tx, "character", ch_id, alias)?;
+        }
+        for (lang, name) in ch.names.iter() {
+            crate::types::upsert_localized_name(tx, "character", ch_id, lang, name)?;
         }
+        store_install_targets(tx, ch_id, None, &ch.install_targets)?;
         for costume in ch.costumes {
-            let co_id =
-                crate::types::upsert_costume(&tx, ch_id, &costume.slug, &costume.display_name)
-                    .map_err(|e| e.to_string())?;
+            let co_id = crate::types::upsert_costume(
+                tx,
+                ch_id,
+                &costume.slug,
+                &costume.display_name,
+                costume.image_url.as_deref(),
+            )?;
             costs_count += 1;
             for alias in costume.aliases.iter() {
-                crate::types::upsert_alias(&tx, "costume", co_id, alias)
-                    .map_err(|e| e.to_string())?;
+                crate::types::upsert_alias(tx, "costume", co_id, alias)?;
             }
+            for (lang, name) in costume.names.iter() {
+                crate::types::upsert_localized_name(tx, "costume", co_id, lang, name)?;
+            }
+            for url in costume.reference_images.iter() {
+                crate::types::upsert_costume_reference(tx, co_id, url)?;
+            }
+            store_install_targets(tx, ch_id, Some(co_id), &costume.install_targets)?;
         }
     }
 
-    tx.commit().map_err(|e| e.to_string())?;
-
     Ok(CatalogReport {
         characters: chars_count,
         costumes: costs_count,
+        removed_characters: 0,
+        removed_costumes: 0,
+    })
+}
+
+const IMAGE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+// one stalled image host must not hold up the rest of the fetch
+const IMAGE_TIMEOUT: Duration = Duration::from_secs(20);
+
+fn images_dir() -> SResult<PathBuf> {
+    let dir = db::data_dir()
+        .map_err(|e| e.to_string())?
+        .join("catalog_images");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn extension_for(url: &str, content_type: Option<&str>) -> &'static str {
+    match content_type.unwrap_or("") {
+        "image/png" => return "png",
+        "image/jpeg" => return "jpg",
+        "image/webp" => return "webp",
+        "image/gif" => return "gif",
+        _ => {}
+    }
+    let lower = url.to_lowercase();
+    let path = lower.split(['?', '#']).next().unwrap_or("");
+    for ext in ["png", "jpg", "jpeg", "webp", "gif"] {
+        if path.ends_with(&format!(".{}", ext)) {
+            return if ext == "jpeg" { "jpg" } else { ext };
+        }
+    }
+    "png"
+}
+
+fn download_image(agent: &ureq::Agent, url: &str, dest_stem: &Path) -> SResult<PathBuf> {
+    let resp = agent
+        .get(url)
+        .call()
+        .map_err(|e| AppError::network(url, e))?;
+    let content_type = resp
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or("").trim().to_string());
+    let mut bytes = Vec::new();
+    resp.into_body()
+        .into_reader()
+        .take(IMAGE_MAX_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| AppError::Network {
+            message: format!("Failed to read {}: {}", url, e),
+            url: Some(url.to_string()),
+        })?;
+    let dest = dest_stem.with_extension(extension_for(url, content_type.as_deref()));
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&dest, bytes)
+        .map_err(|e| format!("Failed to write '{}': {}", dest.display(), e))?;
+    Ok(dest)
+}
+
+/// "Justia (Swimsuit)" -> "justia-swimsuit", the shape the crawler gives slugs.
+pub fn slugify(name: &str) -> String {
+    deunicode(&name.to_lowercase())
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn character_row(conn: &Connection, id: i64) -> SResult<(String, String)> {
+    conn.query_row(
+        "SELECT slug, display_name FROM characters WHERE id = ?1",
+        params![id],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Character", id))
+}
+
+// (character_id, slug, display_name)
+fn costume_row(conn: &Connection, id: i64) -> SResult<(i64, String, String)> {
+    conn.query_row(
+        "SELECT character_id, slug, display_name FROM costumes WHERE id = ?1",
+        params![id],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Costume", id))
+}
+
+fn mod_ids_where(conn: &Connection, sql: &str, id: i64) -> SResult<Vec<i64>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params![id], |r| r.get(0))?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+// Moves aliases of `entity_type` from `src` to `dst`, keeping the source's name as a new
+// alias so folders inferred from it keep resolving. Returns how many aliases moved.
+fn move_aliases(
+    conn: &Connection,
+    entity_type: &str,
+    src: i64,
+    dst: i64,
+    src_name: &str,
+) -> SResult<usize> {
+    let moved = conn.execute(
+        r#"
+            UPDATE OR IGNORE aliases SET entity_id = ?3
+            WHERE entity_type = ?1 AND entity_id = ?2
+            "#,
+        params![entity_type, src, dst],
+    )?;
+    conn.execute(
+        "DELETE FROM aliases WHERE entity_type = ?1 AND entity_id = ?2",
+        params![entity_type, src],
+    )?;
+    let name = src_name.trim();
+    if !name.is_empty() {
+        conn.execute(
+            r#"
+            INSERT OR IGNORE INTO aliases (entity_type, entity_id, alias_text)
+            VALUES (?1, ?2, ?3)
+            "#,
+            params![entity_type, dst, name],
+        )?;
+    }
+    Ok(moved)
+}
+
+// costume merge without its own transaction, shared with merge_characters
+fn fold_costume(
+    conn: &Connection,
+    src: i64,
+    dst: i64,
+    report: &mut CatalogMergeReport,
+) -> SResult<()> {
+    let (_, _, src_name) = costume_row(conn, src)?;
+    let (dst_character, _, _) = costume_row(conn, dst)?;
+    let ids = mod_ids_where(conn, "SELECT id FROM mods WHERE costume_id = ?1", src)?;
+    conn.execute(
+        "UPDATE mods SET costume_id = ?2, character_id = ?3 WHERE costume_id = ?1",
+        params![src, dst, dst_character],
+    )?;
+    report.aliases_moved += move_aliases(conn, "costume", src, dst, &src_name)?;
+    conn.execute(
+        "UPDATE OR IGNORE costume_names SET costume_id = ?2 WHERE costume_id = ?1",
+        params![src, dst],
+    )?;
+    // install targets keyed like the destination's win; the rest follow the costume
+    conn.execute(
+        r#"
+        UPDATE install_targets SET costume_id = ?2, character_id = ?3
+        WHERE costume_id = ?1
+          AND NOT EXISTS (
+            SELECT 1 FROM install_targets t
+            WHERE t.costume_id = ?2 AND t.mod_type IS install_targets.mod_type
+          )
+        "#,
+        params![src, dst, dst_character],
+    )?;
+    // reference images the destination already has go with the source
+    conn.execute(
+        "UPDATE OR IGNORE costume_references SET costume_id = ?2 WHERE costume_id = ?1",
+        params![src, dst],
+    )?;
+    // imported asset and cutscene ids too; the destination's mapping wins on duplicates
+    conn.execute(
+        "UPDATE OR IGNORE costume_assets SET costume_id = ?2 WHERE costume_id = ?1",
+        params![src, dst],
+    )?;
+    conn.execute("DELETE FROM costumes WHERE id = ?1", params![src])?;
+    report.mod_ids.extend(ids);
+    Ok(())
+}
+
+/// Re-points every mod, alias and install target of costume `src` at costume `dst` (which
+/// may belong to another character) and deletes `src`.
+pub fn merge_costumes(conn: &mut Connection, src: i64, dst: i64) -> SResult<CatalogMergeReport> {
+    if src == dst {
+        return Err(AppError::invalid("Cannot merge a costume into itself"));
+    }
+    let tx = conn.transaction()?;
+    let mut report = CatalogMergeReport::default();
+    fold_costume(&tx, src, dst, &mut report)?;
+    tx.commit()?;
+    info!(
+        "[catalog] merged costume id={} into id={} ({} mod(s))",
+        src,
+        dst,
+        report.mod_ids.len()
+    );
+    Ok(report)
+}
+
+/// Folds character `src` into `dst`: mods (including swap sources), aliases and install
+/// targets are re-pointed, costumes move over (or merge into a same-slug costume of `dst`),
+/// then `src` is deleted. All or nothing.
+pub fn merge_characters(conn: &mut Connection, src: i64, dst: i64) -> SResult<CatalogMergeReport> {
+    if src == dst {
+        return Err(AppError::invalid("Cannot merge a character into itself"));
+    }
+    let tx = conn.transaction()?;
+    let (_, src_name) = character_row(&tx, src)?;
+    character_row(&tx, dst)?;
+    let mut report = CatalogMergeReport::default();
+
+    let costumes: Vec<(i64, String)> = {
+        let mut stmt = tx.prepare("SELECT id, slug FROM costumes WHERE character_id = ?1")?;
+        let rows = stmt.query_map(params![src], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for (costume_id, slug) in costumes {
+        let same_slug: Option<i64> = tx
+            .query_row(
+                "SELECT id FROM costumes WHERE character_id = ?1 AND slug = ?2",
+                params![dst, slug],
+                |r| r.get(0),
+            )
+            .optional()?;
+        match same_slug {
+            Some(target) => {
+                fold_costume(&tx, costume_id, target, &mut report)?;
+                report.costumes_merged += 1;
+            }
+            None => {
+                tx.execute(
+                    "UPDATE costumes SET character_id = ?2 WHERE id = ?1",
+                    params![costume_id, dst],
+                )?;
+                tx.execute(
+                    "UPDATE install_targets SET character_id = ?2 WHERE costume_id = ?1",
+                    params![costume_id, dst],
+                )?;
+                report.costumes_moved += 1;
+            }
+        }
+    }
+
+    let ids = mod_ids_where(
+        &tx,
+        "SELECT id FROM mods WHERE character_id = ?1 OR swap_source_character_id = ?1",
+        src,
+    )?;
+    tx.execute(
+        "UPDATE mods SET character_id = ?2 WHERE character_id = ?1",
+        params![src, dst],
+    )?;
+    tx.execute(
+        "UPDATE mods SET swap_source_character_id = ?2 WHERE swap_source_character_id = ?1",
+        params![src, dst],
+    )?;
+    report.aliases_moved += move_aliases(&tx, "character", src, dst, &src_name)?;
+    tx.execute(
+        "UPDATE OR IGNORE character_names SET character_id = ?2 WHERE character_id = ?1",
+        params![src, dst],
+    )?;
+    tx.execute(
+        r#"
+        UPDATE install_targets SET character_id = ?2
+        WHERE character_id = ?1 AND costume_id IS NULL
+          AND NOT EXISTS (
+            SELECT 1 FROM install_targets t
+            WHERE t.character_id = ?2 AND t.costume_id IS NULL
+              AND t.mod_type IS install_targets.mod_type
+          )
+        "#,
+        params![src, dst],
+    )?;
+    tx.execute("DELETE FROM characters WHERE id = ?1", params![src])?;
+    tx.commit()?;
+
+    report.mod_ids.extend(ids);
+    report.mod_ids.sort_unstable();
+    report.mod_ids.dedup();
+    info!(
+        "[catalog] merged character id={} into id={} ({} mod(s), {} costume(s) moved, {} merged)",
+        src,
+        dst,
+        report.mod_ids.len(),
+        report.costumes_moved,
+        report.costumes_merged
+    );
+    Ok(report)
+}
+
+/// Renames a character and regenerates its slug from the new name. The old name is kept as
+/// an alias. Fails when another character already has that slug (merge them instead).
+pub fn rename_character(conn: &mut Connection, id: i64, display_name: &str) -> SResult<String> {
+    let name = display_name.trim();
+    let slug = slugify(name);
+    if slug.is_empty() {
+        return Err(AppError::invalid("Character name is empty"));
+    }
+    let tx = conn.transaction()?;
+    let (_, old_name) = character_row(&tx, id)?;
+    let taken: Option<i64> = tx
+        .query_row(
+            "SELECT id FROM characters WHERE slug = ?1 AND id != ?2",
+            params![slug, id],
+            |r| r.get(0),
+        )
+        .optional()?;
+    if let Some(other) = taken {
+        return Err(AppError::invalid(format!(
+            "Slug '{}' is already used by character id={}; merge the two instead",
+            slug, other
+        )));
+    }
+    tx.execute(
+        "UPDATE characters SET slug = ?2, display_name = ?3 WHERE id = ?1",
+        params![id, slug, name],
+    )?;
+    if old_name.trim() != name {
+        crate::types::upsert_alias(&tx, "character", id, old_name.trim())?;
+    }
+    tx.commit()?;
+    info!(
+        "[catalog] renamed character id={} to '{}' ({})",
+        id, name, slug
+    );
+    Ok(slug)
+}
+
+/// Same as `rename_character` for a costume; slugs only need to be unique per character.
+pub fn rename_costume(conn: &mut Connection, id: i64, display_name: &str) -> SResult<String> {
+    let name = display_name.trim();
+    let slug = slugify(name);
+    if slug.is_empty() {
+        return Err(AppError::invalid("Costume name is empty"));
+    }
+    let tx = conn.transaction()?;
+    let (character_id, _, old_name) = costume_row(&tx, id)?;
+    let taken: Option<i64> = tx
+        .query_row(
+            "SELECT id FROM costumes WHERE character_id = ?1 AND slug = ?2 AND id != ?3",
+            params![character_id, slug, id],
+            |r| r.get(0),
+        )
+        .optional()?;
+    if let Some(other) = taken {
+        return Err(AppError::invalid(format!(
+            "Slug '{}' is already used by costume id={}; merge the two instead",
+            slug, other
+        )));
+    }
+    tx.execute(
+        "UPDATE costumes SET slug = ?2, display_name = ?3 WHERE id = ?1",
+        params![id, slug, name],
+    )?;
+    if old_name.trim() != name {
+        crate::types::upsert_alias(&tx, "costume", id, old_name.trim())?;
+    }
+    tx.commit()?;
+    info!(
+        "[catalog] renamed costume id={} to '{}' ({})",
+        id, name, slug
+    );
+    Ok(slug)
+}
+
+/// Mods pointing at catalog rows that no longer exist, or at a costume of a different
+/// character. A mod is listed once, with the first problem found.
+pub fn orphaned_mods(conn: &Connection) -> SResult<Vec<OrphanedMod>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT m.id, m.display_name, m.character_id, m.costume_id, m.swap_source_character_id,
+              CASE
+                WHEN m.character_id IS NOT NULL AND ch.id IS NULL THEN 'missing_character'
+                WHEN m.costume_id IS NOT NULL AND co.id IS NULL THEN 'missing_costume'
+                WHEN m.swap_source_character_id IS NOT NULL AND sw.id IS NULL
+                  THEN 'missing_swap_source'
+                ELSE 'costume_mismatch'
+              END
+            FROM mods m
+            LEFT JOIN characters ch ON ch.id = m.character_id
+            LEFT JOIN costumes co ON co.id = m.costume_id
+            LEFT JOIN characters sw ON sw.id = m.swap_source_character_id
+            WHERE (m.character_id IS NOT NULL AND ch.id IS NULL)
+               OR (m.costume_id IS NOT NULL AND co.id IS NULL)
+               OR (m.swap_source_character_id IS NOT NULL AND sw.id IS NULL)
+               OR (co.id IS NOT NULL AND m.character_id IS NOT NULL
+                   AND co.character_id != m.character_id)
+            ORDER BY m.display_name
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(OrphanedMod {
+            id: r.get(0)?,
+            display_name: r.get(1)?,
+            character_id: r.get(2)?,
+            costume_id: r.get(3)?,
+            swap_source_character_id: r.get(4)?,
+            reason: r.get(5)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+// one image to cache: written to `<images dir>/<stem>.<ext>`, recorded in `table.column`
+struct ImageJob {
+    table: &'static str,
+    column: &'static str,
+    id: i64,
+    stem: PathBuf,
+    url: String,
+    current: Option<String>,
+}
+
+/// Downloads character/costume images that have an `image_url` into the app data dir and
+/// records the cached file as `thumbnail_path`. Already cached files are kept unless `force`.
+/// Downloads run without `db::write_lock`, which is only taken to record each cached file,
+/// so call it unlocked.
+pub fn fetch_images(conn: &Connection, force: bool) -> SResult<ImageFetchReport> {
+    let root = images_dir()?;
+    let mut report = ImageFetchReport {
+        downloaded: 0,
+        skipped: 0,
+        errors: 0,
+    };
+
+    let mut jobs: Vec<ImageJob> = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT id, slug, image_url, thumbnail_path FROM characters WHERE image_url IS NOT NULL",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, String>(2)?,
+            r.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, slug, url, thumb) = row?;
+        jobs.push(ImageJob {
+            table: "characters",
+            column: "thumbnail_path",
+            id,
+            stem: Path::new("characters").join(&slug),
+            url,
+            current: thumb,
+        });
+    }
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT co.id, ch.slug, co.slug, co.image_url, co.thumbnail_path
+            FROM costumes co JOIN characters ch ON ch.id = co.character_id
+            WHERE co.image_url IS NOT NULL
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, String>(2)?,
+            r.get::<_, String>(3)?,
+            r.get::<_, Option<String>>(4)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, ch_slug, slug, url, thumb) = row?;
+        jobs.push(ImageJob {
+            table: "costumes",
+            column: "thumbnail_path",
+            id,
+            stem: Path::new("costumes").join(&ch_slug).join(&slug),
+            url,
+            current: thumb,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT r.id, ch.slug, co.slug, r.url, r.path
+            FROM costume_references r
+            JOIN costumes co ON co.id = r.costume_id
+            JOIN characters ch ON ch.id = co.character_id
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, String>(2)?,
+            r.get::<_, String>(3)?,
+            r.get::<_, Option<String>>(4)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, ch_slug, slug, url, path) = row?;
+        jobs.push(ImageJob {
+            table: "costume_references",
+            column: "path",
+            id,
+            stem: Path::new("references")
+                .join(&ch_slug)
+                .join(format!("{}-{}", slug, id)),
+            url,
+            current: path,
+        });
+    }
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(IMAGE_TIMEOUT))
+        .build()
+        .into();
+    for ImageJob {
+        table,
+        column,
+        id,
+        stem,
+        url,
+        current,
+    } in jobs
+    {
+        let cached = current
+            .as_deref()
+            .map(Path::new)
+            .is_some_and(|p| p.exists());
+        if cached && !force {
+            report.skipped += 1;
+            continue;
+        }
+        match download_image(&agent, &url, &root.join(&stem)) {
+            Ok(dest) => {
+                let sql = format!("UPDATE {} SET {} = ?2 WHERE id = ?1", table, column);
+                let _writer = db::write_lock();
+                conn.execute(&sql, params![id, dest.to_string_lossy().to_string()])?;
+                report.downloaded += 1;
+            }
+            Err(err) => {
+                warn!(
+                    "[catalog] image fetch failed for {} id={}: {}",
+                    table, id, err
+                );
+                report.errors += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// A costume with its catalog icon and vanilla reference images, for comparing with mod
+/// previews.
+pub fn costume_preview(conn: &Connection, costume_id: i64) -> SResult<CostumePreview> {
+    let (character_id, display_name, character_name, thumbnail_path) = conn
+        .query_row(
+            r#"
+            SELECT co.character_id, co.display_name, ch.display_name, co.thumbnail_path
+            FROM costumes co JOIN characters ch ON ch.id = co.character_id
+            WHERE co.id = ?1
+            "#,
+            [costume_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found("Costume", costume_id))?;
+    let mut stmt =
+        conn.prepare("SELECT url, path FROM costume_references WHERE costume_id = ?1 ORDER BY id")?;
+    let references = stmt
+        .query_map([costume_id], |r| {
+            Ok(CostumeReference {
+                url: r.get(0)?,
+                path: r.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CostumePreview {
+        costume_id,
+        character_id,
+        display_name,
+        character_name,
+        thumbnail_path,
+        references,
     })
 }
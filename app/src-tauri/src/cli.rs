@@ -0,0 +1,238 @@
+use crate::commands::{self, PreviewKind};
+use crate::db::{self, DbPool};
+use crate::errors::AppError;
+use crate::modinfo::{self, ExportFormat};
+use crate::profiles;
+use crate::types::VideoPreviewOverrides;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+const USAGE: &str = "\
+Usage: bd2-mods-handler [OPTIONS]
+
+Runs the given operations without opening the window, in this order:
+  --import <archive>        import a mod archive into the library (repeatable)
+  --author <name>           author recorded for --import
+  --rescan                  rescan the library folders
+  --apply-profile <name>    install exactly the mods of a profile (name or id)
+  --previews <kind>         generate missing previews: image, video or animated
+  --overwrite               with --previews, regenerate existing ones too
+  --export <id> <dest>      export a mod with its modinfo.json (repeatable)
+  --format <zip|folder>     format for --export (default zip)
+  --db-downgrade <version>  revert the database schema to <version>, e.g. before
+                            going back to an older release (back up first)
+  -h, --help                show this help
+
+Exits with 0 when every operation succeeded, 1 otherwise and 2 on bad arguments.";
+
+#[derive(Debug, Default)]
+struct Options {
+    imports: Vec<PathBuf>,
+    author: Option<String>,
+    rescan: bool,
+    profile: Option<String>,
+    previews: Option<PreviewKind>,
+    overwrite: bool,
+    exports: Vec<(i64, PathBuf)>,
+    format: ExportFormat,
+    downgrade_to: Option<i64>,
+}
+
+// any of these makes the run headless; other arguments (a deep link, none at all)
+// open the window
+fn is_headless_flag(arg: &str) -> bool {
+    matches!(
+        arg,
+        "--import"
+            | "--rescan"
+            | "--apply-profile"
+            | "--previews"
+            | "--export"
+            | "--db-downgrade"
+            | "-h"
+            | "--help"
+    )
+}
+
+fn parse(args: &[String]) -> SResult<Options> {
+    fn value(it: &mut std::slice::Iter<String>, flag: &str) -> SResult<String> {
+        it.next()
+            .cloned()
+            .ok_or_else(|| format!("{} needs a value", flag))
+            .map_err(AppError::from)
+    }
+    let mut opts = Options::default();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--import" => opts.imports.push(PathBuf::from(value(&mut it, arg)?)),
+            "--author" => opts.author = Some(value(&mut it, arg)?),
+            "--rescan" => opts.rescan = true,
+            "--apply-profile" => opts.profile = Some(value(&mut it, arg)?),
+            "--previews" => opts.previews = Some(PreviewKind::parse(&value(&mut it, arg)?)?),
+            "--overwrite" => opts.overwrite = true,
+            "--export" => {
+                let id = value(&mut it, arg)?;
+                let id = id
+                    .parse::<i64>()
+                    .map_err(|_| format!("--export: '{}' is not a mod id", id))?;
+                opts.exports.push((id, PathBuf::from(value(&mut it, arg)?)));
+            }
+            "--format" => {
+                opts.format = match value(&mut it, arg)?.as_str() {
+                    "zip" => ExportFormat::Zip,
+                    "folder" => ExportFormat::Folder,
+                    other => return Err(format!("Unknown export format '{}'", other).into()),
+                }
+            }
+            "--db-downgrade" => {
+                let version = value(&mut it, arg)?;
+                opts.downgrade_to = Some(version.parse::<i64>().map_err(|_| {
+                    format!("--db-downgrade: '{}' is not a schema version", version)
+                })?);
+            }
+            "-h" | "--help" => {}
+            other => return Err(format!("Unknown option '{}'", other).into()),
+        }
+    }
+    Ok(opts)
+}
+
+// one writer at a time, like the GUI's commands
+fn write<T>(pool: &DbPool, f: impl FnOnce(&mut rusqlite::Connection) -> SResult<T>) -> SResult<T> {
+    let mut conn = pool.get()?;
+    let _writer = db::write_lock();
+    f(&mut conn)
+}
+
+fn run_all(pool: &DbPool, opts: &Options) -> bool {
+    let mut ok = true;
+    let mut report = |step: &str, result: SResult<String>| match result {
+        Ok(msg) => println!("{}: {}", step, msg),
+        Err(err) => {
+            eprintln!("{}: error: {}", step, err);
+            ok = false;
+        }
+    };
+
+    for archive in opts.imports.iter() {
+        // extracts unlocked and takes the write lock itself
+        let result = pool.get().map_err(AppError::from).and_then(|mut conn| {
+            commands::import_archive(&mut conn, archive, opts.author.clone(), None)
+        });
+        report(
+            &format!("import {}", archive.display()),
+            result.map(|id| format!("mod id={}", id)),
+        );
+    }
+    if opts.rescan {
+        // walks unlocked and takes the write lock itself
+        let result = pool
+            .get()
+            .map_err(AppError::from)
+            .and_then(|mut conn| commands::rescan(&mut conn, None));
+        report(
+            "rescan",
+            result.map(|s| {
+                format!(
+                    "{} added, {} changed, {} missing, {} errors",
+                    s.added, s.changed, s.missing, s.errors
+                )
+            }),
+        );
+    }
+    if let Some(profile) = opts.profile.as_deref() {
+        let result = write(pool, |conn| {
+            let id = profiles::find(conn, profile)?;
+            let game_dir = commands::load_settings(conn)?
+                .game_mods_dir
+                .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+            profiles::apply(conn, id, Path::new(&game_dir), &commands::now_iso())
+        });
+        report(
+            &format!("apply-profile {}", profile),
+            result.and_then(|r| {
+                let msg = format!(
+                    "{} installed, {} uninstalled",
+                    r.installed.len(),
+                    r.uninstalled.len()
+                );
+                if r.errors.is_empty() {
+                    Ok(msg)
+                } else {
+                    Err(format!("{}; failed: {}", msg, r.errors.join("; ")).into())
+                }
+            }),
+        );
+    }
+    if let Some(kind) = opts.previews {
+        let result = commands::generate_previews(
+            None,
+            pool,
+            kind,
+            None,
+            opts.overwrite,
+            VideoPreviewOverrides::default(),
+        );
+        report(
+            "previews",
+            result.map(|s| {
+                format!(
+                    "{} generated, {} skipped, {} errors",
+                    s.generated, s.skipped, s.errors
+                )
+            }),
+        );
+    }
+    for (id, dest) in opts.exports.iter() {
+        let result = pool
+            .get()
+            .map_err(AppError::from)
+            .and_then(|conn| modinfo::export(&conn, *id, dest, opts.format, None));
+        report(
+            &format!("export {}", id),
+            result.map(|out| out.display().to_string()),
+        );
+    }
+    if let Some(target) = opts.downgrade_to {
+        let result = write(pool, |conn| {
+            db::migrate_down(conn, target).map_err(AppError::from)
+        });
+        report(
+            "db-downgrade",
+            result.map(|r| format!("schema v{} -> v{}", r.from_version, r.to_version)),
+        );
+    }
+    ok
+}
+
+/// Runs headless when the arguments ask for it and returns the exit code, or `None` to
+/// start the window. Nothing shows on a console under a release Windows build unless
+/// output is redirected; the exit code is what launcher scripts should check.
+pub fn run(args: &[String]) -> Option<i32> {
+    if !args.iter().any(|a| is_headless_flag(a)) {
+        return None;
+    }
+    let opts = match parse(args) {
+        Ok(opts) => opts,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            return Some(2);
+        }
+    };
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return Some(0);
+    }
+    info!("[cli] headless run: {:?}", args);
+    let pool = match db::init_pool() {
+        Ok(pool) => pool,
+        Err(err) => {
+            eprintln!("failed to open the mods database: {}", err);
+            return Some(1);
+        }
+    };
+    Some(if run_all(&pool, &opts) { 0 } else { 1 })
+}
@@ -1,77 +1,92 @@
+use crate::adopt::{self, AdoptReport};
+use crate::aliases::{self, AliasDictionary, AliasImportReport, AuthorAliasSuggestion};
+use crate::appupdate::{self, AppUpdate};
+use crate::archive;
+use crate::assets::{self, AssetImportReport, CostumeAsset};
+use crate::audit::{self, AuditApplyReport, AuditEntry, AuditResolution};
+use crate::authors::{self, Author, AuthorPatch};
 use crate::catalog;
-use crate::types::{AppSettings, CatalogReport, DraftMod, ScanSummary};
-use anyhow::Result;
+use crate::deeplink;
+use crate::diskspace::SpaceCheck;
+use crate::downloads::{self, Download};
+use crate::duplicates::{self, DuplicateGroup};
+use crate::errors::AppError;
+use crate::events::{self, ModsChangeKind, ScanKind, ScanProgress};
+use crate::favorites::{self, FavoritesReport};
+use crate::gallery;
+use crate::gamelinks::{self, LinkScanReport, LinkedMod};
+use crate::gameupdate;
+use crate::groups::{self, ModGroup};
+use crate::history::{self, Operation, UndoReport};
+use crate::i18n::{self, Message};
+use crate::imports::{self, ImportOutcome, ImportSession, ResumeAction};
+use crate::infer;
+use crate::install::{self, InstallEvent, InstallPlan, InstallReport, ModFileEntry};
+use crate::journal::{self, JournalAction, JournalEntry, JournalResolution};
+use crate::launch::{self, GameLaunch, GameLaunchReport};
+use crate::layouts;
+use crate::library::{self, LibraryExportSummary, LibraryImportReport, MergeStrategy};
+use crate::links::{self, LinkCheck};
+use crate::logging;
+use crate::manifest::{self, FileSearchReport};
+use crate::modinfo::{self, ExportFormat};
+use crate::opener;
+use crate::organize::{self, OrganizeMove, OrganizePlan, OrganizeReport};
+use crate::platform;
+use crate::previews::{
+    self, PosterFrame, PosterSummary, PreviewError, PreviewMigrationReport, PreviewVerifyReport,
+};
+use crate::profiles::{self, Profile, ProfileApplyReport};
+use crate::removal::{self, PurgeReport};
+use crate::report::{self, ReportFormat};
+use crate::roots::{self, LibraryRoot, RelocateReport};
+use crate::safemode::{self, DisableMethod, SafeModeReport};
+use crate::sanitize::{self, RenamePlan};
+use crate::settings;
+use crate::setup::{self, SetupPlan, SetupStatus};
+use crate::shared::{self, SharedFolderStatus, SharedFolderSync};
+use crate::shell::{self, ImportRequest, ShellIntegrationStatus};
+use crate::stats::{self, UsageStats};
+use crate::targets::{self, InstallTarget};
+use crate::thumbs;
+use crate::types::{
+    AnimatedFormat, AnimatedPreviewSettings, AppSettings, CatalogDiff, CatalogMergeReport,
+    CatalogReport, CostumePreview, DraftMod, ImageFetchReport, OrphanedMod, PreviewStorage,
+    ScanError, ScanSummary, VideoPreviewOverrides,
+};
+use crate::updates::{self, ModUpdate, UpdateCheckReport};
+use crate::validate::{self, Validation};
+use crate::verify::{self, DriftRow, GameAuditEntry, GameAuditStatus};
+use crate::wishlist::{self, WishlistEnqueueReport, WishlistItem};
 use deunicode::deunicode;
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
 use rusqlite::{params, Connection, OptionalExtension};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::{
     fs,
+    io::{BufRead, BufReader, Read},
     path::{Path, PathBuf},
-    process::Command,
-    sync::atomic::{AtomicBool, Ordering},
+    process::{Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, OnceLock,
+    },
     thread,
 };
-use tauri::{Emitter, Window};
+use tauri::{AppHandle, Emitter, State, Window};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
-use crate::db;
-use crate::types::{ModFilter, ModRow, ModType, NewMod};
+use crate::db::{self, DbPool, MaintenanceReport, MigrationReport, ReadPool};
+use crate::types::{
+    LibraryDir, LibraryLayout, ModFilter, ModGroupBy, ModListItem, ModPatch, ModRow, ModSection,
+    ModSort, ModType, ModTypeInfo, NewMod, PurgeScope,
+};
+use tracing::{debug, error, info, warn};
 
 /* ===========Helpers=========== */
 
-// quick tokenizer/slugger
-fn norm_tokens(s: &str) -> Vec<String> {
-    let clean = deunicode(&s.to_lowercase());
-    clean
-        .split(|c: char| !c.is_alphanumeric())
-        .filter(|t| !t.is_empty())
-        .map(|t| t.to_string())
-        .collect()
-}
-
-const DEFAULT_TYPE_ALIASES: &[(&str, &str)] = &[
-    // gameplay "idle" equivalents
-    ("idle", "idle"),
-    ("standing", "idle"),
-    ("stand", "idle"),
-    ("idleanim", "idle"),
-    ("loop", "idle"),
-    ("lobby", "idle"),
-    ("illustration", "idle"),
-    ("illust", "idle"),
-    // cutscenes
-    ("burst", "cutscene"),
-    ("cutscene", "cutscene"),
-    ("cut", "cutscene"),
-    ("cs", "cutscene"),
-    ("skillcut", "cutscene"),
-    ("stkillcut", "cutscene"),
-    ("skullcut", "cutscene"),
-    ("skillcit", "cutscene"),
-    ("specialillustration", "cutscene"),
-    ("specialillust", "cutscene"),
-    // history
-    ("history", "history"),
-    ("story", "history"),
-    ("plot", "history"),
-    // date
-    ("date", "date"),
-    ("dating", "date"),
-    // Minigame content
-    ("minigame", "minigame"),
-    // Different characters
-    ("swap", "swap"),
-];
-
-const DEFAULT_AUTHOR_ALIASES: &[(&str, &str)] = &[
-    ("mrmiagi", "MrMiagi"),
-    // Add more aliases here as they become known
-];
-
 static PREVIEW_CANCEL_IMAGE: AtomicBool = AtomicBool::new(false);
 static PREVIEW_CANCEL_VIDEO: AtomicBool = AtomicBool::new(false);
+static PREVIEW_CANCEL_ANIMATED: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug, Serialize)]
 pub struct AuthorFolder {
@@ -86,7 +101,7 @@ pub struct PreviewGenerationSummary {
     pub errors: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 pub struct PreviewInfo {
     pub has_image: bool,
     pub has_video: bool,
@@ -94,6 +109,18 @@ pub struct PreviewInfo {
     pub video_path: Option<String>,
     pub video_mp4_path: Option<String>,
     pub video_webm_path: Option<String>,
+    /// `preview.webp`, or `preview.gif` when only that exists.
+    pub has_animated: bool,
+    pub animated_path: Option<String>,
+    pub gallery: Vec<String>,
+    /// `image_path` is a frame of the video (`previews_extract_posters`), not a still of
+    /// its own.
+    pub image_is_poster: bool,
+    /// "Character (Costume)", or "Source → Target (Costume)" for swap mods.
+    pub caption: Option<String>,
+    /// The mod is NSFW and `hide_nsfw` is on: only a blurred image (with `blur_previews`)
+    /// or nothing is returned.
+    pub nsfw_gated: bool,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -107,15 +134,28 @@ struct PreviewProgressEvent<'a> {
     errors: usize,
     current_mod: Option<String>,
     message: Option<String>,
+    /// `message` for translation, see `i18n::Message`.
+    message_i18n: Option<Message>,
+}
+
+/// Payload of `preview-log`: one line the generator printed while working on `mod_id`.
+#[derive(Debug, Serialize, Clone)]
+struct PreviewLogEvent<'a> {
+    kind: &'a str,
+    mod_id: i64,
+    /// 'stdout' | 'stderr'
+    stream: &'a str,
+    line: &'a str,
 }
 
 struct PreviewTarget {
     id: i64,
     display_name: String,
     folder_path: String,
+    preview_dir: PathBuf,
 }
 
-fn infer_mod_type(folder_name: &str) -> ModType {
+fn infer_mod_type(folder_name: &str, type_aliases: &[(String, String)]) -> ModType {
     let normalized = deunicode(&folder_name.to_lowercase());
     let sanitized: String = normalized.chars().filter(|c| c.is_alphanumeric()).collect();
     if sanitized.is_empty() {
@@ -123,8 +163,8 @@ fn infer_mod_type(folder_name: &str) -> ModType {
     }
 
     let mut best_match: Option<(&str, &str)> = None;
-    for (alias, ty) in DEFAULT_TYPE_ALIASES.iter().copied() {
-        if sanitized.contains(alias) {
+    for (alias, ty) in type_aliases.iter() {
+        if sanitized.contains(alias.as_str()) {
             match best_match {
                 Some((prev_alias, _)) if prev_alias.len() >= alias.len() => continue,
                 _ => best_match = Some((alias, ty)),
@@ -138,7 +178,23 @@ fn infer_mod_type(folder_name: &str) -> ModType {
     ModType::Other
 }
 
-fn infer_author_name(folder_name: &str) -> String {
+/// Type aliases (see `aliases::type_aliases`) contained in a folder name; the longest wins.
+fn type_alias_hits(folder_name: &str, type_aliases: &[(String, String)]) -> Vec<TypeAliasHit> {
+    let normalized = deunicode(&folder_name.to_lowercase());
+    let sanitized: String = normalized.chars().filter(|c| c.is_alphanumeric()).collect();
+    let mut hits: Vec<TypeAliasHit> = type_aliases
+        .iter()
+        .filter(|(alias, _)| !sanitized.is_empty() && sanitized.contains(alias.as_str()))
+        .map(|(alias, ty)| TypeAliasHit {
+            alias: alias.clone(),
+            mod_type: ModType::from_str(ty),
+        })
+        .collect();
+    hits.sort_by_key(|h| std::cmp::Reverse(h.alias.len()));
+    hits
+}
+
+fn infer_author_name(folder_name: &str, author_aliases: &[(String, String)]) -> String {
     let normalized = deunicode(&folder_name.to_lowercase());
     let sanitized: String = normalized.chars().filter(|c| c.is_alphanumeric()).collect();
     if sanitized.is_empty() {
@@ -146,8 +202,8 @@ fn infer_author_name(folder_name: &str) -> String {
     }
 
     let mut best_match: Option<(&str, &str)> = None;
-    for (alias, canonical) in DEFAULT_AUTHOR_ALIASES.iter().copied() {
-        if sanitized.contains(alias) {
+    for (alias, canonical) in author_aliases.iter() {
+        if sanitized.contains(alias.as_str()) {
             match best_match {
                 Some((prev_alias, _)) if prev_alias.len() >= alias.len() => continue,
                 _ => best_match = Some((alias, canonical)),
@@ -162,131 +218,127 @@ fn infer_author_name(folder_name: &str) -> String {
     }
 }
 
-// temporary in-DB lists (later crawler fills)
-fn db_characters(conn: &rusqlite::Connection) -> Result<Vec<(i64, String, String)>, String> {
-    let mut out = Vec::new();
-    let mut stmt = conn
-        .prepare("SELECT id, slug, display_name FROM characters")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-    while let Some(r) = rows.next().map_err(|e| e.to_string())? {
-        out.push((
-            r.get(0).unwrap_or(0),
-            r.get::<_, String>(1).unwrap_or_default(),
-            r.get::<_, String>(2).unwrap_or_default(),
-        ));
-    }
-    Ok(out)
-}
-fn db_costumes(conn: &rusqlite::Connection) -> Result<Vec<(i64, i64, String, String)>, String> {
-    let mut out = Vec::new();
-    let mut stmt = conn
-        .prepare("SELECT id, character_id, slug, display_name FROM costumes")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-    while let Some(r) = rows.next().map_err(|e| e.to_string())? {
-        out.push((
-            r.get(0).unwrap_or(0),
-            r.get(1).unwrap_or(0),
-            r.get::<_, String>(2).unwrap_or_default(),
-            r.get::<_, String>(3).unwrap_or_default(),
-        ));
-    }
-    Ok(out)
+pub(crate) fn now_iso() -> String {
+    OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".into())
 }
 
-fn infer_character_costume(
-    folder_name: &str,
-    chars: &[(i64, String, String)],
-    costumes: &[(i64, i64, String, String)],
-) -> (Option<i64>, Option<i64>, f32) {
-    let matcher = SkimMatcherV2::default();
-    let tokens = norm_tokens(folder_name).join(" ");
-
-    // Try characters
-    let mut best_char: Option<(i64, f32)> = None;
-    for (id, slug, disp) in chars {
-        let score = matcher.fuzzy_match(&tokens, &slug).unwrap_or(0).max(
-            matcher
-                .fuzzy_match(&tokens, &disp.to_lowercase())
-                .unwrap_or(0),
-        ) as f32;
-        if best_char.map(|(_, s)| score > s).unwrap_or(true) {
-            best_char = Some((*id, score));
-        }
-    }
-
-    // Try costumes constrained by character
-    let mut best_cost: Option<(i64, i64, f32)> = None;
-    if let Some((cid, cscore)) = best_char {
-        for (cost_id, ch_id, slug, disp) in costumes {
-            if *ch_id != cid {
-                continue;
-            }
-            let score = matcher.fuzzy_match(&tokens, &slug).unwrap_or(0).max(
-                matcher
-                    .fuzzy_match(&tokens, &disp.to_lowercase())
-                    .unwrap_or(0),
-            ) as f32;
-            if best_cost.map(|(_, _, s)| score > s).unwrap_or(true) {
-                best_cost = Some((*cost_id, *ch_id, score));
-            }
-        }
-        if let Some((cost_id, _ch, cst_score)) = best_cost {
-            // confidence: simple scaled version 0..1
-            let conf = ((cscore + cst_score) / 200.0).clamp(0.0, 1.0);
-            return (Some(cid), Some(cost_id), conf);
-        } else {
-            let conf = (cscore / 100.0).clamp(0.0, 1.0);
-            return (Some(cid), None, conf);
-        }
-    }
-    (None, None, 0.0)
+/// Runs `f` on the blocking thread pool with a pooled connection so commands never hold
+/// the invoke thread while sqlite or the filesystem are busy.
+pub(crate) async fn with_conn<T, F>(pool: &DbPool, f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, AppError> + Send + 'static,
+{
+    let pool = pool.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = pool.get()?;
+        f(&mut conn)
+    })
+    .await
+    .map_err(|e| AppError::from(e.to_string()))?
 }
 
-fn now_iso() -> String {
-    OffsetDateTime::now_utc()
-        .format(&Rfc3339)
-        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".into())
+/// Like `with_conn`, but on the query-only pool so reads never wait behind writers.
+pub(crate) async fn with_read_conn<T, F>(pool: &ReadPool, f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, AppError> + Send + 'static,
+{
+    with_conn(&pool.0, f).await
 }
 
-fn con() -> Result<Connection> {
-    let c = db::open_db()?;
-    db::migrate(&c)?;
-    println!("[db] connection opened");
-    Ok(c)
+/// Like `with_conn`, but queues behind other writers via `db::write_lock`.
+pub(crate) async fn with_write_conn<T, F>(pool: &DbPool, f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, AppError> + Send + 'static,
+{
+    with_conn(pool, move |conn| {
+        let _writer = db::write_lock();
+        f(conn)
+    })
+    .await
 }
 
-fn normalize_path_string(p: &str) -> String {
+pub(crate) fn normalize_path_string(p: &str) -> String {
     match std::fs::canonicalize(p) {
         Ok(abs) => abs.to_string_lossy().to_string(),
         Err(_) => {
             // Fallback: trim trailing separators and normalize separators
-            let mut s = p.replace('\\', "/");
-            while s.ends_with('/') && s.len() > 1 {
-                s.pop();
-            }
-            s
+            platform::slashed(p)
         }
     }
 }
 
-fn tools_dir() -> Result<PathBuf, String> {
-    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    let app_dir = manifest_dir
-        .parent()
-        .ok_or_else(|| "Failed to resolve tools directory (app dir missing)".to_string())?;
-    let repo_root = app_dir
-        .parent()
-        .ok_or_else(|| "Failed to resolve tools directory (repo root missing)".to_string())?;
-    Ok(repo_root.join("tools"))
+/// Re-points the mod in folder `from` and every mod folder below it to `to` (normalized
+/// paths). Root ids are cleared for `roots::assign`. Returns the number of mods updated.
+pub(crate) fn repoint_folder(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+    now: &str,
+) -> Result<usize, AppError> {
+    // a prefix compare, not LIKE: folder names may hold '%' and '_'
+    let sep = std::path::MAIN_SEPARATOR.to_string();
+    Ok(conn.execute(
+        r#"
+        UPDATE mods
+        SET folder_path = ?2 || substr(folder_path, length(?1) + 1),
+            root_id = NULL, rel_path = NULL, updated_at = ?4
+        WHERE folder_path = ?1 OR substr(folder_path, 1, length(?1) + 1) = ?1 || ?3
+        "#,
+        params![from, to, sep, now],
+    )?)
+}
+
+/// Ids of the mod in folder `folder` and every mod folder below it.
+pub(crate) fn folder_mod_ids(conn: &Connection, folder: &str) -> Result<Vec<i64>, AppError> {
+    let sep = std::path::MAIN_SEPARATOR.to_string();
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id FROM mods
+        WHERE folder_path = ?1 OR substr(folder_path, 1, length(?1) + 1) = ?1 || ?2
+        ORDER BY id
+        "#,
+    )?;
+    let rows = stmt.query_map(params![folder, sep], |r| r.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+// the bundle's resource dir (where tauri.conf.json puts tools/), set at startup
+static RESOURCE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+pub(crate) fn set_resource_dir(dir: PathBuf) {
+    let _ = RESOURCE_DIR.set(dir);
 }
 
-fn locate_preview_tool() -> Result<PathBuf, String> {
-    let dir = tools_dir()?;
-    let entries = fs::read_dir(&dir)
-        .map_err(|e| format!("Failed to read tools dir '{}': {}", dir.display(), e))?;
+/// Folders searched for the bundled preview tool: the app resources, next to the
+/// executable and, in debug builds, the repository's `tools/`.
+fn tools_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(resources) = RESOURCE_DIR.get() {
+        dirs.push(resources.join("tools"));
+    }
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        dirs.push(exe_dir.join("tools"));
+    }
+    if cfg!(debug_assertions) {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        if let Some(repo_root) = manifest_dir.parent().and_then(Path::parent) {
+            dirs.push(repo_root.join("tools"));
+        }
+    }
+    dirs
+}
 
+// the newest create_preview*.jar in `dir`
+fn find_preview_jar(dir: &Path) -> Option<PathBuf> {
+    let entries = fs::read_dir(dir).ok()?;
     let mut candidates: Vec<PathBuf> = entries
         .filter_map(|entry| entry.ok())
         .filter(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
@@ -299,29 +351,75 @@ fn locate_preview_tool() -> Result<PathBuf, String> {
             }
         })
         .collect();
+    candidates.sort();
+    candidates.pop()
+}
 
-    if candidates.is_empty() {
-        return Err("No create_preview*.jar found in tools directory".into());
+/// The preview generator jar: `configured` (the jar itself or a folder holding it), else
+/// the bundled one.
+pub(crate) fn locate_preview_tool(configured: Option<&str>) -> Result<PathBuf, AppError> {
+    if let Some(path) = configured.map(str::trim).filter(|p| !p.is_empty()) {
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Ok(path);
+        }
+        if path.is_dir() {
+            return find_preview_jar(&path).ok_or_else(|| AppError::NotConfigured {
+                message: format!(
+                    "No create_preview*.jar found in '{}' (preview_tool_path)",
+                    path.display()
+                ),
+                setting: Some("preview_tool_path".to_string()),
+            });
+        }
+        return Err(AppError::path_missing("Preview tool", path));
     }
+    let dirs = tools_dirs();
+    dirs.iter()
+        .find_map(|dir| find_preview_jar(dir))
+        .ok_or_else(|| {
+            let searched: Vec<String> = dirs.iter().map(|d| d.display().to_string()).collect();
+            AppError::NotConfigured {
+                message: format!(
+                    "No create_preview*.jar found (searched {}); set preview_tool_path in settings",
+                    searched.join(", ")
+                ),
+                setting: Some("preview_tool_path".to_string()),
+            }
+        })
+}
 
-    candidates.sort();
-    Ok(candidates
+/// ffmpeg for poster frames: `configured`, else one in the bundled tools, else whatever
+/// `ffmpeg` is on PATH.
+pub(crate) fn locate_ffmpeg(configured: Option<&str>) -> PathBuf {
+    if let Some(path) = configured.map(str::trim).filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    let exe = if cfg!(windows) {
+        "ffmpeg.exe"
+    } else {
+        "ffmpeg"
+    };
+    tools_dirs()
         .into_iter()
-        .last()
-        .expect("candidates is not empty so last() is Some"))
+        .map(|dir| dir.join(exe))
+        .find(|p| p.is_file())
+        .unwrap_or_else(|| PathBuf::from("ffmpeg"))
 }
 
 #[derive(Clone, Copy, Debug)]
-enum PreviewKind {
+pub(crate) enum PreviewKind {
     Image,
     Video,
+    Animated,
 }
 
 impl PreviewKind {
-    fn target_name(self) -> &'static str {
+    fn target_name(self, animated: &AnimatedPreviewSettings) -> &'static str {
         match self {
             PreviewKind::Image => "preview.png",
             PreviewKind::Video => "preview.mp4",
+            PreviewKind::Animated => animated.format.file_name(),
         }
     }
 
@@ -329,6 +427,19 @@ impl PreviewKind {
         match self {
             PreviewKind::Image => "image",
             PreviewKind::Video => "video",
+            PreviewKind::Animated => "animated",
+        }
+    }
+
+    pub(crate) fn parse(kind: &str) -> Result<Self, AppError> {
+        match kind {
+            "image" => Ok(PreviewKind::Image),
+            "video" => Ok(PreviewKind::Video),
+            "animated" => Ok(PreviewKind::Animated),
+            other => Err(AppError::invalid(format!(
+                "Unknown preview kind '{}'.",
+                other
+            ))),
         }
     }
 }
@@ -337,11 +448,12 @@ fn cancel_flag_for_kind(kind: PreviewKind) -> &'static AtomicBool {
     match kind {
         PreviewKind::Image => &PREVIEW_CANCEL_IMAGE,
         PreviewKind::Video => &PREVIEW_CANCEL_VIDEO,
+        PreviewKind::Animated => &PREVIEW_CANCEL_ANIMATED,
     }
 }
 
 fn emit_preview_progress(
-    window: &Window,
+    window: Option<&Window>,
     kind: PreviewKind,
     status: &'static str,
     total: usize,
@@ -350,7 +462,7 @@ fn emit_preview_progress(
     skipped: usize,
     errors: usize,
     current_mod: Option<String>,
-    message: Option<String>,
+    message: Option<Message>,
 ) {
     let payload = PreviewProgressEvent {
         kind: kind.label(),
@@ -361,37 +473,186 @@ fn emit_preview_progress(
         skipped,
         errors,
         current_mod,
-        message,
+        message: message.as_ref().map(Message::to_string),
+        message_i18n: message,
+    };
+    let Some(window) = window else {
+        return;
     };
     if let Err(err) = window.emit("preview-progress", payload) {
-        println!(
+        warn!(
             "[preview] failed to emit progress event for {:?}: {}",
             kind, err
         );
     }
 }
 
-fn collect_preview_targets(conn: &Connection) -> Result<Vec<PreviewTarget>, String> {
-    let mut stmt = conn
-        .prepare("SELECT id, display_name, folder_path FROM mods ORDER BY display_name ASC")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+struct GeneratorOutput {
+    status: ExitStatus,
+    stdout: String,
+    stderr: String,
+    /// Both streams interleaved as they arrived.
+    combined: String,
+}
+
+// one reader thread per stream so a full stderr pipe cannot block stdout (and vice versa)
+fn forward_lines<R: Read + Send + 'static>(
+    reader: R,
+    stream: &'static str,
+    tx: mpsc::Sender<(&'static str, String)>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let line = String::from_utf8_lossy(&buf).trim_end().to_string();
+                    if tx.send((stream, line)).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Runs the generator with piped output, sending every line as a `preview-log` event
+/// while it runs.
+fn run_generator(
+    cmd: &mut Command,
+    window: Option<&Window>,
+    kind: PreviewKind,
+    mod_id: i64,
+) -> std::io::Result<GeneratorOutput> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let (tx, rx) = mpsc::channel();
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward_lines(stdout, "stdout", tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward_lines(stderr, "stderr", tx.clone()));
+    }
+    drop(tx);
+
+    let mut out = GeneratorOutput {
+        status: ExitStatus::default(),
+        stdout: String::new(),
+        stderr: String::new(),
+        combined: String::new(),
+    };
+    // ends once both pipes are closed
+    for (stream, line) in rx {
+        if let Some(window) = window {
+            let payload = PreviewLogEvent {
+                kind: kind.label(),
+                mod_id,
+                stream,
+                line: &line,
+            };
+            if let Err(err) = window.emit("preview-log", payload) {
+                warn!("[preview] failed to emit log event: {}", err);
+            }
+        }
+        let target = if stream == "stderr" {
+            &mut out.stderr
+        } else {
+            &mut out.stdout
+        };
+        target.push_str(&line);
+        target.push('\n');
+        out.combined.push_str(&line);
+        out.combined.push('\n');
+    }
+    for reader in readers {
+        let _ = reader.join();
+    }
+    out.status = child.wait()?;
+    Ok(out)
+}
+
+// keeps the failure output for `preview_errors` (or drops it after a success) and
+// refreshes the mod's cached preview flags
+fn store_preview_result(
+    pool: &DbPool,
+    kind: PreviewKind,
+    elapsed: std::time::Duration,
+    result: Result<i64, PreviewError>,
+) {
+    let stored = pool.get().map_err(AppError::from).and_then(|conn| {
+        let _guard = db::write_lock();
+        let now = now_iso();
+        let mod_id = match &result {
+            Ok(mod_id) => {
+                previews::clear_error(&conn, *mod_id, kind.label())?;
+                previews::record_source(&conn, *mod_id, kind.label(), &now)?;
+                if matches!(kind, PreviewKind::Image) {
+                    previews::clear_poster(&conn, *mod_id)?;
+                }
+                stats::record_preview(&conn, kind.label(), elapsed.as_millis() as u64, &now);
+                *mod_id
+            }
+            Err(error) => {
+                previews::record_error(&conn, error)?;
+                error.mod_id
+            }
+        };
+        previews::refresh_flags(&conn, Some(&[mod_id])).map(|_| ())
+    });
+    if let Err(err) = stored {
+        warn!("[preview] failed to store the generator result: {}", err);
+    }
+}
+
+// every mod, or only `ids` when given
+fn collect_preview_targets(
+    conn: &Connection,
+    ids: Option<&[i64]>,
+) -> Result<Vec<PreviewTarget>, AppError> {
+    let storage = load_settings(conn)?.preview_storage;
+    let mut stmt =
+        conn.prepare("SELECT id, display_name, folder_path FROM mods ORDER BY display_name ASC")?;
+    let mut rows = stmt.query([])?;
     let mut out = Vec::new();
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        if ids.is_some_and(|ids| !ids.contains(&id)) {
+            continue;
+        }
+        let folder_path: String = row.get(2)?;
         out.push(PreviewTarget {
-            id: row.get(0).map_err(|e| e.to_string())?,
-            display_name: row.get(1).map_err(|e| e.to_string())?,
-            folder_path: row.get(2).map_err(|e| e.to_string())?,
+            id,
+            display_name: row.get(1)?,
+            preview_dir: previews::dir_for(storage, id, &folder_path)?,
+            folder_path,
         });
     }
     Ok(out)
 }
 
-fn generate_previews(
-    window: &Window,
+/// Runs the generator for every mod (or only `ids`). Existing previews are skipped
+/// unless `overwrite` is set, in which case they are regenerated in place. Progress goes
+/// to `window` when there is one (not in headless runs).
+pub(crate) fn generate_previews(
+    window: Option<&Window>,
+    pool: &DbPool,
     kind: PreviewKind,
-) -> Result<PreviewGenerationSummary, String> {
-    let jar = match locate_preview_tool() {
+    ids: Option<&[i64]>,
+    overwrite: bool,
+    video: VideoPreviewOverrides,
+) -> Result<PreviewGenerationSummary, AppError> {
+    let check = {
+        let conn = pool.get()?;
+        setup::preview_tool_check(&load_settings(&conn)?)
+    };
+    let jar = match check.jar() {
         Ok(jar) => jar,
         Err(err) => {
             emit_preview_progress(
@@ -404,16 +665,26 @@ fn generate_previews(
                 0,
                 0,
                 None,
-                Some(err.clone()),
+                Some(err.to_message()),
             );
             return Err(err);
         }
     };
 
-    println!("[preview] using generator jar '{}'", jar.to_string_lossy());
+    info!("[preview] using generator jar '{}'", jar.to_string_lossy());
 
-    let conn = con().map_err(|e| e.to_string())?;
-    let mods = collect_preview_targets(&conn)?;
+    let conn = pool.get()?;
+    let mods = collect_preview_targets(&conn, ids)?;
+    let settings = load_settings(&conn)?;
+    let animated = settings.preview_animated;
+    let video = settings.preview_video.with(video);
+    let java = PathBuf::from(&check.java_path);
+    // stills taken from the video are replaced like missing ones
+    let posters = match kind {
+        PreviewKind::Image => previews::poster_ids(&conn)?,
+        _ => Default::default(),
+    };
+    drop(conn);
     let total = mods.len();
 
     let cancel_flag = cancel_flag_for_kind(kind);
@@ -437,12 +708,12 @@ fn generate_previews(
         None,
         None,
     );
-    println!("[RUST] EMIT: starting generation for {:?}", kind);
+    debug!("[previews] starting generation for {:?}", kind);
 
     for (index, target_mod) in mods.iter().enumerate() {
         if cancel_flag.load(Ordering::SeqCst) {
             cancelled = true;
-            println!(
+            info!(
                 "[preview] cancellation requested for {:?}, stopping after {} processed",
                 kind, processed_count
             );
@@ -451,10 +722,10 @@ fn generate_previews(
 
         let folder = Path::new(&target_mod.folder_path);
         let path_display = target_mod.display_name.clone();
-        let target = folder.join(kind.target_name());
+        let target = target_mod.preview_dir.join(kind.target_name(&animated));
         let processed = index + 1;
         if !folder.exists() {
-            println!(
+            info!(
                 "[preview] skipping mod id={} display='{}' because folder is missing",
                 target_mod.id, target_mod.display_name
             );
@@ -470,16 +741,16 @@ fn generate_previews(
                 summary.skipped,
                 summary.errors,
                 Some(path_display),
-                Some("Folder missing on disk".to_string()),
+                Some(Message::new("preview.folder_missing")),
             );
-            println!(
-                "[RUST] EMIT: progress {}/{} (missing folder)",
+            debug!(
+                "[previews] progress {}/{} (missing folder)",
                 processed, total
             );
             continue;
         }
 
-        if target.exists() {
+        if target.exists() && !overwrite && !posters.contains(&target_mod.id) {
             summary.skipped += 1;
             processed_count = processed;
             emit_preview_progress(
@@ -492,19 +763,26 @@ fn generate_previews(
                 summary.skipped,
                 summary.errors,
                 Some(path_display),
-                Some("Preview already exists".to_string()),
+                Some(Message::new("preview.exists")),
             );
-            println!(
-                "[RUST] EMIT: progress {}/{} (already exists)",
+            debug!(
+                "[previews] progress {}/{} (already exists)",
                 processed, total
             );
             continue;
         }
 
-        println!(
+        info!(
             "[preview] generating {:?} for mod id={} display='{}'",
             kind, target_mod.id, target_mod.display_name
         );
+        if let Err(err) = fs::create_dir_all(&target_mod.preview_dir) {
+            warn!(
+                "[preview] cannot create '{}': {}",
+                target_mod.preview_dir.display(),
+                err
+            );
+        }
 
         emit_preview_progress(
             window,
@@ -516,14 +794,14 @@ fn generate_previews(
             summary.skipped,
             summary.errors,
             Some(target_mod.display_name.clone()),
-            Some("Starting generator".to_string()),
+            Some(Message::new("preview.starting")),
         );
-        println!(
-            "[RUST] EMIT: progress {}/{} (starting generator)",
+        debug!(
+            "[previews] progress {}/{} (starting generator)",
             processed, total
         );
 
-        let mut cmd = Command::new("java");
+        let mut cmd = Command::new(&java);
         cmd.arg("--enable-native-access=ALL-UNNAMED")
             .arg("-jar")
             .arg(&jar)
@@ -536,9 +814,22 @@ fn generate_previews(
             }
             PreviewKind::Video => {
                 cmd.arg("--video-seconds")
-                    .arg("5")
+                    .arg(video.seconds.clamp(1, 60).to_string())
+                    .arg("--fps")
+                    .arg(video.fps.clamp(1, 60).to_string())
+                    .arg("--video-loop")
+                    .arg(video.loop_mode.as_str());
+                if let Some(px) = video.max_size {
+                    cmd.arg("--max-size").arg(px.max(64).to_string());
+                }
+                cmd.arg("--video-output").arg(target.as_os_str());
+            }
+            // the generator picks the encoder from the output extension
+            PreviewKind::Animated => {
+                cmd.arg("--video-seconds")
+                    .arg(animated.seconds.max(1).to_string())
                     .arg("--fps")
-                    .arg("30")
+                    .arg(animated.fps.clamp(1, 60).to_string())
                     .arg("--video-loop")
                     .arg("auto")
                     .arg("--video-output")
@@ -550,10 +841,11 @@ fn generate_previews(
             cmd.current_dir(parent);
         }
 
-        let output = match cmd.output() {
+        let started = std::time::Instant::now();
+        let output = match run_generator(&mut cmd, window, kind, target_mod.id) {
             Ok(output) => output,
             Err(err) => {
-                let msg = format!("Failed to run java command: {}", err);
+                let msg = Message::new("preview.java_failed").with("error", err);
                 emit_preview_progress(
                     window,
                     kind,
@@ -567,44 +859,46 @@ fn generate_previews(
                     Some(msg.clone()),
                 );
                 cancel_flag.store(false, Ordering::SeqCst);
-                return Err(msg);
+                return Err(msg.to_string().into());
             }
         };
 
         if !output.stdout.is_empty() {
-            println!(
-                "[preview] java stdout id={} display='{}':
-{}",
-                target_mod.id,
-                target_mod.display_name,
-                String::from_utf8_lossy(&output.stdout)
+            info!(
+                "[preview] java stdout id={} display='{}':\n{}",
+                target_mod.id, target_mod.display_name, output.stdout
             );
         }
         if !output.stderr.is_empty() {
-            println!(
-                "[preview] java stderr id={} display='{}':
-{}",
-                target_mod.id,
-                target_mod.display_name,
-                String::from_utf8_lossy(&output.stderr)
+            info!(
+                "[preview] java stderr id={} display='{}':\n{}",
+                target_mod.id, target_mod.display_name, output.stderr
             );
         }
 
         let mut message = if output.status.success() {
             summary.generated += 1;
-            "Preview generated".to_string()
+            Message::new("preview.generated")
         } else {
             summary.errors += 1;
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let short = stderr
+            let short = output
+                .stderr
                 .lines()
                 .rev()
                 .find(|line| !line.trim().is_empty())
-                .map(|line| line.trim().to_string())
-                .unwrap_or_else(|| "Preview generation failed".to_string());
-            println!(
+                .map(|line| {
+                    Message::new("preview.generator_error")
+                        // no code when the process was killed
+                        .with(
+                            "code",
+                            output.status.code().map_or("?".into(), |c| c.to_string()),
+                        )
+                        .with("detail", line.trim())
+                })
+                .unwrap_or_else(|| Message::new("preview.failed"));
+            warn!(
                 "[preview] generator failed for id={} status={} stderr={}",
-                target_mod.id, output.status, stderr
+                target_mod.id, output.status, output.stderr
             );
             short
         };
@@ -612,9 +906,28 @@ fn generate_previews(
         if output.status.success() && !target.exists() {
             summary.generated = summary.generated.saturating_sub(1);
             summary.errors += 1;
-            message = "Generator reported success but preview is missing".to_string();
+            message = Message::new("preview.output_missing");
         }
 
+        let failed = !output.status.success() || !target.exists();
+        store_preview_result(
+            pool,
+            kind,
+            started.elapsed(),
+            if failed {
+                Err(PreviewError {
+                    mod_id: target_mod.id,
+                    kind: kind.label().to_string(),
+                    exit_code: output.status.code(),
+                    message: message.to_string(),
+                    output: output.combined,
+                    failed_at: now_iso(),
+                })
+            } else {
+                Ok(target_mod.id)
+            },
+        );
+
         emit_preview_progress(
             window,
             kind,
@@ -627,8 +940,8 @@ fn generate_previews(
             Some(target_mod.display_name.clone()),
             Some(message),
         );
-        println!(
-            "[RUST] EMIT: progress {}/{} (post-generation)",
+        debug!(
+            "[previews] progress {}/{} (post-generation)",
             processed, total
         );
         processed_count = processed;
@@ -638,16 +951,14 @@ fn generate_previews(
 
     let processed_final = if cancelled { processed_count } else { total };
     let completion_msg = if cancelled {
-        format!(
-            "Cancelled after processing {} of {} mods • generated {} • skipped {} • errors {}",
-            processed_count, total, summary.generated, summary.skipped, summary.errors
-        )
+        Message::new("preview.cancelled").with("processed", processed_count)
     } else {
-        format!(
-            "Completed: generated {} / {} • skipped {} • errors {}",
-            summary.generated, total, summary.skipped, summary.errors
-        )
-    };
+        Message::new("preview.completed")
+    }
+    .with("total", total)
+    .with("generated", summary.generated)
+    .with("skipped", summary.skipped)
+    .with("errors", summary.errors);
     let final_status = if cancelled { "cancelled" } else { "done" };
     emit_preview_progress(
         window,
@@ -661,19 +972,24 @@ fn generate_previews(
         None,
         Some(completion_msg),
     );
-    println!(
-        "[RUST] EMIT: final status {:?} processed {}/{}",
+    debug!(
+        "[previews] final status {:?} processed {}/{}",
         final_status, processed_final, total
     );
 
     Ok(summary)
 }
 
-fn preview_info_for_path(folder_path: &str) -> PreviewInfo {
-    let folder = Path::new(folder_path);
+// `preview_dir` holds the preview.* files, `folder_path` the mod (and its gallery)
+fn preview_info_for_path(folder_path: &str, preview_dir: &Path) -> PreviewInfo {
+    let folder = preview_dir;
     let image_path = folder.join("preview.png");
     let video_mp4 = folder.join("preview.mp4");
     let video_webm = folder.join("preview.webm");
+    let animated = [AnimatedFormat::Webp, AnimatedFormat::Gif]
+        .iter()
+        .map(|f| folder.join(f.file_name()))
+        .find(|p| p.exists());
     let has_image = image_path.exists();
     let has_mp4 = video_mp4.exists();
     let has_webm = video_webm.exists();
@@ -683,7 +999,7 @@ fn preview_info_for_path(folder_path: &str) -> PreviewInfo {
     let video_mp4_norm = normalize_path_string(&video_mp4.to_string_lossy());
     let video_webm_norm = normalize_path_string(&video_webm.to_string_lossy());
 
-    println!(
+    info!(
         "[preview_info] folder='{}' image='{}' (exists={}) video_mp4='{}' (exists={}) video_webm='{}' (exists={})",
         folder_path, image_path_norm, has_image, video_mp4_norm, has_mp4, video_webm_norm, has_webm
     );
@@ -709,621 +1025,4031 @@ fn preview_info_for_path(folder_path: &str) -> PreviewInfo {
         } else {
             None
         },
+        has_animated: animated.is_some(),
+        animated_path: animated.map(|p| normalize_path_string(&p.to_string_lossy())),
+        gallery: gallery::list(Path::new(folder_path))
+            .iter()
+            .map(|p| normalize_path_string(&p.to_string_lossy()))
+            .collect(),
+        image_is_poster: false,
+        caption: None,
+        nsfw_gated: false,
     }
 }
 
-fn mod_exists_by_path(conn: &rusqlite::Connection, fp_norm: &str) -> Result<bool, String> {
-    let mut stmt = conn
-        .prepare("SELECT 1 FROM mods WHERE folder_path = ?1 LIMIT 1")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([fp_norm]).map_err(|e| e.to_string())?;
-    let exists = rows.next().map_err(|e| e.to_string())?.is_some();
-    println!("[db] mod_exists_by_path path='{}' -> {}", fp_norm, exists);
-    Ok(exists)
+fn preview_caption(conn: &Connection, id: i64) -> Result<Option<String>, AppError> {
+    let names: Option<(Option<String>, Option<String>, Option<String>)> = conn
+        .query_row(
+            r#"
+            SELECT c.display_name, src.display_name, co.display_name
+            FROM mods m
+            LEFT JOIN characters c ON c.id = m.character_id
+            LEFT JOIN characters src ON src.id = m.swap_source_character_id
+            LEFT JOIN costumes co ON co.id = m.costume_id
+            WHERE m.id = ?1
+            "#,
+            [id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .optional()?;
+    let Some((character, source, costume)) = names else {
+        return Ok(None);
+    };
+    let who = match (source, character) {
+        (Some(source), Some(target)) => format!("{} → {}", source, target),
+        (Some(source), None) => format!("{} → ?", source),
+        (None, Some(target)) => target,
+        (None, None) => return Ok(None),
+    };
+    Ok(Some(match costume {
+        Some(costume) => format!("{} ({})", who, costume),
+        None => who,
+    }))
 }
 
+/// Enumerates every mod type with its label and the folder-name aliases inferring it, so the
+/// frontend never hardcodes the list.
 #[tauri::command]
-pub fn db_init() -> Result<String, String> {
-    println!("[db_init] ensuring database ready");
-    let conn = con().map_err(|e| e.to_string())?;
-    drop(conn);
+pub async fn mod_types_list(pool: State<'_, ReadPool>) -> Result<Vec<ModTypeInfo>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let type_aliases = aliases::type_aliases(conn)?;
+        Ok(ModType::ALL
+            .iter()
+            .map(|ty| ModTypeInfo {
+                value: *ty,
+                label: ty.label().to_string(),
+                aliases: type_aliases
+                    .iter()
+                    .filter(|(_, t)| t == ty.as_str())
+                    .map(|(alias, _)| alias.clone())
+                    .collect(),
+            })
+            .collect())
+    })
+    .await
+}
 
-    match catalog::sync_builtin() {
-        Ok(report) => {
-            println!(
-                "[catalog] builtin sync characters={} costumes={}",
-                report.characters, report.costumes
-            );
+/// How long one `db_init` step took.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepTiming {
+    pub step: &'static str,
+    pub ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbInitReport {
+    #[serde(flatten)]
+    pub migration: MigrationReport,
+    /// Set when the bundled catalog changed since the last launch and was synced.
+    pub catalog: Option<CatalogReport>,
+    pub catalog_hash: String,
+    pub timings: Vec<StepTiming>,
+    pub total_ms: u64,
+}
+
+/// Seeds defaults and syncs the catalog (skipped when the bundled one is unchanged);
+/// returns what the startup migration did, integrity problems and per-step timings.
+#[tauri::command]
+pub async fn db_init(pool: State<'_, DbPool>) -> Result<DbInitReport, AppError> {
+    with_write_conn(&pool, move |conn| {
+        info!("[db_init] ensuring database ready");
+        let started = std::time::Instant::now();
+        let mut timings = Vec::new();
+        let mut step = |name: &'static str, since: std::time::Instant| {
+            timings.push(StepTiming {
+                step: name,
+                ms: since.elapsed().as_millis() as u64,
+            })
+        };
+
+        let t = std::time::Instant::now();
+        aliases::seed_defaults(conn)?;
+        step("aliases", t);
+        let t = std::time::Instant::now();
+        authors::sync(conn, &now_iso())?;
+        step("authors", t);
+        let t = std::time::Instant::now();
+        downloads::recover(conn)?;
+        step("downloads", t);
+        let t = std::time::Instant::now();
+        imports::recover(conn)?;
+        step("import_sessions", t);
+        let t = std::time::Instant::now();
+        journal::recover(conn)?;
+        step("install_journal", t);
+        // picks up metadata edited on the other machines sharing a library folder
+        let t = std::time::Instant::now();
+        if let Err(err) = shared::sync(conn) {
+            warn!("[db_init] shared library sync failed: {}", err);
         }
-        Err(e) => {
-            eprintln!("[catalog] builtin sync failed: {}", e);
-            return Err(e);
+        step("shared_library", t);
+        // previews may have been added or removed by hand since the last run
+        let t = std::time::Instant::now();
+        if let Err(err) = previews::refresh_flags(conn, None) {
+            warn!("[db_init] preview flags not refreshed: {}", err);
         }
-    }
+        step("preview_flags", t);
+
+        let t = std::time::Instant::now();
+        let catalog = match catalog::sync_builtin_if_changed(conn) {
+            Ok(Some(report)) => {
+                info!(
+                    "[catalog] builtin sync characters={} costumes={}",
+                    report.characters, report.costumes
+                );
+                Some(report)
+            }
+            Ok(None) => {
+                info!("[catalog] builtin catalog unchanged, sync skipped");
+                None
+            }
+            Err(e) => {
+                error!("[catalog] builtin sync failed: {}", e);
+                return Err(e);
+            }
+        };
+        step("catalog", t);
 
-    Ok("ok".to_string())
+        let total_ms = started.elapsed().as_millis() as u64;
+        info!("[db_init] ready in {} ms", total_ms);
+        Ok(DbInitReport {
+            migration: db::startup_report(),
+            catalog,
+            catalog_hash: catalog::builtin_hash().to_string(),
+            timings,
+            total_ms,
+        })
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn mods_add(new_mod: NewMod) -> Result<i64, String> {
-    let conn = con().map_err(|e| e.to_string())?;
-    let now = now_iso();
-    println!(
-        "[mods_add] inserting manual mod display_name='{}' folder_path='{}'",
-        new_mod.display_name, new_mod.folder_path
-    );
-    let mut stmt = conn
-        .prepare(
+pub async fn mods_add(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    new_mod: NewMod,
+) -> Result<i64, AppError> {
+    let id = with_write_conn(&pool, move |conn| {
+        let now = now_iso();
+        info!(
+            "[mods_add] inserting manual mod display_name='{}' folder_path='{}'",
+            new_mod.display_name, new_mod.folder_path
+        );
+        let mut stmt = conn.prepare(
             r#"
         INSERT INTO mods (
           character_id, costume_id, author, download_url, installed, installed_at,
-          target_path, mod_type, folder_path, display_name, created_at, updated_at
-        ) VALUES (?1, ?2, ?3, ?4, 0, NULL, NULL, ?5, ?6, ?7, ?8, ?8)
+          target_path, mod_type, folder_path, display_name, created_at, updated_at,
+          swap_source_character_id, nsfw
+        ) VALUES (?1, ?2, ?3, ?4, 0, NULL, NULL, ?5, ?6, ?7, ?8, ?8, ?9, ?10)
         "#,
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
-    let mod_type_str = new_mod.mod_type.to_string();
+        let mod_type_str = new_mod.mod_type.to_string();
+        let nsfw = new_mod
+            .nsfw
+            .unwrap_or_else(|| infer::looks_nsfw(&new_mod.display_name));
 
-    stmt.execute(params![
-        new_mod.character_id,
-        new_mod.costume_id,
-        new_mod.author,
-        new_mod.download_url,
-        mod_type_str,
-        new_mod.folder_path,
-        new_mod.display_name,
-        now
-    ])
-    .map_err(|e| e.to_string())?;
+        stmt.execute(params![
+            new_mod.character_id,
+            new_mod.costume_id,
+            new_mod.author,
+            new_mod.download_url,
+            mod_type_str,
+            new_mod.folder_path,
+            new_mod.display_name,
+            now,
+            new_mod.swap_source_character_id,
+            nsfw
+        ])?;
+        let id = conn.last_insert_rowid();
+        manifest::store_stat(conn, id, Path::new(&new_mod.folder_path))?;
+        authors::sync(conn, &now)?;
 
-    Ok(conn.last_insert_rowid())
+        Ok(id)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Added, vec![id]);
+    Ok(id)
 }
 
 /* ===========Commands=========== */
 
 #[tauri::command]
-pub fn previews_generate_images(window: Window) -> Result<(), String> {
-    println!("[RUST] COMMAND START (images)");
-    thread::yield_now();
-    println!("[RUST] YIELDED (images)");
+pub async fn previews_generate_images(
+    window: Window,
+    pool: State<'_, DbPool>,
+    overwrite: Option<bool>,
+) -> Result<(), AppError> {
+    info!("[previews] generate images");
     PREVIEW_CANCEL_IMAGE.store(false, Ordering::SeqCst);
+    let pool = pool.inner().clone();
+    let overwrite = overwrite.unwrap_or(false);
     tauri::async_runtime::spawn_blocking(move || {
-        let _ = generate_previews(&window, PreviewKind::Image);
+        let _ = generate_previews(
+            Some(&window),
+            &pool,
+            PreviewKind::Image,
+            None,
+            overwrite,
+            VideoPreviewOverrides::default(),
+        );
     });
     Ok(())
 }
 
+/// `video` overrides the `preview_video` settings for this run, e.g. longer clips for
+/// cutscene mods.
 #[tauri::command]
-pub fn previews_generate_videos(window: Window) -> Result<(), String> {
-    println!("[RUST] COMMAND START (videos)");
-    thread::yield_now();
-    println!("[RUST] YIELDED (videos)");
+pub async fn previews_generate_videos(
+    window: Window,
+    pool: State<'_, DbPool>,
+    overwrite: Option<bool>,
+    video: Option<VideoPreviewOverrides>,
+) -> Result<(), AppError> {
+    info!("[previews] generate videos");
     PREVIEW_CANCEL_VIDEO.store(false, Ordering::SeqCst);
+    let pool = pool.inner().clone();
+    let overwrite = overwrite.unwrap_or(false);
     tauri::async_runtime::spawn_blocking(move || {
-        let _ = generate_previews(&window, PreviewKind::Video);
+        let _ = generate_previews(
+            Some(&window),
+            &pool,
+            PreviewKind::Video,
+            None,
+            overwrite,
+            video.unwrap_or_default(),
+        );
     });
     Ok(())
 }
 
+/// Same as the bulk commands but limited to the selected mods, e.g. to regenerate one
+/// mod's preview after updating it. Progress goes through the same `preview-progress`
+/// events and `previews_cancel(kind)` stops it. `video` applies to video previews as in
+/// `previews_generate_videos`.
 #[tauri::command]
-pub fn previews_cancel(kind: String) -> Result<(), String> {
-    match kind.as_str() {
-        "image" => {
-            PREVIEW_CANCEL_IMAGE.store(true, Ordering::SeqCst);
-            println!("[preview] cancel requested for image previews");
-            Ok(())
-        }
-        "video" => {
-            PREVIEW_CANCEL_VIDEO.store(true, Ordering::SeqCst);
-            println!("[preview] cancel requested for video previews");
-            Ok(())
-        }
-        other => Err(format!("Unknown preview cancel kind '{}'.", other)),
+pub async fn previews_generate_for(
+    window: Window,
+    pool: State<'_, DbPool>,
+    ids: Vec<i64>,
+    kind: String,
+    overwrite: Option<bool>,
+    video: Option<VideoPreviewOverrides>,
+) -> Result<(), AppError> {
+    let kind = PreviewKind::parse(&kind)?;
+    if ids.is_empty() {
+        return Ok(());
     }
+    info!(
+        "[preview] generating {:?} for {} selected mods",
+        kind,
+        ids.len()
+    );
+    cancel_flag_for_kind(kind).store(false, Ordering::SeqCst);
+    let pool = pool.inner().clone();
+    let overwrite = overwrite.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        let _ = generate_previews(
+            Some(&window),
+            &pool,
+            kind,
+            Some(&ids),
+            overwrite,
+            video.unwrap_or_default(),
+        );
+    });
+    Ok(())
 }
 
+/// Generates `preview.webp` (or `.gif`, per the `preview_animated` settings).
 #[tauri::command]
-pub fn mod_preview_info(id: i64) -> Result<PreviewInfo, String> {
-    let conn = con().map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare("SELECT folder_path FROM mods WHERE id = ?1")
-        .map_err(|e| e.to_string())?;
-    let folder_path = stmt
-        .query_row([id], |r| r.get::<_, String>(0))
-        .optional()
-        .map_err(|e| e.to_string())?;
-
-    match folder_path {
-        Some(path) => Ok(preview_info_for_path(&path)),
-        None => Err(format!("Mod with id={} not found", id)),
-    }
+pub async fn previews_generate_animated(
+    window: Window,
+    pool: State<'_, DbPool>,
+    overwrite: Option<bool>,
+) -> Result<(), AppError> {
+    info!("[previews] generate animated");
+    PREVIEW_CANCEL_ANIMATED.store(false, Ordering::SeqCst);
+    let pool = pool.inner().clone();
+    let overwrite = overwrite.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        let _ = generate_previews(
+            Some(&window),
+            &pool,
+            PreviewKind::Animated,
+            None,
+            overwrite,
+            VideoPreviewOverrides::default(),
+        );
+    });
+    Ok(())
 }
 
+/// The output of the last failed generator run per preview kind, so a mod that keeps
+/// failing can be looked into.
 #[tauri::command]
-pub fn mods_list(filter: Option<ModFilter>) -> Result<Vec<ModRow>, String> {
-    use rusqlite::{params, Rows};
+pub async fn previews_errors(
+    pool: State<'_, ReadPool>,
+    id: i64,
+) -> Result<Vec<PreviewError>, AppError> {
+    with_read_conn(&pool, move |conn| previews::errors_for(conn, id)).await
+}
 
-    println!(
-        "[mods_list] listing mods with filter={}",
-        filter.as_ref().map(|_| "some").unwrap_or("none")
-    );
-    let conn = con().map_err(|e| e.to_string())?;
+/// Previews older than their mod's files, for `previews_generate_for` with `overwrite`.
+#[tauri::command]
+pub async fn previews_verify(
+    pool: State<'_, ReadPool>,
+    ids: Option<Vec<i64>>,
+) -> Result<PreviewVerifyReport, AppError> {
+    with_read_conn(&pool, move |conn| previews::verify(conn, ids.as_deref())).await
+}
 
-    // Normalize filter inputs; everything optional is allowed to be NULL.
-    let (cid, coid, author_like, q_like) = if let Some(f) = filter {
-        let author_like = f.author.map(|s| format!("%{}%", s));
-        let q_like = f.q.map(|s| format!("%{}%", s));
-        (f.character_id, f.costume_id, author_like, q_like)
-    } else {
-        (None, None, None, None)
-    };
+/// Extracts a poster frame (the middle one unless `frame` says `first`) from the preview
+/// video of mods (`ids`, or all) that have no `preview.png`, so the grid shows something
+/// before hover.
+#[tauri::command]
+pub async fn previews_extract_posters(
+    pool: State<'_, DbPool>,
+    ids: Option<Vec<i64>>,
+    frame: Option<PosterFrame>,
+) -> Result<PosterSummary, AppError> {
+    with_conn(&pool, move |conn| {
+        let ffmpeg = locate_ffmpeg(load_settings(conn)?.ffmpeg_path.as_deref());
+        previews::extract_posters(
+            conn,
+            &ffmpeg,
+            ids.as_deref(),
+            frame.unwrap_or_default(),
+            &now_iso(),
+        )
+    })
+    .await
+}
 
-    // Use positional parameters ?1 ?2 ?3 ?4
-    let sql = r#"
-        SELECT id, display_name, folder_path, author, download_url,
-               character_id, costume_id, mod_type, installed, installed_at,
-               target_path, created_at, updated_at
-        FROM mods
-        WHERE (?1 IS NULL OR character_id = ?1)
-          AND (?2 IS NULL OR costume_id  = ?2)
-          AND (?3 IS NULL OR author LIKE ?3)
-          AND (?4 IS NULL OR display_name LIKE ?4 OR folder_path LIKE ?4)
-        ORDER BY LOWER(display_name) ASC, id ASC
-    "#;
-
-    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
-    let mut rows: Rows = stmt
-        .query(params![cid, coid, author_like, q_like])
-        .map_err(|e| e.to_string())?;
+/// Moves existing previews between the mod folders and the app cache and switches the
+/// `preview_storage` setting.
+#[tauri::command]
+pub async fn previews_migrate(
+    pool: State<'_, DbPool>,
+    storage: PreviewStorage,
+) -> Result<PreviewMigrationReport, AppError> {
+    with_write_conn(&pool, move |conn| previews::migrate(conn, storage)).await
+}
 
-    let mut out = Vec::new();
-    while let Some(r) = rows.next().map_err(|e| e.to_string())? {
-        let mod_type_s: String = r.get(7).map_err(|e| e.to_string())?;
-        let mt = ModType::from_str(mod_type_s.as_str());
-        out.push(ModRow {
-            id: r.get(0).map_err(|e| e.to_string())?,
-            display_name: r.get(1).map_err(|e| e.to_string())?,
-            folder_path: r.get(2).map_err(|e| e.to_string())?,
-            author: r.get(3).map_err(|e| e.to_string())?,
-            download_url: r.get(4).map_err(|e| e.to_string())?,
-            character_id: r.get(5).map_err(|e| e.to_string())?,
-            costume_id: r.get(6).map_err(|e| e.to_string())?,
-            mod_type: mt,
-            installed: r.get::<_, i64>(8).map_err(|e| e.to_string())? != 0,
-            installed_at: r.get(9).map_err(|e| e.to_string())?,
-            target_path: r.get(10).map_err(|e| e.to_string())?,
-            created_at: r.get(11).map_err(|e| e.to_string())?,
-            updated_at: r.get(12).map_err(|e| e.to_string())?,
-        });
+#[tauri::command]
+pub fn previews_cancel(kind: String) -> Result<(), AppError> {
+    let kind = PreviewKind::parse(&kind)?;
+    cancel_flag_for_kind(kind).store(true, Ordering::SeqCst);
+    info!("[preview] cancel requested for {} previews", kind.label());
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreviewGate {
+    Open,
+    Blurred,
+    Hidden,
+}
+
+// how the NSFW settings let a mod's previews be shown
+fn preview_gate(
+    conn: &Connection,
+    id: i64,
+    settings: &AppSettings,
+) -> Result<PreviewGate, AppError> {
+    if !settings.hide_nsfw {
+        return Ok(PreviewGate::Open);
     }
+    let nsfw = conn
+        .query_row("SELECT nsfw FROM mods WHERE id = ?1", [id], |r| {
+            r.get::<_, i64>(0)
+        })
+        .optional()?
+        .unwrap_or(0)
+        != 0;
+    Ok(match (nsfw, settings.blur_previews) {
+        (false, _) => PreviewGate::Open,
+        (true, true) => PreviewGate::Blurred,
+        (true, false) => PreviewGate::Hidden,
+    })
+}
 
-    Ok(out)
+// preview files of one mod as the UI may show them, see `PreviewGate`
+fn preview_info(
+    conn: &Connection,
+    id: i64,
+    path: &str,
+    settings: &AppSettings,
+) -> Result<PreviewInfo, AppError> {
+    let preview_dir = previews::dir_for(settings.preview_storage, id, path)?;
+    let caption = preview_caption(conn, id)?;
+    let gate = preview_gate(conn, id, settings)?;
+    if gate == PreviewGate::Open {
+        let info = preview_info_for_path(path, &preview_dir);
+        return Ok(PreviewInfo {
+            caption,
+            image_is_poster: info.has_image && previews::poster_ids(conn)?.contains(&id),
+            ..info
+        });
+    }
+    // videos, animations and screenshots stay hidden; the still becomes a blur
+    let placeholder = if gate == PreviewGate::Blurred {
+        thumbs::ensure(id, Path::new(path), &preview_dir, 512, true)?
+    } else {
+        None
+    };
+    Ok(PreviewInfo {
+        has_image: placeholder.is_some(),
+        image_path: placeholder.map(|p| normalize_path_string(&p.to_string_lossy())),
+        caption,
+        nsfw_gated: true,
+        ..Default::default()
+    })
 }
 
 #[tauri::command]
-pub fn mods_set_installed(
-    id: i64,
-    installed: bool,
-    target_path: Option<String>,
-) -> Result<(), String> {
-    use rusqlite::params;
-    println!(
-        "[mods_set_installed] id={} installed={} target_path={:?}",
-        id, installed, target_path
-    );
-    let conn = con().map_err(|e| e.to_string())?;
-    let now = now_iso();
+pub async fn mod_preview_info(pool: State<'_, ReadPool>, id: i64) -> Result<PreviewInfo, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let mut stmt = conn.prepare("SELECT folder_path FROM mods WHERE id = ?1")?;
+        let folder_path = stmt.query_row([id], |r| r.get::<_, String>(0)).optional()?;
+        let Some(path) = folder_path else {
+            return Err(AppError::not_found("Mod", id));
+        };
+        preview_info(conn, id, &path, &load_settings(conn)?)
+    })
+    .await
+}
 
-    let installed_int = if installed { 1 } else { 0 };
-    let installed_at = if installed { Some(now.clone()) } else { None }; // <-- clone here
+/// One mod of a `mods_compare` set.
+#[derive(Debug, Serialize)]
+pub struct ModCompareEntry {
+    #[serde(flatten)]
+    pub item: ModListItem,
+    pub preview: PreviewInfo,
+}
 
-    let n = conn
-        .execute(
-            r#"
-            UPDATE mods
-            SET installed = ?2,
-                installed_at = ?3,
-                target_path = CASE WHEN ?2 = 1 THEN ?4 ELSE NULL END,
-                updated_at = ?5
-            WHERE id = ?1
-            "#,
-            params![id, installed_int, installed_at, target_path, now],
-        )
-        .map_err(|e| e.to_string())?;
+/// Every mod for a costume (optionally of one type) with its previews, best rated first,
+/// so a compare screen needs a single call.
+#[tauri::command]
+pub async fn mods_compare(
+    pool: State<'_, ReadPool>,
+    costume_id: i64,
+    mod_type: Option<ModType>,
+) -> Result<Vec<ModCompareEntry>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let settings = load_settings(conn)?;
+        let filter = ModFilter {
+            costume_id: Some(costume_id),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        for item in list_mods(conn, Some(filter))? {
+            if mod_type.is_some_and(|t| t != item.row.mod_type) {
+                continue;
+            }
+            let preview = preview_info(conn, item.row.id, &item.row.folder_path, &settings)?;
+            out.push(ModCompareEntry { item, preview });
+        }
+        // unrated last; list_mods already sorted by name
+        out.sort_by_key(|e| std::cmp::Reverse(e.item.row.rating.unwrap_or(0)));
+        Ok(out)
+    })
+    .await
+}
 
+/// Sets the user's 1-5 star rating of a mod; `None` clears it.
+#[tauri::command]
+pub async fn mods_set_rating(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+    rating: Option<u8>,
+) -> Result<(), AppError> {
+    if rating.is_some_and(|r| !(1..=5).contains(&r)) {
+        return Err(AppError::invalid("Rating must be between 1 and 5"));
+    }
+    let n = with_write_conn(&pool, move |conn| {
+        conn.execute(
+            "UPDATE mods SET rating = ?2 WHERE id = ?1",
+            params![id, rating],
+        )
+        .map_err(AppError::from)
+    })
+    .await?;
     if n == 0 {
-        return Err("Mod not found".to_string());
+        return Err(AppError::not_found("Mod", id));
     }
+    events::mods_changed(&app, ModsChangeKind::Updated, vec![id]);
     Ok(())
 }
 
+/// Flags or unflags mods as favorites.
 #[tauri::command]
-pub fn settings_get() -> Result<AppSettings, String> {
-    println!("[settings_get] loading settings");
-    let conn = con().map_err(|e| e.to_string())?;
-    let val: Option<String> = conn
+pub async fn mods_set_favorite(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    ids: Vec<i64>,
+    favorite: bool,
+) -> Result<(), AppError> {
+    let changed = with_write_conn(&pool, move |conn| {
+        let mut changed = Vec::new();
+        for id in ids {
+            let n = conn.execute(
+                "UPDATE mods SET favorite = ?2 WHERE id = ?1 AND favorite != ?2",
+                params![id, favorite],
+            )?;
+            if n > 0 {
+                changed.push(id);
+            }
+        }
+        Ok(changed)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, changed);
+    Ok(())
+}
+
+/// Path of a 256 or 512 px WebP thumbnail of the mod's preview (or first screenshot),
+/// generated on demand; `None` when there is nothing to make one from.
+#[tauri::command]
+pub async fn mod_thumbnail(
+    pool: State<'_, ReadPool>,
+    id: i64,
+    size: u32,
+) -> Result<Option<String>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let folder_path: String = conn
+            .query_row("SELECT folder_path FROM mods WHERE id = ?1", [id], |r| {
+                r.get(0)
+            })
+            .optional()?
+            .ok_or_else(|| AppError::not_found("Mod", id))?;
+        let settings = load_settings(conn)?;
+        let preview_dir = previews::dir_for(settings.preview_storage, id, &folder_path)?;
+        let blurred = match preview_gate(conn, id, &settings)? {
+            PreviewGate::Open => false,
+            PreviewGate::Blurred => true,
+            PreviewGate::Hidden => return Ok(None),
+        };
+        let thumb = thumbs::ensure(id, Path::new(&folder_path), &preview_dir, size, blurred)?;
+        Ok(thumb.map(|p| normalize_path_string(&p.to_string_lossy())))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn mods_attach_screenshot(
+    pool: State<'_, ReadPool>,
+    id: i64,
+    source_path: String,
+) -> Result<PreviewInfo, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let folder_path: String = conn
+            .query_row("SELECT folder_path FROM mods WHERE id = ?1", [id], |r| {
+                r.get(0)
+            })
+            .optional()?
+            .ok_or_else(|| AppError::not_found("Mod", id))?;
+
+        let stored = gallery::attach_screenshot(Path::new(&folder_path), Path::new(&source_path))?;
+        info!(
+            "[mods_attach_screenshot] id={} source='{}' stored='{}'",
+            id,
+            source_path,
+            stored.display()
+        );
+        let storage = load_settings(conn)?.preview_storage;
+        Ok(PreviewInfo {
+            caption: preview_caption(conn, id)?,
+            ..preview_info_for_path(&folder_path, &previews::dir_for(storage, id, &folder_path)?)
+        })
+    })
+    .await
+}
+
+// the query behind both listings; the joins are cheap enough to always run
+fn list_mods(conn: &Connection, filter: Option<ModFilter>) -> Result<Vec<ModListItem>, AppError> {
+    use rusqlite::{params, Rows};
+
+    info!(
+        "[mods_list] listing mods with filter={}",
+        filter.as_ref().map(|_| "some").unwrap_or("none")
+    );
+
+    // Normalize filter inputs; everything optional is allowed to be NULL.
+    let filter = filter.unwrap_or_default();
+    let author_like = filter.author.map(|s| format!("%{}%", s));
+    let q_like = filter.q.map(|s| format!("%{}%", s));
+    let min_size = filter.min_size_mb.map(|mb| (mb * 1_000_000) as i64);
+    // with blur_previews the mods stay listed and only their previews are gated
+    let settings = load_settings(conn)?;
+    let hide_nsfw = settings.hide_nsfw && !settings.blur_previews;
+    let game_version = settings.game_version;
+    // mods not measured yet (NULL size) sort last either way
+    let order = match filter.sort {
+        ModSort::Name => "LOWER(m.display_name) ASC, m.id ASC",
+        ModSort::SizeDesc => "m.size_bytes IS NULL, m.size_bytes DESC, m.id ASC",
+        ModSort::SizeAsc => "m.size_bytes IS NULL, m.size_bytes ASC, m.id ASC",
+    };
+
+    // Use positional parameters ?1 .. ?15
+    let sql = format!(
+        r#"
+        SELECT m.id, m.display_name, m.folder_path, m.author, m.download_url,
+               m.character_id, m.costume_id, m.mod_type, m.installed, m.installed_at,
+               m.target_path, m.created_at, m.updated_at, m.swap_source_character_id,
+               m.validation_status, m.size_bytes, m.file_count,
+               ch.display_name, co.display_name, sw.display_name, m.has_image, m.has_video,
+               m.nsfw, m.verified_game_version, m.verified_at, m.risk_flags,
+               m.risk_confirmed_at, m.rating, m.favorite
+        FROM mods m
+        LEFT JOIN characters ch ON ch.id = m.character_id
+        LEFT JOIN costumes co ON co.id = m.costume_id
+        LEFT JOIN characters sw ON sw.id = m.swap_source_character_id
+        WHERE (?1 IS NULL OR m.character_id = ?1)
+          AND (?2 IS NULL OR m.costume_id  = ?2)
+          AND (?3 IS NULL OR m.author LIKE ?3)
+          AND (?4 IS NULL OR m.display_name LIKE ?4 OR m.folder_path LIKE ?4)
+          AND (?5 IS NULL OR m.swap_source_character_id = ?5)
+          AND (?6 IS NULL OR m.author_id = ?6)
+          AND (?7 IS NULL OR m.size_bytes > ?7)
+          AND (?8 = 0 OR m.nsfw = 0)
+          AND (?9 = 0 OR ?10 IS NULL OR m.verified_game_version IS NOT ?10)
+          AND (?11 IS NULL OR (m.has_image = 1 OR m.has_video = 1) = ?11)
+          AND (?12 = 0 OR m.character_id IS NULL)
+          AND (?13 IS NULL OR m.validation_status = ?13)
+          AND (?14 IS NULL OR m.installed = ?14)
+          AND (?15 IS NULL OR m.favorite = ?15)
+        ORDER BY {}
+    "#,
+        order
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows: Rows = stmt.query(params![
+        filter.character_id,
+        filter.costume_id,
+        author_like,
+        q_like,
+        filter.swap_source_character_id,
+        filter.author_id,
+        min_size,
+        hide_nsfw,
+        filter.untested,
+        game_version,
+        filter.has_preview,
+        filter.missing_character,
+        filter.validation_status.map(|v| v.as_str()),
+        filter.installed,
+        filter.favorite
+    ])?;
+
+    let mut out = Vec::new();
+    while let Some(r) = rows.next()? {
+        let mod_type_s: String = r.get(7)?;
+        let mt = ModType::from_str(mod_type_s.as_str());
+        let row = ModRow {
+            id: r.get(0)?,
+            display_name: r.get(1)?,
+            folder_path: r.get(2)?,
+            author: r.get(3)?,
+            download_url: r.get(4)?,
+            character_id: r.get(5)?,
+            costume_id: r.get(6)?,
+            mod_type: mt,
+            swap_source_character_id: r.get(13)?,
+            installed: r.get::<_, i64>(8)? != 0,
+            installed_at: r.get(9)?,
+            target_path: r.get(10)?,
+            created_at: r.get(11)?,
+            updated_at: r.get(12)?,
+            validation_status: r.get(14)?,
+            size_bytes: r.get::<_, Option<i64>>(15)?.map(|n| n as u64),
+            file_count: r.get::<_, Option<i64>>(16)?.map(|n| n as u64),
+            nsfw: r.get::<_, i64>(22)? != 0,
+            verified_game_version: r.get(23)?,
+            verified_at: r.get(24)?,
+            risk_flags: r
+                .get::<_, Option<String>>(25)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            risk_confirmed_at: r.get(26)?,
+            rating: r.get(27)?,
+            favorite: r.get::<_, i64>(28)? != 0,
+        };
+        out.push(ModListItem {
+            row,
+            character_name: r.get(17)?,
+            costume_name: r.get(18)?,
+            swap_source_character_name: r.get(19)?,
+            has_image: r.get::<_, i64>(20)? != 0,
+            has_video: r.get::<_, i64>(21)? != 0,
+        });
+    }
+
+    Ok(out)
+}
+
+// sections of `list_mods` in list order, mods without a key last
+fn group_mods(
+    conn: &Connection,
+    group_by: ModGroupBy,
+    filter: Option<ModFilter>,
+) -> Result<Vec<ModSection>, AppError> {
+    let settings = load_settings(conn)?;
+    let mut groups: Vec<ModSection> = Vec::new();
+    let mut index: std::collections::HashMap<Option<String>, usize> =
+        std::collections::HashMap::new();
+    for item in list_mods(conn, filter)? {
+        let row = &item.row;
+        let (key, label) = match group_by {
+            ModGroupBy::Character => (
+                row.character_id.map(|id| id.to_string()),
+                item.character_name.clone(),
+            ),
+            ModGroupBy::Costume => (
+                row.costume_id.map(|id| id.to_string()),
+                item.costume_name.clone(),
+            ),
+            ModGroupBy::Author => {
+                let author = row
+                    .author
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|a| !a.is_empty())
+                    .map(str::to_string);
+                (author.as_ref().map(|a| a.to_lowercase()), author)
+            }
+            ModGroupBy::ModType => (
+                Some(row.mod_type.as_str().to_string()),
+                Some(row.mod_type.as_str().to_string()),
+            ),
+        };
+        let i = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push(ModSection {
+                key,
+                label,
+                count: 0,
+                installed: 0,
+                preview_path: None,
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[i];
+        group.count += 1;
+        if row.installed {
+            group.installed += 1;
+        }
+        if group.preview_path.is_none()
+            && item.has_image
+            && preview_gate(conn, row.id, &settings)? == PreviewGate::Open
+        {
+            let dir = previews::dir_for(settings.preview_storage, row.id, &row.folder_path)?;
+            group.preview_path = Some(normalize_path_string(
+                &dir.join("preview.png").to_string_lossy(),
+            ));
+        }
+    }
+    groups.sort_by_key(|g| g.key.is_none());
+    Ok(groups)
+}
+
+#[tauri::command]
+pub async fn mods_list(
+    pool: State<'_, ReadPool>,
+    filter: Option<ModFilter>,
+) -> Result<Vec<ModRow>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        Ok(list_mods(conn, filter)?
+            .into_iter()
+            .map(|item| item.row)
+            .collect())
+    })
+    .await
+}
+
+/// `mods_list` with character/costume names and preview availability in the same rows.
+#[tauri::command]
+pub async fn mods_list_enriched(
+    pool: State<'_, ReadPool>,
+    filter: Option<ModFilter>,
+) -> Result<Vec<ModListItem>, AppError> {
+    with_read_conn(&pool, move |conn| list_mods(conn, filter)).await
+}
+
+/// The list (narrowed by `filter`) in sections by character, costume, author or mod type,
+/// with counts and a preview per section, so the grid renders them without every row.
+#[tauri::command]
+pub async fn mods_grouped(
+    pool: State<'_, ReadPool>,
+    group_by: Option<ModGroupBy>,
+    filter: Option<ModFilter>,
+) -> Result<Vec<ModSection>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        group_mods(conn, group_by.unwrap_or_default(), filter)
+    })
+    .await
+}
+
+/// Marks a mod as working with `game_version` (the current one when omitted), so after
+/// the next game update it shows up as untested.
+#[tauri::command]
+pub async fn mods_mark_verified(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+    game_version: Option<String>,
+) -> Result<(), AppError> {
+    let marked = with_write_conn(&pool, move |conn| {
+        gameupdate::mark_verified(conn, &[id], game_version, &now_iso())
+    })
+    .await?;
+    if marked == 0 {
+        return Err(AppError::not_found("Mod", id));
+    }
+    events::mods_changed(&app, ModsChangeKind::Updated, vec![id]);
+    Ok(())
+}
+
+/// Renames a mod and optionally its folder, see `organize::rename`. Returns the folder path.
+#[tauri::command]
+pub async fn mods_rename(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+    new_display_name: String,
+    rename_folder: Option<bool>,
+) -> Result<String, AppError> {
+    let folder_path = with_write_conn(&pool, move |conn| {
+        organize::rename(
+            conn,
+            id,
+            &new_display_name,
+            rename_folder.unwrap_or(false),
+            &now_iso(),
+        )
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, vec![id]);
+    Ok(folder_path)
+}
+
+/// Accepts the executables or scripts flagged in a mod so it can be installed.
+#[tauri::command]
+pub async fn mods_confirm_risk(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+) -> Result<(), AppError> {
+    with_write_conn(&pool, move |conn| {
+        install::confirm_risk(conn, id, &now_iso())
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, vec![id]);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn mods_set_installed(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+    installed: bool,
+    target_path: Option<String>,
+) -> Result<(), AppError> {
+    let () = with_write_conn(&pool, move |conn| {
+        use rusqlite::params;
+        info!(
+            "[mods_set_installed] id={} installed={} target_path={:?}",
+            id, installed, target_path
+        );
+        let now = now_iso();
+
+        let installed_int = if installed { 1 } else { 0 };
+        let installed_at = if installed { Some(now.clone()) } else { None };
+
+        let tx = conn.transaction()?;
+        let Some(before) = history::snapshot(&tx, id)? else {
+            return Err(AppError::not_found("Mod", id));
+        };
+        let n = tx.execute(
+            r#"
+            UPDATE mods
+            SET installed = ?2,
+                installed_at = ?3,
+                target_path = CASE WHEN ?2 = 1 THEN ?4 ELSE NULL END,
+                updated_at = ?5
+            WHERE id = ?1
+            "#,
+            params![id, installed_int, installed_at, target_path, now],
+        )?;
+
+        if n == 0 {
+            return Err(AppError::not_found("Mod", id));
+        }
+        let (kind, verb) = if installed {
+            ("install", "Installed")
+        } else {
+            ("uninstall", "Uninstalled")
+        };
+        let event_target = if installed {
+            target_path.clone()
+        } else {
+            before.target_path.clone()
+        };
+        history::log(
+            &tx,
+            kind,
+            &format!("{} '{}'", verb, before.display_name),
+            &[(id, Some(before))],
+            &now,
+        )?;
+        install::record_event(&tx, id, kind, event_target.as_deref(), None, &now)?;
+        tx.commit()?;
+        Ok(())
+    })
+    .await?;
+    let kind = if installed {
+        ModsChangeKind::Installed
+    } else {
+        ModsChangeKind::Uninstalled
+    };
+    events::mods_changed(&app, kind, vec![id]);
+    Ok(())
+}
+
+pub(crate) fn load_settings(conn: &Connection) -> Result<AppSettings, AppError> {
+    settings::load(conn)
+}
+
+pub(crate) fn save_settings(conn: &Connection, settings: &AppSettings) -> Result<(), AppError> {
+    settings::save(conn, settings)
+}
+
+#[tauri::command]
+pub async fn mods_delete(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    ids: Vec<i64>,
+    delete_files: Option<bool>,
+) -> Result<usize, AppError> {
+    let delete_files = delete_files.unwrap_or(false);
+    let report = with_write_conn(&pool, move |conn| {
+        let settings = load_settings(conn)?;
+        removal::purge(
+            conn,
+            &ids,
+            delete_files,
+            settings.permanent_delete,
+            settings.game_mods_dir.as_deref().map(Path::new),
+            &now_iso(),
+        )
+    })
+    .await?;
+    let count = report.deleted.len();
+    events::mods_changed(&app, ModsChangeKind::Deleted, report.deleted);
+    // nothing went away: surface why instead of reporting 0
+    if count == 0 {
+        if let Some(error) = report.results.into_iter().find_map(|r| r.error) {
+            return Err(error.into());
+        }
+    }
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn settings_get(pool: State<'_, ReadPool>) -> Result<AppSettings, AppError> {
+    with_read_conn(&pool, move |conn| {
+        info!("[settings_get] loading settings");
+        let settings = load_settings(conn)?;
+        info!(
+            "[settings_get] loaded library_dirs={} game_mods_dir={:?} last_library_pick={:?}",
+            settings.library_dirs.len(),
+            settings.game_mods_dir,
+            settings.last_library_pick
+        );
+        Ok(settings)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn settings_set(
+    pool: State<'_, DbPool>,
+    new_settings: AppSettings,
+) -> Result<AppSettings, AppError> {
+    with_write_conn(&pool, move |conn| {
+        info!(
+        "[settings_set] saving settings library_dirs={} game_mods_dir={:?} last_library_pick={:?}",
+        new_settings.library_dirs.len(),
+        new_settings.game_mods_dir,
+        new_settings.last_library_pick
+    );
+        logging::set_level(&new_settings.log_level)?;
+        save_settings(conn, &new_settings)?;
+        for dir in new_settings.library_dirs.iter() {
+            roots::register(conn, &normalize_path_string(&dir.path))?;
+        }
+        Ok(new_settings)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn library_add(
+    pool: State<'_, DbPool>,
+    dir: LibraryDir,
+) -> Result<Vec<LibraryDir>, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let dirs = settings::library_add(conn, dir)?;
+        for d in dirs.iter() {
+            roots::register(conn, &d.path)?;
+        }
+        Ok(dirs)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn library_remove(
+    pool: State<'_, DbPool>,
+    path: String,
+) -> Result<Vec<LibraryDir>, AppError> {
+    with_write_conn(&pool, move |conn| settings::library_remove(conn, &path)).await
+}
+
+/// Replaces the library folder entry registered for `path` (label, enabled, exclusions,
+/// layout, or the path itself).
+#[tauri::command]
+pub async fn library_update(
+    pool: State<'_, DbPool>,
+    path: String,
+    dir: LibraryDir,
+) -> Result<Vec<LibraryDir>, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let dirs = settings::library_update(conn, &path, dir)?;
+        for d in dirs.iter() {
+            roots::register(conn, &d.path)?;
+        }
+        Ok(dirs)
+    })
+    .await
+}
+
+/// Brings back the settings as they were before the last save.
+#[tauri::command]
+pub async fn settings_restore_backup(pool: State<'_, DbPool>) -> Result<AppSettings, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let restored = settings::restore_backup(conn)?;
+        logging::set_level(&restored.log_level)?;
+        Ok(restored)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn library_author_dirs(
+    pool: State<'_, ReadPool>,
+    lib_root: String,
+) -> Result<Vec<AuthorFolder>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        use walkdir::WalkDir;
+        info!("[library_author_dirs] root='{}'", lib_root);
+        let author_aliases = aliases::author_aliases(conn)?;
+        let mut out = Vec::new();
+        for entry in WalkDir::new(&lib_root).min_depth(1).max_depth(1) {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(e) => {
+                    warn!(
+                        "[library_author_dirs] failed to access entry under '{}' err={}",
+                        lib_root, e
+                    );
+                    continue;
+                }
+            };
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+            let folder_path = normalize_path_string(&entry.path().to_string_lossy());
+            let folder_name = entry.file_name().to_string_lossy().to_string();
+            let inferred = infer_author_name(&folder_name, &author_aliases);
+            out.push(AuthorFolder {
+                folder_path,
+                inferred_author: inferred,
+            });
+        }
+        Ok(out)
+    })
+    .await
+}
+
+fn walk_error_record(fallback: &str, err: &walkdir::Error) -> ScanError {
+    let path = err
+        .path()
+        .map(|p| platform::slashed(&p.to_string_lossy()))
+        .unwrap_or_else(|| fallback.to_string());
+    let kind = match err.io_error().map(|io| io.kind()) {
+        _ if err.io_error().is_some_and(platform::is_transient) => "network",
+        Some(std::io::ErrorKind::NotFound) => "not_found",
+        Some(std::io::ErrorKind::PermissionDenied) => "permission_denied",
+        Some(_) => "io",
+        None if err.loop_ancestor().is_some() => "loop",
+        None => "io",
+    };
+    ScanError {
+        path,
+        kind: kind.to_string(),
+        message: err.to_string(),
+    }
+}
+
+enum ScanOutcome {
+    Added,
+    Changed,
+    Unchanged,
+}
+
+// id, display name, fingerprint, missing since and risk flags of a scanned folder's row
+type ScannedRow = (i64, String, Option<String>, Option<String>, Option<String>);
+
+/// Inserts a discovered folder, or updates its row only when the folder content (per its
+/// fingerprint) or name changed, so `updated_at` keeps meaning "last actually changed".
+/// A curated author is kept; only rows without one take the inferred author.
+fn scan_upsert(
+    conn: &Connection,
+    folder_path: &str,
+    display_name: &str,
+    author: &str,
+    stat: &manifest::FolderStat,
+    now: &str,
+) -> rusqlite::Result<ScanOutcome> {
+    let existing: Option<ScannedRow> = conn
         .query_row(
-            "SELECT value_json FROM settings WHERE key='app_settings'",
-            [],
-            |r| r.get(0),
+            r#"
+            SELECT id, display_name, scan_fingerprint, missing_at, risk_flags FROM mods
+            WHERE folder_path = ?1
+            "#,
+            [folder_path],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
         )
-        .optional()
-        .map_err(|e| e.to_string())?;
+        .optional()?;
+    // compared on every scan, which also fills rows scanned before flags existed
+    let risk_flags = validate::risk_flags_json(&stat.risk_flags);
+    match existing {
+        None => {
+            conn.execute(
+                r#"
+                INSERT INTO mods (
+                  character_id, costume_id, author, download_url, installed, installed_at,
+                  target_path, mod_type, folder_path, display_name, created_at, updated_at,
+                  size_bytes, file_count, scan_fingerprint, risk_flags
+                ) VALUES (NULL, NULL, ?1, NULL, 0, NULL, NULL, 'other', ?2, ?3, ?4, ?4, ?5, ?6, ?7, ?8)
+                "#,
+                params![
+                    author,
+                    folder_path,
+                    display_name,
+                    now,
+                    stat.size_bytes as i64,
+                    stat.file_count as i64,
+                    stat.fingerprint,
+                    risk_flags
+                ],
+            )?;
+            Ok(ScanOutcome::Added)
+        }
+        Some((_, name, fingerprint, missing_at, flags))
+            if name == display_name
+                && fingerprint.as_deref() == Some(stat.fingerprint.as_str())
+                && missing_at.is_none()
+                && flags == risk_flags =>
+        {
+            Ok(ScanOutcome::Unchanged)
+        }
+        Some((id, ..)) => {
+            conn.execute(
+                r#"
+                UPDATE mods SET
+                  display_name = ?2,
+                  author = CASE WHEN author IS NULL OR author = 'unknown' THEN ?3 ELSE author END,
+                  missing_at = NULL,
+                  size_bytes = ?4,
+                  file_count = ?5,
+                  scan_fingerprint = ?6,
+                  -- new or different files need a fresh confirmation
+                  risk_confirmed_at = CASE WHEN ?8 IS risk_flags
+                    THEN risk_confirmed_at ELSE NULL END,
+                  risk_flags = ?8,
+                  updated_at = ?7
+                WHERE id = ?1
+                "#,
+                params![
+                    id,
+                    display_name,
+                    author,
+                    stat.size_bytes as i64,
+                    stat.file_count as i64,
+                    stat.fingerprint,
+                    now,
+                    risk_flags
+                ],
+            )?;
+            Ok(ScanOutcome::Changed)
+        }
+    }
+}
+
+/// Walks every library root (per its configured layout) and upserts what it finds,
+/// reporting each folder as `scan-progress` when `app` is given. Walking and measuring run
+/// unlocked; `db::write_lock` is taken only around the writes, so call it without the lock.
+pub(crate) fn rescan(
+    conn: &mut Connection,
+    app: Option<&AppHandle>,
+) -> Result<ScanSummary, AppError> {
+    use rayon::prelude::*;
+
+    info!("[paths_rescan] started");
+    let settings = load_settings(conn)?;
+    let pool = manifest::worker_pool(settings.scan_threads)?;
+    let ignores = manifest::Ignores::new(&settings.ignore_globs)?;
+    let author_aliases = aliases::author_aliases(conn)?;
+
+    let mut scanned_dirs = 0usize;
+    let mut discovered_mods = 0usize;
+    let mut added = 0usize;
+    let mut changed = 0usize;
+    let mut unchanged = 0usize;
+    let mut errors: Vec<ScanError> = Vec::new();
+    let now = now_iso();
+
+    let run_id = {
+        let _writer = db::write_lock();
+        conn.execute(
+            "INSERT INTO scan_runs (started_at) VALUES (?1)",
+            params![now],
+        )?;
+        conn.last_insert_rowid()
+    };
+
+    for lib in settings.library_dirs.iter().filter(|d| d.enabled) {
+        let lib_root = &lib.path;
+        scanned_dirs += 1;
+        {
+            let _writer = db::write_lock();
+            roots::register(conn, &normalize_path_string(lib_root))?;
+        }
+
+        let layout = lib.layout;
+        info!(
+            "[paths_rescan] scanning library root='{}' layout={:?}",
+            lib_root, layout
+        );
+        let exclusions = layouts::Exclusions::new(&lib.exclude_globs)?;
+        events::scan_progress(
+            app,
+            ScanProgress {
+                kind: ScanKind::Rescan,
+                status: "walking",
+                processed: discovered_mods,
+                total: discovered_mods,
+                errors: errors.len(),
+                current_path: Some(lib_root.clone()),
+            },
+        );
+        // verbatim paths so deep NAS folders stay below MAX_PATH
+        let root = platform::long_path(Path::new(lib_root));
+        if let Err(e) = platform::retry_transient(|| fs::read_dir(&root).map(|_| ())) {
+            errors.push(ScanError {
+                path: lib_root.clone(),
+                kind: if platform::is_transient(&e) {
+                    "network"
+                } else {
+                    "unreachable"
+                }
+                .to_string(),
+                message: e.to_string(),
+            });
+            continue;
+        }
+        let mut walk_errors = Vec::new();
+        let found = layouts::discover_retrying(&root, layout, &exclusions, &mut walk_errors);
+        errors.extend(walk_errors.iter().map(|e| walk_error_record(lib_root, e)));
+        let total = discovered_mods + found.len();
+
+        // measuring is the slow part on big libraries: done on the worker pool up front,
+        // the upserts below stay on this connection
+        let measured = AtomicUsize::new(0);
+        type Measured = std::io::Result<(manifest::FolderStat, Option<modinfo::ModInfo>)>;
+        let stats: Vec<Measured> = pool.install(|| {
+            found
+                .par_iter()
+                .map(|mod_entry| {
+                    // a folder that cannot be listed would be measured as empty
+                    let stat =
+                        platform::retry_transient(|| fs::read_dir(&mod_entry.path).map(|_| ()))
+                            .map(|_| {
+                                (
+                                    manifest::folder_stat(&mod_entry.path, &ignores),
+                                    modinfo::read(&mod_entry.path),
+                                )
+                            });
+                    events::scan_progress(
+                        app,
+                        ScanProgress {
+                            kind: ScanKind::Rescan,
+                            status: "measuring",
+                            processed: discovered_mods
+                                + measured.fetch_add(1, Ordering::Relaxed)
+                                + 1,
+                            total,
+                            errors: errors.len(),
+                            current_path: Some(mod_entry.path.to_string_lossy().to_string()),
+                        },
+                    );
+                    stat
+                })
+                .collect()
+        });
+
+        let _writer = db::write_lock();
+        for (mod_entry, stat) in found.into_iter().zip(stats) {
+            let author_folder = mod_entry.author_folder.as_deref().unwrap_or_default();
+            let author = infer_author_name(author_folder, &author_aliases);
+            let display_name = mod_entry
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let folder_path = normalize_path_string(&mod_entry.path.to_string_lossy());
+            debug!(
+                "[paths_rescan] discovered author_folder='{}' author='{}' display='{}' folder='{}'",
+                author_folder, author, display_name, folder_path
+            );
+            events::scan_progress(
+                app,
+                ScanProgress {
+                    kind: ScanKind::Rescan,
+                    status: "scanning",
+                    processed: discovered_mods,
+                    total,
+                    errors: errors.len(),
+                    current_path: Some(folder_path.clone()),
+                },
+            );
+            discovered_mods += 1;
+
+            let (stat, info) = match stat {
+                Ok(measured) => measured,
+                Err(e) => {
+                    errors.push(ScanError {
+                        path: folder_path.clone(),
+                        kind: if platform::is_transient(&e) {
+                            "network"
+                        } else {
+                            "io"
+                        }
+                        .to_string(),
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            let outcome = scan_upsert(conn, &folder_path, &display_name, &author, &stat, &now);
+            if matches!(outcome, Ok(ScanOutcome::Added | ScanOutcome::Changed)) {
+                if let Some(info) = info {
+                    let applied = conn
+                        .query_row(
+                            "SELECT id FROM mods WHERE folder_path = ?1",
+                            [&folder_path],
+                            |r| r.get::<_, i64>(0),
+                        )
+                        .map_err(AppError::from)
+                        .and_then(|id| modinfo::apply(conn, id, &info, &now));
+                    if let Err(e) = applied {
+                        errors.push(ScanError {
+                            path: folder_path.clone(),
+                            kind: "modinfo".to_string(),
+                            message: e.to_string(),
+                        });
+                    }
+                }
+            }
+            match outcome {
+                Ok(ScanOutcome::Added) => added += 1,
+                Ok(ScanOutcome::Changed) => changed += 1,
+                Ok(ScanOutcome::Unchanged) => unchanged += 1,
+                Err(e) => errors.push(ScanError {
+                    path: folder_path.clone(),
+                    kind: "database".to_string(),
+                    message: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    // registered mods whose folder is gone (see `mods_audit` to resolve them)
+    let missing = {
+        let mut stmt = conn.prepare("SELECT folder_path FROM mods")?;
+        let paths = stmt
+            .query_map([], |r| r.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.iter().filter(|p| !Path::new(p).is_dir()).count()
+    };
+    let upserts = added + changed;
+
+    let _writer = db::write_lock();
+    let attached = roots::assign(conn)?;
+    authors::sync(conn, &now)?;
+    info!("[paths_rescan] attached {} mods to library roots", attached);
+
+    for err in errors.iter() {
+        warn!(
+            "[paths_rescan] error kind={} path='{}' msg={}",
+            err.kind, err.path, err.message
+        );
+        conn.execute(
+            "INSERT INTO scan_errors (run_id, path, kind, message) VALUES (?1, ?2, ?3, ?4)",
+            params![run_id, err.path, err.kind, err.message],
+        )?;
+    }
+    conn.execute(
+        r#"
+        UPDATE scan_runs
+        SET finished_at = ?2, scanned_dirs = ?3, discovered_mods = ?4, upserts = ?5, errors = ?6
+        WHERE id = ?1
+        "#,
+        params![
+            run_id,
+            now_iso(),
+            scanned_dirs as i64,
+            discovered_mods as i64,
+            upserts as i64,
+            errors.len() as i64
+        ],
+    )?;
+    events::scan_progress(
+        app,
+        ScanProgress {
+            kind: ScanKind::Rescan,
+            status: "done",
+            processed: discovered_mods,
+            total: discovered_mods,
+            errors: errors.len(),
+            current_path: None,
+        },
+    );
+
+    Ok(ScanSummary {
+        run_id,
+        scanned_dirs,
+        discovered_mods,
+        upserts,
+        added,
+        changed,
+        unchanged,
+        missing,
+        errors: errors.len(),
+        failed: errors,
+    })
+}
+
+#[tauri::command]
+pub async fn paths_rescan(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+) -> Result<ScanSummary, AppError> {
+    let progress = app.clone();
+    let summary = with_conn(&pool, move |conn| rescan(conn, Some(&progress))).await?;
+    if summary.added + summary.changed + summary.missing > 0 {
+        events::mods_changed(&app, ModsChangeKind::Reset, Vec::new());
+    }
+    Ok(summary)
+}
+
+/// Lists the per-path errors recorded for a scan run (latest run when `run_id` is omitted).
+#[tauri::command]
+pub async fn scan_errors_list(
+    pool: State<'_, ReadPool>,
+    run_id: Option<i64>,
+) -> Result<Vec<ScanError>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let run_id = match run_id {
+            Some(id) => Some(id),
+            None => conn.query_row("SELECT MAX(id) FROM scan_runs", [], |r| r.get(0))?,
+        };
+        let Some(run_id) = run_id else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT path, kind, message FROM scan_errors WHERE run_id = ?1 ORDER BY id ASC",
+        )?;
+        let rows = stmt.query_map([run_id], |r| {
+            Ok(ScanError {
+                path: r.get(0)?,
+                kind: r.get(1)?,
+                message: r.get(2)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+    })
+    .await
+}
+
+/// Infers character, costume and type of a mod folder from its name and checks its Spine
+/// assets.
+pub(crate) fn draft_for_folder(
+    folder: &Path,
+    author: Option<String>,
+    download_url: Option<String>,
+    catalog: &infer::Catalog,
+    type_aliases: &[(String, String)],
+) -> DraftMod {
+    let display_name = folder
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let folder_path = normalize_path_string(&folder.to_string_lossy());
+
+    let inference = catalog.infer(&display_name);
+    let mut character_id = inference.character_id;
+    let mut costume_id = inference.costume_id;
+
+    let mut mt = infer_mod_type(&display_name, type_aliases);
+    let nsfw = infer::looks_nsfw(&display_name);
+    let validation = validate::validate(folder);
+    let risk_flags = validate::risk_flags(folder);
+    if !risk_flags.is_empty() {
+        warn!(
+            "[import] '{}' contains executables or scripts: {:?}",
+            folder_path, risk_flags
+        );
+    }
+    let sidecar = modinfo::read(folder);
+
+    let mut swap_source_character_id = None;
+    if mt == ModType::Swap {
+        if let Some((source, target)) = catalog.swap_pair(&display_name) {
+            swap_source_character_id = Some(source);
+            if character_id != Some(target) {
+                character_id = Some(target);
+                costume_id =
+                    costume_id.filter(|cid| catalog.costume_character(*cid) == Some(target));
+            }
+        }
+    }
+
+    // a modinfo.json sidecar is trusted over inference
+    let mut author = author;
+    let mut download_url = download_url;
+    let mut confidence = inference.confidence;
+    if let Some(info) = sidecar {
+        if let Some(declared) = info.author.filter(|a| !a.trim().is_empty()) {
+            author = Some(declared);
+        }
+        if let Some(url) = info.download_url {
+            download_url = Some(url);
+        }
+        if let Some(ty) = info.mod_type {
+            mt = ty;
+        }
+        if let Some(ch) = info
+            .character
+            .as_deref()
+            .and_then(|s| catalog.character_by_slug(s))
+        {
+            character_id = Some(ch);
+            costume_id = info
+                .costume
+                .as_deref()
+                .and_then(|s| catalog.costume_by_slug(ch, s));
+            confidence = 1.0;
+        }
+        if let Some(src) = info
+            .swap_source
+            .as_deref()
+            .and_then(|s| catalog.character_by_slug(s))
+        {
+            swap_source_character_id = Some(src);
+        }
+    }
+
+    DraftMod {
+        display_name,
+        folder_path,
+        author,
+        download_url,
+        mod_type: mt,
+        character_id,
+        costume_id,
+        swap_source_character_id,
+        infer_confidence: confidence,
+        infer_candidates: inference.candidates,
+        validation_status: Some(validation.status.as_str().to_string()),
+        validation_problems: validation.problems,
+        validation_messages: validation.messages,
+        nsfw,
+        risk_flags,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeAliasHit {
+    pub alias: String,
+    pub mod_type: ModType,
+}
+
+/// How an import would read a folder name, for `inference_test`.
+#[derive(Debug, Clone, Serialize)]
+pub struct InferenceTest {
+    pub folder_name: String,
+    #[serde(flatten)]
+    pub trace: infer::InferenceTrace,
+    pub type_alias_hits: Vec<TypeAliasHit>,
+    pub mod_type: ModType,
+}
+
+/// Runs character, costume and type inference on an arbitrary name and shows the
+/// workings, so a surprising import ("Celia Burst 2" as Other) can be explained.
+#[tauri::command]
+pub async fn inference_test(
+    pool: State<'_, ReadPool>,
+    folder_name: String,
+) -> Result<InferenceTest, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let type_aliases = aliases::type_aliases(conn)?;
+        let trace = infer::Catalog::load(conn)?.trace(&folder_name);
+        Ok(InferenceTest {
+            type_alias_hits: type_alias_hits(&folder_name, &type_aliases),
+            mod_type: infer_mod_type(&folder_name, &type_aliases),
+            folder_name,
+            trace,
+        })
+    })
+    .await
+}
+
+/// A re-run of folder name inference for a registered mod that differs from its row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReinferProposal {
+    pub mod_id: i64,
+    pub draft: DraftMod,
+    /// Fields the draft would change: "author", "character", "costume", "swap_source",
+    /// "mod_type".
+    pub changes: Vec<String>,
+}
+
+/// Re-infers author, character, costume and type of existing mods against the current
+/// catalog and aliases. Inference never clears a value the row already has.
+fn reinfer_proposals(
+    conn: &Connection,
+    ids: Option<&[i64]>,
+    only_unassigned: bool,
+) -> Result<Vec<ReinferProposal>, AppError> {
+    let catalog = infer::Catalog::load(conn)?;
+    let type_aliases = aliases::type_aliases(conn)?;
+    let author_aliases = aliases::author_aliases(conn)?;
+
+    let rows: Vec<(i64, String, ModPatch)> = {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT id, folder_path, author, download_url, character_id, costume_id,
+                       swap_source_character_id, mod_type
+                FROM mods
+                WHERE (?1 = 0 OR character_id IS NULL)
+                ORDER BY id
+                "#,
+        )?;
+        let rows = stmt.query_map([only_unassigned as i64], |r| {
+            Ok((
+                r.get(0)?,
+                r.get(1)?,
+                ModPatch {
+                    author: r.get(2)?,
+                    download_url: r.get(3)?,
+                    character_id: r.get(4)?,
+                    costume_id: r.get(5)?,
+                    swap_source_character_id: r.get(6)?,
+                    mod_type: Some(ModType::from_str(&r.get::<_, String>(7)?)),
+                    nsfw: None,
+                },
+            ))
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut out = Vec::new();
+    for (mod_id, folder_path, current) in rows {
+        if ids.is_some_and(|ids| !ids.contains(&mod_id)) {
+            continue;
+        }
+        let folder = Path::new(&folder_path);
+        let author = current
+            .author
+            .clone()
+            .filter(|a| !a.trim().is_empty() && a != "unknown")
+            .or_else(|| {
+                folder
+                    .parent()
+                    .and_then(|p| p.file_name())
+                    .map(|n| infer_author_name(&n.to_string_lossy(), &author_aliases))
+            });
+        let mut draft = draft_for_folder(
+            folder,
+            author,
+            current.download_url.clone(),
+            &catalog,
+            &type_aliases,
+        );
+        if draft.character_id.is_none() {
+            draft.character_id = current.character_id;
+        }
+        if draft.character_id == current.character_id && draft.costume_id.is_none() {
+            draft.costume_id = current.costume_id;
+        }
+        if draft.swap_source_character_id.is_none() {
+            draft.swap_source_character_id = current.swap_source_character_id;
+        }
+        if draft.mod_type == ModType::Other {
+            draft.mod_type = current.mod_type.unwrap_or(ModType::Other);
+        }
+
+        let mut changes = Vec::new();
+        if draft.author != current.author && draft.author.as_deref() != Some("unknown") {
+            changes.push("author".to_string());
+        }
+        if draft.character_id != current.character_id {
+            changes.push("character".to_string());
+        }
+        if draft.costume_id != current.costume_id {
+            changes.push("costume".to_string());
+        }
+        if draft.swap_source_character_id != current.swap_source_character_id {
+            changes.push("swap_source".to_string());
+        }
+        if Some(draft.mod_type) != current.mod_type {
+            changes.push("mod_type".to_string());
+        }
+        if !changes.is_empty() {
+            out.push(ReinferProposal {
+                mod_id,
+                draft,
+                changes,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Inserts a draft, or updates the mod already registered at `folder_path`. Returns its id.
+pub(crate) fn upsert_draft(
+    conn: &Connection,
+    d: &DraftMod,
+    folder_path: &str,
+    now: &str,
+) -> Result<i64, AppError> {
+    conn.execute(
+        r#"
+        INSERT INTO mods (
+          character_id, costume_id, author, download_url, installed, installed_at,
+          target_path, mod_type, folder_path, display_name, created_at, updated_at,
+          swap_source_character_id, validation_status, validation_problems, nsfw, risk_flags
+        ) VALUES (?1, ?2, ?3, ?4, 0, NULL, NULL, ?5, ?6, ?7, ?8, ?8, ?9, ?10, ?11, ?12, ?13)
+        ON CONFLICT(folder_path) DO UPDATE SET
+          display_name = excluded.display_name,
+          author = excluded.author,
+          download_url = excluded.download_url,
+          character_id = excluded.character_id,
+          costume_id = excluded.costume_id,
+          mod_type = excluded.mod_type,
+          swap_source_character_id = excluded.swap_source_character_id,
+          validation_status = COALESCE(excluded.validation_status, validation_status),
+          validation_problems = COALESCE(excluded.validation_problems, validation_problems),
+          -- a flag set by hand survives rescans
+          nsfw = MAX(nsfw, excluded.nsfw),
+          -- new or different files need a fresh confirmation
+          risk_confirmed_at = CASE WHEN excluded.risk_flags IS risk_flags
+            THEN risk_confirmed_at ELSE NULL END,
+          risk_flags = excluded.risk_flags,
+          updated_at = excluded.updated_at
+        "#,
+        params![
+            d.character_id,
+            d.costume_id,
+            d.author,
+            d.download_url,
+            d.mod_type.to_string(),
+            folder_path,
+            d.display_name,
+            now,
+            d.swap_source_character_id,
+            d.validation_status,
+            d.validation_status
+                .as_ref()
+                .map(|_| serde_json::to_string(&d.validation_problems).unwrap_or_default()),
+            d.nsfw,
+            validate::risk_flags_json(&d.risk_flags)
+        ],
+    )?;
+    let id = conn.query_row(
+        "SELECT id FROM mods WHERE folder_path = ?1",
+        [folder_path],
+        |r| r.get(0),
+    )?;
+    manifest::store_stat(conn, id, Path::new(folder_path))?;
+    Ok(id)
+}
+
+/// Extracts an archive into `<first library dir>/<author>/` (the dir itself for flat
+/// layouts) and registers the extracted folder as a mod (journaled like any import).
+/// Returns the new mod id. Extraction runs unlocked; `db::write_lock` is taken only for the
+/// registration, so callers must not hold it.
+pub(crate) fn import_archive(
+    conn: &mut Connection,
+    archive_path: &Path,
+    author: Option<String>,
+    download_url: Option<String>,
+) -> Result<i64, AppError> {
+    let settings = load_settings(conn)?;
+    let Some(lib) = settings.library_dirs.iter().find(|d| d.enabled) else {
+        return Err(AppError::not_configured("library_dirs"));
+    };
+    let lib_root = &lib.path;
+    let author = author
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    // also reached from downloads and the wishlist, not only from checked links
+    if !platform::is_single_component(&author) {
+        return Err(AppError::invalid(format!(
+            "Author '{}' cannot be used as a folder name",
+            author
+        )));
+    }
+    let parent = match lib.layout {
+        LibraryLayout::Flat => PathBuf::from(lib_root),
+        _ => Path::new(lib_root).join(&author),
+    };
+    let folder = archive::extract(archive_path, &parent, settings.min_free_space_mb)?;
+
+    let draft = draft_for_folder(
+        &folder,
+        Some(author),
+        download_url,
+        &infer::Catalog::load(conn)?,
+        &aliases::type_aliases(conn)?,
+    );
+    let now = now_iso();
+    let _writer = db::write_lock();
+    let tx = conn.transaction()?;
+    let mod_id = upsert_draft(&tx, &draft, &draft.folder_path, &now)?;
+    roots::register(&tx, lib_root)?;
+    roots::assign(&tx)?;
+    authors::sync(&tx, &now)?;
+    history::log(
+        &tx,
+        "import",
+        &format!("Imported archive '{}'", draft.display_name),
+        &[(mod_id, None)],
+        &now,
+    )?;
+    tx.commit()?;
+    info!(
+        "[import_archive] '{}' -> mod id={} folder='{}'",
+        archive_path.display(),
+        mod_id,
+        draft.folder_path
+    );
+    Ok(mod_id)
+}
+
+#[tauri::command]
+pub async fn mods_import_dry_run(
+    app: AppHandle,
+    pool: State<'_, ReadPool>,
+    author_dir: String,
+    default_author: Option<String>,
+    default_download_url: Option<String>,
+    _default_mod_type: Option<String>,
+) -> Result<Vec<DraftMod>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        use walkdir::WalkDir;
+        info!(
+            "[mods_import_dry_run] dir='{}' default_author={:?}",
+            author_dir, default_author
+        );
+        let catalog = infer::Catalog::load(conn)?;
+        let type_aliases = aliases::type_aliases(conn)?;
+        let author_aliases = aliases::author_aliases(conn)?;
+
+        let inferred_author = std::path::Path::new(&author_dir)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(|s| infer_author_name(s, &author_aliases));
+
+        let author = default_author
+            .and_then(|raw| {
+                let trimmed = raw.trim();
+                if trimmed.is_empty() {
+                    None
+                } else {
+                    Some(trimmed.to_string())
+                }
+            })
+            .or(inferred_author)
+            .map(|name| {
+                if name.trim().is_empty() {
+                    "unknown".to_string()
+                } else {
+                    name
+                }
+            });
+
+        // listed first so progress has a total
+        let folders: Vec<PathBuf> = WalkDir::new(&author_dir)
+            .min_depth(1)
+            .max_depth(1)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_type().is_dir())
+            .map(|e| e.into_path())
+            .collect();
+        let progress = |status, processed, current_path| {
+            events::scan_progress(
+                Some(&app),
+                ScanProgress {
+                    kind: ScanKind::ImportDryRun,
+                    status,
+                    processed,
+                    total: folders.len(),
+                    errors: 0,
+                    current_path,
+                },
+            )
+        };
+
+        let mut out = Vec::new();
+        for (i, folder) in folders.iter().enumerate() {
+            progress(
+                "scanning",
+                i,
+                Some(normalize_path_string(&folder.to_string_lossy())),
+            );
+            out.push(draft_for_folder(
+                folder,
+                author.clone(),
+                default_download_url.clone(),
+                &catalog,
+                &type_aliases,
+            ));
+        }
+        progress("done", folders.len(), None);
+        Ok(out)
+    })
+    .await
+}
+
+/// Registers the drafts in chunks, tracked as an import session: a batch that fails or is
+/// cut short can be finished or rolled back with `import_resume`.
+#[tauri::command]
+pub async fn mods_import_commit(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    drafts: Vec<DraftMod>,
+) -> Result<(usize, usize), AppError> {
+    let outcome = with_write_conn(&pool, move |conn| {
+        info!("[mods_import_commit] committing {} drafts", drafts.len());
+        let now = now_iso();
+        let session_id = imports::start(conn, &drafts, &now)?;
+        let outcome = imports::run(conn, session_id, &now)?;
+        // authors typed in the dialog for folders no alias knew
+        if let Err(err) = aliases::learn_author_aliases(conn, &drafts) {
+            warn!(
+                "[mods_import_commit] failed to learn author aliases: {}",
+                err
+            );
+        }
+        Ok(outcome)
+    })
+    .await?;
+    let (inserted, updated) = (outcome.inserted, outcome.updated);
+    let counts = (inserted.len(), updated.len());
+    events::mods_changed(&app, ModsChangeKind::Added, inserted);
+    events::mods_changed(&app, ModsChangeKind::Updated, updated);
+    Ok(counts)
+}
+
+/// Messages of `locale` ("en", "ko", "ja", "zh"), English where a translation is missing,
+/// to render `i18n::Message`s, error codes and event statuses.
+#[tauri::command]
+pub fn i18n_table(locale: String) -> Result<std::collections::BTreeMap<String, String>, AppError> {
+    i18n::table(&locale)
+}
+
+/// Import batches cut short by a crash or the app closing, to finish or roll back.
+#[tauri::command]
+pub async fn import_sessions_interrupted(
+    pool: State<'_, ReadPool>,
+) -> Result<Vec<ImportSession>, AppError> {
+    with_read_conn(&pool, move |conn| imports::interrupted(conn)).await
+}
+
+/// Commits the rest of an interrupted import (`finish`, the default) or undoes what it
+/// had committed (`rollback`).
+#[tauri::command]
+pub async fn import_resume(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    session_id: i64,
+    action: Option<ResumeAction>,
+) -> Result<ImportOutcome, AppError> {
+    let action = action.unwrap_or_default();
+    let outcome = with_write_conn(&pool, move |conn| {
+        imports::resume(conn, session_id, action, &now_iso())
+    })
+    .await?;
+    match action {
+        ResumeAction::Finish => {
+            events::mods_changed(&app, ModsChangeKind::Added, outcome.inserted.clone());
+            events::mods_changed(&app, ModsChangeKind::Updated, outcome.updated.clone());
+        }
+        ResumeAction::Rollback => events::mods_changed(&app, ModsChangeKind::Reset, Vec::new()),
+    }
+    Ok(outcome)
+}
+
+#[tauri::command]
+pub async fn sanitize_preview(
+    pool: State<'_, ReadPool>,
+    dir: String,
+) -> Result<Vec<RenamePlan>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let rules = load_settings(conn)?.sanitize;
+        info!("[sanitize_preview] dir='{}'", dir);
+        sanitize::plan_for_children(Path::new(&dir), &rules).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn sanitize_apply(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    plans: Vec<RenamePlan>,
+) -> Result<Vec<RenamePlan>, AppError> {
+    let (applied, ids) = with_write_conn(&pool, move |conn| {
+        let now = now_iso();
+        let mut applied = Vec::new();
+        let mut ids = Vec::new();
+        for plan in plans {
+            let from = Path::new(&plan.from);
+            let to = Path::new(&plan.to);
+            // plans come back from the frontend: only renames within the same folder
+            let name = to.file_name().map(|n| n.to_string_lossy().to_string());
+            let Some(name) =
+                name.filter(|n| to.parent() == from.parent() && platform::is_single_component(n))
+            else {
+                info!(
+                    "[sanitize_apply] skipped: '{}' is not a sibling name",
+                    plan.to
+                );
+                continue;
+            };
+            if let Err(err) = shared::ensure_writable(conn, from) {
+                info!("[sanitize_apply] skipped: {}", err);
+                continue;
+            }
+            let from_norm = normalize_path_string(&plan.from);
+            let to_norm = normalize_path_string(
+                &Path::new(&from_norm)
+                    .with_file_name(&name)
+                    .to_string_lossy(),
+            );
+
+            // the rows first, so a failed rename only has to point them back
+            let tx = conn.transaction()?;
+            let n = repoint_folder(&tx, &from_norm, &to_norm, &now)?;
+            roots::assign(&tx)?;
+            tx.commit()?;
+            if let Err(err) = sanitize::apply_plan(&plan) {
+                info!("[sanitize_apply] skipped: {}", err);
+                let tx = conn.transaction()?;
+                repoint_folder(&tx, &to_norm, &from_norm, &now)?;
+                roots::assign(&tx)?;
+                tx.commit()?;
+                continue;
+            }
+            info!(
+                "[sanitize_apply] renamed '{}' -> '{}' (mods updated={})",
+                from_norm, to_norm, n
+            );
+            ids.extend(folder_mod_ids(conn, &to_norm)?);
+            applied.push(plan);
+        }
+        Ok((applied, ids))
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, ids);
+    Ok(applied)
+}
+
+/// Where mods would move under the organize template (`template` overrides the setting
+/// for this preview), see `organize::plan`.
+#[tauri::command]
+pub async fn library_organize_preview(
+    pool: State<'_, ReadPool>,
+    ids: Option<Vec<i64>>,
+    template: Option<String>,
+) -> Result<OrganizePlan, AppError> {
+    with_read_conn(&pool, move |conn| {
+        organize::plan(conn, ids.as_deref(), template.as_deref())
+    })
+    .await
+}
+
+/// Moves mod folders as planned by `library_organize_preview` (pass the same `template`);
+/// installed mods and moves that stray from the plan are refused.
+#[tauri::command]
+pub async fn library_organize(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    moves: Vec<OrganizeMove>,
+    template: Option<String>,
+) -> Result<OrganizeReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        organize::apply(conn, &moves, template.as_deref(), &now_iso())
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, report.moved.clone());
+    Ok(report)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManifestSummary {
+    pub built: usize,
+    pub skipped: usize,
+    pub errors: usize,
+}
+
+// hashes on the worker pool and stores each manifest as it arrives, so the connection
+// stays on this thread; only the stores take `db::write_lock`
+fn build_manifests(
+    conn: &Connection,
+    ids: Option<Vec<i64>>,
+    force: bool,
+    app: Option<&AppHandle>,
+) -> Result<ManifestSummary, AppError> {
+    use rayon::prelude::*;
+
+    let now = now_iso();
+    let settings = load_settings(conn)?;
+    let pool = manifest::worker_pool(settings.scan_threads)?;
+    let ignores = manifest::Ignores::new(&settings.ignore_globs)?;
+    let mut summary = ManifestSummary {
+        built: 0,
+        skipped: 0,
+        errors: 0,
+    };
+    let mut targets: Vec<(i64, String)> = Vec::new();
+    let mut stmt = conn
+        .prepare("SELECT id, folder_path, manifest_built_at IS NOT NULL FROM mods ORDER BY id")?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, bool>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, folder, built) = row?;
+        if !ids.as_ref().map(|ids| ids.contains(&id)).unwrap_or(true) {
+            continue;
+        }
+        if built && !force {
+            summary.skipped += 1;
+        } else {
+            targets.push((id, folder));
+        }
+    }
+
+    let total = targets.len();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let (pool, jobs, ignores) = (&pool, &targets, &ignores);
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            pool.install(|| {
+                jobs.par_iter()
+                    .for_each_with(sender, |sender, (id, folder)| {
+                        let _ =
+                            sender.send((*id, folder, manifest::build(Path::new(folder), ignores)));
+                    })
+            })
+        });
+        for (processed, (id, folder, built)) in receiver.iter().enumerate() {
+            match built.and_then(|entries| {
+                let _writer = db::write_lock();
+                manifest::store(conn, id, &entries, &now)
+            }) {
+                Ok(()) => summary.built += 1,
+                Err(err) => {
+                    warn!("[manifest] failed for mod id={} err={}", id, err);
+                    summary.errors += 1;
+                }
+            }
+            events::scan_progress(
+                app,
+                ScanProgress {
+                    kind: ScanKind::Manifest,
+                    status: "hashing",
+                    processed: processed + 1,
+                    total,
+                    errors: summary.errors,
+                    current_path: Some(folder.clone()),
+                },
+            );
+        }
+    });
+    events::scan_progress(
+        app,
+        ScanProgress {
+            kind: ScanKind::Manifest,
+            status: "done",
+            processed: total,
+            total,
+            errors: summary.errors,
+            current_path: None,
+        },
+    );
+    Ok(summary)
+}
+
+/// Hashes the files of the given mods (all when `ids` is omitted) into `mod_files`.
+#[tauri::command]
+pub async fn mods_build_manifest(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    ids: Option<Vec<i64>>,
+    force: Option<bool>,
+) -> Result<ManifestSummary, AppError> {
+    let force = force.unwrap_or(false);
+    with_conn(&pool, move |conn| {
+        build_manifests(conn, ids, force, Some(&app))
+    })
+    .await
+}
+
+/// Mods containing a file whose relative path matches `pattern` (a substring, or a glob
+/// such as `cutscene_*.skel`), from the file manifests.
+#[tauri::command]
+pub async fn mods_search_files(
+    pool: State<'_, ReadPool>,
+    pattern: String,
+) -> Result<FileSearchReport, AppError> {
+    with_read_conn(&pool, move |conn| manifest::search(conn, &pattern)).await
+}
+
+/// Groups mods whose file contents are identical or near-identical. `threshold` is the
+/// minimum Jaccard similarity of the file hash sets (default 0.9).
+#[tauri::command]
+pub async fn mods_find_duplicates(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    threshold: Option<f32>,
+) -> Result<Vec<DuplicateGroup>, AppError> {
+    let threshold = threshold.unwrap_or(0.9).clamp(0.0, 1.0);
+    with_conn(&pool, move |conn| {
+        let summary = build_manifests(conn, None, false, Some(&app))?;
+        info!(
+            "[mods_find_duplicates] manifests built={} errors={} threshold={}",
+            summary.built, summary.errors, threshold
+        );
+        duplicates::find_groups(conn, threshold)
+    })
+    .await
+}
+
+/// Keeps `keep_id`, copies over metadata it is missing from the duplicates, then removes the
+/// duplicate rows. Folders on disk are left untouched.
+#[tauri::command]
+pub async fn mods_merge_duplicates(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    keep_id: i64,
+    remove_ids: Vec<i64>,
+) -> Result<usize, AppError> {
+    let removed_ids = remove_ids.clone();
+    let removed = with_write_conn(&pool, move |conn| {
+        let tx = conn.transaction()?;
+        let now = now_iso();
+        let mut removed = 0usize;
+        for id in remove_ids.iter().copied().filter(|id| *id != keep_id) {
+            tx.execute(
+                r#"
+                UPDATE mods SET
+                  author = COALESCE(mods.author, src.author),
+                  download_url = COALESCE(mods.download_url, src.download_url),
+                  character_id = COALESCE(mods.character_id, src.character_id),
+                  costume_id = COALESCE(mods.costume_id, src.costume_id),
+                  swap_source_character_id =
+                    COALESCE(mods.swap_source_character_id, src.swap_source_character_id),
+                  updated_at = ?3
+                FROM (
+                  SELECT author, download_url, character_id, costume_id, swap_source_character_id
+                  FROM mods WHERE id = ?2
+                ) AS src
+                WHERE mods.id = ?1
+                "#,
+                params![keep_id, id, now],
+            )?;
+            removed += tx.execute("DELETE FROM mods WHERE id = ?1", params![id])?;
+        }
+        tx.commit()?;
+        for id in remove_ids.into_iter().filter(|id| *id != keep_id) {
+            previews::drop_cache(id);
+        }
+        info!(
+            "[mods_merge_duplicates] kept id={} removed={}",
+            keep_id, removed
+        );
+        Ok(removed)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Deleted, removed_ids);
+    events::mods_changed(&app, ModsChangeKind::Updated, vec![keep_id]);
+    Ok(removed)
+}
+
+/// Installed files the background verifier found modified or missing.
+#[tauri::command]
+pub async fn installed_drift_list(pool: State<'_, ReadPool>) -> Result<Vec<DriftRow>, AppError> {
+    with_read_conn(&pool, move |conn| verify::drift_list(conn)).await
+}
+
+/// Compares every installed mod with the files actually in the game folder. With `repair`,
+/// mods that are not intact are reinstalled.
+#[tauri::command]
+pub async fn game_audit(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    repair: Option<bool>,
+) -> Result<Vec<GameAuditEntry>, AppError> {
+    // hashing runs without the writer lock; the audit takes it per mod to store results
+    let entries = with_conn(&pool, move |conn| {
+        let now = now_iso();
+        let mut entries = verify::game_audit(conn, &now)?;
+        if repair.unwrap_or(false) {
+            let game_dir = load_settings(conn)?
+                .game_mods_dir
+                .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+            for entry in entries
+                .iter_mut()
+                .filter(|e| e.status != GameAuditStatus::Intact)
+            {
+                let _writer = db::write_lock();
+                match install::install(conn, entry.mod_id, None, Path::new(&game_dir), None, &now) {
+                    Ok(_) => entry.repaired = true,
+                    Err(err) => {
+                        warn!(
+                            "[game_audit] repair of mod id={} failed: {}",
+                            entry.mod_id, err
+                        );
+                        entry.error = Some(err.to_string());
+                    }
+                }
+            }
+        }
+        info!(
+            "[game_audit] audited={} not_intact={} repaired={}",
+            entries.len(),
+            entries
+                .iter()
+                .filter(|e| e.status != GameAuditStatus::Intact)
+                .count(),
+            entries.iter().filter(|e| e.repaired).count()
+        );
+        Ok(entries)
+    })
+    .await?;
+    let repaired = entries
+        .iter()
+        .filter(|e| e.repaired)
+        .map(|e| e.mod_id)
+        .collect();
+    events::mods_changed(&app, ModsChangeKind::Updated, repaired);
+    Ok(entries)
+}
+
+/// Optionally applies a profile, audits the installed mods and starts the game, recording
+/// which mods were active.
+#[tauri::command]
+pub async fn game_launch(
+    pool: State<'_, DbPool>,
+    profile_id: Option<i64>,
+) -> Result<GameLaunchReport, AppError> {
+    // the audit hashes without the writer lock; launch takes it for its writes
+    with_conn(&pool, move |conn| {
+        launch::launch(conn, profile_id, &now_iso())
+    })
+    .await
+}
+
+/// Copies mods installed by hand in the game folder into the library and marks them
+/// installed, see `adopt::adopt_existing`.
+#[tauri::command]
+pub async fn game_adopt_existing(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+) -> Result<AdoptReport, AppError> {
+    // copies without the writer lock; each mod takes it to be registered
+    let report = with_conn(&pool, move |conn| adopt::adopt_existing(conn, &now_iso())).await?;
+    events::mods_changed(&app, ModsChangeKind::Added, report.adopted.clone());
+    Ok(report)
+}
+
+/// Past launches with the mods that were installed, newest first.
+#[tauri::command]
+pub async fn game_launches_list(
+    pool: State<'_, ReadPool>,
+    limit: Option<usize>,
+) -> Result<Vec<GameLaunch>, AppError> {
+    with_read_conn(&pool, move |conn| launch::list(conn, limit.unwrap_or(20))).await
+}
+
+#[tauri::command]
+pub async fn aliases_export(
+    pool: State<'_, ReadPool>,
+    path: String,
+) -> Result<AliasDictionary, AppError> {
+    with_read_conn(&pool, move |conn| {
+        info!("[aliases_export] writing '{}'", path);
+        aliases::export_to_path(conn, Path::new(&path))
+    })
+    .await
+}
+
+/// Author folders whose mods have no known author, ranked by mod count, to add aliases for.
+#[tauri::command]
+pub async fn author_alias_suggestions(
+    pool: State<'_, ReadPool>,
+) -> Result<Vec<AuthorAliasSuggestion>, AppError> {
+    with_read_conn(&pool, move |conn| aliases::author_alias_suggestions(conn)).await
+}
+
+#[tauri::command]
+pub async fn aliases_import(
+    pool: State<'_, DbPool>,
+    path: String,
+) -> Result<AliasImportReport, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let report = aliases::import_from_path(conn, Path::new(&path))?;
+        info!(
+            "[aliases_import] '{}' type={} author={} catalog={} skipped={}",
+            path,
+            report.type_aliases,
+            report.author_aliases,
+            report.catalog_aliases,
+            report.skipped
+        );
+        Ok(report)
+    })
+    .await
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatsBucket {
+    pub key: Option<String>,
+    pub mods: usize,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LibraryStats {
+    pub total_mods: usize,
+    pub total_size_bytes: u64,
+    pub by_author: Vec<StatsBucket>,
+    pub by_character: Vec<StatsBucket>,
+    pub by_mod_type: Vec<StatsBucket>,
+    pub missing_previews: usize,
+    pub missing_character: usize,
+    pub missing_on_disk: usize,
+    // mods whose size has not been computed by a rescan yet
+    pub size_unknown: usize,
+}
+
+fn stats_buckets(
+    conn: &Connection,
+    key_sql: &str,
+    from_sql: &str,
+) -> Result<Vec<StatsBucket>, AppError> {
+    let sql = format!(
+        "SELECT {key} AS k, COUNT(*), COALESCE(SUM(m.size_bytes), 0) FROM {from} GROUP BY k ORDER BY 3 DESC, 2 DESC",
+        key = key_sql,
+        from = from_sql
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |r| {
+        Ok(StatsBucket {
+            key: r.get(0)?,
+            mods: r.get::<_, i64>(1)? as usize,
+            size_bytes: r.get::<_, i64>(2)? as u64,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+/// Aggregated library numbers for the stats page. Sizes come from the columns cached by
+/// `paths_rescan`; only cheap existence checks touch the filesystem.
+#[tauri::command]
+pub async fn library_stats(pool: State<'_, ReadPool>) -> Result<LibraryStats, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let (total_mods, total_size, missing_character, size_unknown): (i64, i64, i64, i64) = conn
+            .query_row(
+                r#"
+            SELECT COUNT(*), COALESCE(SUM(size_bytes), 0),
+                   SUM(CASE WHEN character_id IS NULL THEN 1 ELSE 0 END),
+                   SUM(CASE WHEN size_bytes IS NULL THEN 1 ELSE 0 END)
+            FROM mods
+            "#,
+                [],
+                |r| {
+                    Ok((
+                        r.get(0)?,
+                        r.get(1)?,
+                        r.get::<_, Option<i64>>(2)?.unwrap_or(0),
+                        r.get::<_, Option<i64>>(3)?.unwrap_or(0),
+                    ))
+                },
+            )?;
+
+        let mut missing_previews = 0usize;
+        let mut missing_on_disk = 0usize;
+        let storage = load_settings(conn)?.preview_storage;
+        let mut stmt = conn.prepare("SELECT id, folder_path FROM mods")?;
+        let folders = stmt.query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?;
+        for folder in folders {
+            let (id, folder) = folder?;
+            let path = Path::new(&folder);
+            if !path.is_dir() {
+                missing_on_disk += 1;
+                continue;
+            }
+            let preview_dir = previews::dir_for(storage, id, &folder)?;
+            let has_preview = manifest::GENERATED_FILES
+                .iter()
+                .any(|name| preview_dir.join(name).exists());
+            if !has_preview {
+                missing_previews += 1;
+            }
+        }
+
+        Ok(LibraryStats {
+            total_mods: total_mods as usize,
+            total_size_bytes: total_size as u64,
+            by_author: stats_buckets(conn, "m.author", "mods m")?,
+            by_character: stats_buckets(
+                conn,
+                "c.display_name",
+                "mods m LEFT JOIN characters c ON c.id = m.character_id",
+            )?,
+            by_mod_type: stats_buckets(conn, "m.mod_type", "mods m")?,
+            missing_previews,
+            missing_character: missing_character as usize,
+            missing_on_disk,
+            size_unknown: size_unknown as usize,
+        })
+    })
+    .await
+}
+
+#[derive(Serialize)]
+pub struct CatalogCharacterRow {
+    pub id: i64,
+    pub slug: String,
+    pub display_name: String,
+    pub image_url: Option<String>,
+    pub thumbnail_path: Option<String>,
+    /// Mods assigned to the character; 0 flags an unused entry.
+    pub mod_count: usize,
+    /// Swap mods that replace this character.
+    pub swap_source_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct CatalogCostumeRow {
+    pub id: i64,
+    pub character_id: i64,
+    pub slug: String,
+    pub display_name: String,
+    pub image_url: Option<String>,
+    pub thumbnail_path: Option<String>,
+    pub mod_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct CatalogListResponse {
+    pub characters: Vec<CatalogCharacterRow>,
+    pub costumes: Vec<CatalogCostumeRow>,
+    pub orphaned_mods: Vec<OrphanedMod>,
+}
+
+#[tauri::command]
+pub async fn catalog_import_from_file(
+    pool: State<'_, DbPool>,
+    path: String,
+) -> Result<CatalogReport, AppError> {
+    let trimmed = path.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid("path is empty"));
+    }
+    with_write_conn(&pool, move |conn| {
+        let path = Path::new(&trimmed);
+        info!("[catalog] importing from {}", path.display());
+        catalog::sync_from_path(conn, path)
+    })
+    .await
+}
+
+/// Imports a community mapping of catalog costumes to the game's asset ids and cutscene
+/// codes, used by the `{asset}` install path placeholder and install conflict checks.
+#[tauri::command]
+pub async fn costume_assets_import_from_file(
+    pool: State<'_, DbPool>,
+    path: String,
+) -> Result<AssetImportReport, AppError> {
+    let trimmed = path.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid("path is empty"));
+    }
+    with_write_conn(&pool, move |conn| {
+        let path = Path::new(&trimmed);
+        info!("[assets] importing mapping from {}", path.display());
+        assets::import_from_path(conn, path)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn costume_asset_ids(
+    pool: State<'_, ReadPool>,
+    costume_id: i64,
+) -> Result<Vec<CostumeAsset>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        assets::costume_asset_ids(conn, costume_id)
+    })
+    .await
+}
+
+/// Re-applies the bundled catalog even when it was already synced.
+#[tauri::command]
+pub async fn catalog_force_sync(pool: State<'_, DbPool>) -> Result<CatalogReport, AppError> {
+    info!("[catalog] forced builtin sync");
+    with_write_conn(&pool, catalog::sync_builtin).await
+}
+
+#[tauri::command]
+pub async fn catalog_diff_from_file(
+    pool: State<'_, ReadPool>,
+    path: String,
+) -> Result<CatalogDiff, AppError> {
+    let trimmed = path.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid("path is empty"));
+    }
+    with_read_conn(&pool, move |conn| {
+        let path = Path::new(&trimmed);
+        info!("[catalog] computing diff for {}", path.display());
+        catalog::diff_from_path(conn, path)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn catalog_apply_diff(
+    pool: State<'_, DbPool>,
+    diff: CatalogDiff,
+    remove_missing: Option<bool>,
+) -> Result<CatalogReport, AppError> {
+    let remove_missing = remove_missing.unwrap_or(false);
+    info!(
+        "[catalog] applying diff new_characters={} new_costumes={} remove_missing={}",
+        diff.new_characters.len(),
+        diff.new_costumes.len(),
+        remove_missing
+    );
+    with_write_conn(&pool, move |conn| {
+        catalog::apply_diff(conn, diff, remove_missing)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn catalog_list(pool: State<'_, ReadPool>) -> Result<CatalogListResponse, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT c.id, c.slug, c.display_name, c.image_url, c.thumbnail_path,
+                  (SELECT COUNT(*) FROM mods m WHERE m.character_id = c.id),
+                  (SELECT COUNT(*) FROM mods m WHERE m.swap_source_character_id = c.id)
+                FROM characters c
+                "#,
+        )?;
+        let characters = stmt
+            .query_map([], |r| {
+                Ok(CatalogCharacterRow {
+                    id: r.get(0)?,
+                    slug: r.get(1)?,
+                    display_name: r.get(2)?,
+                    image_url: r.get(3)?,
+                    thumbnail_path: r.get(4)?,
+                    mod_count: r.get::<_, i64>(5)? as usize,
+                    swap_source_count: r.get::<_, i64>(6)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT c.id, c.character_id, c.slug, c.display_name, c.image_url, c.thumbnail_path,
+                  (SELECT COUNT(*) FROM mods m WHERE m.costume_id = c.id)
+                FROM costumes c
+                "#,
+        )?;
+        let costumes = stmt
+            .query_map([], |r| {
+                Ok(CatalogCostumeRow {
+                    id: r.get(0)?,
+                    character_id: r.get(1)?,
+                    slug: r.get(2)?,
+                    display_name: r.get(3)?,
+                    image_url: r.get(4)?,
+                    thumbnail_path: r.get(5)?,
+                    mod_count: r.get::<_, i64>(6)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let orphaned_mods = catalog::orphaned_mods(conn)?;
+
+        Ok(CatalogListResponse {
+            characters,
+            costumes,
+            orphaned_mods,
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn catalog_merge_characters(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    src_id: i64,
+    dst_id: i64,
+) -> Result<CatalogMergeReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        catalog::merge_characters(conn, src_id, dst_id)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, report.mod_ids.clone());
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn catalog_merge_costumes(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    src_id: i64,
+    dst_id: i64,
+) -> Result<CatalogMergeReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        catalog::merge_costumes(conn, src_id, dst_id)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, report.mod_ids.clone());
+    Ok(report)
+}
+
+/// Returns the regenerated slug.
+#[tauri::command]
+pub async fn catalog_rename_character(
+    pool: State<'_, DbPool>,
+    id: i64,
+    display_name: String,
+) -> Result<String, AppError> {
+    with_write_conn(&pool, move |conn| {
+        catalog::rename_character(conn, id, &display_name)
+    })
+    .await
+}
+
+/// Returns the regenerated slug.
+#[tauri::command]
+pub async fn catalog_rename_costume(
+    pool: State<'_, DbPool>,
+    id: i64,
+    display_name: String,
+) -> Result<String, AppError> {
+    with_write_conn(&pool, move |conn| {
+        catalog::rename_costume(conn, id, &display_name)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn catalog_fetch_images(
+    pool: State<'_, DbPool>,
+    force: Option<bool>,
+) -> Result<ImageFetchReport, AppError> {
+    let force = force.unwrap_or(false);
+    info!("[catalog] fetching images force={}", force);
+    // downloads unlocked, see `catalog::fetch_images`
+    with_conn(&pool, move |conn| catalog::fetch_images(conn, force)).await
+}
+
+/// Vanilla art of a costume for the compare view; `catalog_fetch_images` caches it.
+#[tauri::command]
+pub async fn costume_preview(
+    pool: State<'_, ReadPool>,
+    costume_id: i64,
+) -> Result<CostumePreview, AppError> {
+    with_read_conn(&pool, move |conn| {
+        catalog::costume_preview(conn, costume_id)
+    })
+    .await
+}
+
+/// Removes every mod in `scope` (all by default) from the library; `delete_files` also
+/// sends their folders to the recycle bin (or deletes them with `permanent_delete`).
+#[tauri::command]
+pub async fn mods_purge_all(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    scope: Option<PurgeScope>,
+    delete_files: Option<bool>,
+) -> Result<PurgeReport, AppError> {
+    let delete_files = delete_files.unwrap_or(false);
+    let report = with_write_conn(&pool, move |conn| {
+        let ids: Vec<i64> = match scope.unwrap_or_default() {
+            PurgeScope::All => {
+                let mut stmt = conn.prepare("SELECT id FROM mods ORDER BY id")?;
+                let rows = stmt.query_map([], |r| r.get(0))?;
+                rows.collect::<Result<_, _>>()?
+            }
+            PurgeScope::Author { author_id } => {
+                let mut stmt =
+                    conn.prepare("SELECT id FROM mods WHERE author_id = ?1 ORDER BY id")?;
+                let rows = stmt.query_map([author_id], |r| r.get(0))?;
+                rows.collect::<Result<_, _>>()?
+            }
+            PurgeScope::Filter { filter } => list_mods(conn, Some(filter))?
+                .into_iter()
+                .map(|m| m.row.id)
+                .collect(),
+        };
+        let settings = load_settings(conn)?;
+        removal::purge(
+            conn,
+            &ids,
+            delete_files,
+            settings.permanent_delete,
+            settings.game_mods_dir.as_deref().map(Path::new),
+            &now_iso(),
+        )
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Deleted, report.deleted.clone());
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn db_maintenance(pool: State<'_, DbPool>) -> Result<MaintenanceReport, AppError> {
+    info!("[db_maintenance] running VACUUM/ANALYZE");
+    with_write_conn(&pool, move |conn| {
+        db::maintenance(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn db_backup(pool: State<'_, ReadPool>, path: String) -> Result<(), AppError> {
+    let trimmed = path.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid("path is empty"));
+    }
+    with_read_conn(&pool, move |conn| {
+        db::backup_to(conn, Path::new(&trimmed)).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn db_restore(pool: State<'_, DbPool>, path: String) -> Result<i64, AppError> {
+    let trimmed = path.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid("path is empty"));
+    }
+    with_write_conn(&pool, move |conn| {
+        db::restore_from(conn, Path::new(&trimmed))
+            .map_err(|e| format!("{:#}", e))
+            .map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn library_roots_list(pool: State<'_, ReadPool>) -> Result<Vec<LibraryRoot>, AppError> {
+    with_read_conn(&pool, move |conn| roots::list(conn)).await
+}
+
+/// Moves every mod below `old_root` to `new_root` (e.g. after the library drive letter
+/// changed) and updates the configured library folders accordingly.
+#[tauri::command]
+pub async fn library_relocate(
+    pool: State<'_, DbPool>,
+    old_root: String,
+    new_root: String,
+) -> Result<RelocateReport, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let new_root = normalize_path_string(new_root.trim());
+        let report = roots::relocate(conn, old_root.trim(), &new_root)?;
+        info!(
+            "[library_relocate] '{}' -> '{}' updated={} missing={}",
+            report.old_root,
+            report.new_root,
+            report.updated,
+            report.missing.len()
+        );
+
+        let mut settings = load_settings(conn)?;
+        let old_key = platform::path_key(&normalize_path_string(&report.old_root));
+        for dir in settings.library_dirs.iter_mut() {
+            if platform::path_key(&normalize_path_string(&dir.path)) == old_key {
+                dir.path = new_root.clone();
+            }
+        }
+        save_settings(conn, &settings)?;
+        Ok(report)
+    })
+    .await
+}
+
+/// Flags mods whose folder disappeared and suggests how to reconcile each one.
+/// Meant to run right after `paths_rescan`, which picks up moved folders as new rows.
+#[tauri::command]
+pub async fn mods_audit(pool: State<'_, DbPool>) -> Result<Vec<AuditEntry>, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let entries = audit::run(conn, &now_iso())?;
+        info!(
+            "[mods_audit] missing={} with_candidate={}",
+            entries.len(),
+            entries.iter().filter(|e| e.candidate.is_some()).count()
+        );
+        Ok(entries)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn mods_audit_apply(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    resolutions: Vec<AuditResolution>,
+) -> Result<AuditApplyReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        let report = audit::apply(conn, &resolutions, &now_iso())?;
+        info!(
+            "[mods_audit_apply] relinked={} marked_missing={} deleted={}",
+            report.relinked, report.marked_missing, report.deleted
+        );
+        Ok(report)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Reset, Vec::new());
+    Ok(report)
+}
+
+/// Applies the fields set in `patch` to every mod in `ids` (journaled, see `history_undo`).
+/// With `write_sidecar` the result is also written to each folder's `modinfo.json`.
+#[tauri::command]
+pub async fn mods_bulk_update(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    ids: Vec<i64>,
+    patch: ModPatch,
+    write_sidecar: Option<bool>,
+) -> Result<usize, AppError> {
+    let updated = with_write_conn(&pool, move |conn| {
+        let now = now_iso();
+        let tx = conn.transaction()?;
+        let mut journal = Vec::new();
+        for id in ids {
+            let Some(before) = history::snapshot(&tx, id)? else {
+                continue;
+            };
+            tx.execute(
+                r#"
+                UPDATE mods SET
+                  author = COALESCE(?2, author),
+                  download_url = COALESCE(?3, download_url),
+                  character_id = COALESCE(?4, character_id),
+                  costume_id = COALESCE(?5, costume_id),
+                  swap_source_character_id = COALESCE(?6, swap_source_character_id),
+                  mod_type = COALESCE(?7, mod_type),
+                  nsfw = COALESCE(?9, nsfw),
+                  updated_at = ?8
+                WHERE id = ?1
+                "#,
+                params![
+                    id,
+                    patch.author,
+                    patch.download_url,
+                    patch.character_id,
+                    patch.costume_id,
+                    patch.swap_source_character_id,
+                    patch.mod_type.map(|t| t.to_string()),
+                    now,
+                    patch.nsfw
+                ],
+            )?;
+            journal.push((id, Some(before)));
+        }
+        let updated = journal.len();
+        authors::sync(&tx, &now)?;
+        history::log(
+            &tx,
+            "bulk_edit",
+            &format!("Edited {} mods", updated),
+            &journal,
+            &now,
+        )?;
+        tx.commit()?;
+        if write_sidecar.unwrap_or(false) {
+            for (id, _) in journal.iter() {
+                if let Err(err) = modinfo::write_back(conn, *id) {
+                    warn!("[mods_bulk_update] modinfo.json for id={}: {}", id, err);
+                }
+            }
+        }
+        info!("[mods_bulk_update] updated {} mods", updated);
+        Ok(journal.into_iter().map(|(id, _)| id).collect::<Vec<i64>>())
+    })
+    .await?;
+    let count = updated.len();
+    events::mods_changed(&app, ModsChangeKind::Updated, updated);
+    Ok(count)
+}
+
+/// Proposes re-inferred metadata for existing mods (all, or `ids`; with `only_unassigned`
+/// just those without a character) so catalog improvements reach old imports. Nothing is
+/// written; pass the accepted proposals to `mods_reinfer_apply`.
+#[tauri::command]
+pub async fn mods_reinfer(
+    pool: State<'_, ReadPool>,
+    ids: Option<Vec<i64>>,
+    only_unassigned: Option<bool>,
+) -> Result<Vec<ReinferProposal>, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let proposals = reinfer_proposals(conn, ids.as_deref(), only_unassigned.unwrap_or(false))?;
+        info!("[mods_reinfer] {} mods would change", proposals.len());
+        Ok(proposals)
+    })
+    .await
+}
+
+/// Writes confirmed re-inference proposals (journaled, see `history_undo`).
+#[tauri::command]
+pub async fn mods_reinfer_apply(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    proposals: Vec<ReinferProposal>,
+) -> Result<usize, AppError> {
+    let updated = with_write_conn(&pool, move |conn| {
+        let now = now_iso();
+        let tx = conn.transaction()?;
+        let mut journal = Vec::new();
+        for p in proposals.iter() {
+            let Some(before) = history::snapshot(&tx, p.mod_id)? else {
+                continue;
+            };
+            let d = &p.draft;
+            tx.execute(
+                r#"
+                UPDATE mods SET
+                  author = COALESCE(?2, author),
+                  character_id = ?3,
+                  costume_id = ?4,
+                  swap_source_character_id = ?5,
+                  mod_type = ?6,
+                  updated_at = ?7
+                WHERE id = ?1
+                "#,
+                params![
+                    p.mod_id,
+                    d.author.as_deref().filter(|a| *a != "unknown"),
+                    d.character_id,
+                    d.costume_id,
+                    d.swap_source_character_id,
+                    d.mod_type.to_string(),
+                    now
+                ],
+            )?;
+            journal.push((p.mod_id, Some(before)));
+        }
+        let updated = journal.len();
+        authors::sync(&tx, &now)?;
+        history::log(
+            &tx,
+            "reinfer",
+            &format!("Re-inferred {} mods", updated),
+            &journal,
+            &now,
+        )?;
+        tx.commit()?;
+        info!("[mods_reinfer_apply] updated {} mods", updated);
+        Ok(journal.into_iter().map(|(id, _)| id).collect::<Vec<i64>>())
+    })
+    .await?;
+    let count = updated.len();
+    events::mods_changed(&app, ModsChangeKind::Updated, updated);
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn history_list(
+    pool: State<'_, ReadPool>,
+    limit: Option<usize>,
+) -> Result<Vec<Operation>, AppError> {
+    let limit = limit.unwrap_or(50);
+    with_read_conn(&pool, move |conn| history::list(conn, limit)).await
+}
+
+/// Reverts a journaled import, bulk edit, install or uninstall.
+#[tauri::command]
+pub async fn history_undo(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    op_id: i64,
+) -> Result<UndoReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        let now = now_iso();
+        let report = history::undo(conn, op_id, &now)?;
+        // restored author strings are re-linked to their author rows
+        authors::sync(conn, &now)?;
+        info!(
+            "[history_undo] op id={} restored={} removed={}",
+            op_id, report.restored, report.removed
+        );
+        Ok(report)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Reset, Vec::new());
+    Ok(report)
+}
+
+/// The mod's files with the install selection flags (builds the manifest if needed).
+#[tauri::command]
+pub async fn mod_files_list(
+    pool: State<'_, DbPool>,
+    id: i64,
+) -> Result<Vec<ModFileEntry>, AppError> {
+    with_write_conn(&pool, move |conn| install::files_list(conn, id, &now_iso())).await
+}
+
+/// Every install/uninstall of the mod, newest first.
+#[tauri::command]
+pub async fn mods_history(
+    pool: State<'_, ReadPool>,
+    id: i64,
+) -> Result<Vec<InstallEvent>, AppError> {
+    with_read_conn(&pool, move |conn| install::events(conn, id)).await
+}
+
+/// Copies the mod into the game mods folder. `include_paths` picks a subset of files and is
+/// remembered for later reinstalls.
+#[tauri::command]
+pub async fn mods_install(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+    include_paths: Option<Vec<String>>,
+) -> Result<InstallReport, AppError> {
+    // hashes and copies unlocked, see `install::install_unlocked`
+    let report = with_conn(&pool, move |conn| {
+        let game_dir = load_settings(conn)?
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        let report = install::install_unlocked(
+            conn,
+            id,
+            include_paths,
+            Path::new(&game_dir),
+            None,
+            &now_iso(),
+        )?;
+        info!(
+            "[mods_install] id={} target='{}' copied={} skipped={}",
+            id, report.target_path, report.files_copied, report.files_skipped
+        );
+        Ok(report)
+    })
+    .await?;
+    events::mods_changed(
+        &app,
+        ModsChangeKind::Uninstalled,
+        report.swapped_out.clone(),
+    );
+    events::mods_changed(&app, ModsChangeKind::Installed, vec![id]);
+    Ok(report)
+}
+
+/// Installs cut short by a crash or a failed copy, to roll back or replay.
+#[tauri::command]
+pub async fn install_journal_incomplete(
+    pool: State<'_, ReadPool>,
+) -> Result<Vec<JournalEntry>, AppError> {
+    with_read_conn(&pool, move |conn| journal::incomplete(conn)).await
+}
+
+/// Removes what an interrupted install had copied (`rollback`, the default) or runs it
+/// again (`replay`).
+#[tauri::command]
+pub async fn install_journal_resolve(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+    action: Option<JournalAction>,
+) -> Result<JournalResolution, AppError> {
+    let action = action.unwrap_or_default();
+    let resolution = with_write_conn(&pool, move |conn| {
+        let game_dir = load_settings(conn)?
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        journal::resolve(conn, id, action, Path::new(&game_dir), &now_iso())
+    })
+    .await?;
+    match &resolution.report {
+        Some(report) => {
+            events::mods_changed(
+                &app,
+                ModsChangeKind::Uninstalled,
+                report.swapped_out.clone(),
+            );
+            events::mods_changed(&app, ModsChangeKind::Installed, vec![resolution.mod_id]);
+        }
+        None => events::mods_changed(&app, ModsChangeKind::Uninstalled, vec![resolution.mod_id]),
+    }
+    Ok(resolution)
+}
+
+/// Installs every favorite of `character_id` (all favorites without one) as a batch that is
+/// undone on the first failure. Progress goes out as `install-progress`.
+#[tauri::command]
+pub async fn mods_install_favorites(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    character_id: Option<i64>,
+) -> Result<FavoritesReport, AppError> {
+    apply_favorites(app, pool, character_id, true).await
+}
+
+/// The inverse of `mods_install_favorites`: uninstalls the installed favorites.
+#[tauri::command]
+pub async fn mods_uninstall_favorites(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    character_id: Option<i64>,
+) -> Result<FavoritesReport, AppError> {
+    apply_favorites(app, pool, character_id, false).await
+}
+
+async fn apply_favorites(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    character_id: Option<i64>,
+    install: bool,
+) -> Result<FavoritesReport, AppError> {
+    let handle = app.clone();
+    let report = with_write_conn(&pool, move |conn| {
+        let game_dir = load_settings(conn)?
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        favorites::apply(
+            conn,
+            Some(&handle),
+            character_id,
+            install,
+            Path::new(&game_dir),
+            &now_iso(),
+        )
+    })
+    .await?;
+    if report.error.is_some() {
+        // a rollback may have put swapped-out variants back
+        events::mods_changed(&app, ModsChangeKind::Reset, Vec::new());
+    } else {
+        events::mods_changed(&app, ModsChangeKind::Installed, report.installed.clone());
+        let uninstalled = [report.uninstalled.clone(), report.swapped_out.clone()].concat();
+        events::mods_changed(&app, ModsChangeKind::Uninstalled, uninstalled);
+    }
+    Ok(report)
+}
+
+/// Rebuilds installed state from symbolic links in the game mods folder (mods linked there
+/// by hand), see `gamelinks::scan`. Only reports unless `apply` is set.
+#[tauri::command]
+pub async fn game_scan_links(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    apply: Option<bool>,
+) -> Result<LinkScanReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        let game_dir = load_settings(conn)?
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        gamelinks::scan(
+            conn,
+            Path::new(&game_dir),
+            apply.unwrap_or(false),
+            &now_iso(),
+        )
+    })
+    .await?;
+    if report.applied {
+        let ids = |mods: &[LinkedMod]| mods.iter().map(|m| m.mod_id).collect();
+        events::mods_changed(&app, ModsChangeKind::Installed, ids(&report.linked));
+        events::mods_changed(&app, ModsChangeKind::Uninstalled, ids(&report.unlinked));
+    }
+    Ok(report)
+}
+
+/// The file operations installing the mod would perform in the game folder (copies,
+/// overwrites, deletions and whose files they hit), without changing anything.
+#[tauri::command]
+pub async fn mods_install_dry_run(
+    pool: State<'_, DbPool>,
+    id: i64,
+    include_paths: Option<Vec<String>>,
+) -> Result<InstallPlan, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let game_dir = load_settings(conn)?
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        install::dry_run(conn, id, include_paths, Path::new(&game_dir), &now_iso())
+    })
+    .await
+}
+
+/// Whether installing the mod fits on the game folder's volume, for warning up front.
+#[tauri::command]
+pub async fn install_space_check(
+    pool: State<'_, ReadPool>,
+    id: i64,
+) -> Result<SpaceCheck, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let settings = load_settings(conn)?;
+        let game_dir = settings
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        install::space_check(conn, id, Path::new(&game_dir), settings.min_free_space_mb)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn mod_groups_list(pool: State<'_, ReadPool>) -> Result<Vec<ModGroup>, AppError> {
+    with_read_conn(&pool, move |conn| groups::list(conn)).await
+}
 
-    let settings: AppSettings = val
-        .and_then(|json| serde_json::from_str(&json).ok())
-        .unwrap_or_default();
-    println!(
-        "[settings_get] loaded library_dirs={} game_mods_dir={:?} last_library_pick={:?}",
-        settings.library_dirs.len(),
-        settings.game_mods_dir,
-        settings.last_library_pick
-    );
-    Ok(settings)
+/// Groups `mod_ids` as variants of one mod and returns the new group id.
+#[tauri::command]
+pub async fn mod_groups_create(
+    pool: State<'_, DbPool>,
+    name: String,
+    mod_ids: Vec<i64>,
+) -> Result<i64, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let tx = conn.transaction()?;
+        let id = groups::create(&tx, &name, &mod_ids, &now_iso())?;
+        tx.commit()?;
+        info!(
+            "[mod_groups_create] id={} name='{}' members={}",
+            id,
+            name,
+            mod_ids.len()
+        );
+        Ok(id)
+    })
+    .await
 }
 
+/// Adds mods to a group, or ungroups them when `group_id` is omitted.
 #[tauri::command]
-pub fn settings_set(new_settings: AppSettings) -> Result<AppSettings, String> {
-    println!(
-        "[settings_set] saving settings library_dirs={} game_mods_dir={:?} last_library_pick={:?}",
-        new_settings.library_dirs.len(),
-        new_settings.game_mods_dir,
-        new_settings.last_library_pick
+pub async fn mod_groups_assign(
+    pool: State<'_, DbPool>,
+    group_id: Option<i64>,
+    mod_ids: Vec<i64>,
+) -> Result<usize, AppError> {
+    with_write_conn(&pool, move |conn| {
+        groups::set_group(conn, group_id, &mod_ids)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn mod_groups_set_active(
+    pool: State<'_, DbPool>,
+    group_id: i64,
+    mod_id: i64,
+) -> Result<(), AppError> {
+    with_write_conn(&pool, move |conn| {
+        groups::set_active(conn, group_id, mod_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn mod_groups_delete(pool: State<'_, DbPool>, group_id: i64) -> Result<(), AppError> {
+    with_write_conn(&pool, move |conn| groups::delete(conn, group_id)).await
+}
+
+#[tauri::command]
+pub async fn profiles_list(pool: State<'_, ReadPool>) -> Result<Vec<Profile>, AppError> {
+    with_read_conn(&pool, move |conn| profiles::list(conn)).await
+}
+
+/// Creates a profile from `mod_ids` (or replaces the mods of an existing one by that name).
+#[tauri::command]
+pub async fn profile_save(
+    pool: State<'_, DbPool>,
+    name: String,
+    mod_ids: Vec<i64>,
+) -> Result<i64, AppError> {
+    with_write_conn(&pool, move |conn| {
+        profiles::save(conn, &name, &mod_ids, &now_iso())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn profile_delete(pool: State<'_, DbPool>, id: i64) -> Result<(), AppError> {
+    with_write_conn(&pool, move |conn| profiles::delete(conn, id)).await
+}
+
+/// Installs exactly the profile's mods, uninstalling every other installed mod.
+#[tauri::command]
+pub async fn profile_apply(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+) -> Result<ProfileApplyReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        let game_dir = load_settings(conn)?
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        profiles::apply(conn, id, Path::new(&game_dir), &now_iso())
+    })
+    .await?;
+    events::mods_changed(
+        &app,
+        ModsChangeKind::Uninstalled,
+        report.uninstalled.clone(),
     );
-    let conn = con().map_err(|e| e.to_string())?;
-    let json = serde_json::to_string(&new_settings).map_err(|e| e.to_string())?;
-    conn.execute(
-        r#"
-        INSERT INTO settings(key, value_json)
-        VALUES ('app_settings', ?1)
-        ON CONFLICT(key) DO UPDATE SET value_json=excluded.value_json
-        "#,
-        rusqlite::params![json],
-    )
-    .map_err(|e| e.to_string())?;
-    Ok(new_settings)
+    events::mods_changed(&app, ModsChangeKind::Installed, report.installed.clone());
+    Ok(report)
 }
 
+/// Safe mode: turns every mod off (uninstalls them, or with `rename_folder` moves the game's
+/// mods folder aside) and remembers what was on for `mods_restore_last_state`.
 #[tauri::command]
-pub fn library_author_dirs(lib_root: String) -> Result<Vec<AuthorFolder>, String> {
-    use walkdir::WalkDir;
-    println!("[library_author_dirs] root='{}'", lib_root);
-    let mut out = Vec::new();
-    for entry in WalkDir::new(&lib_root).min_depth(1).max_depth(1) {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(e) => {
-                println!(
-                    "[library_author_dirs] failed to access entry under '{}' err={}",
-                    lib_root, e
-                );
-                continue;
-            }
+pub async fn mods_disable_all(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    rename_folder: Option<bool>,
+) -> Result<SafeModeReport, AppError> {
+    let method = if rename_folder.unwrap_or(false) {
+        DisableMethod::RenameFolder
+    } else {
+        DisableMethod::Uninstall
+    };
+    let report = with_write_conn(&pool, move |conn| {
+        let game_dir = load_settings(conn)?
+            .game_mods_dir
+            .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+        safemode::disable_all(conn, Path::new(&game_dir), method, &now_iso())
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Uninstalled, report.mod_ids.clone());
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn mods_restore_last_state(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+) -> Result<SafeModeReport, AppError> {
+    let report =
+        with_write_conn(&pool, move |conn| safemode::restore_last(conn, &now_iso())).await?;
+    events::mods_changed(&app, ModsChangeKind::Installed, report.mod_ids.clone());
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn authors_list(pool: State<'_, ReadPool>) -> Result<Vec<Author>, AppError> {
+    with_read_conn(&pool, move |conn| authors::list(conn)).await
+}
+
+/// Merges `source_ids` into `target_id`; returns the number of mods that moved.
+#[tauri::command]
+pub async fn authors_merge(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    target_id: i64,
+    source_ids: Vec<i64>,
+) -> Result<usize, AppError> {
+    let (moved, ids) = with_write_conn(&pool, move |conn| {
+        let tx = conn.transaction()?;
+        let moved = authors::merge(&tx, target_id, &source_ids)?;
+        tx.commit()?;
+        let ids = if moved > 0 {
+            authors::mod_ids(conn, target_id)?
+        } else {
+            Vec::new()
         };
-        if !entry.file_type().is_dir() {
-            continue;
+        Ok((moved, ids))
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, ids);
+    Ok(moved)
+}
+
+#[tauri::command]
+pub async fn authors_update(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+    patch: AuthorPatch,
+) -> Result<(), AppError> {
+    let ids = with_write_conn(&pool, move |conn| {
+        let tx = conn.transaction()?;
+        authors::update(&tx, id, &patch)?;
+        tx.commit()?;
+        // only a rename reaches the mods
+        if patch.name.is_some() {
+            authors::mod_ids(conn, id)
+        } else {
+            Ok(Vec::new())
         }
-        let folder_path = normalize_path_string(&entry.path().to_string_lossy());
-        let folder_name = entry.file_name().to_string_lossy().to_string();
-        let inferred = infer_author_name(&folder_name);
-        out.push(AuthorFolder {
-            folder_path,
-            inferred_author: inferred,
-        });
-    }
-    Ok(out)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Updated, ids);
+    Ok(())
 }
 
+/// Extracts a downloaded archive into the library and registers it as a mod.
 #[tauri::command]
-pub fn paths_rescan() -> Result<ScanSummary, String> {
-    use walkdir::WalkDir;
-    println!("[paths_rescan] started");
-    let conn = con().map_err(|e| e.to_string())?;
-    let settings = settings_get()?;
+pub async fn mods_import_archive(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    path: String,
+    author: Option<String>,
+    download_url: Option<String>,
+) -> Result<i64, AppError> {
+    let id = with_conn(&pool, move |conn| {
+        import_archive(conn, Path::new(&path), author, download_url)
+    })
+    .await?;
+    events::mods_changed(&app, ModsChangeKind::Added, vec![id]);
+    Ok(id)
+}
 
-    let mut scanned_dirs = 0usize;
-    let mut discovered_mods = 0usize;
-    let mut upserts = 0usize;
-    let mut errors = 0usize;
-    let now = now_iso();
+#[tauri::command]
+pub async fn downloads_list(pool: State<'_, ReadPool>) -> Result<Vec<Download>, AppError> {
+    with_read_conn(&pool, move |conn| downloads::list(conn)).await
+}
 
-    for lib_root in settings.library_dirs.iter() {
-        scanned_dirs += 1;
+/// Queues a download and starts it; progress arrives as `download-progress` events and the
+/// finished archive is imported into the library.
+#[tauri::command]
+pub async fn download_enqueue(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    url: String,
+    author: Option<String>,
+) -> Result<i64, AppError> {
+    let id = with_write_conn(&pool, move |conn| downloads::enqueue(conn, &url, author)).await?;
+    downloads::start(app, pool.inner().clone(), id)?;
+    Ok(id)
+}
 
-        println!("[paths_rescan] scanning library root='{}'", lib_root);
-        // Expect structure: lib_root/AuthorName/ModFolder
-        for author_entry in WalkDir::new(lib_root).min_depth(1).max_depth(1) {
-            let author_entry = match author_entry {
-                Ok(e) => e,
-                Err(_) => {
-                    errors += 1;
-                    continue;
-                }
-            };
-            if !author_entry.file_type().is_dir() {
-                continue;
-            }
-            let author_folder = author_entry.file_name().to_string_lossy().to_string();
-            let author = infer_author_name(&author_folder);
-
-            // Iterate mod folders inside this author folder
-            for mod_entry in WalkDir::new(author_entry.path()).min_depth(1).max_depth(1) {
-                let mod_entry = match mod_entry {
-                    Ok(e) => e,
-                    Err(_) => {
-                        errors += 1;
-                        continue;
-                    }
-                };
-                if !mod_entry.file_type().is_dir() {
-                    continue;
-                }
-                let display_name = mod_entry.file_name().to_string_lossy().to_string();
-                let folder_path = normalize_path_string(&mod_entry.path().to_string_lossy());
-                println!(
-                    "[paths_rescan] discovered author_folder='{}' author='{}' display='{}' folder='{}'",
-                    author_folder, author, display_name, folder_path
-                );
-                discovered_mods += 1;
-
-                // Upsert (author + names)
-                let n = conn
-                    .execute(
-                        r#"
-                    INSERT INTO mods (
-                      character_id, costume_id, author, download_url, installed, installed_at,
-                      target_path, mod_type, folder_path, display_name, created_at, updated_at
-                    ) VALUES (NULL, NULL, ?1, NULL, 0, NULL, NULL, 'other', ?2, ?3, ?4, ?4)
-                    ON CONFLICT(folder_path) DO UPDATE SET
-                      display_name=excluded.display_name,
-                      author=excluded.author,
-                      updated_at=excluded.updated_at
-                    "#,
-                        rusqlite::params![author, folder_path, display_name, now],
-                    )
-                    .map_err(|e| e.to_string())?;
-                if n > 0 {
-                    upserts += 1;
-                }
-            }
+#[tauri::command]
+pub fn download_pause(id: i64) -> Result<bool, AppError> {
+    Ok(downloads::pause(id))
+}
+
+/// Resumes a paused or failed download from its partial file.
+#[tauri::command]
+pub async fn download_resume(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: i64,
+) -> Result<(), AppError> {
+    downloads::start(app, pool.inner().clone(), id)
+}
+
+#[tauri::command]
+pub async fn download_cancel(pool: State<'_, DbPool>, id: i64) -> Result<(), AppError> {
+    with_write_conn(&pool, move |conn| downloads::cancel(conn, id)).await
+}
+
+#[tauri::command]
+pub async fn wishlist_list(pool: State<'_, ReadPool>) -> Result<Vec<WishlistItem>, AppError> {
+    with_read_conn(&pool, move |conn| wishlist::list(conn)).await
+}
+
+/// Notes a mod to get later; `name` defaults to the URL.
+#[tauri::command]
+pub async fn wishlist_add(
+    pool: State<'_, DbPool>,
+    url: String,
+    name: String,
+    author: Option<String>,
+    note: Option<String>,
+) -> Result<i64, AppError> {
+    with_write_conn(&pool, move |conn| {
+        wishlist::add(conn, &url, &name, author, note, &now_iso())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn wishlist_remove(pool: State<'_, DbPool>, id: i64) -> Result<bool, AppError> {
+    with_write_conn(&pool, move |conn| wishlist::remove(conn, id)).await
+}
+
+#[tauri::command]
+pub async fn wishlist_mark_acquired(
+    pool: State<'_, DbPool>,
+    id: i64,
+    mod_id: i64,
+) -> Result<(), AppError> {
+    with_write_conn(&pool, move |conn| {
+        wishlist::mark_acquired(conn, id, mod_id, &now_iso())
+    })
+    .await
+}
+
+/// Sends open wishlist items (all, or `ids`) to the download manager; each finished
+/// download marks its item acquired.
+#[tauri::command]
+pub async fn wishlist_enqueue(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    ids: Option<Vec<i64>>,
+) -> Result<WishlistEnqueueReport, AppError> {
+    let mut report =
+        with_write_conn(&pool, move |conn| wishlist::enqueue(conn, ids.as_deref())).await?;
+    for id in report.download_ids.iter() {
+        if let Err(err) = downloads::start(app.clone(), pool.inner().clone(), *id) {
+            report.errors.push(format!("download id={}: {}", id, err));
         }
     }
+    Ok(report)
+}
 
-    Ok(ScanSummary {
-        scanned_dirs,
-        discovered_mods,
-        upserts,
-        errors,
-    })
+/// Install links (`bd2mods://install?url=...`) received and not yet confirmed.
+#[tauri::command]
+pub fn deep_links_pending() -> Vec<deeplink::InstallRequest> {
+    deeplink::list()
 }
 
+/// Enqueues the download of a confirmed install link; returns the download id.
 #[tauri::command]
-pub fn mods_import_dry_run(
-    author_dir: String,
-    default_author: Option<String>,
-    default_download_url: Option<String>,
-    _default_mod_type: Option<String>,
-) -> Result<Vec<DraftMod>, String> {
-    use walkdir::WalkDir;
-    println!(
-        "[mods_import_dry_run] dir='{}' default_author={:?}",
-        author_dir, default_author
-    );
-    let conn = con().map_err(|e| e.to_string())?;
-    let chars = db_characters(&conn)?;
-    let costumes = db_costumes(&conn)?;
+pub async fn deep_link_confirm(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    id: u64,
+) -> Result<i64, AppError> {
+    let request = deeplink::take(id).ok_or_else(|| format!("No pending link with id {}", id))?;
+    let download_id = with_write_conn(&pool, move |conn| {
+        downloads::enqueue(conn, &request.url, request.author)
+    })
+    .await?;
+    downloads::start(app, pool.inner().clone(), download_id)?;
+    Ok(download_id)
+}
 
-    let inferred_author = std::path::Path::new(&author_dir)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .map(|s| infer_author_name(s));
+#[tauri::command]
+pub fn deep_link_dismiss(id: u64) -> bool {
+    deeplink::take(id).is_some()
+}
 
-    let author = default_author
-        .and_then(|raw| {
-            let trimmed = raw.trim();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(trimmed.to_string())
-            }
-        })
-        .or(inferred_author)
-        .map(|name| {
-            if name.trim().is_empty() {
-                "unknown".to_string()
-            } else {
-                name
-            }
-        });
+#[tauri::command]
+pub fn shell_integration_status() -> ShellIntegrationStatus {
+    shell::status()
+}
 
-    let mut out = Vec::new();
-    for entry in WalkDir::new(&author_dir).min_depth(1).max_depth(1) {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        if !entry.file_type().is_dir() {
-            continue;
-        }
-        let display_name = entry.file_name().to_string_lossy().to_string();
-        let folder_path = normalize_path_string(&entry.path().to_string_lossy());
+/// Adds "Import into BD2 Mods Handler" to the Explorer menu of folders and .zip files
+/// (Windows only, current user).
+#[tauri::command]
+pub fn shell_integration_install() -> Result<(), AppError> {
+    let exe = std::env::current_exe()?;
+    shell::install(&exe)
+}
 
-        let (character_id, costume_id, conf) =
-            infer_character_costume(&display_name, &chars, &costumes);
+#[tauri::command]
+pub fn shell_integration_uninstall() -> Result<(), AppError> {
+    shell::uninstall()
+}
 
-        let mt = infer_mod_type(&display_name);
+/// Paths sent from the Explorer menu that the import flow has not picked up yet.
+#[tauri::command]
+pub fn shell_imports_take() -> Vec<ImportRequest> {
+    shell::take_all()
+}
 
-        out.push(DraftMod {
-            display_name,
-            folder_path,
-            author: author.clone(),
-            download_url: default_download_url.clone(),
-            mod_type: mt,
-            character_id,
-            costume_id,
-            infer_confidence: conf,
-        });
-    }
-    Ok(out)
+/// Checks that download URLs still resolve (all mods, or `ids`) and extracts host ids.
+#[tauri::command]
+pub async fn mods_validate_urls(
+    pool: State<'_, DbPool>,
+    ids: Option<Vec<i64>>,
+) -> Result<Vec<LinkCheck>, AppError> {
+    let pool = pool.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || links::validate(&pool, ids))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
+/// Compares the running version with the latest GitHub release (also run on launch when
+/// `check_app_update` is set).
 #[tauri::command]
-pub fn mods_import_commit(drafts: Vec<DraftMod>) -> Result<(usize, usize), String> {
-    use rusqlite::params;
-    use std::collections::HashSet;
+pub async fn app_check_update(app: AppHandle) -> Result<AppUpdate, AppError> {
+    let current = app.package_info().version.to_string();
+    tauri::async_runtime::spawn_blocking(move || appupdate::check(&current))
+        .await
+        .map_err(|e| e.to_string())?
+}
 
-    println!("[mods_import_commit] committing {} drafts", drafts.len());
-    let mut conn = con().map_err(|e| e.to_string())?;
-    let tx = conn.transaction().map_err(|e| e.to_string())?;
-    let now = now_iso();
+/// Checks every mod's download URL for a newer file (also run by the scheduler).
+#[tauri::command]
+pub async fn mods_check_updates(pool: State<'_, DbPool>) -> Result<UpdateCheckReport, AppError> {
+    let pool = pool.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || updates::check(&pool))
+        .await
+        .map_err(|e| e.to_string())?
+}
 
-    let mut inserted = 0usize;
-    let mut updated = 0usize;
+#[tauri::command]
+pub async fn mods_updates_list(pool: State<'_, ReadPool>) -> Result<Vec<ModUpdate>, AppError> {
+    with_read_conn(&pool, move |conn| updates::list(conn)).await
+}
 
-    // De-dupe in the backend too (belt & suspenders)
-    let mut seen = HashSet::<String>::new();
+#[tauri::command]
+pub async fn mods_updates_dismiss(
+    pool: State<'_, DbPool>,
+    ids: Vec<i64>,
+) -> Result<usize, AppError> {
+    with_write_conn(&pool, move |conn| updates::dismiss(conn, &ids)).await
+}
 
-    for d in drafts {
-        let fp_norm = normalize_path_string(&d.folder_path);
-        if !seen.insert(fp_norm.clone()) {
-            // duplicate in same batch → skip
-            println!(
-                "[mods_import_commit] duplicate draft skipped for folder_path='{}'",
-                fp_norm
-            );
-            continue;
-        }
+/// The last `lines` lines (default 200) of the current log file, for bug reports.
+#[tauri::command]
+pub async fn logs_tail(lines: Option<usize>) -> Result<Vec<String>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || logging::tail(lines.unwrap_or(200)))
+        .await
+        .map_err(|e| e.to_string())?
+}
 
-        let existed = mod_exists_by_path(&tx, &fp_norm)?;
-        println!(
-            "[mods_import_commit] processing display='{}' path='{}' existed_in_db={}",
-            d.display_name, fp_norm, existed
-        );
+#[tauri::command]
+pub fn logs_open_folder() -> Result<(), AppError> {
+    opener::open_folder(&logging::logs_dir()?)
+}
 
-        tx.execute(
-            r#"
-            INSERT INTO mods (
-              character_id, costume_id, author, download_url, installed, installed_at,
-              target_path, mod_type, folder_path, display_name, created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, 0, NULL, NULL, ?5, ?6, ?7, ?8, ?8)
-            ON CONFLICT(folder_path) DO UPDATE SET
-              display_name = excluded.display_name,
-              author = excluded.author,
-              download_url = excluded.download_url,
-              character_id = excluded.character_id,
-              costume_id = excluded.costume_id,
-              mod_type = excluded.mod_type,
-              updated_at = excluded.updated_at
-            "#,
-            params![
-                d.character_id,
-                d.costume_id,
-                d.author,
-                d.download_url,
-                d.mod_type.to_string(),
-                fp_norm,
-                d.display_name,
-                now
-            ],
-        )
-        .map_err(|e| {
-            println!(
-                "[mods_import_commit] upsert FAILED path='{}' err={}",
-                fp_norm, e
-            );
-            e.to_string()
-        })?;
+/// Installed mods as Markdown (default) or JSON, to share a setup or ask for help.
+#[tauri::command]
+pub async fn report_generate(
+    pool: State<'_, ReadPool>,
+    format: Option<ReportFormat>,
+) -> Result<String, AppError> {
+    with_read_conn(&pool, move |conn| {
+        report::generate(conn, format.unwrap_or_default(), &now_iso())
+    })
+    .await
+}
 
-        println!(
-            "[mods_import_commit] upsert success path='{}' action={}",
-            fp_norm,
-            if existed { "updated" } else { "inserted" }
-        );
+/// Java and preview generator status, with the reason when previews cannot run.
+#[tauri::command]
+pub async fn preview_tool_check(
+    pool: State<'_, ReadPool>,
+) -> Result<setup::PreviewToolCheck, AppError> {
+    with_read_conn(&pool, move |conn| {
+        Ok(setup::preview_tool_check(&load_settings(conn)?))
+    })
+    .await
+}
 
-        if existed {
-            updated += 1;
-        } else {
-            inserted += 1;
-        }
-    }
+/// What the first-run wizard still has to do.
+#[tauri::command]
+pub async fn setup_status(pool: State<'_, ReadPool>) -> Result<SetupStatus, AppError> {
+    with_read_conn(&pool, move |conn| setup::status(conn)).await
+}
 
-    tx.commit().map_err(|e| {
-        println!("[mods_import_commit] commit FAILED err={}", e);
-        e.to_string()
-    })?;
-    println!(
-        "[mods_import_commit] done inserted={} updated={}",
-        inserted, updated
-    );
-    Ok((inserted, updated))
+#[tauri::command]
+pub async fn setup_apply(
+    pool: State<'_, DbPool>,
+    plan: SetupPlan,
+) -> Result<SetupStatus, AppError> {
+    with_write_conn(&pool, move |conn| setup::apply(conn, &plan)).await
 }
 
-#[derive(Serialize)]
-pub struct CatalogCharacterRow {
-    pub id: i64,
-    pub slug: String,
-    pub display_name: String,
+/// Writes the library's metadata (not its files) to a versioned JSON file.
+#[tauri::command]
+pub async fn library_export(
+    pool: State<'_, ReadPool>,
+    path: String,
+) -> Result<LibraryExportSummary, AppError> {
+    with_read_conn(&pool, move |conn| {
+        info!("[library_export] writing '{}'", path);
+        library::export_to_path(conn, Path::new(&path))
+    })
+    .await
 }
 
-#[derive(Serialize)]
-pub struct CatalogCostumeRow {
-    pub id: i64,
-    pub character_id: i64,
-    pub slug: String,
-    pub display_name: String,
+#[tauri::command]
+pub async fn library_import(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+    path: String,
+    merge_strategy: Option<MergeStrategy>,
+) -> Result<LibraryImportReport, AppError> {
+    let report = with_write_conn(&pool, move |conn| {
+        library::import_from_path(conn, Path::new(&path), merge_strategy.unwrap_or_default())
+    })
+    .await?;
+    if report.added + report.updated > 0 {
+        events::mods_changed(&app, ModsChangeKind::Reset, Vec::new());
+    }
+    Ok(report)
 }
 
-#[derive(Serialize)]
-pub struct CatalogListResponse {
-    pub characters: Vec<CatalogCharacterRow>,
-    pub costumes: Vec<CatalogCostumeRow>,
+/// Shared library folders and whether their metadata file changed since the last sync.
+#[tauri::command]
+pub async fn library_shared_status(
+    pool: State<'_, ReadPool>,
+) -> Result<Vec<SharedFolderStatus>, AppError> {
+    with_read_conn(&pool, move |conn| shared::status(conn)).await
 }
 
+/// Merges the metadata other machines wrote to the shared library folders and writes this
+/// machine's, see `shared::sync`.
 #[tauri::command]
-pub fn catalog_import_from_file(path: String) -> Result<CatalogReport, String> {
-    let trimmed = path.trim();
-    if trimmed.is_empty() {
-        return Err("path is empty".to_string());
+pub async fn library_shared_sync(
+    app: AppHandle,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<SharedFolderSync>, AppError> {
+    let synced = with_write_conn(&pool, shared::sync).await?;
+    if synced.iter().any(|s| s.pulled.is_some()) {
+        events::mods_changed(&app, ModsChangeKind::Reset, Vec::new());
     }
-    let path = Path::new(trimmed);
-    println!("[catalog] importing from {}", path.display());
-    catalog::sync_from_path(path)
+    Ok(synced)
 }
 
+/// Packages a mod for sharing: a zip (default) or folder copy with a generated
+/// `modinfo.json`. Returns the written path.
 #[tauri::command]
-pub fn catalog_list() -> Result<CatalogListResponse, String> {
-    let conn = con().map_err(|e| e.to_string())?;
-    let chars = db_characters(&conn)?;
-    let costumes = db_costumes(&conn)?;
+pub async fn mods_export(
+    pool: State<'_, ReadPool>,
+    id: i64,
+    dest: String,
+    format: Option<ExportFormat>,
+    version: Option<String>,
+) -> Result<String, AppError> {
+    with_read_conn(&pool, move |conn| {
+        let out = modinfo::export(
+            conn,
+            id,
+            Path::new(&dest),
+            format.unwrap_or_default(),
+            version,
+        )?;
+        info!("[mods_export] id={} -> '{}'", id, out.display());
+        Ok(normalize_path_string(&out.to_string_lossy()))
+    })
+    .await
+}
 
-    Ok(CatalogListResponse {
-        characters: chars
-            .into_iter()
-            .map(|(id, slug, display_name)| CatalogCharacterRow {
-                id,
-                slug,
-                display_name,
+/// Opens the mod's folder in the system file explorer.
+#[tauri::command]
+pub async fn mod_open_folder(pool: State<'_, ReadPool>, id: i64) -> Result<(), AppError> {
+    with_read_conn(&pool, move |conn| {
+        let folder_path: String = conn
+            .query_row("SELECT folder_path FROM mods WHERE id = ?1", [id], |r| {
+                r.get(0)
             })
-            .collect(),
-        costumes: costumes
-            .into_iter()
-            .map(|(id, character_id, slug, display_name)| CatalogCostumeRow {
-                id,
-                character_id,
-                slug,
-                display_name,
+            .optional()?
+            .ok_or_else(|| AppError::not_found("Mod", id))?;
+        if !Path::new(&folder_path).is_dir() {
+            return Err(AppError::PathMissing {
+                message: format!(
+                    "The mod folder '{}' is missing; run an audit to relink it",
+                    folder_path
+                ),
+                path: Some(folder_path),
+            });
+        }
+        opener::open_folder(Path::new(&folder_path))
+    })
+    .await
+}
+
+/// Opens the mod's download page in the default browser.
+#[tauri::command]
+pub async fn mod_open_url(pool: State<'_, ReadPool>, id: i64) -> Result<(), AppError> {
+    with_read_conn(&pool, move |conn| {
+        let url: Option<String> = conn
+            .query_row("SELECT download_url FROM mods WHERE id = ?1", [id], |r| {
+                r.get(0)
             })
-            .collect(),
+            .optional()?
+            .ok_or_else(|| AppError::not_found("Mod", id))?;
+        let url = url
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .ok_or_else(|| "This mod has no download URL".to_string())?;
+        // only web links; anything else would be handed to an arbitrary handler
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(AppError::invalid(format!("Not a web link: '{}'", url)));
+        }
+        opener::open(&url)
+    })
+    .await
+}
+
+/// Re-checks the mod folder's Spine assets and stores the result.
+#[tauri::command]
+pub async fn mod_validate(pool: State<'_, DbPool>, id: i64) -> Result<Validation, AppError> {
+    with_write_conn(&pool, move |conn| {
+        let folder_path: String = conn
+            .query_row("SELECT folder_path FROM mods WHERE id = ?1", [id], |r| {
+                r.get(0)
+            })
+            .optional()?
+            .ok_or_else(|| AppError::not_found("Mod", id))?;
+        let validation = validate::validate(Path::new(&folder_path));
+        conn.execute(
+            "UPDATE mods SET validation_status = ?2, validation_problems = ?3 WHERE id = ?1",
+            params![
+                id,
+                validation.status.as_str(),
+                serde_json::to_string(&validation.problems)?
+            ],
+        )?;
+        Ok(validation)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn install_targets_list(
+    pool: State<'_, ReadPool>,
+) -> Result<Vec<InstallTarget>, AppError> {
+    with_read_conn(&pool, move |conn| targets::list(conn)).await
+}
+
+/// Sets (or, with an empty pattern, removes) the install path rule for a scope.
+#[tauri::command]
+pub async fn install_target_set(
+    pool: State<'_, DbPool>,
+    target: InstallTarget,
+) -> Result<(), AppError> {
+    with_write_conn(&pool, move |conn| targets::set(conn, &target)).await
+}
+
+/// Where `mods_install` would put the mod, relative to the game mods folder.
+#[tauri::command]
+pub async fn install_target_resolve(
+    pool: State<'_, ReadPool>,
+    id: i64,
+) -> Result<String, AppError> {
+    with_read_conn(&pool, move |conn| {
+        targets::resolve(conn, id).map(|p| p.to_string_lossy().replace('\\', "/"))
     })
+    .await
+}
+
+/// Local usage counters (installs, most installed mods and characters, preview times) for
+/// the user's own dashboards; recorded only while `usage_stats` is on.
+#[tauri::command]
+pub async fn usage_stats(
+    pool: State<'_, ReadPool>,
+    limit: Option<usize>,
+) -> Result<UsageStats, AppError> {
+    with_read_conn(&pool, move |conn| stats::usage(conn, limit)).await
 }
 
+/// Deletes every usage counter.
 #[tauri::command]
-pub fn mods_purge_all() -> Result<usize, String> {
-    let conn = con().map_err(|e| e.to_string())?;
-    let affected = conn
-        .execute("DELETE FROM mods", [])
-        .map_err(|e| e.to_string())?;
-    println!("[mods_purge_all] deleted {} mods", affected);
-    Ok(affected as usize)
+pub async fn usage_stats_purge(pool: State<'_, DbPool>) -> Result<usize, AppError> {
+    with_write_conn(&pool, move |conn| stats::purge(conn)).await
 }
@@ -1,47 +1,223 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use rusqlite::Connection;
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
-pub fn db_path() -> Result<PathBuf> {
+// WAL lets readers run alongside a writer, but sqlite still allows a single writer at a
+// time; funnel writes through this lock so they queue instead of failing with SQLITE_BUSY.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn data_dir() -> Result<PathBuf> {
     // Change org/app names to your identifiers
     let proj = ProjectDirs::from("org", "BrownDust2", "ModsHandler")
         .context("Cannot resolve platform data dir")?;
     let data_dir = proj.data_dir();
     fs::create_dir_all(data_dir).context("Failed to create app data dir")?;
-    Ok(data_dir.join("mods.db"))
+    Ok(data_dir.to_path_buf())
 }
 
-pub fn open_db() -> Result<Connection> {
-    let path = db_path()?;
-    let conn = Connection::open(path).context("Failed to open sqlite")?;
-    conn.pragma_update(None, "foreign_keys", "ON")?;
-    Ok(conn)
+pub fn db_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("mods.db"))
 }
 
-pub fn migrate(conn: &Connection) -> Result<()> {
-    // Simple versioned migrations
+fn configure(conn: &Connection) -> rusqlite::Result<()> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
     conn.execute_batch(
         r#"
-        CREATE TABLE IF NOT EXISTS _schema_version (
-          id INTEGER PRIMARY KEY CHECK (id = 1),
-          version INTEGER NOT NULL
-        );
-        INSERT INTO _schema_version(id, version)
-          SELECT 1, 0 WHERE NOT EXISTS (SELECT 1 FROM _schema_version WHERE id=1);
+        PRAGMA journal_mode = WAL;
+        PRAGMA synchronous = NORMAL;
+        PRAGMA foreign_keys = ON;
         "#,
-    )?;
+    )
+}
+
+/// Serializes writers across commands and background workers. Hold the guard for the
+/// whole write (including transactions).
+pub fn write_lock() -> MutexGuard<'static, ()> {
+    WRITE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
 
-    let current: i64 =
-        conn.query_row("SELECT version FROM _schema_version WHERE id=1;", [], |r| {
-            r.get(0)
-        })?;
+#[derive(Debug, Serialize)]
+pub struct MaintenanceReport {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
 
-    if current < 1 {
-        // v1 schema
-        conn.execute_batch(
-            r#"
+fn db_size() -> u64 {
+    let Ok(path) = db_path() else { return 0 };
+    ["", "-wal"]
+        .iter()
+        .filter_map(|suffix| {
+            let mut p = path.clone().into_os_string();
+            p.push(suffix);
+            fs::metadata(p).ok()
+        })
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Checkpoints the WAL, rebuilds the file and refreshes planner statistics.
+/// The caller must hold `write_lock`.
+pub fn maintenance(conn: &Connection) -> Result<MaintenanceReport> {
+    let bytes_before = db_size();
+    conn.execute_batch(
+        r#"
+        PRAGMA wal_checkpoint(TRUNCATE);
+        VACUUM;
+        ANALYZE;
+        PRAGMA wal_checkpoint(TRUNCATE);
+        "#,
+    )
+    .context("Database maintenance failed")?;
+    let bytes_after = db_size();
+    info!(
+        "[db] maintenance done bytes_before={} bytes_after={}",
+        bytes_before, bytes_after
+    );
+    Ok(MaintenanceReport {
+        bytes_before,
+        bytes_after,
+    })
+}
+
+/// r2d2 manager handing out sqlite connections with the app's pragmas applied.
+pub struct SqliteManager {
+    path: PathBuf,
+    read_only: bool,
+}
+
+impl r2d2::ManageConnection for SqliteManager {
+    type Connection = Connection;
+    type Error = rusqlite::Error;
+
+    fn connect(&self) -> Result<Connection, rusqlite::Error> {
+        let conn = Connection::open(&self.path)?;
+        configure(&conn)?;
+        if self.read_only {
+            conn.pragma_update(None, "query_only", "ON")?;
+        }
+        Ok(conn)
+    }
+
+    fn is_valid(&self, conn: &mut Connection) -> Result<(), rusqlite::Error> {
+        conn.execute_batch("SELECT 1;")
+    }
+
+    fn has_broken(&self, _conn: &mut Connection) -> bool {
+        false
+    }
+}
+
+pub type DbPool = r2d2::Pool<SqliteManager>;
+
+/// Query-only connections for listing/searching. Under WAL they read the last committed
+/// snapshot, so they never wait on a long import holding the write lock.
+#[derive(Clone)]
+pub struct ReadPool(pub DbPool);
+
+/// Opens the connection pool shared through Tauri state and runs migrations once.
+pub fn init_pool() -> Result<DbPool> {
+    let manager = SqliteManager {
+        path: db_path()?,
+        read_only: false,
+    };
+    let pool = r2d2::Pool::builder()
+        .max_size(8)
+        .build(manager)
+        .context("Failed to build sqlite pool")?;
+    let conn = pool.get().context("Failed to get sqlite connection")?;
+    let mut report = migrate(&conn)?;
+    report.integrity_problems = integrity_check(&conn)?;
+    for problem in report.integrity_problems.iter() {
+        warn!("[db] integrity: {}", problem);
+    }
+    let _ = STARTUP_REPORT.set(report);
+    info!("[db] pool ready");
+    Ok(pool)
+}
+
+/// Opens the read-only pool. Call after `init_pool` so the schema is migrated.
+pub fn init_read_pool() -> Result<ReadPool> {
+    let manager = SqliteManager {
+        path: db_path()?,
+        read_only: true,
+    };
+    let pool = r2d2::Pool::builder()
+        .max_size(4)
+        .build(manager)
+        .context("Failed to build sqlite read pool")?;
+    info!("[db] read pool ready");
+    Ok(ReadPool(pool))
+}
+
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.query_row(
+        "SELECT version FROM _schema_version WHERE id = 1",
+        [],
+        |r| r.get(0),
+    )
+    .context("Not a mods database (no schema version)")
+}
+
+/// Copies the live database to `dest` with sqlite's online backup API.
+pub fn backup_to(conn: &Connection, dest: &Path) -> Result<()> {
+    conn.backup(DatabaseName::Main, dest, None)
+        .with_context(|| format!("Failed to back up to '{}'", dest.display()))?;
+    info!("[db] backup written to {}", dest.display());
+    Ok(())
+}
+
+/// Replaces the live database with the backup at `src`. Backups from a newer schema are
+/// refused; older ones are migrated after the restore. The caller must hold `write_lock`.
+pub fn restore_from(conn: &mut Connection, src: &Path) -> Result<i64> {
+    let backup = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open backup '{}'", src.display()))?;
+    let backup_version = schema_version(&backup)?;
+    let current_version = schema_version(conn)?;
+    if backup_version > current_version {
+        anyhow::bail!(
+            "Backup uses schema v{} but this version of the app only knows v{}",
+            backup_version,
+            current_version
+        );
+    }
+    drop(backup);
+
+    conn.restore(DatabaseName::Main, src, None::<fn(Progress)>)
+        .with_context(|| format!("Failed to restore from '{}'", src.display()))?;
+    migrate(conn)?;
+    info!(
+        "[db] restored {} (schema v{} -> v{})",
+        src.display(),
+        backup_version,
+        current_version
+    );
+    Ok(backup_version)
+}
+
+/// One schema step. `up` runs in a transaction together with the version bump.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    up: &'static str,
+    /// Reverses `up` for `migrate_down`; `None` when sqlite cannot undo it in place.
+    down: Option<&'static str>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial schema",
+        up: r#"
             -- canonical lists (catalog managed)
             CREATE TABLE characters (
               id INTEGER PRIMARY KEY,
@@ -82,38 +258,34 @@ pub fn migrate(conn: &Connection) -> Result<()> {
             CREATE INDEX mods_character_costume_idx ON mods(character_id, costume_id);
             CREATE INDEX mods_author_idx ON mods(author);
             "#,
-        )?;
-        conn.execute("UPDATE _schema_version SET version=1 WHERE id=1;", [])?;
-    }
-
-    if current < 2 {
-        println!("[db::migrate] upgrading schema to v2 (unique folder paths)");
-        conn.execute_batch(
-            r#"
-                -- drop duplicate folder paths before enforcing uniqueness (keep latest updated_at)
-                WITH ranked AS (
-                    SELECT
-                        id,
-                        ROW_NUMBER() OVER (
-                            PARTITION BY folder_path
-                            ORDER BY updated_at DESC, id DESC
-                        ) AS rn
-                    FROM mods
-                )
-                DELETE FROM mods
-                WHERE id IN (SELECT id FROM ranked WHERE rn > 1);
-
-                -- ensure each mod folder path is unique
-                CREATE UNIQUE INDEX IF NOT EXISTS mods_folder_path_unique ON mods(folder_path);
-                "#,
-        )?;
-        conn.execute("UPDATE _schema_version SET version=2 WHERE id=1;", [])?;
-    }
+        down: None,
+    },
+    Migration {
+        version: 2,
+        name: "unique folder paths",
+        up: r#"
+            -- drop duplicate folder paths before enforcing uniqueness (keep latest updated_at)
+            WITH ranked AS (
+                SELECT
+                    id,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY folder_path
+                        ORDER BY updated_at DESC, id DESC
+                    ) AS rn
+                FROM mods
+            )
+            DELETE FROM mods
+            WHERE id IN (SELECT id FROM ranked WHERE rn > 1);
 
-    if current < 3 {
-        println!("[db::migrate] upgrading schema to v3 (aliases & legacy crawler sources)");
-        conn.execute_batch(
-            r#"
+            -- ensure each mod folder path is unique
+            CREATE UNIQUE INDEX IF NOT EXISTS mods_folder_path_unique ON mods(folder_path);
+            "#,
+        down: Some("DROP INDEX IF EXISTS mods_folder_path_unique;"),
+    },
+    Migration {
+        version: 3,
+        name: "aliases & legacy crawler sources",
+        up: r#"
             -- store alternative names for characters & costumes
             CREATE TABLE IF NOT EXISTS aliases (
               id INTEGER PRIMARY KEY,
@@ -137,27 +309,29 @@ pub fn migrate(conn: &Connection) -> Result<()> {
               last_error TEXT
             );
             "#,
-        )?;
-        conn.execute("UPDATE _schema_version SET version=3 WHERE id=1;", [])?;
-    }
-
-    if current < 4 {
-        println!("[db::migrate] upgrading schema to v4 (app settings)");
-        conn.execute_batch(
+        down: Some(
             r#"
+            DROP TABLE crawler_sources;
+            DROP TABLE aliases;
+            "#,
+        ),
+    },
+    Migration {
+        version: 4,
+        name: "app settings",
+        up: r#"
             CREATE TABLE IF NOT EXISTS settings (
               key TEXT PRIMARY KEY,
               value_json TEXT NOT NULL
             );
             "#,
-        )?;
-        conn.execute("UPDATE _schema_version SET version=4 WHERE id=1;", [])?;
-    }
-
-    if current < 5 {
-        println!("[db::migrate] upgrading schema to v5 (expanded mod types)");
-        conn.execute_batch(
-            r#"
+        down: Some("DROP TABLE settings;"),
+    },
+    // rebuilds `mods`; undoing it would take another rebuild
+    Migration {
+        version: 5,
+        name: "expanded mod types",
+        up: r#"
             DROP INDEX IF EXISTS mods_character_costume_idx;
             DROP INDEX IF EXISTS mods_author_idx;
             DROP INDEX IF EXISTS mods_folder_path_unique;
@@ -201,9 +375,832 @@ pub fn migrate(conn: &Connection) -> Result<()> {
 
             DROP TABLE mods_old;
             "#,
+        down: None,
+    },
+    Migration {
+        version: 6,
+        name: "catalog images",
+        up: r#"
+            ALTER TABLE characters ADD COLUMN image_url TEXT;
+            ALTER TABLE characters ADD COLUMN thumbnail_path TEXT;  -- cached copy in the app data dir
+            ALTER TABLE costumes ADD COLUMN image_url TEXT;
+            ALTER TABLE costumes ADD COLUMN thumbnail_path TEXT;
+            "#,
+        down: Some(
+            r#"
+            ALTER TABLE costumes DROP COLUMN thumbnail_path;
+            ALTER TABLE costumes DROP COLUMN image_url;
+            ALTER TABLE characters DROP COLUMN thumbnail_path;
+            ALTER TABLE characters DROP COLUMN image_url;
+            "#,
+        ),
+    },
+    Migration {
+        version: 7,
+        name: "scan runs & errors",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS scan_runs (
+              id INTEGER PRIMARY KEY,
+              started_at TEXT NOT NULL,
+              finished_at TEXT,
+              scanned_dirs INTEGER NOT NULL DEFAULT 0,
+              discovered_mods INTEGER NOT NULL DEFAULT 0,
+              upserts INTEGER NOT NULL DEFAULT 0,
+              errors INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS scan_errors (
+              id INTEGER PRIMARY KEY,
+              run_id INTEGER NOT NULL REFERENCES scan_runs(id) ON DELETE CASCADE,
+              path TEXT NOT NULL,
+              kind TEXT NOT NULL,                -- 'not_found' | 'permission_denied' | 'io' | 'database' ...
+              message TEXT NOT NULL
+            );
+            CREATE INDEX scan_errors_run_idx ON scan_errors(run_id);
+            "#,
+        down: Some(
+            r#"
+            DROP TABLE scan_errors;
+            DROP TABLE scan_runs;
+            "#,
+        ),
+    },
+    Migration {
+        version: 8,
+        name: "per-file manifest",
+        up: r#"
+            ALTER TABLE mods ADD COLUMN manifest_built_at TEXT;
+
+            CREATE TABLE IF NOT EXISTS mod_files (
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              rel_path TEXT NOT NULL,           -- '/'-separated, relative to folder_path
+              size INTEGER NOT NULL,
+              mtime INTEGER NOT NULL,           -- seconds since epoch
+              hash TEXT NOT NULL,               -- blake3 hex
+              PRIMARY KEY (mod_id, rel_path)
+            );
+            CREATE INDEX mod_files_hash_idx ON mod_files(hash);
+            "#,
+        down: Some(
+            r#"
+            DROP TABLE mod_files;
+            ALTER TABLE mods DROP COLUMN manifest_built_at;
+            "#,
+        ),
+    },
+    Migration {
+        version: 9,
+        name: "background install checks",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS install_checks (
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              rel_path TEXT NOT NULL,
+              status TEXT NOT NULL CHECK (status IN ('ok','modified','missing')),
+              checked_at TEXT NOT NULL,
+              PRIMARY KEY (mod_id, rel_path)
+            );
+            "#,
+        down: Some("DROP TABLE install_checks;"),
+    },
+    Migration {
+        version: 10,
+        name: "type & author aliases",
+        up: r#"
+            -- folder-name keyword -> mod type (keys are lowercase ascii alphanumerics)
+            CREATE TABLE IF NOT EXISTS type_aliases (
+              alias TEXT PRIMARY KEY,
+              mod_type TEXT NOT NULL
+                CHECK (
+                  mod_type IN (
+                    'idle','cutscene','date','battle','ui','other',
+                    'history','minigame','swap'
+                  )
+                )
+            );
+
+            -- folder-name keyword -> canonical author name
+            CREATE TABLE IF NOT EXISTS author_aliases (
+              alias TEXT PRIMARY KEY,
+              author TEXT NOT NULL
+            );
+            "#,
+        down: Some(
+            r#"
+            DROP TABLE author_aliases;
+            DROP TABLE type_aliases;
+            "#,
+        ),
+    },
+    Migration {
+        version: 11,
+        name: "cached folder sizes",
+        up: r#"
+            ALTER TABLE mods ADD COLUMN size_bytes INTEGER;   -- refreshed by paths_rescan
+            ALTER TABLE mods ADD COLUMN file_count INTEGER;
+            "#,
+        down: Some(
+            r#"
+            ALTER TABLE mods DROP COLUMN file_count;
+            ALTER TABLE mods DROP COLUMN size_bytes;
+            "#,
+        ),
+    },
+    // v12, v13, v17 and v18 add columns with a foreign key, which sqlite cannot drop
+    Migration {
+        version: 12,
+        name: "swap source character",
+        up: r#"
+            -- character replaced by a swap mod; character_id stays the one shown in its place
+            ALTER TABLE mods ADD COLUMN swap_source_character_id INTEGER
+              REFERENCES characters(id) ON DELETE SET NULL;
+            CREATE INDEX IF NOT EXISTS mods_swap_source_idx ON mods(swap_source_character_id);
+            "#,
+        down: None,
+    },
+    Migration {
+        version: 13,
+        name: "library roots",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS library_roots (
+              id INTEGER PRIMARY KEY,
+              path TEXT NOT NULL UNIQUE
+            );
+            -- folder_path stays the resolved absolute path; root_id + rel_path survive a move
+            ALTER TABLE mods ADD COLUMN root_id INTEGER
+              REFERENCES library_roots(id) ON DELETE SET NULL;
+            ALTER TABLE mods ADD COLUMN rel_path TEXT;   -- '/'-separated
+            CREATE INDEX IF NOT EXISTS mods_root_idx ON mods(root_id);
+            "#,
+        down: None,
+    },
+    Migration {
+        version: 14,
+        name: "missing folder flag",
+        up: r#"
+            ALTER TABLE mods ADD COLUMN missing_at TEXT;   -- set by mods_audit_apply, cleared on rescan
+            "#,
+        down: Some("ALTER TABLE mods DROP COLUMN missing_at;"),
+    },
+    Migration {
+        version: 15,
+        name: "operations journal",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS operations (
+              id INTEGER PRIMARY KEY,
+              kind TEXT NOT NULL,          -- import | bulk_edit | install | uninstall
+              summary TEXT NOT NULL,
+              created_at TEXT NOT NULL,
+              undone_at TEXT
+            );
+            -- no FK on mod_id: the journal must outlive deleted mods to restore them
+            CREATE TABLE IF NOT EXISTS operation_mods (
+              op_id INTEGER NOT NULL REFERENCES operations(id) ON DELETE CASCADE,
+              seq INTEGER NOT NULL,
+              mod_id INTEGER NOT NULL,
+              before_json TEXT,            -- NULL when the operation created the mod
+              PRIMARY KEY (op_id, seq)
+            );
+            "#,
+        down: Some(
+            r#"
+            DROP TABLE operation_mods;
+            DROP TABLE operations;
+            "#,
+        ),
+    },
+    Migration {
+        version: 16,
+        name: "install file selection",
+        up: r#"
+            -- files chosen for installation; no rows means the whole mod
+            CREATE TABLE IF NOT EXISTS install_selections (
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              rel_path TEXT NOT NULL,
+              PRIMARY KEY (mod_id, rel_path)
+            );
+            "#,
+        down: Some("DROP TABLE install_selections;"),
+    },
+    Migration {
+        version: 17,
+        name: "mod variant groups",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS mod_groups (
+              id INTEGER PRIMARY KEY,
+              name TEXT NOT NULL,
+              active_mod_id INTEGER REFERENCES mods(id) ON DELETE SET NULL,
+              created_at TEXT NOT NULL
+            );
+            ALTER TABLE mods ADD COLUMN group_id INTEGER
+              REFERENCES mod_groups(id) ON DELETE SET NULL;
+            CREATE INDEX IF NOT EXISTS mods_group_idx ON mods(group_id);
+            "#,
+        down: None,
+    },
+    // existing author strings are linked by authors::sync on db_init
+    Migration {
+        version: 18,
+        name: "authors",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS authors (
+              id INTEGER PRIMARY KEY,
+              name TEXT NOT NULL,
+              key TEXT NOT NULL UNIQUE,         -- normalized name, see aliases::normalize_alias
+              homepage_url TEXT,
+              kofi_url TEXT,
+              created_at TEXT NOT NULL
+            );
+            ALTER TABLE mods ADD COLUMN author_id INTEGER
+              REFERENCES authors(id) ON DELETE SET NULL;
+            CREATE INDEX IF NOT EXISTS mods_author_idx ON mods(author_id);
+            "#,
+        down: None,
+    },
+    Migration {
+        version: 19,
+        name: "downloads",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS downloads (
+              id INTEGER PRIMARY KEY,
+              url TEXT NOT NULL,
+              author TEXT,
+              file_name TEXT NOT NULL,
+              status TEXT NOT NULL
+                CHECK (status IN ('queued','running','paused','importing','done','failed','cancelled')),
+              received_bytes INTEGER NOT NULL DEFAULT 0,
+              total_bytes INTEGER,
+              error TEXT,
+              mod_id INTEGER REFERENCES mods(id) ON DELETE SET NULL,
+              created_at TEXT NOT NULL,
+              updated_at TEXT NOT NULL
+            );
+            "#,
+        down: Some("DROP TABLE downloads;"),
+    },
+    Migration {
+        version: 20,
+        name: "download update checks",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS mod_remote_state (
+              mod_id INTEGER PRIMARY KEY REFERENCES mods(id) ON DELETE CASCADE,
+              etag TEXT,
+              last_modified TEXT,
+              content_length INTEGER,
+              checked_at TEXT NOT NULL,
+              update_available INTEGER NOT NULL DEFAULT 0
+            );
+            "#,
+        down: Some("DROP TABLE mod_remote_state;"),
+    },
+    Migration {
+        version: 21,
+        name: "incremental rescan",
+        up: r#"
+            -- see manifest::folder_stat; NULL until the next rescan
+            ALTER TABLE mods ADD COLUMN scan_fingerprint TEXT;
+            "#,
+        down: Some("ALTER TABLE mods DROP COLUMN scan_fingerprint;"),
+    },
+    Migration {
+        version: 22,
+        name: "spine validation",
+        up: r#"
+            ALTER TABLE mods ADD COLUMN validation_status TEXT;   -- 'ok' | 'incomplete' | 'no_assets'
+            ALTER TABLE mods ADD COLUMN validation_problems TEXT; -- JSON array of messages
+            "#,
+        down: Some(
+            r#"
+            ALTER TABLE mods DROP COLUMN validation_problems;
+            ALTER TABLE mods DROP COLUMN validation_status;
+            "#,
+        ),
+    },
+    Migration {
+        version: 23,
+        name: "install targets",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS install_targets (
+              character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+              costume_id INTEGER REFERENCES costumes(id) ON DELETE CASCADE,  -- NULL: any costume
+              mod_type TEXT,                                                -- NULL: any type
+              path_pattern TEXT NOT NULL     -- relative to the game mods dir, see targets.rs
+            );
+            CREATE INDEX IF NOT EXISTS install_targets_character_idx
+              ON install_targets(character_id);
+            "#,
+        down: Some("DROP TABLE install_targets;"),
+    },
+    Migration {
+        version: 24,
+        name: "profiles",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS profiles (
+              id INTEGER PRIMARY KEY,
+              name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+              created_at TEXT NOT NULL,
+              updated_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS profile_mods (
+              profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              PRIMARY KEY (profile_id, mod_id)
+            );
+            "#,
+        down: Some(
+            r#"
+            DROP TABLE profile_mods;
+            DROP TABLE profiles;
+            "#,
+        ),
+    },
+    Migration {
+        version: 25,
+        name: "game launches",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS game_launches (
+              id INTEGER PRIMARY KEY,
+              launched_at TEXT NOT NULL,
+              profile_id INTEGER REFERENCES profiles(id) ON DELETE SET NULL,
+              method TEXT NOT NULL,       -- 'steam' | 'exe'
+              mods_json TEXT NOT NULL     -- the installed mods at launch, see launch.rs
+            );
+            "#,
+        down: Some("DROP TABLE game_launches;"),
+    },
+    Migration {
+        version: 26,
+        name: "localized catalog names",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS character_names (
+              character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+              lang TEXT NOT NULL,   -- 'ko', 'ja', ...
+              name TEXT NOT NULL,
+              PRIMARY KEY (character_id, lang)
+            );
+            CREATE TABLE IF NOT EXISTS costume_names (
+              costume_id INTEGER NOT NULL REFERENCES costumes(id) ON DELETE CASCADE,
+              lang TEXT NOT NULL,
+              name TEXT NOT NULL,
+              PRIMARY KEY (costume_id, lang)
+            );
+            "#,
+        down: Some(
+            r#"
+            DROP TABLE costume_names;
+            DROP TABLE character_names;
+            "#,
+        ),
+    },
+    Migration {
+        version: 27,
+        name: "install events",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS install_events (
+              id INTEGER PRIMARY KEY,
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              action TEXT NOT NULL,        -- 'install' | 'uninstall'
+              at TEXT NOT NULL,
+              profile_id INTEGER REFERENCES profiles(id) ON DELETE SET NULL,
+              target_path TEXT,
+              app_version TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS install_events_mod_idx ON install_events(mod_id);
+            "#,
+        down: Some("DROP TABLE install_events;"),
+    },
+    Migration {
+        version: 28,
+        name: "safe mode",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS safe_mode_states (
+              id INTEGER PRIMARY KEY,
+              created_at TEXT NOT NULL,
+              method TEXT NOT NULL,        -- 'uninstall' | 'rename_folder'
+              game_mods_dir TEXT NOT NULL,
+              mod_ids_json TEXT NOT NULL,  -- the mods that were installed
+              restored_at TEXT
+            );
+            "#,
+        down: Some("DROP TABLE safe_mode_states;"),
+    },
+    Migration {
+        version: 29,
+        name: "download link checks",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS mod_links (
+              mod_id INTEGER PRIMARY KEY REFERENCES mods(id) ON DELETE CASCADE,
+              url TEXT NOT NULL,           -- the download_url that was checked
+              status TEXT NOT NULL,        -- 'ok' | 'dead' | 'error'
+              http_status INTEGER,
+              final_url TEXT,
+              host TEXT,                   -- see links::canonical_id
+              canonical_id TEXT,
+              checked_at TEXT NOT NULL
+            );
+            "#,
+        down: Some("DROP TABLE mod_links;"),
+    },
+    Migration {
+        version: 30,
+        name: "preview errors",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS preview_errors (
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              kind TEXT NOT NULL,          -- 'image' | 'video' | 'animated'
+              exit_code INTEGER,
+              message TEXT NOT NULL,
+              output TEXT NOT NULL,        -- tail of the generator's stdout/stderr
+              failed_at TEXT NOT NULL,
+              PRIMARY KEY (mod_id, kind)
+            );
+            "#,
+        down: Some("DROP TABLE preview_errors;"),
+    },
+    Migration {
+        version: 31,
+        name: "cached preview flags",
+        up: r#"
+            -- see previews::refresh_flags; filled in on the next db_init
+            ALTER TABLE mods ADD COLUMN has_image INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE mods ADD COLUMN has_video INTEGER NOT NULL DEFAULT 0;
+            "#,
+        down: Some(
+            r#"
+            ALTER TABLE mods DROP COLUMN has_video;
+            ALTER TABLE mods DROP COLUMN has_image;
+            "#,
+        ),
+    },
+    Migration {
+        version: 32,
+        name: "nsfw flag",
+        up: r#"
+            ALTER TABLE mods ADD COLUMN nsfw INTEGER NOT NULL DEFAULT 0;
+            "#,
+        down: Some("ALTER TABLE mods DROP COLUMN nsfw;"),
+    },
+    Migration {
+        version: 33,
+        name: "wishlist",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS wishlist (
+              id INTEGER PRIMARY KEY,
+              url TEXT NOT NULL UNIQUE,
+              name TEXT NOT NULL,
+              author TEXT,
+              note TEXT,
+              created_at TEXT NOT NULL,
+              download_id INTEGER REFERENCES downloads(id) ON DELETE SET NULL,
+              mod_id INTEGER REFERENCES mods(id) ON DELETE SET NULL,
+              acquired_at TEXT
+            );
+            "#,
+        down: Some("DROP TABLE wishlist;"),
+    },
+    Migration {
+        version: 34,
+        name: "verified game version",
+        up: r#"
+            -- game version (see gameupdate::fingerprint) the mod was last confirmed working with
+            ALTER TABLE mods ADD COLUMN verified_game_version TEXT;
+            ALTER TABLE mods ADD COLUMN verified_at TEXT;
+            "#,
+        down: Some(
+            r#"
+            ALTER TABLE mods DROP COLUMN verified_at;
+            ALTER TABLE mods DROP COLUMN verified_game_version;
+            "#,
+        ),
+    },
+    Migration {
+        version: 35,
+        name: "risk flags",
+        up: r#"
+            -- JSON array of executable/script paths in the folder, NULL when there are none
+            ALTER TABLE mods ADD COLUMN risk_flags TEXT;
+            -- set by mods_confirm_risk; install refuses flagged mods until then
+            ALTER TABLE mods ADD COLUMN risk_confirmed_at TEXT;
+            "#,
+        down: Some(
+            r#"
+            ALTER TABLE mods DROP COLUMN risk_confirmed_at;
+            ALTER TABLE mods DROP COLUMN risk_flags;
+            "#,
+        ),
+    },
+    Migration {
+        version: 36,
+        name: "costume reference images",
+        up: r#"
+            -- vanilla art of a costume from the catalog, for comparing with mod previews
+            CREATE TABLE IF NOT EXISTS costume_references (
+              id INTEGER PRIMARY KEY,
+              costume_id INTEGER NOT NULL REFERENCES costumes(id) ON DELETE CASCADE,
+              url TEXT NOT NULL,
+              path TEXT,                        -- cached copy, see catalog::fetch_images
+              UNIQUE(costume_id, url)
+            );
+            "#,
+        down: Some("DROP TABLE costume_references;"),
+    },
+    Migration {
+        version: 37,
+        name: "mod rating",
+        up: r#"
+            ALTER TABLE mods ADD COLUMN rating INTEGER;   -- 1-5 stars, NULL when unrated
+            "#,
+        down: Some("ALTER TABLE mods DROP COLUMN rating;"),
+    },
+    Migration {
+        version: 38,
+        name: "import sessions",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS import_sessions (
+              id INTEGER PRIMARY KEY,
+              status TEXT NOT NULL,             -- 'running' | 'interrupted' | 'done' | 'rolled_back'
+              drafts_json TEXT NOT NULL,        -- the whole batch, see imports::start
+              total INTEGER NOT NULL,
+              committed INTEGER NOT NULL DEFAULT 0,  -- drafts done, in batch order
+              op_ids TEXT NOT NULL DEFAULT '[]',     -- JSON array of the journaled operations
+              started_at TEXT NOT NULL,
+              updated_at TEXT NOT NULL
+            );
+            "#,
+        down: Some("DROP TABLE import_sessions;"),
+    },
+    Migration {
+        version: 39,
+        name: "install journal",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS install_journal (
+              id INTEGER PRIMARY KEY,
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              target_path TEXT NOT NULL,
+              files_json TEXT NOT NULL,         -- rel paths to copy, in order
+              swapped_out_json TEXT NOT NULL,   -- sibling ids to uninstall first
+              status TEXT NOT NULL,             -- 'pending' | 'interrupted' | 'done' | 'rolled_back' | 'replayed'
+              stage TEXT NOT NULL,              -- 'swapping' | 'clearing' | 'copying' | 'committing'
+              completed INTEGER NOT NULL DEFAULT 0,  -- files copied, in files_json order
+              error TEXT,
+              started_at TEXT NOT NULL,
+              finished_at TEXT
+            );
+            CREATE INDEX IF NOT EXISTS install_journal_status_idx ON install_journal(status);
+            "#,
+        down: Some("DROP TABLE install_journal;"),
+    },
+    Migration {
+        version: 40,
+        name: "costume asset ids",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS costume_assets (
+              id INTEGER PRIMARY KEY,
+              costume_id INTEGER NOT NULL REFERENCES costumes(id) ON DELETE CASCADE,
+              kind TEXT NOT NULL,               -- 'asset' | 'cutscene'
+              asset_id TEXT NOT NULL,           -- as the game names it, e.g. "char000104"
+              source TEXT,                      -- file name of the imported mapping
+              UNIQUE(costume_id, kind, asset_id)
+            );
+            CREATE INDEX IF NOT EXISTS costume_assets_asset_idx ON costume_assets(asset_id);
+            "#,
+        down: Some("DROP TABLE costume_assets;"),
+    },
+    Migration {
+        version: 41,
+        name: "preview sources",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS preview_sources (
+              mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+              kind TEXT NOT NULL,          -- 'image' | 'video' | 'animated'
+              fingerprint TEXT NOT NULL,   -- manifest::folder_stat of the mod when generated
+              generated_at TEXT NOT NULL,
+              PRIMARY KEY (mod_id, kind)
+            );
+            "#,
+        down: Some("DROP TABLE preview_sources;"),
+    },
+    Migration {
+        version: 42,
+        name: "favorite mods",
+        up: r#"
+            -- see favorites::apply
+            ALTER TABLE mods ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0;
+            "#,
+        down: Some("ALTER TABLE mods DROP COLUMN favorite;"),
+    },
+    Migration {
+        version: 43,
+        name: "usage stats",
+        up: r#"
+            -- local-only counters, see stats.rs
+            CREATE TABLE IF NOT EXISTS usage_stats (
+              kind TEXT NOT NULL,            -- 'install' | 'character' | 'preview'
+              key TEXT NOT NULL,             -- mod id, character id or preview kind
+              count INTEGER NOT NULL DEFAULT 0,
+              total_ms INTEGER NOT NULL DEFAULT 0,
+              first_at TEXT NOT NULL,
+              last_at TEXT NOT NULL,
+              PRIMARY KEY (kind, key)
+            );
+            "#,
+        down: Some("DROP TABLE usage_stats;"),
+    },
+    Migration {
+        version: 44,
+        name: "author id index",
+        up: r#"
+            -- v18 asked for mods_author_idx on author_id, but that name was already taken
+            -- by the index on the author text column, so it never existed
+            CREATE INDEX IF NOT EXISTS mods_author_id_idx ON mods(author_id);
+            "#,
+        down: Some("DROP INDEX IF EXISTS mods_author_id_idx;"),
+    },
+    Migration {
+        version: 45,
+        name: "install journal backup",
+        up: r#"
+            -- where the copy an install replaced was moved aside, see journal::backup_dir
+            ALTER TABLE install_journal ADD COLUMN backup_path TEXT;
+            "#,
+        down: Some("ALTER TABLE install_journal DROP COLUMN backup_path;"),
+    },
+    Migration {
+        version: 46,
+        name: "import session renames",
+        up: r#"
+            -- JSON array of [from, to] folder renames done by sanitizing, see imports::run
+            ALTER TABLE import_sessions ADD COLUMN renames_json TEXT NOT NULL DEFAULT '[]';
+            "#,
+        down: Some("ALTER TABLE import_sessions DROP COLUMN renames_json;"),
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub duration_ms: u64,
+}
+
+/// What `migrate` (or `migrate_down`) did, plus the startup integrity check; `db_init`
+/// hands it to the frontend.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub from_version: i64,
+    pub to_version: i64,
+    pub applied: Vec<AppliedMigration>,
+    /// `PRAGMA integrity_check` and `foreign_key_check` findings; empty when healthy.
+    pub integrity_problems: Vec<String>,
+}
+
+static STARTUP_REPORT: OnceLock<MigrationReport> = OnceLock::new();
+
+/// The report of the migration run at startup by `init_pool`.
+pub fn startup_report() -> MigrationReport {
+    STARTUP_REPORT.get().cloned().unwrap_or_default()
+}
+
+fn ensure_bookkeeping(conn: &Connection) -> Result<i64> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS _schema_version (
+          id INTEGER PRIMARY KEY CHECK (id = 1),
+          version INTEGER NOT NULL
+        );
+        INSERT INTO _schema_version(id, version)
+          SELECT 1, 0 WHERE NOT EXISTS (SELECT 1 FROM _schema_version WHERE id=1);
+        -- applied_at is NULL for steps applied before this table existed
+        CREATE TABLE IF NOT EXISTS _migrations (
+          version INTEGER PRIMARY KEY,
+          name TEXT NOT NULL,
+          applied_at TEXT
+        );
+        "#,
+    )?;
+    let current = schema_version(conn)?;
+    for m in MIGRATIONS.iter().filter(|m| m.version <= current) {
+        conn.execute(
+            "INSERT OR IGNORE INTO _migrations (version, name, applied_at) VALUES (?1, ?2, NULL)",
+            rusqlite::params![m.version, m.name],
+        )?;
+    }
+    Ok(current)
+}
+
+/// Applies every pending step of `MIGRATIONS` in order, each in its own transaction, so a
+/// failing step leaves the database at the previous version.
+pub fn migrate(conn: &Connection) -> Result<MigrationReport> {
+    let current = ensure_bookkeeping(conn)?;
+    let mut report = MigrationReport {
+        from_version: current,
+        to_version: current,
+        ..Default::default()
+    };
+    for m in MIGRATIONS.iter().filter(|m| m.version > current) {
+        info!(
+            "[db::migrate] upgrading schema to v{} ({})",
+            m.version, m.name
+        );
+        let started = Instant::now();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(m.up)
+            .with_context(|| format!("Migration v{} ({}) failed", m.version, m.name))?;
+        tx.execute(
+            "UPDATE _schema_version SET version = ?1 WHERE id = 1",
+            [m.version],
+        )?;
+        tx.execute(
+            r#"
+            INSERT OR REPLACE INTO _migrations (version, name, applied_at)
+            VALUES (?1, ?2, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+            "#,
+            rusqlite::params![m.version, m.name],
         )?;
-        conn.execute("UPDATE _schema_version SET version=5 WHERE id=1;", [])?;
+        tx.commit()?;
+        report.to_version = m.version;
+        report.applied.push(AppliedMigration {
+            version: m.version,
+            name: m.name.to_string(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
     }
+    Ok(report)
+}
 
-    Ok(())
+/// Reverts the schema to `target` by running the `down` steps newest first. Refuses
+/// before touching anything when a step in the range has no `down`.
+pub fn migrate_down(conn: &Connection, target: i64) -> Result<MigrationReport> {
+    let current = ensure_bookkeeping(conn)?;
+    if target >= current {
+        anyhow::bail!(
+            "Schema is at v{}, nothing to revert to v{}",
+            current,
+            target
+        );
+    }
+    let steps: Vec<&Migration> = MIGRATIONS
+        .iter()
+        .rev()
+        .filter(|m| m.version > target && m.version <= current)
+        .collect();
+    if let Some(m) = steps.iter().find(|m| m.down.is_none()) {
+        anyhow::bail!(
+            "Migration v{} ({}) cannot be reverted; the lowest reachable version is v{}",
+            m.version,
+            m.name,
+            m.version
+        );
+    }
+
+    let mut report = MigrationReport {
+        from_version: current,
+        to_version: current,
+        ..Default::default()
+    };
+    for m in steps {
+        warn!("[db::migrate] reverting schema v{} ({})", m.version, m.name);
+        let started = Instant::now();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(m.down.unwrap_or_default())
+            .with_context(|| format!("Reverting v{} ({}) failed", m.version, m.name))?;
+        tx.execute(
+            "UPDATE _schema_version SET version = ?1 WHERE id = 1",
+            [m.version - 1],
+        )?;
+        tx.execute("DELETE FROM _migrations WHERE version = ?1", [m.version])?;
+        tx.commit()?;
+        report.to_version = m.version - 1;
+        report.applied.push(AppliedMigration {
+            version: m.version,
+            name: m.name.to_string(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+    Ok(report)
+}
+
+/// Runs sqlite's consistency checks; returns the problems found (none when healthy).
+pub fn integrity_check(conn: &Connection) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |r| r.get::<_, String>(0))?;
+    for row in rows {
+        let row = row?;
+        if row != "ok" {
+            problems.push(row);
+        }
+    }
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let rows = stmt.query_map([], |r| {
+        Ok(format!(
+            "{} row {} references missing {}",
+            r.get::<_, String>(0)?,
+            r.get::<_, Option<i64>>(1)?.unwrap_or_default(),
+            r.get::<_, String>(2)?
+        ))
+    })?;
+    for row in rows {
+        problems.push(row?);
+    }
+    Ok(problems)
 }
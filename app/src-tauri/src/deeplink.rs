@@ -0,0 +1,132 @@
+use crate::errors::AppError;
+use crate::instance;
+use crate::platform;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+pub const SCHEME: &str = "bd2mods";
+
+/// An install link waiting for the user to confirm it; nothing is written before that.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallRequest {
+    pub id: u64,
+    pub url: String,
+    pub author: Option<String>,
+    pub link: String,
+}
+
+// links received so far, kept until confirmed or dismissed so a link that arrives
+// before the frontend listens is not lost
+fn pending() -> &'static Mutex<Vec<InstallRequest>> {
+    static PENDING: OnceLock<Mutex<Vec<InstallRequest>>> = OnceLock::new();
+    PENDING.get_or_init(Default::default)
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Reads `bd2mods://install?url=<download url>&author=<name>`.
+pub fn parse(link: &Url) -> SResult<(String, Option<String>)> {
+    if link.scheme() != SCHEME {
+        return Err(AppError::invalid(format!(
+            "Not a {}:// link: '{}'",
+            SCHEME, link
+        )));
+    }
+    if link.host_str() != Some("install") {
+        return Err(AppError::invalid(format!(
+            "Unknown link action in '{}'",
+            link
+        )));
+    }
+    let mut url = None;
+    let mut author = None;
+    for (key, value) in link.query_pairs() {
+        match key.as_ref() {
+            "url" => url = Some(value.trim().to_string()),
+            "author" if !value.trim().is_empty() => author = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    let url = url.ok_or_else(|| format!("Link '{}' has no url", link))?;
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(AppError::invalid(format!("Not an http(s) URL: '{}'", url)));
+    }
+    // the author names the folder the archive is extracted into
+    if let Some(author) = author.as_deref() {
+        if !platform::is_single_component(author) {
+            return Err(AppError::invalid(format!(
+                "Link '{}' has an invalid author '{}'",
+                link, author
+            )));
+        }
+    }
+    Ok((url, author))
+}
+
+/// Queues a received link for confirmation and tells the frontend ("deep-link-install").
+pub fn handle(app: &AppHandle, link: &Url) {
+    let (url, author) = match parse(link) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            warn!("[deeplink] {}", err);
+            return;
+        }
+    };
+    let request = InstallRequest {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        url,
+        author,
+        link: link.to_string(),
+    };
+    info!("[deeplink] install link for {}", request.url);
+    pending()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(request.clone());
+    instance::focus_main(app);
+    if let Err(err) = app.emit("deep-link-install", request) {
+        warn!("[deeplink] failed to emit install request: {}", err);
+    }
+}
+
+/// Registers the scheme where that happens at runtime and hooks up the link handler,
+/// including the link the app was started with.
+pub fn init(app: &AppHandle) {
+    // installers register the scheme on Windows/macOS; AppImages and dev builds do not
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(err) = app.deep_link().register_all() {
+        warn!("[deeplink] failed to register {}://: {}", SCHEME, err);
+    }
+    let app_handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for link in event.urls() {
+            handle(&app_handle, &link);
+        }
+    });
+    match app.deep_link().get_current() {
+        Ok(Some(links)) => {
+            for link in links {
+                handle(app, &link);
+            }
+        }
+        Ok(None) => {}
+        Err(err) => warn!("[deeplink] failed to read the start-up link: {}", err),
+    }
+}
+
+pub fn list() -> Vec<InstallRequest> {
+    pending().lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Removes and returns a pending request.
+pub fn take(id: u64) -> Option<InstallRequest> {
+    let mut pending = pending().lock().unwrap_or_else(|e| e.into_inner());
+    let pos = pending.iter().position(|r| r.id == id)?;
+    Some(pending.remove(pos))
+}
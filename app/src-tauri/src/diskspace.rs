@@ -0,0 +1,107 @@
+use crate::errors::AppError;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Whether `required_bytes` fit on the volume holding `path` while keeping
+/// `min_free_bytes` free.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpaceCheck {
+    pub path: String,
+    pub required_bytes: u64,
+    /// `None` when the volume could not be determined; the check then passes.
+    pub available_bytes: Option<u64>,
+    pub min_free_bytes: u64,
+    pub enough: bool,
+}
+
+impl SpaceCheck {
+    /// The user-facing error when there is not enough space.
+    pub fn error(&self) -> Option<String> {
+        if self.enough {
+            return None;
+        }
+        let available = self.available_bytes.unwrap_or(0);
+        let mut msg = format!(
+            "Not enough free space for '{}': need {}, only {} free",
+            self.path,
+            format_bytes(self.required_bytes),
+            format_bytes(available)
+        );
+        if self.min_free_bytes > 0 {
+            msg.push_str(&format!(
+                " (keeping {} free)",
+                format_bytes(self.min_free_bytes)
+            ));
+        }
+        Some(msg)
+    }
+
+    /// `Ok` when there is enough space, else an `insufficient_space` error.
+    pub fn ensure(&self) -> SResult<()> {
+        match self.error() {
+            Some(msg) => Err(AppError::insufficient_space(msg, &self.path)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// "800 MB", "2.3 GB" (decimal units, as file managers show them).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit < 3 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+// the path itself may not exist yet (a new mod folder)
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    let mut current = Some(path);
+    while let Some(p) = current {
+        if p.exists() {
+            return std::fs::canonicalize(p).ok();
+        }
+        current = p.parent();
+    }
+    None
+}
+
+/// Free bytes on the volume holding `path` (the disk with the longest matching mount point).
+pub fn available(path: &Path) -> Option<u64> {
+    let path = existing_ancestor(path)?;
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space())
+}
+
+pub fn check(path: &Path, required_bytes: u64, min_free_mb: u64) -> SpaceCheck {
+    let available_bytes = available(path);
+    let min_free_bytes = min_free_mb * 1_000_000;
+    SpaceCheck {
+        path: path.to_string_lossy().to_string(),
+        required_bytes,
+        available_bytes,
+        min_free_bytes,
+        enough: available_bytes
+            .is_none_or(|free| free >= required_bytes.saturating_add(min_free_bytes)),
+    }
+}
+
+/// Errors with the `SpaceCheck` message when `required_bytes` do not fit.
+pub fn ensure(path: &Path, required_bytes: u64, min_free_mb: u64) -> SResult<()> {
+    check(path, required_bytes, min_free_mb).ensure()
+}
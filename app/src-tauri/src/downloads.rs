@@ -0,0 +1,480 @@
+use crate::commands;
+use crate::db::{self, DbPool};
+use crate::errors::AppError;
+use crate::i18n::Message;
+use crate::platform;
+use crate::wishlist;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+// the whole body; an interrupted download resumes from its partial file
+const BODY_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Download {
+    pub id: i64,
+    pub url: String,
+    pub author: Option<String>,
+    pub file_name: String,
+    pub status: String, // 'queued' | 'running' | 'paused' | 'importing' | 'done' | 'failed' | 'cancelled'
+    pub received_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub error: Option<String>,
+    pub mod_id: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgressEvent {
+    pub id: i64,
+    pub status: String,
+    pub received_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub message: Option<String>,
+    /// `message` for translation, see `i18n::Message`.
+    pub message_i18n: Option<Message>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StopReason {
+    Pause,
+    Cancel,
+}
+
+// why a worker was asked to stop; the worker itself records the final status
+type StopSignal = Arc<Mutex<Option<StopReason>>>;
+
+fn stop_reason(stop: &StopSignal) -> Option<StopReason> {
+    *stop.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// stop signals of the running workers, keyed by download id
+fn workers() -> &'static Mutex<HashMap<i64, StopSignal>> {
+    static WORKERS: OnceLock<Mutex<HashMap<i64, StopSignal>>> = OnceLock::new();
+    WORKERS.get_or_init(Default::default)
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .timeout_recv_response(Some(RESPONSE_TIMEOUT))
+        .timeout_recv_body(Some(BODY_TIMEOUT))
+        .build()
+        .into()
+}
+
+fn temp_dir() -> SResult<PathBuf> {
+    let dir = db::data_dir()?.join("downloads");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn part_path(id: i64) -> SResult<PathBuf> {
+    Ok(temp_dir()?.join(format!("{}.part", id)))
+}
+
+// the last URL segment when it is a plain file name, else `download-<id>.<ext>`; the name
+// ends up in a path, so separators, `..` and drive prefixes are never kept
+fn file_name_for(url: &str, id: i64) -> String {
+    let last = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit(['/', '\\']).next())
+        .map(str::trim)
+        .unwrap_or("");
+    if last.contains('.') && !last.contains(':') && platform::is_single_component(last) {
+        return last.to_string();
+    }
+    let ext = last
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or("zip");
+    format!("download-{}.{}", id, ext)
+}
+
+const DOWNLOAD_COLUMNS: &str = "id, url, author, file_name, status, received_bytes, total_bytes, \
+     error, mod_id, created_at, updated_at";
+
+fn download_from_row(r: &rusqlite::Row<'_>) -> rusqlite::Result<Download> {
+    Ok(Download {
+        id: r.get(0)?,
+        url: r.get(1)?,
+        author: r.get(2)?,
+        file_name: r.get(3)?,
+        status: r.get(4)?,
+        received_bytes: r.get::<_, i64>(5)? as u64,
+        total_bytes: r.get::<_, Option<i64>>(6)?.map(|v| v as u64),
+        error: r.get(7)?,
+        mod_id: r.get(8)?,
+        created_at: r.get(9)?,
+        updated_at: r.get(10)?,
+    })
+}
+
+pub fn get(conn: &Connection, id: i64) -> SResult<Download> {
+    conn.query_row(
+        &format!("SELECT {} FROM downloads WHERE id = ?1", DOWNLOAD_COLUMNS),
+        [id],
+        download_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Download", id))
+}
+
+pub fn list(conn: &Connection) -> SResult<Vec<Download>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM downloads ORDER BY id DESC",
+        DOWNLOAD_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], download_from_row)?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Downloads interrupted by a shutdown can be resumed from where they stopped.
+pub fn recover(conn: &Connection) -> SResult<usize> {
+    conn.execute(
+        "UPDATE downloads SET status = 'paused' WHERE status IN ('queued', 'running')",
+        [],
+    )
+    .map_err(AppError::from)
+}
+
+fn set_status(
+    pool: &DbPool,
+    id: i64,
+    status: &str,
+    received: u64,
+    total: Option<u64>,
+    error: Option<&str>,
+) -> SResult<()> {
+    let conn = pool.get()?;
+    let _guard = db::write_lock();
+    conn.execute(
+        r#"
+        UPDATE downloads SET status = ?2, received_bytes = ?3, total_bytes = ?4, error = ?5,
+          updated_at = ?6
+        WHERE id = ?1
+        "#,
+        params![
+            id,
+            status,
+            received as i64,
+            total.map(|t| t as i64),
+            error,
+            commands::now_iso()
+        ],
+    )?;
+    Ok(())
+}
+
+fn emit(
+    app: &AppHandle,
+    id: i64,
+    status: &str,
+    received: u64,
+    total: Option<u64>,
+    message: Option<Message>,
+) {
+    let payload = DownloadProgressEvent {
+        id,
+        status: status.to_string(),
+        received_bytes: received,
+        total_bytes: total,
+        message: message.as_ref().map(Message::to_string),
+        message_i18n: message,
+    };
+    if let Err(err) = app.emit("download-progress", payload) {
+        warn!("[downloads] failed to emit progress for id={}: {}", id, err);
+    }
+}
+
+pub fn enqueue(conn: &Connection, url: &str, author: Option<String>) -> SResult<i64> {
+    let url = url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(AppError::invalid(format!("Not an http(s) URL: '{}'", url)));
+    }
+    let now = commands::now_iso();
+    conn.execute(
+        r#"
+        INSERT INTO downloads (url, author, file_name, status, received_bytes, created_at, updated_at)
+        VALUES (?1, ?2, '', 'queued', 0, ?3, ?3)
+        "#,
+        params![url, author, now],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "UPDATE downloads SET file_name = ?2 WHERE id = ?1",
+        params![id, file_name_for(url, id)],
+    )?;
+    Ok(id)
+}
+
+/// Starts (or resumes) the worker thread of a download.
+pub fn start(app: AppHandle, pool: DbPool, id: i64) -> SResult<()> {
+    // the status is checked under the workers lock, so a cancel cannot slip in between
+    let mut running = workers().lock().unwrap_or_else(|e| e.into_inner());
+    if running.contains_key(&id) {
+        return Err(format!("Download id={} is already running", id).into());
+    }
+    let download = {
+        let conn = pool.get()?;
+        get(&conn, id)?
+    };
+    if !matches!(download.status.as_str(), "queued" | "paused" | "failed") {
+        return Err(format!(
+            "Download id={} cannot be started while {}",
+            id, download.status
+        )
+        .into());
+    }
+    let stop: StopSignal = Arc::default();
+    running.insert(id, stop.clone());
+    drop(running);
+    thread::spawn(move || {
+        let result = run(&app, &pool, &download, &stop);
+        if let Err(err) = result.as_ref() {
+            if stop_reason(&stop) == Some(StopReason::Cancel) {
+                // cancelled while failing: drop the partial file rather than keep it for a retry
+                let _ = finish_cancel(&pool, id);
+                emit(&app, id, "cancelled", 0, download.total_bytes, None);
+            } else {
+                record_failure(&app, &pool, &download, err);
+            }
+        }
+        // removed last, so `cancel` only writes the status itself once no worker can
+        workers()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id);
+    });
+    Ok(())
+}
+
+fn record_failure(app: &AppHandle, pool: &DbPool, download: &Download, err: &AppError) {
+    let id = download.id;
+    warn!("[downloads] id={} failed: {}", id, err);
+    let received = part_path(id)
+        .ok()
+        .and_then(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let _ = set_status(
+        pool,
+        id,
+        "failed",
+        received,
+        download.total_bytes,
+        Some(err.message()),
+    );
+    emit(
+        app,
+        id,
+        "failed",
+        received,
+        download.total_bytes,
+        Some(err.to_message()),
+    );
+}
+
+// signals a running worker; false when none runs for `id`
+fn signal(workers: &HashMap<i64, StopSignal>, id: i64, reason: StopReason) -> bool {
+    match workers.get(&id) {
+        Some(stop) => {
+            let mut current = stop.lock().unwrap_or_else(|e| e.into_inner());
+            // a pause never downgrades a cancel
+            if *current != Some(StopReason::Cancel) {
+                *current = Some(reason);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Asks a running worker to stop; the partial file is kept so the download can resume.
+pub fn pause(id: i64) -> bool {
+    let workers = workers().lock().unwrap_or_else(|e| e.into_inner());
+    signal(&workers, id, StopReason::Pause)
+}
+
+/// Stops the download and drops its partial file. A running worker records the
+/// cancellation itself; otherwise it is recorded here.
+pub fn cancel(conn: &Connection, id: i64) -> SResult<()> {
+    // held while writing, so a worker cannot start or finish in between
+    let workers = workers().lock().unwrap_or_else(|e| e.into_inner());
+    if signal(&workers, id, StopReason::Cancel) {
+        return Ok(());
+    }
+    mark_cancelled(conn, id)
+}
+
+fn mark_cancelled(conn: &Connection, id: i64) -> SResult<()> {
+    conn.execute(
+        r#"
+        UPDATE downloads SET status = 'cancelled', updated_at = ?2
+        WHERE id = ?1 AND status NOT IN ('importing', 'done')
+        "#,
+        params![id, commands::now_iso()],
+    )?;
+    let part = part_path(id)?;
+    if part.exists() {
+        fs::remove_file(&part)?;
+    }
+    Ok(())
+}
+
+fn finish_cancel(pool: &DbPool, id: i64) -> SResult<()> {
+    let conn = pool.get()?;
+    let _guard = db::write_lock();
+    mark_cancelled(&conn, id)
+}
+
+/// Streams the body into the partial file, resuming from its current length. Returns the
+/// final size, or `None` when the worker was asked to stop.
+fn fetch(
+    app: &AppHandle,
+    pool: &DbPool,
+    download: &Download,
+    stop: &StopSignal,
+) -> SResult<Option<u64>> {
+    let id = download.id;
+    let part = part_path(id)?;
+    let mut received = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = agent().get(&download.url);
+    if received > 0 {
+        request = request.header("Range", &format!("bytes={}-", received));
+    }
+    let resp = match request.call() {
+        Ok(resp) => resp,
+        // the partial file already holds everything (e.g. a failed import being retried)
+        Err(ureq::Error::StatusCode(416)) if received > 0 => return Ok(Some(received)),
+        Err(e) => return Err(AppError::network(&download.url, e)),
+    };
+    let resumed = resp.status().as_u16() == 206;
+    if !resumed {
+        // the server ignored the range (or this is a fresh start)
+        received = 0;
+    }
+    let remaining: Option<u64> = resp
+        .headers()
+        .get("content-length")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let total = remaining.map(|r| r + received);
+    info!(
+        "[downloads] id={} start url='{}' resumed={} offset={} total={:?}",
+        id, download.url, resumed, received, total
+    );
+
+    let mut out = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .map_err(|e| format!("Failed to open '{}': {}", part.display(), e))?;
+    set_status(pool, id, "running", received, total, None)?;
+    emit(app, id, "running", received, total, None);
+
+    let mut reader = resp.into_body().into_reader();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut last_emit = Instant::now();
+    loop {
+        if let Some(reason) = stop_reason(stop) {
+            out.flush()?;
+            drop(out);
+            let status = match reason {
+                StopReason::Cancel => {
+                    finish_cancel(pool, id)?;
+                    "cancelled"
+                }
+                StopReason::Pause => {
+                    set_status(pool, id, "paused", received, total, None)?;
+                    "paused"
+                }
+            };
+            info!("[downloads] id={} {} at {} bytes", id, status, received);
+            emit(app, id, status, received, total, None);
+            return Ok(None);
+        }
+        let n = reader.read(&mut buf).map_err(|e| AppError::Network {
+            message: format!("Download interrupted: {}", e),
+            url: Some(download.url.clone()),
+        })?;
+        if n == 0 {
+            break;
+        }
+        out.write_all(&buf[..n])?;
+        received += n as u64;
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            set_status(pool, id, "running", received, total, None)?;
+            emit(app, id, "running", received, total, None);
+        }
+    }
+    out.flush()?;
+    Ok(Some(received))
+}
+
+fn run(app: &AppHandle, pool: &DbPool, download: &Download, stop: &StopSignal) -> SResult<()> {
+    let id = download.id;
+    let Some(size) = fetch(app, pool, download, stop)? else {
+        return Ok(());
+    };
+
+    // hand the finished file to the archive importer under its real name
+    set_status(pool, id, "importing", size, Some(size), None)?;
+    emit(app, id, "importing", size, Some(size), None);
+    let part = part_path(id)?;
+    let archive = temp_dir()?.join(format!("{}-{}", id, download.file_name));
+    fs::rename(&part, &archive)?;
+    let imported = {
+        // extracts unlocked and takes the write lock itself
+        let mut conn = pool.get()?;
+        commands::import_archive(
+            &mut conn,
+            &archive,
+            download.author.clone(),
+            Some(download.url.clone()),
+        )
+    };
+    let mod_id = match imported {
+        Ok(mod_id) => mod_id,
+        Err(err) => {
+            // keep the file as the partial so a retry does not download it again
+            let _ = fs::rename(&archive, &part);
+            return Err(err);
+        }
+    };
+    let _ = fs::remove_file(&archive);
+    {
+        let conn = pool.get()?;
+        let _guard = db::write_lock();
+        conn.execute(
+            "UPDATE downloads SET mod_id = ?2 WHERE id = ?1",
+            params![id, mod_id],
+        )?;
+        wishlist::download_imported(&conn, id, mod_id, &commands::now_iso())?;
+    }
+    set_status(pool, id, "done", size, Some(size), None)?;
+    info!("[downloads] id={} done mod id={}", id, mod_id);
+    emit(app, id, "done", size, Some(size), None);
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use crate::errors::AppError;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateMember {
+    pub id: i64,
+    pub display_name: String,
+    pub folder_path: String,
+    pub author: Option<String>,
+    pub installed: bool,
+    pub file_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    // lowest pairwise similarity inside the group; 1.0 means identical file sets
+    pub similarity: f32,
+    pub members: Vec<DuplicateMember>,
+}
+
+fn find(parent: &mut HashMap<i64, i64>, x: i64) -> i64 {
+    let p = *parent.get(&x).unwrap_or(&x);
+    if p == x {
+        return x;
+    }
+    let root = find(parent, p);
+    parent.insert(x, root);
+    root
+}
+
+/// Groups mods whose manifests share at least `threshold` of their content hashes
+/// (Jaccard similarity over the sets of file hashes, so renamed files still match).
+pub fn find_groups(conn: &Connection, threshold: f32) -> SResult<Vec<DuplicateGroup>> {
+    let mut sets: HashMap<i64, HashSet<String>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT mod_id, hash FROM mod_files")?;
+    let rows = stmt.query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (id, hash) = row?;
+        sets.entry(id).or_default().insert(hash);
+    }
+
+    // inverted index hash -> mods, so only mods sharing at least one file are compared
+    let mut by_hash: HashMap<&str, Vec<i64>> = HashMap::new();
+    for (id, hashes) in sets.iter() {
+        for h in hashes {
+            by_hash.entry(h.as_str()).or_default().push(*id);
+        }
+    }
+    let mut shared: HashMap<(i64, i64), usize> = HashMap::new();
+    for ids in by_hash.values() {
+        for (i, a) in ids.iter().enumerate() {
+            for b in ids.iter().skip(i + 1) {
+                let key = if a < b { (*a, *b) } else { (*b, *a) };
+                *shared.entry(key).or_default() += 1;
+            }
+        }
+    }
+
+    let mut parent: HashMap<i64, i64> = HashMap::new();
+    let mut pair_scores: Vec<((i64, i64), f32)> = Vec::new();
+    for ((a, b), common) in shared {
+        let union = sets[&a].len() + sets[&b].len() - common;
+        let score = if union == 0 {
+            0.0
+        } else {
+            common as f32 / union as f32
+        };
+        if score >= threshold {
+            pair_scores.push(((a, b), score));
+            let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+            if ra != rb {
+                parent.insert(ra, rb);
+            }
+        }
+    }
+
+    let mut groups: HashMap<i64, (HashSet<i64>, f32)> = HashMap::new();
+    for ((a, b), score) in pair_scores {
+        let root = find(&mut parent, a);
+        let entry = groups.entry(root).or_insert_with(|| (HashSet::new(), 1.0));
+        entry.0.insert(a);
+        entry.0.insert(b);
+        entry.1 = entry.1.min(score);
+    }
+
+    let mut out = Vec::new();
+    for (_, (ids, similarity)) in groups {
+        let mut members = Vec::new();
+        for id in ids {
+            let member = conn.query_row(
+                "SELECT id, display_name, folder_path, author, installed FROM mods WHERE id = ?1",
+                [id],
+                |r| {
+                    Ok(DuplicateMember {
+                        id: r.get(0)?,
+                        display_name: r.get(1)?,
+                        folder_path: r.get(2)?,
+                        author: r.get(3)?,
+                        installed: r.get::<_, i64>(4)? != 0,
+                        file_count: sets.get(&id).map(|s| s.len()).unwrap_or(0),
+                    })
+                },
+            )?;
+            members.push(member);
+        }
+        members.sort_by_key(|m| m.id);
+        out.push(DuplicateGroup {
+            similarity,
+            members,
+        });
+    }
+    out.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then(a.members[0].id.cmp(&b.members[0].id))
+    });
+    Ok(out)
+}
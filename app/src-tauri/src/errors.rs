@@ -0,0 +1,225 @@
+use crate::i18n::Message;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// Error returned by every command. Serialized as `{ "code": "...", "message": "...", ... }`
+/// so the frontend can branch on `code` and still show `message`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum AppError {
+    /// A mod, group, profile, download... that does not exist.
+    NotFound {
+        message: String,
+        id: Option<i64>,
+    },
+    /// SQLite is busy: another writer or another process holds the database.
+    DatabaseLocked {
+        message: String,
+    },
+    Database {
+        message: String,
+    },
+    /// A mod folder, archive or configured path is gone.
+    PathMissing {
+        message: String,
+        path: Option<String>,
+    },
+    /// A setting the operation needs (game mods folder, library folder) is not set.
+    NotConfigured {
+        message: String,
+        setting: Option<String>,
+    },
+    /// The target volume would drop below the free-space threshold.
+    InsufficientSpace {
+        message: String,
+        path: Option<String>,
+    },
+    /// The request itself is wrong (empty name, unknown kind, bad path).
+    InvalidInput {
+        message: String,
+    },
+    /// A download, image fetch or update check that did not get through.
+    Network {
+        message: String,
+        url: Option<String>,
+    },
+    Internal {
+        message: String,
+    },
+}
+
+impl AppError {
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotFound { message, .. }
+            | AppError::DatabaseLocked { message }
+            | AppError::Database { message }
+            | AppError::PathMissing { message, .. }
+            | AppError::NotConfigured { message, .. }
+            | AppError::InsufficientSpace { message, .. }
+            | AppError::InvalidInput { message }
+            | AppError::Network { message, .. }
+            | AppError::Internal { message } => message,
+        }
+    }
+
+    /// The error as `error.<code>`, with `message` and the detail fields that are set.
+    pub fn to_message(&self) -> Message {
+        let key = match self {
+            AppError::NotFound { .. } => "error.not_found",
+            AppError::DatabaseLocked { .. } => "error.database_locked",
+            AppError::Database { .. } => "error.database",
+            AppError::PathMissing { .. } => "error.path_missing",
+            AppError::NotConfigured { .. } => "error.not_configured",
+            AppError::InsufficientSpace { .. } => "error.insufficient_space",
+            AppError::InvalidInput { .. } => "error.invalid_input",
+            AppError::Network { .. } => "error.network",
+            AppError::Internal { .. } => "error.internal",
+        };
+        let mut message = Message::new(key).with("message", self.message());
+        match self {
+            AppError::NotFound { id: Some(id), .. } => message = message.with("id", id),
+            AppError::PathMissing {
+                path: Some(path), ..
+            }
+            | AppError::InsufficientSpace {
+                path: Some(path), ..
+            } => message = message.with("path", path),
+            AppError::NotConfigured {
+                setting: Some(setting),
+                ..
+            } => message = message.with("setting", setting),
+            AppError::Network { url: Some(url), .. } => message = message.with("url", url),
+            _ => {}
+        }
+        message
+    }
+    pub fn invalid(message: impl Into<String>) -> Self {
+        AppError::InvalidInput {
+            message: message.into(),
+        }
+    }
+
+    /// `what` is the kind of record: "Mod", "Download", "Install journal"...
+    pub fn not_found(what: &str, id: i64) -> Self {
+        AppError::NotFound {
+            message: format!("{} with id={} not found", what, id),
+            id: Some(id),
+        }
+    }
+
+    /// `what` names the path for the message: "Mod folder", "Game mods folder"...
+    pub fn path_missing(what: &str, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_string_lossy().to_string();
+        AppError::PathMissing {
+            message: format!("{} '{}' is missing", what, path),
+            path: Some(path),
+        }
+    }
+
+    /// `setting` is the settings key the operation needs.
+    pub fn not_configured(setting: &str) -> Self {
+        let message = match setting {
+            "game_mods_dir" => "Game mods folder is not configured".to_string(),
+            "library_dirs" => "Add a library folder first".to_string(),
+            "preview_tool_path" => "Preview tool not found".to_string(),
+            other => format!("Setting '{}' is not configured", other),
+        };
+        AppError::NotConfigured {
+            message,
+            setting: Some(setting.to_string()),
+        }
+    }
+
+    /// `err` is what the HTTP client reported for `url`.
+    pub fn network(url: &str, err: impl fmt::Display) -> Self {
+        AppError::Network {
+            message: format!("Request to {} failed: {}", url, err),
+            url: Some(url.to_string()),
+        }
+    }
+
+    pub fn insufficient_space(message: impl Into<String>, path: impl AsRef<Path>) -> Self {
+        AppError::InsufficientSpace {
+            message: message.into(),
+            path: Some(path.as_ref().to_string_lossy().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Anything the feature modules did not classify themselves.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal { message }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::from(message.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        let message = err.to_string();
+        match err.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+                AppError::DatabaseLocked { message }
+            }
+            Some(_) => AppError::Database { message },
+            None => AppError::Internal { message },
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        AppError::Internal {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<rusqlite::Error>() {
+            Ok(err) => AppError::from(err),
+            Err(err) => AppError::Internal {
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::Internal {
+            message: err.to_string(),
+        }
+    }
+}
+
+/// For the few places that still report plain strings (the CLI, setup).
+impl From<AppError> for String {
+    fn from(err: AppError) -> Self {
+        err.message().to_string()
+    }
+}
+
+impl From<r2d2::Error> for AppError {
+    fn from(err: r2d2::Error) -> Self {
+        AppError::DatabaseLocked {
+            message: format!("No database connection available: {}", err),
+        }
+    }
+}
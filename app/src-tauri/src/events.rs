@@ -0,0 +1,103 @@
+use crate::i18n::Message;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModsChangeKind {
+    Added,
+    Updated,
+    Installed,
+    Uninstalled,
+    Deleted,
+    /// Too much changed to list (purge, undo); refetch everything.
+    Reset,
+}
+
+/// Payload of `mods-changed`, emitted after every command that writes mods.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModsChanged {
+    pub kind: ModsChangeKind,
+    pub ids: Vec<i64>,
+}
+
+pub fn mods_changed(app: &AppHandle, kind: ModsChangeKind, ids: Vec<i64>) {
+    if ids.is_empty() && kind != ModsChangeKind::Reset {
+        return;
+    }
+    if let Err(err) = app.emit("mods-changed", ModsChanged { kind, ids }) {
+        warn!("[events] failed to emit mods-changed: {}", err);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanKind {
+    Rescan,
+    ImportDryRun,
+    /// `mods_build_manifest` (and `mods_find_duplicates`) hashing mod folders.
+    Manifest,
+}
+
+/// Payload of `scan-progress`, emitted per folder by `paths_rescan`,
+/// `mods_import_dry_run` and manifest builds. `total` grows while a rescan walks further library roots.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgress {
+    pub kind: ScanKind,
+    /// "walking" (listing a library root) | "measuring" | "hashing" | "scanning" | "done"
+    pub status: &'static str,
+    pub processed: usize,
+    pub total: usize,
+    pub errors: usize,
+    pub current_path: Option<String>,
+}
+
+pub fn scan_progress(app: Option<&AppHandle>, progress: ScanProgress) {
+    let Some(app) = app else { return };
+    if let Err(err) = app.emit("scan-progress", progress) {
+        warn!("[events] failed to emit scan-progress: {}", err);
+    }
+}
+
+/// Payload of `install-progress`, emitted per mod by batch installs (`mods_install_favorites`
+/// and its inverse).
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallProgress {
+    /// "install" | "uninstall"
+    pub action: &'static str,
+    /// "running" | "rolling_back" | "done" | "failed"
+    pub status: &'static str,
+    pub processed: usize,
+    pub total: usize,
+    pub mod_id: Option<i64>,
+    pub display_name: Option<String>,
+    /// `<action>.<status>` with `name`, `processed` and `total`, see `i18n::Message`.
+    /// Filled in by `install_progress`.
+    pub message: Option<Message>,
+}
+
+fn install_message(progress: &InstallProgress) -> Message {
+    let key = match (progress.action, progress.status) {
+        ("uninstall", "running") => "uninstall.running",
+        ("uninstall", "rolling_back") => "uninstall.rolling_back",
+        ("uninstall", "done") => "uninstall.done",
+        ("uninstall", _) => "uninstall.failed",
+        (_, "running") => "install.running",
+        (_, "rolling_back") => "install.rolling_back",
+        (_, "done") => "install.done",
+        _ => "install.failed",
+    };
+    Message::new(key)
+        .with("name", progress.display_name.as_deref().unwrap_or_default())
+        .with("processed", progress.processed)
+        .with("total", progress.total)
+}
+
+pub fn install_progress(app: Option<&AppHandle>, mut progress: InstallProgress) {
+    let Some(app) = app else { return };
+    progress.message = Some(install_message(&progress));
+    if let Err(err) = app.emit("install-progress", progress) {
+        warn!("[events] failed to emit install-progress: {}", err);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::errors::AppError;
+use crate::events::{self, InstallProgress};
+use crate::groups;
+use crate::install;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FavoritesReport {
+    pub installed: Vec<i64>,
+    pub uninstalled: Vec<i64>,
+    /// Installed variants the installed favorites replaced.
+    pub swapped_out: Vec<i64>,
+    /// Already in the wanted state, or another favorite of the same variant group won.
+    pub skipped: Vec<i64>,
+    /// The failure that stopped the batch, after which what it had changed is undone.
+    pub error: Option<String>,
+    /// Whether that undo went through completely.
+    pub rolled_back: bool,
+}
+
+// favorites of one character (every favorite when `None`) and whether each is installed
+fn favorites(conn: &Connection, character_id: Option<i64>) -> SResult<Vec<(i64, String, bool)>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT id, display_name, installed FROM mods
+            WHERE favorite = 1 AND (?1 IS NULL OR character_id = ?1)
+            ORDER BY id
+            "#,
+    )?;
+    let rows = stmt.query_map(params![character_id], |r| {
+        Ok((r.get(0)?, r.get(1)?, r.get::<_, i64>(2)? != 0))
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Installs (or with `install` false, uninstalls) the favorites of a character, or all of
+/// them, as one batch: the first failure undoes what the batch had changed, so it ends
+/// all applied or as it was. Progress goes out as `install-progress`.
+pub fn apply(
+    conn: &mut Connection,
+    app: Option<&AppHandle>,
+    character_id: Option<i64>,
+    install: bool,
+    game_mods_dir: &Path,
+    now: &str,
+) -> SResult<FavoritesReport> {
+    let action = if install { "install" } else { "uninstall" };
+    let mut report = FavoritesReport::default();
+    let mut todo = Vec::new();
+    let mut groups_seen = HashSet::new();
+    for (id, name, installed) in favorites(conn, character_id)? {
+        // variants replace each other; the first favorite of a group is kept
+        let taken = match groups::group_of(conn, id)? {
+            Some(group_id) if install => !groups_seen.insert(group_id),
+            _ => false,
+        };
+        if installed == install || taken {
+            report.skipped.push(id);
+        } else {
+            todo.push((id, name));
+        }
+    }
+
+    let total = todo.len();
+    let progress = |status, processed, current: Option<&(i64, String)>| {
+        events::install_progress(
+            app,
+            InstallProgress {
+                action,
+                status,
+                processed,
+                total,
+                mod_id: current.map(|(id, _)| *id),
+                display_name: current.map(|(_, name)| name.clone()),
+                message: None,
+            },
+        )
+    };
+    for (i, item) in todo.iter().enumerate() {
+        progress("running", i, Some(item));
+        let id = item.0;
+        let result = if install {
+            install::install(conn, id, None, game_mods_dir, None, now).map(|r| {
+                report.swapped_out.extend(r.swapped_out);
+                report.installed.push(id);
+            })
+        } else {
+            let tx = conn.transaction()?;
+            install::uninstall(&tx, id, game_mods_dir, None, now)
+                .and_then(|()| tx.commit().map_err(AppError::from))
+                .map(|()| report.uninstalled.push(id))
+        };
+        if let Err(err) = result {
+            warn!("[favorites] {} of id={} failed: {}", action, id, err);
+            report.error = Some(format!("{}: {}", item.1, err));
+            break;
+        }
+    }
+
+    if report.error.is_some() {
+        progress("rolling_back", total, None);
+        let mut undo_errors = Vec::new();
+        for id in report.installed.drain(..).rev() {
+            let tx = conn.transaction()?;
+            if let Err(err) = install::uninstall(&tx, id, game_mods_dir, None, now)
+                .and_then(|()| tx.commit().map_err(AppError::from))
+            {
+                undo_errors.push(format!("id={}: {}", id, err));
+            }
+        }
+        let reinstall: Vec<i64> = report
+            .uninstalled
+            .drain(..)
+            .chain(report.swapped_out.drain(..))
+            .collect();
+        for id in reinstall {
+            if let Err(err) = install::install(conn, id, None, game_mods_dir, None, now) {
+                undo_errors.push(format!("id={}: {}", id, err));
+            }
+        }
+        report.rolled_back = undo_errors.is_empty();
+        if !undo_errors.is_empty() {
+            warn!(
+                "[favorites] rollback incomplete: {}",
+                undo_errors.join("; ")
+            );
+            if let Some(err) = report.error.as_mut() {
+                err.push_str(&format!(
+                    " (rollback incomplete: {})",
+                    undo_errors.join("; ")
+                ));
+            }
+        }
+        progress("failed", total, None);
+        return Ok(report);
+    }
+
+    progress("done", total, None);
+    info!(
+        "[favorites] {} character={:?} installed={} uninstalled={} skipped={}",
+        action,
+        character_id,
+        report.installed.len(),
+        report.uninstalled.len(),
+        report.skipped.len()
+    );
+    Ok(report)
+}
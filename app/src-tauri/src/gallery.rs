@@ -0,0 +1,74 @@
+use crate::errors::AppError;
+use image::imageops::FilterType;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub type SResult<T> = Result<T, AppError>;
+
+pub const GALLERY_DIR: &str = "gallery";
+const SCREENSHOT_PREFIX: &str = "screenshot-";
+// in-game captures are usually 4K; keep gallery entries light enough for the grid
+const MAX_WIDTH: u32 = 1920;
+const MAX_HEIGHT: u32 = 1080;
+
+pub fn gallery_dir(folder: &Path) -> PathBuf {
+    folder.join(GALLERY_DIR)
+}
+
+/// Lists gallery images of a mod folder in name order (oldest screenshot first).
+pub fn list(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(gallery_dir(folder)) else {
+        return Vec::new();
+    };
+    let mut out: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| matches!(e.to_lowercase().as_str(), "png" | "jpg" | "jpeg" | "webp"))
+                .unwrap_or(false)
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+fn next_index(dir: &Path) -> u32 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 1;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let rest = name.strip_prefix(SCREENSHOT_PREFIX)?;
+            rest.split('.').next()?.parse::<u32>().ok()
+        })
+        .max()
+        .map(|n| n + 1)
+        .unwrap_or(1)
+}
+
+/// Copies a screenshot into `<folder>/gallery/screenshot-NNN.png`, downscaling it to fit
+/// 1920x1080. Returns the path of the stored file.
+pub fn attach_screenshot(folder: &Path, source: &Path) -> SResult<PathBuf> {
+    if !folder.is_dir() {
+        return Err(AppError::path_missing("Mod folder", folder));
+    }
+    let img = image::open(source)
+        .map_err(|e| format!("Failed to read image '{}': {}", source.display(), e))?;
+    let img = if img.width() > MAX_WIDTH || img.height() > MAX_HEIGHT {
+        img.resize(MAX_WIDTH, MAX_HEIGHT, FilterType::Lanczos3)
+    } else {
+        img
+    };
+
+    let dir = gallery_dir(folder);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create gallery '{}': {}", dir.display(), e))?;
+    let dest = dir.join(format!("{}{:03}.png", SCREENSHOT_PREFIX, next_index(&dir)));
+    img.save(&dest)
+        .map_err(|e| format!("Failed to write '{}': {}", dest.display(), e))?;
+    Ok(dest)
+}
@@ -0,0 +1,218 @@
+use crate::commands::normalize_path_string;
+use crate::errors::AppError;
+use crate::history;
+use crate::install;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// A mod whose installed state follows a symbolic link in the game folder.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkedMod {
+    pub mod_id: i64,
+    pub display_name: String,
+    /// The link (or the folder holding the links to the mod's files).
+    pub target_path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LinkScanReport {
+    /// Symbolic links found below the game mods folder.
+    pub links: usize,
+    /// Linked mods the database had as not installed, or installed elsewhere.
+    pub linked: Vec<LinkedMod>,
+    /// Mods installed at a link that is broken or leads to another folder now.
+    pub unlinked: Vec<LinkedMod>,
+    /// Links leading outside every library mod folder (or nowhere).
+    pub unmatched: Vec<String>,
+    /// Whether the database was updated; otherwise the report only shows what would change.
+    pub applied: bool,
+}
+
+struct ModFolder {
+    id: i64,
+    display_name: String,
+    folder: PathBuf, // canonical
+    installed: bool,
+    target_path: Option<String>,
+}
+
+fn mod_folders(conn: &Connection) -> SResult<Vec<ModFolder>> {
+    let mut stmt =
+        conn.prepare("SELECT id, display_name, folder_path, installed, target_path FROM mods")?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, String>(2)?,
+                r.get::<_, i64>(3)? != 0,
+                r.get::<_, Option<String>>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    // folders that are gone cannot be linked to
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, display_name, folder_path, installed, target_path)| {
+            Some(ModFolder {
+                folder: fs::canonicalize(&folder_path).ok()?,
+                id,
+                display_name,
+                installed,
+                target_path,
+            })
+        })
+        .collect())
+}
+
+// the install folder of a link into a mod: the link itself for a link to the mod folder,
+// else the folder its path below the mod folder is mirrored from
+fn install_folder(link: &Path, rel: &Path) -> PathBuf {
+    let depth = rel.components().count();
+    link.ancestors().nth(depth).unwrap_or(link).to_path_buf()
+}
+
+fn resolves_to(link: &Path, folder: &Path) -> bool {
+    fs::canonicalize(link).is_ok_and(|t| t.starts_with(folder))
+}
+
+/// Rebuilds installed state from the symbolic links in the game mods folder: mods linked
+/// there are installed at the link, and mods installed at a link that no longer leads to
+/// them are not. Mods installed by copying are left alone. With `apply` false nothing is
+/// written.
+pub fn scan(
+    conn: &mut Connection,
+    game_mods_dir: &Path,
+    apply: bool,
+    now: &str,
+) -> SResult<LinkScanReport> {
+    if !game_mods_dir.is_dir() {
+        return Err(AppError::path_missing("Game mods folder", game_mods_dir));
+    }
+    let mods = mod_folders(conn)?;
+    let mut report = LinkScanReport::default();
+    let mut seen = std::collections::HashSet::new();
+
+    // links are not followed, so a linked folder is looked at once
+    for entry in WalkDir::new(game_mods_dir).min_depth(1) {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(err) => {
+                warn!("[gamelinks] {}", err);
+                continue;
+            }
+        };
+        if !entry.path_is_symlink() {
+            continue;
+        }
+        report.links += 1;
+        let link = entry.path();
+        // the most nested mod folder, for mods inside other mods' folders
+        let owner = fs::canonicalize(link).ok().and_then(|resolved| {
+            mods.iter()
+                .filter_map(|m| Some((m, resolved.strip_prefix(&m.folder).ok()?.to_path_buf())))
+                .max_by_key(|(m, _)| m.folder.components().count())
+        });
+        let Some((m, rel)) = owner else {
+            report
+                .unmatched
+                .push(normalize_path_string(&link.to_string_lossy()));
+            continue;
+        };
+        if !seen.insert(m.id) {
+            continue;
+        }
+        let target_path = normalize_path_string(&install_folder(link, &rel).to_string_lossy());
+        let recorded = m.target_path.as_deref().map(normalize_path_string);
+        if !m.installed || recorded.as_deref() != Some(target_path.as_str()) {
+            report.linked.push(LinkedMod {
+                mod_id: m.id,
+                display_name: m.display_name.clone(),
+                target_path,
+            });
+        }
+    }
+
+    for m in mods.iter().filter(|m| m.installed && !seen.contains(&m.id)) {
+        let Some(target_path) = m.target_path.as_deref() else {
+            continue;
+        };
+        let target = Path::new(target_path);
+        let is_link = fs::symlink_metadata(target).is_ok_and(|meta| meta.file_type().is_symlink());
+        if is_link && !resolves_to(target, &m.folder) {
+            report.unlinked.push(LinkedMod {
+                mod_id: m.id,
+                display_name: m.display_name.clone(),
+                target_path: target_path.to_string(),
+            });
+        }
+    }
+
+    if apply && !(report.linked.is_empty() && report.unlinked.is_empty()) {
+        let tx = conn.transaction()?;
+        let mut before = Vec::new();
+        for m in report.linked.iter() {
+            before.push((m.mod_id, history::snapshot(&tx, m.mod_id)?));
+            tx.execute(
+                r#"
+                UPDATE mods SET installed = 1, installed_at = ?2, target_path = ?3, updated_at = ?2
+                WHERE id = ?1
+                "#,
+                params![m.mod_id, now, m.target_path],
+            )?;
+            install::record_event(&tx, m.mod_id, "install", Some(&m.target_path), None, now)?;
+        }
+        if !before.is_empty() {
+            history::log(
+                &tx,
+                "install",
+                &format!("Found {} mod(s) linked into the game folder", before.len()),
+                &before,
+                now,
+            )?;
+        }
+        let mut before = Vec::new();
+        for m in report.unlinked.iter() {
+            before.push((m.mod_id, history::snapshot(&tx, m.mod_id)?));
+            tx.execute(
+                r#"
+                UPDATE mods SET installed = 0, installed_at = NULL, target_path = NULL,
+                  updated_at = ?2
+                WHERE id = ?1
+                "#,
+                params![m.mod_id, now],
+            )?;
+            tx.execute("DELETE FROM install_checks WHERE mod_id = ?1", [m.mod_id])?;
+            install::record_event(&tx, m.mod_id, "uninstall", Some(&m.target_path), None, now)?;
+        }
+        if !before.is_empty() {
+            history::log(
+                &tx,
+                "uninstall",
+                &format!(
+                    "{} mod(s) no longer linked into the game folder",
+                    before.len()
+                ),
+                &before,
+                now,
+            )?;
+        }
+        tx.commit()?;
+        report.applied = true;
+    }
+    info!(
+        "[gamelinks] links={} linked={} unlinked={} unmatched={} applied={}",
+        report.links,
+        report.linked.len(),
+        report.unlinked.len(),
+        report.unmatched.len(),
+        report.applied
+    );
+    Ok(report)
+}
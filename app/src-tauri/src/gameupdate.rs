@@ -0,0 +1,147 @@
+use crate::commands::load_settings;
+use crate::db;
+use crate::errors::AppError;
+use crate::platform;
+use crate::settings;
+use crate::verify::{self, GameAuditEntry, GameAuditStatus};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+use walkdir::WalkDir;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Payload of `game-updated`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameUpdatedEvent {
+    pub exe_path: String,
+    pub previous: String,
+    pub current: String,
+    /// Installed mods whose files the patch overwrote or removed.
+    pub affected: Vec<GameAuditEntry>,
+}
+
+fn secs(time: std::io::Result<SystemTime>) -> Option<u64> {
+    Some(time.ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+// total size and newest mtime of the files in the data folder next to the executable
+// (`<exe name>_Data`, where the game keeps its assets)
+fn data_fingerprint(exe: &Path) -> Option<String> {
+    let stem = exe.file_stem()?.to_string_lossy();
+    let dir = exe.with_file_name(format!("{}_Data", stem));
+    if !dir.is_dir() {
+        return None;
+    }
+    let (mut size, mut newest) = (0u64, 0u64);
+    for entry in WalkDir::new(&dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+    {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        size += meta.len();
+        newest = newest.max(secs(meta.modified()).unwrap_or(0));
+    }
+    Some(format!("{}-{}", size, newest))
+}
+
+/// "<size>-<mtime>" of the executable, followed by the same of its data folder when there
+/// is one. Patches replace the binary or the assets, so one of them changes; the Windows
+/// version resource is not readable on every OS.
+pub fn fingerprint(exe: &Path) -> Option<String> {
+    let meta = std::fs::metadata(exe).ok()?;
+    let exe_part = format!("{}-{}", meta.len(), secs(meta.modified())?);
+    Some(match data_fingerprint(exe) {
+        Some(data) => format!("{}-{}", exe_part, data),
+        None => exe_part,
+    })
+}
+
+/// Compares the game executable with the fingerprint stored in settings. On a change the
+/// new one is stored and the installed mods are audited; returns the event to emit. The
+/// first sighting is only recorded.
+pub fn check(conn: &Connection, now: &str) -> SResult<Option<GameUpdatedEvent>> {
+    let settings = load_settings(conn)?;
+    let exe = match settings.game_exe.as_deref().map(str::trim) {
+        Some(p) if !p.is_empty() => Some(Path::new(p).to_path_buf()),
+        _ => platform::find_game_exe(),
+    };
+    let Some(exe) = exe else {
+        return Ok(None);
+    };
+    let Some(current) = fingerprint(&exe) else {
+        return Ok(None);
+    };
+    let previous = settings.game_version;
+    if previous.as_deref() == Some(current.as_str()) {
+        return Ok(None);
+    }
+    // recorded before the data folder was part of the fingerprint, same executable
+    let upgraded = previous
+        .as_deref()
+        .is_some_and(|p| current.starts_with(&format!("{}-", p)) && p.split('-').count() == 2);
+    {
+        let _writer = db::write_lock();
+        settings::set_game_version(conn, &current)?;
+        if upgraded {
+            conn.execute(
+                "UPDATE mods SET verified_game_version = ?2 WHERE verified_game_version = ?1",
+                params![previous, current],
+            )?;
+        }
+    }
+    let Some(previous) = previous.filter(|_| !upgraded) else {
+        info!("[gameupdate] recorded game version {}", current);
+        return Ok(None);
+    };
+
+    let affected: Vec<GameAuditEntry> = verify::game_audit(conn, now)?
+        .into_iter()
+        .filter(|a| a.status != GameAuditStatus::Intact)
+        .collect();
+    info!(
+        "[gameupdate] game changed {} -> {}; {} installed mod(s) affected",
+        previous,
+        current,
+        affected.len()
+    );
+    Ok(Some(GameUpdatedEvent {
+        exe_path: exe.to_string_lossy().to_string(),
+        previous,
+        current,
+        affected,
+    }))
+}
+
+/// Records that `ids` were checked to work with `game_version`, by default the current one
+/// from settings. Mods whose version differs show up under `ModFilter::untested`.
+pub fn mark_verified(
+    conn: &Connection,
+    ids: &[i64],
+    game_version: Option<String>,
+    now: &str,
+) -> SResult<usize> {
+    let version = match game_version.filter(|v| !v.trim().is_empty()) {
+        Some(v) => v,
+        None => load_settings(conn)?.game_version.ok_or_else(|| {
+            "The game version is not known yet; set the game executable in settings".to_string()
+        })?,
+    };
+    let mut marked = 0;
+    for id in ids {
+        marked += conn.execute(
+            "UPDATE mods SET verified_game_version = ?2, verified_at = ?3 WHERE id = ?1",
+            params![id, version, now],
+        )?;
+    }
+    info!(
+        "[gameupdate] marked {} mod(s) verified with {}",
+        marked, version
+    );
+    Ok(marked)
+}
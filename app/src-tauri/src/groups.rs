@@ -0,0 +1,131 @@
+use crate::errors::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupMember {
+    pub id: i64,
+    pub display_name: String,
+    pub installed: bool,
+}
+
+/// Variants of the same mod (v1/v2/recolors). At most one member is active; installing a
+/// member swaps out the installed sibling.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModGroup {
+    pub id: i64,
+    pub name: String,
+    pub active_mod_id: Option<i64>,
+    pub members: Vec<GroupMember>,
+}
+
+pub fn create(conn: &Connection, name: &str, mod_ids: &[i64], now: &str) -> SResult<i64> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid("Group name is empty"));
+    }
+    conn.execute(
+        "INSERT INTO mod_groups (name, created_at) VALUES (?1, ?2)",
+        params![name, now],
+    )?;
+    let group_id = conn.last_insert_rowid();
+    set_group(conn, Some(group_id), mod_ids)?;
+    Ok(group_id)
+}
+
+/// Moves mods into `group_id`, or out of any group with `None`.
+pub fn set_group(conn: &Connection, group_id: Option<i64>, mod_ids: &[i64]) -> SResult<usize> {
+    let mut changed = 0usize;
+    for id in mod_ids {
+        changed += conn.execute(
+            "UPDATE mods SET group_id = ?2 WHERE id = ?1",
+            params![id, group_id],
+        )?;
+    }
+    // an active variant that left its group is no longer active there
+    conn.execute(
+        r#"
+        UPDATE mod_groups SET active_mod_id = NULL
+        WHERE active_mod_id IS NOT NULL
+          AND active_mod_id NOT IN (SELECT id FROM mods WHERE group_id = mod_groups.id)
+        "#,
+        [],
+    )?;
+    Ok(changed)
+}
+
+pub fn group_of(conn: &Connection, mod_id: i64) -> SResult<Option<i64>> {
+    conn.query_row("SELECT group_id FROM mods WHERE id = ?1", [mod_id], |r| {
+        r.get::<_, Option<i64>>(0)
+    })
+    .optional()
+    .map(Option::flatten)
+    .map_err(AppError::from)
+}
+
+/// Installed members of the mod's group other than the mod itself.
+pub fn installed_siblings(conn: &Connection, mod_id: i64) -> SResult<Vec<i64>> {
+    let Some(group_id) = group_of(conn, mod_id)? else {
+        return Ok(Vec::new());
+    };
+    let mut stmt =
+        conn.prepare("SELECT id FROM mods WHERE group_id = ?1 AND id != ?2 AND installed = 1")?;
+    let rows = stmt.query_map(params![group_id, mod_id], |r| r.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+pub fn set_active(conn: &Connection, group_id: i64, mod_id: i64) -> SResult<()> {
+    let member: Option<i64> = group_of(conn, mod_id)?;
+    if member != Some(group_id) {
+        return Err(format!(
+            "Mod id={} is not a variant in group id={}",
+            mod_id, group_id
+        )
+        .into());
+    }
+    conn.execute(
+        "UPDATE mod_groups SET active_mod_id = ?2 WHERE id = ?1",
+        params![group_id, mod_id],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, group_id: i64) -> SResult<()> {
+    conn.execute("DELETE FROM mod_groups WHERE id = ?1", [group_id])?;
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> SResult<Vec<ModGroup>> {
+    let mut groups: Vec<ModGroup> = {
+        let mut stmt = conn
+            .prepare("SELECT id, name, active_mod_id FROM mod_groups ORDER BY LOWER(name), id")?;
+        let rows = stmt.query_map([], |r| {
+            Ok(ModGroup {
+                id: r.get(0)?,
+                name: r.get(1)?,
+                active_mod_id: r.get(2)?,
+                members: Vec::new(),
+            })
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT id, display_name, installed FROM mods
+            WHERE group_id = ?1 ORDER BY LOWER(display_name), id
+            "#,
+    )?;
+    for group in groups.iter_mut() {
+        let rows = stmt.query_map([group.id], |r| {
+            Ok(GroupMember {
+                id: r.get(0)?,
+                display_name: r.get(1)?,
+                installed: r.get::<_, i64>(2)? != 0,
+            })
+        })?;
+        group.members = rows.collect::<Result<_, _>>()?;
+    }
+    Ok(groups)
+}
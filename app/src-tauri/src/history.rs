@@ -0,0 +1,244 @@
+use crate::errors::AppError;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// The user-editable state of a `mods` row, as stored in the operations journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModSnapshot {
+    pub id: i64,
+    pub display_name: String,
+    pub folder_path: String,
+    pub author: Option<String>,
+    pub download_url: Option<String>,
+    pub character_id: Option<i64>,
+    pub costume_id: Option<i64>,
+    pub swap_source_character_id: Option<i64>,
+    pub mod_type: String,
+    pub installed: bool,
+    pub installed_at: Option<String>,
+    pub target_path: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Missing in journals written before the flag existed.
+    #[serde(default)]
+    pub nsfw: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    pub id: i64,
+    pub kind: String, // "import" | "bulk_edit" | "reinfer" | "install" | "uninstall"
+    pub summary: String,
+    pub mod_count: usize,
+    pub created_at: String,
+    pub undone_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UndoReport {
+    pub op_id: i64,
+    pub restored: usize,
+    pub removed: usize,
+}
+
+const SNAPSHOT_COLUMNS: &str =
+    "id, display_name, folder_path, author, download_url, character_id, \
+     costume_id, swap_source_character_id, mod_type, installed, installed_at, target_path, \
+     created_at, updated_at, nsfw";
+
+fn snapshot_from_row(r: &Row<'_>) -> rusqlite::Result<ModSnapshot> {
+    Ok(ModSnapshot {
+        id: r.get(0)?,
+        display_name: r.get(1)?,
+        folder_path: r.get(2)?,
+        author: r.get(3)?,
+        download_url: r.get(4)?,
+        character_id: r.get(5)?,
+        costume_id: r.get(6)?,
+        swap_source_character_id: r.get(7)?,
+        mod_type: r.get(8)?,
+        installed: r.get::<_, i64>(9)? != 0,
+        installed_at: r.get(10)?,
+        target_path: r.get(11)?,
+        created_at: r.get(12)?,
+        updated_at: r.get(13)?,
+        nsfw: r.get::<_, i64>(14)? != 0,
+    })
+}
+
+pub fn snapshot(conn: &Connection, mod_id: i64) -> SResult<Option<ModSnapshot>> {
+    conn.query_row(
+        &format!("SELECT {} FROM mods WHERE id = ?1", SNAPSHOT_COLUMNS),
+        [mod_id],
+        snapshot_from_row,
+    )
+    .optional()
+    .map_err(AppError::from)
+}
+
+pub fn snapshot_by_path(conn: &Connection, folder_path: &str) -> SResult<Option<ModSnapshot>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM mods WHERE folder_path = ?1",
+            SNAPSHOT_COLUMNS
+        ),
+        [folder_path],
+        snapshot_from_row,
+    )
+    .optional()
+    .map_err(AppError::from)
+}
+
+/// Journals an operation. `before` holds each touched mod with its state prior to the
+/// operation (`None` when the operation created it). Returns the operation id.
+pub fn log(
+    conn: &Connection,
+    kind: &str,
+    summary: &str,
+    before: &[(i64, Option<ModSnapshot>)],
+    now: &str,
+) -> SResult<i64> {
+    conn.execute(
+        "INSERT INTO operations (kind, summary, created_at) VALUES (?1, ?2, ?3)",
+        params![kind, summary, now],
+    )?;
+    let op_id = conn.last_insert_rowid();
+    let mut stmt = conn.prepare(
+        "INSERT INTO operation_mods (op_id, seq, mod_id, before_json) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (seq, (mod_id, snap)) in before.iter().enumerate() {
+        let json = match snap {
+            Some(s) => Some(serde_json::to_string(s)?),
+            None => None,
+        };
+        stmt.execute(params![op_id, seq as i64, mod_id, json])?;
+    }
+    info!(
+        "[history] op id={} kind={} mods={} '{}'",
+        op_id,
+        kind,
+        before.len(),
+        summary
+    );
+    Ok(op_id)
+}
+
+pub fn list(conn: &Connection, limit: usize) -> SResult<Vec<Operation>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT o.id, o.kind, o.summary, COUNT(m.mod_id), o.created_at, o.undone_at
+            FROM operations o LEFT JOIN operation_mods m ON m.op_id = o.id
+            GROUP BY o.id ORDER BY o.id DESC LIMIT ?1
+            "#,
+    )?;
+    let rows = stmt.query_map([limit as i64], |r| {
+        Ok(Operation {
+            id: r.get(0)?,
+            kind: r.get(1)?,
+            summary: r.get(2)?,
+            mod_count: r.get::<_, i64>(3)? as usize,
+            created_at: r.get(4)?,
+            undone_at: r.get(5)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+fn restore(conn: &Connection, s: &ModSnapshot) -> SResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO mods (
+          id, display_name, folder_path, author, download_url, character_id, costume_id,
+          swap_source_character_id, mod_type, installed, installed_at, target_path,
+          created_at, updated_at, nsfw
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+        ON CONFLICT(id) DO UPDATE SET
+          display_name = excluded.display_name,
+          folder_path = excluded.folder_path,
+          author = excluded.author,
+          download_url = excluded.download_url,
+          character_id = excluded.character_id,
+          costume_id = excluded.costume_id,
+          swap_source_character_id = excluded.swap_source_character_id,
+          mod_type = excluded.mod_type,
+          installed = excluded.installed,
+          installed_at = excluded.installed_at,
+          target_path = excluded.target_path,
+          updated_at = excluded.updated_at,
+          nsfw = excluded.nsfw
+        "#,
+        params![
+            s.id,
+            s.display_name,
+            s.folder_path,
+            s.author,
+            s.download_url,
+            s.character_id,
+            s.costume_id,
+            s.swap_source_character_id,
+            s.mod_type,
+            s.installed as i64,
+            s.installed_at,
+            s.target_path,
+            s.created_at,
+            s.updated_at,
+            s.nsfw as i64
+        ],
+    )?;
+    Ok(())
+}
+
+/// Puts every mod touched by `op_id` back into its journaled state: rows the operation
+/// created are deleted, the others are restored (re-inserted if deleted since).
+pub fn undo(conn: &mut Connection, op_id: i64, now: &str) -> SResult<UndoReport> {
+    let tx = conn.transaction()?;
+    let undone_at: Option<Option<String>> = tx
+        .query_row(
+            "SELECT undone_at FROM operations WHERE id = ?1",
+            [op_id],
+            |r| r.get(0),
+        )
+        .optional()?;
+    match undone_at {
+        None => return Err(AppError::not_found("Operation", op_id)),
+        Some(Some(at)) => {
+            return Err(format!("Operation id={} was already undone at {}", op_id, at).into())
+        }
+        Some(None) => {}
+    }
+
+    let entries: Vec<(i64, Option<String>)> = {
+        let mut stmt = tx.prepare(
+            "SELECT mod_id, before_json FROM operation_mods WHERE op_id = ?1 ORDER BY seq DESC",
+        )?;
+        let rows = stmt.query_map([op_id], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut report = UndoReport {
+        op_id,
+        restored: 0,
+        removed: 0,
+    };
+    for (mod_id, before) in entries {
+        match before {
+            Some(json) => {
+                let snap: ModSnapshot = serde_json::from_str(&json)?;
+                restore(&tx, &snap)?;
+                report.restored += 1;
+            }
+            None => {
+                report.removed += tx.execute("DELETE FROM mods WHERE id = ?1", [mod_id])?;
+            }
+        }
+    }
+    tx.execute(
+        "UPDATE operations SET undone_at = ?2 WHERE id = ?1",
+        params![op_id, now],
+    )?;
+    tx.commit()?;
+    Ok(report)
+}
@@ -0,0 +1,131 @@
+use crate::errors::AppError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+pub type SResult<T> = Result<T, AppError>;
+
+pub const DEFAULT_LOCALE: &str = "en";
+pub const LOCALES: &[&str] = &["en", "ko", "ja", "zh"];
+
+// flat "key": "template" objects; `{name}` is replaced by the parameter of that name
+fn bundled(locale: &str) -> Option<&'static str> {
+    match locale {
+        "en" => Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/data/i18n/en.json"
+        ))),
+        "ko" => Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/data/i18n/ko.json"
+        ))),
+        "ja" => Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/data/i18n/ja.json"
+        ))),
+        "zh" => Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/data/i18n/zh.json"
+        ))),
+        _ => None,
+    }
+}
+
+/// A backend-generated message as a key into the locale tables plus its parameters, sent
+/// next to the English text so the frontend can show a translation.
+///
+/// Errors render as `error.<code>` with their own fields (`message`, `path`, `id`,
+/// `setting`, `url`) as parameters, see `AppError::to_message`. Event statuses render as
+/// `scan.<status>` and `download.<status>`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Message {
+    pub key: &'static str,
+    pub params: BTreeMap<&'static str, String>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Message {
+            key,
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.params.insert(name, value.to_string());
+        self
+    }
+}
+
+/// The English text.
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&render(DEFAULT_LOCALE, self))
+    }
+}
+
+/// The messages of `locale`, with English filling keys it lacks. Region suffixes are
+/// ignored ("ko-KR" is "ko").
+pub fn table(locale: &str) -> SResult<BTreeMap<String, String>> {
+    let lang = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let parse = |raw: &str| -> SResult<BTreeMap<String, String>> {
+        serde_json::from_str(raw)
+            .map_err(|e| format!("Bundled messages are invalid: {}", e))
+            .map_err(AppError::from)
+    };
+    let mut messages = parse(bundled(DEFAULT_LOCALE).unwrap_or("{}"))?;
+    if lang != DEFAULT_LOCALE {
+        let raw = bundled(&lang).ok_or_else(|| {
+            AppError::invalid(format!(
+                "Unknown locale '{}' (available: {})",
+                locale,
+                LOCALES.join(", ")
+            ))
+        })?;
+        messages.extend(parse(raw)?);
+    }
+    Ok(messages)
+}
+
+// the backend renders English for every progress message, so it is parsed once
+fn english() -> &'static BTreeMap<String, String> {
+    static ENGLISH: OnceLock<BTreeMap<String, String>> = OnceLock::new();
+    ENGLISH.get_or_init(|| table(DEFAULT_LOCALE).unwrap_or_default())
+}
+
+/// `message` in `locale`; unknown keys render as the key itself.
+pub fn render(locale: &str, message: &Message) -> String {
+    let template = if locale == DEFAULT_LOCALE {
+        english().get(message.key).cloned()
+    } else {
+        table(locale).ok().and_then(|mut t| t.remove(message.key))
+    }
+    .unwrap_or_else(|| message.key.to_string());
+    // one pass, so a value holding "{name}" (a folder name, say) is not substituted again
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let param = after
+            .find('}')
+            .and_then(|close| Some((message.params.get(&after[..close])?, close)));
+        match param {
+            Some((value, close)) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
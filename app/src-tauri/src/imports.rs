@@ -0,0 +1,388 @@
+use crate::authors;
+use crate::commands::{load_settings, normalize_path_string, repoint_folder, upsert_draft};
+use crate::errors::AppError;
+use crate::history::{self, ModSnapshot};
+use crate::roots;
+use crate::sanitize;
+use crate::shared;
+use crate::types::DraftMod;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+// drafts per transaction: a crash loses at most one chunk, which rolls back on its own
+const CHUNK: usize = 50;
+
+/// An import batch that was cut short (the app closed mid-commit), offered on the next
+/// launch through `import_resume`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSession {
+    pub id: i64,
+    pub status: String, // 'running' | 'interrupted' | 'done' | 'rolled_back'
+    pub total: usize,
+    pub committed: usize,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResumeAction {
+    /// Commits the drafts that were not reached.
+    #[default]
+    Finish,
+    /// Undoes the chunks that were committed and gives folders renamed by sanitizing
+    /// their old names back.
+    Rollback,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportOutcome {
+    pub session_id: i64,
+    pub inserted: Vec<i64>,
+    pub updated: Vec<i64>,
+    /// Rollback only: rows removed and restored.
+    pub removed: usize,
+    pub restored: usize,
+    /// Rollback only: sanitized folders renamed back.
+    pub renamed_back: usize,
+    /// Rollback only: folders that could not be renamed back.
+    pub errors: Vec<String>,
+}
+
+/// Records a batch before anything of it is written.
+pub fn start(conn: &Connection, drafts: &[DraftMod], now: &str) -> SResult<i64> {
+    let json = serde_json::to_string(drafts)?;
+    conn.execute(
+        r#"
+        INSERT INTO import_sessions (status, drafts_json, total, started_at, updated_at)
+        VALUES ('running', ?1, ?2, ?3, ?3)
+        "#,
+        params![json, drafts.len() as i64, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Sessions still running belong to a process that is gone; called once at startup.
+pub fn recover(conn: &Connection) -> SResult<usize> {
+    let n = conn.execute(
+        "UPDATE import_sessions SET status = 'interrupted' WHERE status = 'running'",
+        [],
+    )?;
+    if n > 0 {
+        warn!("[imports] {} import(s) were interrupted", n);
+    }
+    Ok(n)
+}
+
+pub fn interrupted(conn: &Connection) -> SResult<Vec<ImportSession>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT id, status, total, committed, started_at, updated_at FROM import_sessions
+            WHERE status = 'interrupted' ORDER BY id DESC
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(ImportSession {
+            id: r.get(0)?,
+            status: r.get(1)?,
+            total: r.get::<_, i64>(2)? as usize,
+            committed: r.get::<_, i64>(3)? as usize,
+            started_at: r.get(4)?,
+            updated_at: r.get(5)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+// a folder renamed by sanitizing, as (from, to) normalized paths
+type Rename = (String, String);
+
+struct Session {
+    status: String,
+    drafts: Vec<DraftMod>,
+    committed: usize,
+    op_ids: Vec<i64>,
+    renames: Vec<Rename>,
+}
+
+fn load(conn: &Connection, id: i64) -> SResult<Session> {
+    let (status, drafts, committed, op_ids, renames): (String, String, i64, String, String) = conn
+        .query_row(
+            r#"
+            SELECT status, drafts_json, committed, op_ids, renames_json FROM import_sessions
+            WHERE id = ?1
+            "#,
+            [id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found("Import session", id))?;
+    Ok(Session {
+        status,
+        drafts: serde_json::from_str(&drafts)?,
+        committed: committed as usize,
+        op_ids: serde_json::from_str(&op_ids)?,
+        renames: serde_json::from_str(&renames)?,
+    })
+}
+
+fn set_status(conn: &Connection, id: i64, status: &str, now: &str) -> SResult<()> {
+    conn.execute(
+        "UPDATE import_sessions SET status = ?2, updated_at = ?3 WHERE id = ?1",
+        params![id, status, now],
+    )?;
+    Ok(())
+}
+
+// the folder a draft is committed with. Sanitizing renames happen here, outside the chunk
+// transaction, and are stored with the session right away: a crash before the chunk
+// commits still knows which folders moved, and a later run reuses the rename.
+fn folder_for(
+    conn: &Connection,
+    id: i64,
+    d: &DraftMod,
+    rules: &crate::types::SanitizeRules,
+    renames: &mut Vec<Rename>,
+) -> SResult<String> {
+    let fp_norm = normalize_path_string(&d.folder_path);
+    if let Some((_, to)) = renames.iter().find(|(from, _)| *from == fp_norm) {
+        return Ok(to.clone());
+    }
+    // folders of a read-only library keep their names
+    if !rules.apply_on_import || shared::read_only_root(conn, Path::new(&fp_norm))?.is_some() {
+        return Ok(fp_norm);
+    }
+    let Some(plan) = sanitize::plan_for_path(Path::new(&fp_norm), rules) else {
+        return Ok(fp_norm);
+    };
+    sanitize::apply_plan(&plan)?;
+    let to = normalize_path_string(&plan.to);
+    renames.push((fp_norm, to.clone()));
+    let recorded = serde_json::to_string(renames)
+        .map_err(AppError::from)
+        .and_then(|json| {
+            conn.execute(
+                "UPDATE import_sessions SET renames_json = ?2 WHERE id = ?1",
+                params![id, json],
+            )
+            .map_err(AppError::from)
+        });
+    if let Err(err) = recorded {
+        // a rename the session does not know about could not be undone later
+        renames.pop();
+        if let Err(e) = fs::rename(&plan.to, &plan.from) {
+            warn!(
+                "[mods_import_commit] failed to rename '{}' back: {}",
+                plan.to, e
+            );
+        }
+        return Err(err);
+    }
+    info!(
+        "[mods_import_commit] sanitized folder '{}' -> '{}'",
+        plan.from, plan.to
+    );
+    Ok(to)
+}
+
+// upserts one draft at `fp_norm`; `None` for a duplicate of the batch
+fn commit_draft(
+    conn: &Connection,
+    d: &DraftMod,
+    fp_norm: &str,
+    seen: &mut HashSet<String>,
+    now: &str,
+) -> SResult<Option<(i64, Option<ModSnapshot>)>> {
+    if !seen.insert(fp_norm.to_string()) {
+        // duplicate in same batch → skip
+        debug!(
+            "[mods_import_commit] duplicate draft skipped for folder_path='{}'",
+            fp_norm
+        );
+        return Ok(None);
+    }
+
+    let before = history::snapshot_by_path(conn, fp_norm)?;
+    debug!(
+        "[mods_import_commit] processing display='{}' path='{}' existed_in_db={}",
+        d.display_name,
+        fp_norm,
+        before.is_some()
+    );
+    let mod_id = upsert_draft(conn, d, fp_norm, now).map_err(|e| {
+        warn!(
+            "[mods_import_commit] upsert FAILED path='{}' err={}",
+            fp_norm, e
+        );
+        e
+    })?;
+    Ok(Some((mod_id, before)))
+}
+
+/// Commits the drafts of a session from where it stopped, one transaction per chunk with
+/// the progress stored in the same transaction.
+pub fn run(conn: &mut Connection, id: i64, now: &str) -> SResult<ImportOutcome> {
+    let Session {
+        drafts,
+        committed,
+        mut op_ids,
+        mut renames,
+        ..
+    } = load(conn, id)?;
+    let rules = load_settings(conn)?.sanitize;
+    // De-dupe in the backend too (belt & suspenders)
+    let mut seen = HashSet::<String>::new();
+    let mut outcome = ImportOutcome {
+        session_id: id,
+        ..Default::default()
+    };
+
+    let mut done = committed;
+    for chunk in drafts[committed.min(drafts.len())..].chunks(CHUNK) {
+        let folders = chunk
+            .iter()
+            .map(|d| folder_for(conn, id, d, &rules, &mut renames))
+            .collect::<SResult<Vec<_>>>()?;
+        let tx = conn.transaction()?;
+        let mut journal = Vec::new();
+        let (mut inserted, mut updated) = (0, 0);
+        for (d, fp_norm) in chunk.iter().zip(folders.iter()) {
+            let Some((mod_id, before)) = commit_draft(&tx, d, fp_norm, &mut seen, now)? else {
+                continue;
+            };
+            if before.is_some() {
+                outcome.updated.push(mod_id);
+                updated += 1;
+            } else {
+                outcome.inserted.push(mod_id);
+                inserted += 1;
+            }
+            journal.push((mod_id, before));
+        }
+        roots::assign(&tx)?;
+        authors::sync(&tx, now)?;
+        if !journal.is_empty() {
+            op_ids.push(history::log(
+                &tx,
+                "import",
+                &format!("Imported {} new, updated {}", inserted, updated),
+                &journal,
+                now,
+            )?);
+        }
+        done += chunk.len();
+        tx.execute(
+            "UPDATE import_sessions SET committed = ?2, op_ids = ?3, updated_at = ?4 WHERE id = ?1",
+            params![id, done as i64, serde_json::to_string(&op_ids)?, now],
+        )?;
+        tx.commit().map_err(|e| {
+            warn!("[mods_import_commit] commit FAILED err={}", e);
+            e.to_string()
+        })?;
+    }
+    set_status(conn, id, "done", now)?;
+    info!(
+        "[mods_import_commit] session id={} done inserted={} updated={}",
+        id,
+        outcome.inserted.len(),
+        outcome.updated.len()
+    );
+    Ok(outcome)
+}
+
+// gives sanitized folders their old names back, newest rename first; rows still at the new
+// name follow
+fn undo_renames(conn: &mut Connection, renames: &[Rename], outcome: &mut ImportOutcome, now: &str) {
+    for (from, to) in renames.iter().rev() {
+        if !Path::new(to).is_dir() || Path::new(from).exists() {
+            outcome
+                .errors
+                .push(format!("'{}' was not renamed back to '{}'", to, from));
+            continue;
+        }
+        if let Err(err) = fs::rename(to, from) {
+            outcome
+                .errors
+                .push(format!("Failed to rename '{}' -> '{}': {}", to, from, err));
+            continue;
+        }
+        let repointed = (|| -> SResult<()> {
+            let tx = conn.transaction()?;
+            repoint_folder(&tx, to, from, now)?;
+            roots::assign(&tx)?;
+            tx.commit()?;
+            Ok(())
+        })();
+        if let Err(err) = repointed {
+            warn!(
+                "[imports] failed to repoint '{}' -> '{}': {}",
+                to, from, err
+            );
+            outcome.errors.push(err.to_string());
+        }
+        outcome.renamed_back += 1;
+    }
+}
+
+/// Finishes or rolls back an interrupted session.
+pub fn resume(
+    conn: &mut Connection,
+    id: i64,
+    action: ResumeAction,
+    now: &str,
+) -> SResult<ImportOutcome> {
+    let session = load(conn, id)?;
+    if session.status != "interrupted" {
+        return Err(format!(
+            "Import session id={} is {}, not interrupted",
+            id, session.status
+        )
+        .into());
+    }
+    match action {
+        ResumeAction::Finish => {
+            // drafts renamed before the interruption are picked up at their new names
+            info!(
+                "[imports] finishing session id={} from {}",
+                id, session.committed
+            );
+            set_status(conn, id, "running", now)?;
+            run(conn, id, now)
+        }
+        ResumeAction::Rollback => {
+            let mut outcome = ImportOutcome {
+                session_id: id,
+                ..Default::default()
+            };
+            for op_id in session.op_ids.iter().rev() {
+                // undone from the history already
+                let open: Option<bool> = conn
+                    .query_row(
+                        "SELECT undone_at IS NULL FROM operations WHERE id = ?1",
+                        [op_id],
+                        |r| r.get(0),
+                    )
+                    .optional()?;
+                if open != Some(true) {
+                    continue;
+                }
+                let undone = history::undo(conn, *op_id, now)?;
+                outcome.removed += undone.removed;
+                outcome.restored += undone.restored;
+            }
+            undo_renames(conn, &session.renames, &mut outcome, now);
+            set_status(conn, id, "rolled_back", now)?;
+            info!(
+                "[imports] rolled back session id={} removed={} restored={} renamed_back={}",
+                id, outcome.removed, outcome.restored, outcome.renamed_back
+            );
+            Ok(outcome)
+        }
+    }
+}
@@ -0,0 +1,574 @@
+use crate::commands::load_settings;
+use crate::errors::AppError;
+use crate::types::InferWeights;
+use deunicode::deunicode;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Below this confidence an inferred character is only offered, not assigned.
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+const MAX_CANDIDATES: usize = 3;
+// a costume must be named about as clearly as a character to be picked
+pub const DEFAULT_COSTUME_MIN_SCORE: f32 = 0.5;
+// fuzzy (typo-tolerant) hits never outrank a name found token for token
+pub const DEFAULT_FUZZY_WEIGHT: f32 = 0.6;
+// a full costume name ("Summer Liatris") also identifies its character
+pub const DEFAULT_COSTUME_NAME_WEIGHT: f32 = 0.9;
+// whole tokens only, so e.g. "Sunudes" is not flagged
+const NSFW_KEYWORDS: &[&str] = &[
+    "nsfw",
+    "r18",
+    "nude",
+    "nudes",
+    "naked",
+    "lewd",
+    "hentai",
+    "ecchi",
+    "topless",
+    "uncensored",
+    "xxx",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferCandidate {
+    pub character_id: i64,
+    pub costume_id: Option<i64>,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Inference {
+    /// The best candidate's ids, or `None` when its score is below the threshold.
+    pub character_id: Option<i64>,
+    pub costume_id: Option<i64>,
+    pub confidence: f32,
+    pub candidates: Vec<InferCandidate>,
+}
+
+/// A catalog name found in a folder name, see `Catalog::trace`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NameHit {
+    /// "character" | "costume"
+    pub kind: &'static str,
+    pub id: i64,
+    pub slug: String,
+    /// The matched name (slug, display name, alias or localized name), tokenized.
+    pub name: String,
+    /// "romanized" | "native": which tokenization of the folder name it was found in.
+    pub tokens: &'static str,
+    pub score: f32,
+}
+
+/// Everything `Catalog::infer` looked at for one folder name, for `inference_test`.
+#[derive(Debug, Clone, Serialize)]
+pub struct InferenceTrace {
+    pub tokens: Vec<String>,
+    pub raw_tokens: Vec<String>,
+    /// Best first; names scoring zero are left out.
+    pub name_hits: Vec<NameHit>,
+    pub candidates: Vec<InferCandidate>,
+    pub character_id: Option<i64>,
+    pub costume_id: Option<i64>,
+    pub confidence: f32,
+    pub min_confidence: f32,
+    pub weights: InferWeights,
+    /// `(source, target)` when the name reads like a swap, see `Catalog::swap_pair`.
+    pub swap_pair: Option<(i64, i64)>,
+    pub nsfw: bool,
+}
+
+const MAX_NAME_HITS: usize = 20;
+
+struct Entry {
+    id: i64,
+    character_id: i64,
+    slug: String,
+    names: Vec<Vec<String>>, // slug, display name, aliases and localized names, tokenized
+}
+
+/// Characters and costumes with their names and aliases, loaded once per import batch.
+pub struct Catalog {
+    characters: Vec<Entry>,
+    costumes: Vec<Entry>,
+    min_confidence: f32,
+    weights: InferWeights,
+}
+
+// quick tokenizer/slugger
+pub fn norm_tokens(s: &str) -> Vec<String> {
+    let clean = deunicode(&s.to_lowercase());
+    clean
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// Like `norm_tokens` but without transliteration, so Korean/Japanese names ("라텔") can be
+/// matched as written; deunicode only approximates their romanization.
+pub fn raw_tokens(s: &str) -> Vec<String> {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// Whether a mod name carries an NSFW marker ("[NSFW]", "R18", "18+", "nude", ...).
+pub fn looks_nsfw(name: &str) -> bool {
+    // "18+" loses its '+' in tokenizing and a bare "18" is often a version
+    name.contains("18+")
+        || norm_tokens(name)
+            .iter()
+            .any(|t| NSFW_KEYWORDS.contains(&t.as_str()))
+}
+
+fn is_ascii_name(tokens: &[String]) -> bool {
+    tokens.iter().all(|t| t.is_ascii())
+}
+
+// aliases plus localized names (character_names / costume_names)
+fn aliases_of(conn: &Connection, entity_type: &str) -> SResult<HashMap<i64, Vec<String>>> {
+    let mut out: HashMap<i64, Vec<String>> = HashMap::new();
+    let sql = match entity_type {
+        "character" => {
+            r#"
+            SELECT entity_id, alias_text FROM aliases WHERE entity_type = ?1
+            UNION ALL
+            SELECT character_id, name FROM character_names
+            "#
+        }
+        _ => {
+            r#"
+            SELECT entity_id, alias_text FROM aliases WHERE entity_type = ?1
+            UNION ALL
+            SELECT costume_id, name FROM costume_names
+            "#
+        }
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([entity_type], |r| {
+        Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (id, alias) = row?;
+        out.entry(id).or_default().push(alias);
+    }
+    Ok(out)
+}
+
+// non-Latin aliases are kept in their original script as well as romanized
+fn entry_names(slug: &str, display_name: &str, aliases: &[String]) -> Vec<Vec<String>> {
+    let mut names = vec![norm_tokens(slug), norm_tokens(display_name)];
+    for alias in aliases {
+        names.push(norm_tokens(alias));
+        let raw = raw_tokens(alias);
+        if !is_ascii_name(&raw) {
+            names.push(raw);
+        }
+    }
+    names.retain(|n| !n.is_empty());
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn load_entries(
+    conn: &Connection,
+    sql: &str,
+    mut aliases: HashMap<i64, Vec<String>>,
+) -> SResult<Vec<Entry>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, i64>(1)?,
+            r.get::<_, String>(2)?,
+            r.get::<_, String>(3)?,
+        ))
+    })?;
+    let mut out = Vec::new();
+    for row in rows {
+        let (id, character_id, slug, display_name) = row?;
+        let names = entry_names(
+            &slug,
+            &display_name,
+            &aliases.remove(&id).unwrap_or_default(),
+        );
+        out.push(Entry {
+            id,
+            character_id,
+            slug,
+            names,
+        });
+    }
+    Ok(out)
+}
+
+impl Catalog {
+    pub fn load(conn: &Connection) -> SResult<Self> {
+        let settings = load_settings(conn)?;
+        Ok(Catalog {
+            characters: load_entries(
+                conn,
+                "SELECT id, id, slug, display_name FROM characters",
+                aliases_of(conn, "character")?,
+            )?,
+            costumes: load_entries(
+                conn,
+                "SELECT id, character_id, slug, display_name FROM costumes",
+                aliases_of(conn, "costume")?,
+            )?,
+            min_confidence: settings.infer_min_confidence.clamp(0.0, 1.0),
+            weights: settings.infer_weights,
+        })
+    }
+
+    pub fn character_by_slug(&self, slug: &str) -> Option<i64> {
+        self.characters
+            .iter()
+            .find(|c| c.slug == slug)
+            .map(|c| c.id)
+    }
+
+    pub fn costume_by_slug(&self, character_id: i64, slug: &str) -> Option<i64> {
+        self.costumes
+            .iter()
+            .find(|c| c.character_id == character_id && c.slug == slug)
+            .map(|c| c.id)
+    }
+
+    pub fn costume_character(&self, costume_id: i64) -> Option<i64> {
+        self.costumes
+            .iter()
+            .find(|c| c.id == costume_id)
+            .map(|c| c.character_id)
+    }
+
+    /// Ranks characters (each with its best costume) by how well the folder name names
+    /// them and keeps the top three.
+    ///
+    /// Folder names in Korean or Japanese are first matched as written against the
+    /// original-script names; the romanized form is only used when that finds nothing
+    /// acceptable, since deunicode rarely spells a name the way the catalog does.
+    pub fn infer(&self, folder_name: &str) -> Inference {
+        let raw = raw_tokens(folder_name);
+        if is_ascii_name(&raw) {
+            return self.rank(&norm_tokens(folder_name));
+        }
+        let native = self.rank(&raw);
+        if native.character_id.is_some() {
+            return native;
+        }
+        let romanized = self.rank(&norm_tokens(folder_name));
+        if romanized.confidence > native.confidence {
+            romanized
+        } else {
+            native
+        }
+    }
+
+    fn rank(&self, folder: &[String]) -> Inference {
+        let squashed = folder.concat();
+        let matcher = SkimMatcherV2::default();
+        let w = &self.weights;
+
+        let mut candidates: Vec<InferCandidate> = Vec::new();
+        for ch in self.characters.iter() {
+            let costumes: Vec<&Entry> = self
+                .costumes
+                .iter()
+                .filter(|c| c.character_id == ch.id)
+                .collect();
+            let by_costume_name = costumes
+                .iter()
+                .flat_map(|c| c.names.iter())
+                .map(|n| name_score(n, folder, &squashed, &matcher, w.fuzzy_weight))
+                .filter(|s| *s >= 1.0)
+                .map(|_| w.costume_name_weight)
+                .fold(0.0, f32::max);
+            let score = best_score(&ch.names, folder, &squashed, &matcher, w.fuzzy_weight)
+                .max(by_costume_name);
+            if score <= 0.0 {
+                continue;
+            }
+
+            // judge costumes on what they add to the character's name ("summer" in
+            // "Summer Liatris")
+            let own: Vec<&String> = ch.names.iter().flatten().collect();
+            let costume = costumes
+                .iter()
+                .map(|c| {
+                    let names: Vec<Vec<String>> = c
+                        .names
+                        .iter()
+                        .map(|n| n.iter().filter(|t| !own.contains(t)).cloned().collect())
+                        .filter(|n: &Vec<String>| !n.is_empty())
+                        .collect();
+                    (
+                        c.id,
+                        best_score(&names, folder, &squashed, &matcher, w.fuzzy_weight),
+                    )
+                })
+                .filter(|(_, s)| *s >= w.costume_min_score)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(id, _)| id);
+
+            candidates.push(InferCandidate {
+                character_id: ch.id,
+                costume_id: costume,
+                score: (score * 100.0).round() / 100.0,
+            });
+        }
+        candidates.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.costume_id.is_some().cmp(&a.costume_id.is_some()))
+        });
+        candidates.truncate(MAX_CANDIDATES);
+
+        let confidence = candidates.first().map(|c| c.score).unwrap_or(0.0);
+        let accepted = candidates
+            .first()
+            .filter(|c| c.score >= self.min_confidence);
+        Inference {
+            character_id: accepted.map(|c| c.character_id),
+            costume_id: accepted.and_then(|c| c.costume_id),
+            confidence,
+            candidates,
+        }
+    }
+
+    /// `infer` with its workings: the tokens, every catalog name that scored and the
+    /// settings the decision was made with.
+    pub fn trace(&self, folder_name: &str) -> InferenceTrace {
+        let tokens = norm_tokens(folder_name);
+        let raw = raw_tokens(folder_name);
+        let matcher = SkimMatcherV2::default();
+        let mut forms = vec![("romanized", &tokens)];
+        if !is_ascii_name(&raw) {
+            forms.push(("native", &raw));
+        }
+
+        let mut name_hits = Vec::new();
+        for (form, folder) in forms {
+            let squashed = folder.concat();
+            let entries = self
+                .characters
+                .iter()
+                .map(|e| ("character", e))
+                .chain(self.costumes.iter().map(|e| ("costume", e)));
+            for (kind, entry) in entries {
+                for name in entry.names.iter() {
+                    let score =
+                        name_score(name, folder, &squashed, &matcher, self.weights.fuzzy_weight);
+                    if score <= 0.0 {
+                        continue;
+                    }
+                    name_hits.push(NameHit {
+                        kind,
+                        id: entry.id,
+                        slug: entry.slug.clone(),
+                        name: name.join(" "),
+                        tokens: form,
+                        score: (score * 100.0).round() / 100.0,
+                    });
+                }
+            }
+        }
+        name_hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        name_hits.truncate(MAX_NAME_HITS);
+
+        let inference = self.infer(folder_name);
+        InferenceTrace {
+            tokens,
+            raw_tokens: raw,
+            name_hits,
+            candidates: inference.candidates,
+            character_id: inference.character_id,
+            costume_id: inference.costume_id,
+            confidence: inference.confidence,
+            min_confidence: self.min_confidence,
+            weights: self.weights,
+            swap_pair: self.swap_pair(folder_name),
+            nsfw: looks_nsfw(folder_name),
+        }
+    }
+
+    /// Swap mods are usually named "<source> to <target>". Returns `(source, target)` when
+    /// two distinct characters appear as whole words in the folder name, in order of
+    /// appearance.
+    pub fn swap_pair(&self, folder_name: &str) -> Option<(i64, i64)> {
+        let tokens = norm_tokens(folder_name);
+        let mut hits: Vec<(usize, i64)> = self
+            .characters
+            .iter()
+            .filter_map(|ch| {
+                ch.names
+                    .iter()
+                    .filter_map(|needle| {
+                        if needle.len() > tokens.len() {
+                            return None;
+                        }
+                        tokens
+                            .windows(needle.len())
+                            .position(|w| w == needle.as_slice())
+                    })
+                    .min()
+                    .map(|pos| (pos, ch.id))
+            })
+            .collect();
+        hits.sort();
+        match hits.as_slice() {
+            [(_, source), (_, target), ..] => Some((*source, *target)),
+            _ => None,
+        }
+    }
+}
+
+// short tokens ("of", "2") say little about which character is meant
+fn token_weight(token: &str) -> f32 {
+    match token.chars().count() {
+        0..=2 => 0.3,
+        3 => 0.6,
+        _ => 1.0,
+    }
+}
+
+/// How well one (tokenized) name is present in the folder name, 0..=1: the length-weighted
+/// share of its tokens found as words, 1.0 when it appears whole even without separators
+/// ("liatrisidle"), or a discounted fuzzy score for typos.
+fn name_score(
+    name: &[String],
+    folder: &[String],
+    squashed: &str,
+    matcher: &SkimMatcherV2,
+    fuzzy_weight: f32,
+) -> f32 {
+    let total: f32 = name.iter().map(|t| token_weight(t)).sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    let hit: f32 = name
+        .iter()
+        .filter(|t| folder.contains(t))
+        .map(|t| token_weight(t))
+        .sum();
+    let overlap = hit / total;
+    let joined = name.concat();
+    if overlap >= 1.0 || (joined.len() >= 4 && squashed.contains(&joined)) {
+        return 1.0;
+    }
+    let fuzzy = if joined.len() >= 4 {
+        let perfect = matcher.fuzzy_match(&joined, &joined).unwrap_or(0);
+        let got = matcher.fuzzy_match(squashed, &joined).unwrap_or(0);
+        if perfect > 0 {
+            (got as f32 / perfect as f32).clamp(0.0, 1.0) * fuzzy_weight
+        } else {
+            0.0
+        }
+    } else {
+        0.0
+    };
+    overlap.max(fuzzy)
+}
+
+fn best_score(
+    names: &[Vec<String>],
+    folder: &[String],
+    squashed: &str,
+    matcher: &SkimMatcherV2,
+    fuzzy_weight: f32,
+) -> f32 {
+    names
+        .iter()
+        .map(|n| name_score(n, folder, squashed, matcher, fuzzy_weight))
+        .fold(0.0, f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, character_id: i64, slug: &str, display: &str, aliases: &[&str]) -> Entry {
+        let aliases: Vec<String> = aliases.iter().map(|a| a.to_string()).collect();
+        Entry {
+            id,
+            character_id,
+            slug: slug.to_string(),
+            names: entry_names(slug, display, &aliases),
+        }
+    }
+
+    fn catalog() -> Catalog {
+        Catalog {
+            characters: vec![
+                entry(1, 1, "justia", "Justia", &["유스티아", "ユスティア"]),
+                entry(2, 2, "liatris", "Liatris", &["리아트리스"]),
+                entry(3, 3, "lathel", "Lathel", &[]),
+            ],
+            costumes: vec![
+                entry(10, 1, "swimsuit", "Swimsuit", &["수영복", "水着"]),
+                entry(11, 1, "maid", "Maid", &["메이드"]),
+                entry(20, 2, "bunny-girl", "Bunny Girl", &["바니걸"]),
+            ],
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            weights: InferWeights::default(),
+        }
+    }
+
+    fn picked(folder: &str) -> (Option<i64>, Option<i64>) {
+        let inference = catalog().infer(folder);
+        (inference.character_id, inference.costume_id)
+    }
+
+    #[test]
+    fn raw_tokens_keep_hangul() {
+        assert_eq!(
+            raw_tokens("유스티아_수영복_컷신"),
+            vec!["유스티아", "수영복", "컷신"]
+        );
+    }
+
+    #[test]
+    fn korean_folder_with_separators() {
+        assert_eq!(picked("유스티아_수영복_컷신"), (Some(1), Some(10)));
+        assert_eq!(picked("리아트리스 바니걸 스탠딩"), (Some(2), Some(20)));
+    }
+
+    #[test]
+    fn korean_folder_without_separators() {
+        assert_eq!(picked("유스티아메이드"), (Some(1), Some(11)));
+    }
+
+    #[test]
+    fn japanese_folder() {
+        assert_eq!(picked("ユスティア_水着"), (Some(1), Some(10)));
+    }
+
+    #[test]
+    fn mixed_script_folder() {
+        assert_eq!(picked("Justia_수영복_v2"), (Some(1), Some(10)));
+    }
+
+    #[test]
+    fn falls_back_to_romanization() {
+        // full-width Latin only matches once transliterated
+        assert_eq!(picked("ＬＡＴＨＥＬ"), (Some(3), None));
+    }
+
+    #[test]
+    fn unknown_korean_name_is_not_assigned() {
+        assert_eq!(picked("새로운캐릭터_컷신"), (None, None));
+    }
+
+    #[test]
+    fn latin_folders_unchanged() {
+        assert_eq!(picked("Liatris Bunny Girl idle"), (Some(2), Some(20)));
+    }
+}
@@ -0,0 +1,750 @@
+use crate::assets::{self, AssetConflict};
+use crate::commands::load_settings;
+use crate::db;
+use crate::diskspace::{self, SpaceCheck};
+use crate::errors::AppError;
+use crate::groups;
+use crate::history;
+use crate::journal;
+use crate::manifest;
+use crate::stats;
+use crate::targets;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::MutexGuard;
+use tracing::warn;
+use walkdir::WalkDir;
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModFileEntry {
+    pub rel_path: String,
+    pub size: u64,
+    pub hash: String,
+    pub selected: bool,
+}
+
+/// One install or uninstall of a mod, kept so the user can see when it was last active.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallEvent {
+    pub id: i64,
+    pub mod_id: i64,
+    pub action: String, // 'install' | 'uninstall' | 'adopt' (found installed by hand)
+    pub at: String,
+    pub profile_id: Option<i64>,
+    pub profile_name: Option<String>,
+    pub target_path: Option<String>,
+    pub app_version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallReport {
+    pub mod_id: i64,
+    pub target_path: String,
+    pub files_copied: usize,
+    pub bytes_copied: u64,
+    pub files_skipped: usize,  // not in the selection
+    pub swapped_out: Vec<i64>, // installed variants of the same group that were removed
+}
+
+/// What installing does to one file in the game folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileAction {
+    /// Copied to a path where nothing is.
+    Copy,
+    /// Copied over an existing file.
+    Overwrite,
+    /// Removed: the target folder is cleared first, and swapped-out variants are uninstalled.
+    Delete,
+    /// Not in the install selection.
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedFile {
+    pub action: FileAction,
+    /// Relative to the mod folder, or to `dest`'s folder for deleted files.
+    pub rel_path: String,
+    pub dest: String,
+    pub size: u64,
+    /// The other installed mod the existing file at `dest` belongs to, which loses it.
+    pub owner_mod_id: Option<i64>,
+}
+
+/// What `install` would do, without touching anything. Nothing is backed up: replaced and
+/// deleted files are gone after the install.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallPlan {
+    pub mod_id: i64,
+    pub target_path: String,
+    pub files: Vec<PlannedFile>,
+    pub bytes_to_copy: u64,
+    /// Installed variants of the same group that would be uninstalled.
+    pub swapped_out: Vec<i64>,
+    pub space: SpaceCheck,
+    /// Installed mods replacing the same game assets, by the imported asset mapping.
+    pub asset_conflicts: Vec<AssetConflict>,
+    /// Why the install would be refused as things are (unconfirmed risky files, no space).
+    pub blocked: Option<String>,
+}
+
+/// Persisted file subset of a mod; empty means "everything".
+pub fn selection(conn: &Connection, mod_id: i64) -> SResult<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT rel_path FROM install_selections WHERE mod_id = ?1")?;
+    let rows = stmt.query_map([mod_id], |r| r.get::<_, String>(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+pub(crate) fn store_selection(
+    conn: &Connection,
+    mod_id: i64,
+    paths: &HashSet<String>,
+) -> SResult<()> {
+    conn.execute("DELETE FROM install_selections WHERE mod_id = ?1", [mod_id])?;
+    let mut stmt =
+        conn.prepare("INSERT INTO install_selections (mod_id, rel_path) VALUES (?1, ?2)")?;
+    for p in paths {
+        stmt.execute(params![mod_id, p])?;
+    }
+    Ok(())
+}
+
+fn folder_of(conn: &Connection, mod_id: i64) -> SResult<(String, String)> {
+    conn.query_row(
+        "SELECT folder_path, display_name FROM mods WHERE id = ?1",
+        [mod_id],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Mod", mod_id))
+}
+
+/// Refuses mods with executables or scripts in their folder until the user confirmed them
+/// (`mods_confirm_risk`), see `validate::risk_flags`.
+fn ensure_risk_confirmed(conn: &Connection, mod_id: i64, display_name: &str) -> SResult<()> {
+    let (flags, confirmed): (Option<String>, Option<String>) = conn.query_row(
+        "SELECT risk_flags, risk_confirmed_at FROM mods WHERE id = ?1",
+        [mod_id],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )?;
+    let flags: Vec<String> = flags
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    if flags.is_empty() || confirmed.is_some() {
+        return Ok(());
+    }
+    Err(format!(
+        "'{}' contains executable or script files ({}); confirm them before installing",
+        display_name,
+        flags.join(", ")
+    )
+    .into())
+}
+
+/// The mod's manifest with the persisted install selection applied.
+pub fn files_list(conn: &Connection, mod_id: i64, now: &str) -> SResult<Vec<ModFileEntry>> {
+    let (folder_path, _) = folder_of(conn, mod_id)?;
+    manifest::ensure(conn, mod_id, Path::new(&folder_path), false, now)?;
+    let selected = selection(conn, mod_id)?;
+    Ok(manifest::load_installable(conn, mod_id)?
+        .into_iter()
+        .map(|f| ModFileEntry {
+            selected: selected.is_empty() || selected.contains(&f.rel_path),
+            rel_path: f.rel_path,
+            size: f.size,
+            hash: f.hash,
+        })
+        .collect())
+}
+
+/// Whether installing the mod fits on the game folder's volume. The mod's current copy
+/// is replaced, so the space it takes counts as free.
+pub fn space_check(
+    conn: &Connection,
+    mod_id: i64,
+    game_mods_dir: &Path,
+    min_free_mb: u64,
+) -> SResult<SpaceCheck> {
+    let (folder_path, _) = folder_of(conn, mod_id)?;
+    let selected = selection(conn, mod_id)?;
+    let files = manifest::load_installable(conn, mod_id)?;
+    // no manifest yet: the whole folder is a close enough estimate
+    let needed: u64 = if files.is_empty() {
+        manifest::folder_stat(
+            Path::new(&folder_path),
+            &manifest::Ignores::from_settings(conn)?,
+        )
+        .size_bytes
+    } else {
+        files
+            .iter()
+            .filter(|f| selected.is_empty() || selected.contains(&f.rel_path))
+            .map(|f| f.size)
+            .sum()
+    };
+    let target = game_mods_dir.join(targets::resolve(conn, mod_id)?);
+    let replaced = if target.exists() {
+        manifest::folder_stat(&target, &manifest::Ignores::default()).size_bytes
+    } else {
+        0
+    };
+    Ok(diskspace::check(
+        game_mods_dir,
+        needed.saturating_sub(replaced),
+        min_free_mb,
+    ))
+}
+
+/// Appends to the mod's install timeline. `profile_id` is set when a profile caused it.
+pub fn record_event(
+    conn: &Connection,
+    mod_id: i64,
+    action: &str,
+    target_path: Option<&str>,
+    profile_id: Option<i64>,
+    now: &str,
+) -> SResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO install_events (mod_id, action, at, profile_id, target_path, app_version)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+        params![
+            mod_id,
+            action,
+            now,
+            profile_id,
+            target_path,
+            env!("CARGO_PKG_VERSION")
+        ],
+    )?;
+    Ok(())
+}
+
+/// The install timeline of a mod, newest first.
+pub fn events(conn: &Connection, mod_id: i64) -> SResult<Vec<InstallEvent>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT e.id, e.mod_id, e.action, e.at, e.profile_id, p.name, e.target_path,
+                   e.app_version
+            FROM install_events e
+            LEFT JOIN profiles p ON p.id = e.profile_id
+            WHERE e.mod_id = ?1
+            ORDER BY e.id DESC
+            "#,
+    )?;
+    let rows = stmt.query_map([mod_id], |r| {
+        Ok(InstallEvent {
+            id: r.get(0)?,
+            mod_id: r.get(1)?,
+            action: r.get(2)?,
+            at: r.get(3)?,
+            profile_id: r.get(4)?,
+            profile_name: r.get(5)?,
+            target_path: r.get(6)?,
+            app_version: r.get(7)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+// installed mods and where they are, to name whose files an install replaces
+fn installed_targets(conn: &Connection, except: i64) -> SResult<Vec<(i64, PathBuf)>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, target_path FROM mods WHERE installed = 1 AND target_path IS NOT NULL AND id != ?1",
+        )?;
+    let rows = stmt.query_map([except], |r| {
+        Ok((r.get(0)?, PathBuf::from(r.get::<_, String>(1)?)))
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+// the most nested installed folder holding `path`
+fn owner_of(installed: &[(i64, PathBuf)], path: &Path) -> Option<i64> {
+    installed
+        .iter()
+        .filter(|(_, target)| path.starts_with(target))
+        .max_by_key(|(_, target)| target.components().count())
+        .map(|(id, _)| *id)
+}
+
+// every file under `dir`, relative to it
+fn existing_files(dir: &Path) -> Vec<(String, PathBuf, u64)> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(dir).ok()?;
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            let size = e.metadata().map(|m| m.len()).unwrap_or(0);
+            Some((rel, e.path().to_path_buf(), size))
+        })
+        .collect()
+}
+
+/// The file operations `install` would perform for `mod_id` with `include` (or the
+/// persisted selection), and whose files they replace. Builds the manifest if needed.
+pub fn dry_run(
+    conn: &Connection,
+    mod_id: i64,
+    include: Option<Vec<String>>,
+    game_mods_dir: &Path,
+    now: &str,
+) -> SResult<InstallPlan> {
+    let (folder_path, display_name) = folder_of(conn, mod_id)?;
+    let folder = Path::new(&folder_path);
+    let target = game_mods_dir.join(targets::resolve(conn, mod_id)?);
+    manifest::ensure(conn, mod_id, folder, false, now)?;
+    let files = manifest::load_installable(conn, mod_id)?;
+    let selected = match include {
+        Some(include) => {
+            let known: HashSet<&str> = files.iter().map(|f| f.rel_path.as_str()).collect();
+            if let Some(unknown) = include.iter().find(|p| !known.contains(p.as_str())) {
+                return Err(format!("'{}' is not a file of this mod", unknown).into());
+            }
+            include.into_iter().collect()
+        }
+        None => selection(conn, mod_id)?,
+    };
+    let installed = installed_targets(conn, mod_id)?;
+    let swapped_out = groups::installed_siblings(conn, mod_id)?;
+
+    let mut planned = Vec::new();
+    let mut copied: HashSet<&str> = HashSet::new();
+    let mut bytes_to_copy = 0;
+    for f in files.iter() {
+        let dest = target.join(&f.rel_path);
+        let action = if !selected.is_empty() && !selected.contains(&f.rel_path) {
+            FileAction::Skip
+        } else {
+            copied.insert(f.rel_path.as_str());
+            bytes_to_copy += f.size;
+            if dest.is_file() {
+                FileAction::Overwrite
+            } else {
+                FileAction::Copy
+            }
+        };
+        planned.push(PlannedFile {
+            action,
+            owner_mod_id: match action {
+                FileAction::Overwrite => owner_of(&installed, &dest),
+                _ => None,
+            },
+            rel_path: f.rel_path.clone(),
+            dest: dest.to_string_lossy().to_string(),
+            size: f.size,
+        });
+    }
+    // what clearing the target folder removes without copying it back
+    if target.is_dir() {
+        for (rel, path, size) in existing_files(&target) {
+            if copied.contains(rel.as_str()) {
+                continue;
+            }
+            planned.push(PlannedFile {
+                action: FileAction::Delete,
+                owner_mod_id: owner_of(&installed, &path),
+                rel_path: rel,
+                dest: path.to_string_lossy().to_string(),
+                size,
+            });
+        }
+    }
+    for (id, sibling_target) in installed.iter().filter(|(id, _)| swapped_out.contains(id)) {
+        if sibling_target == &target || !sibling_target.is_dir() {
+            continue;
+        }
+        for (rel, path, size) in existing_files(sibling_target) {
+            planned.push(PlannedFile {
+                action: FileAction::Delete,
+                owner_mod_id: Some(*id),
+                rel_path: rel,
+                dest: path.to_string_lossy().to_string(),
+                size,
+            });
+        }
+    }
+
+    let min_free_mb = load_settings(conn)?.min_free_space_mb;
+    let space = space_check(conn, mod_id, game_mods_dir, min_free_mb)?;
+    let blocked = ensure_risk_confirmed(conn, mod_id, &display_name)
+        .err()
+        .map(|e| e.to_string())
+        .or_else(|| space.error());
+    Ok(InstallPlan {
+        mod_id,
+        target_path: target.to_string_lossy().to_string(),
+        files: planned,
+        bytes_to_copy,
+        swapped_out,
+        space,
+        asset_conflicts: assets::conflicts(conn, mod_id)?,
+        blocked,
+    })
+}
+
+// hands out `db::write_lock` to `install_unlocked`, nothing to callers that hold it
+type Lock<'a> = &'a dyn Fn() -> Option<MutexGuard<'static, ()>>;
+
+fn locked<T>(lock: Lock, f: impl FnOnce() -> SResult<T>) -> SResult<T> {
+    let _writer = lock();
+    f()
+}
+
+/// Copies the mod into `<game_mods_dir>/<folder name>`. `include` replaces the persisted
+/// selection; without it the previous selection (or every file) is installed. The target
+/// folder is replaced as a whole so files dropped from the selection do not linger.
+/// Expects the caller to hold `db::write_lock`.
+pub fn install(
+    conn: &mut Connection,
+    mod_id: i64,
+    include: Option<Vec<String>>,
+    game_mods_dir: &Path,
+    profile_id: Option<i64>,
+    now: &str,
+) -> SResult<InstallReport> {
+    run(
+        conn,
+        mod_id,
+        include,
+        game_mods_dir,
+        profile_id,
+        now,
+        &|| None,
+    )
+}
+
+/// `install` for a caller without `db::write_lock`: hashing and copying run unlocked, the
+/// lock is taken for the journal writes and for swapping the copy into place.
+pub fn install_unlocked(
+    conn: &mut Connection,
+    mod_id: i64,
+    include: Option<Vec<String>>,
+    game_mods_dir: &Path,
+    profile_id: Option<i64>,
+    now: &str,
+) -> SResult<InstallReport> {
+    run(
+        conn,
+        mod_id,
+        include,
+        game_mods_dir,
+        profile_id,
+        now,
+        &|| Some(db::write_lock()),
+    )
+}
+
+fn run(
+    conn: &mut Connection,
+    mod_id: i64,
+    include: Option<Vec<String>>,
+    game_mods_dir: &Path,
+    profile_id: Option<i64>,
+    now: &str,
+    lock: Lock,
+) -> SResult<InstallReport> {
+    let (folder_path, display_name) = folder_of(conn, mod_id)?;
+    ensure_risk_confirmed(conn, mod_id, &display_name)?;
+    let folder = Path::new(&folder_path);
+    let target = game_mods_dir.join(targets::resolve(conn, mod_id)?);
+    ensure_inside(game_mods_dir, &target)?;
+
+    if let Some(entries) = manifest::build_missing(conn, mod_id, folder)? {
+        locked(lock, || manifest::store(conn, mod_id, &entries, now))?;
+    }
+    let files = manifest::load_installable(conn, mod_id)?;
+    // stored with the install, so a failed one keeps the previous selection
+    let chosen = match include {
+        Some(include) => {
+            let known: HashSet<&str> = files.iter().map(|f| f.rel_path.as_str()).collect();
+            if let Some(unknown) = include.iter().find(|p| !known.contains(p.as_str())) {
+                return Err(format!("'{}' is not a file of this mod", unknown).into());
+            }
+            let chosen: HashSet<String> = include.into_iter().collect();
+            // selecting everything is stored as "no selection" so new files are picked up
+            Some(if chosen.len() == files.len() {
+                HashSet::new()
+            } else {
+                chosen
+            })
+        }
+        None => None,
+    };
+    let selected = match &chosen {
+        Some(chosen) => chosen.clone(),
+        None => selection(conn, mod_id)?,
+    };
+    let min_free_mb = load_settings(conn)?.min_free_space_mb;
+    space_check(conn, mod_id, game_mods_dir, min_free_mb)?.ensure()?;
+    let to_copy: Vec<String> = files
+        .iter()
+        .filter(|f| selected.is_empty() || selected.contains(&f.rel_path))
+        .map(|f| f.rel_path.clone())
+        .collect();
+    let swapped_out = groups::installed_siblings(conn, mod_id)?;
+
+    let mut report = InstallReport {
+        mod_id,
+        target_path: target.to_string_lossy().to_string(),
+        files_copied: 0,
+        bytes_copied: 0,
+        files_skipped: files.len() - to_copy.len(),
+        swapped_out,
+    };
+    // written before the game folder is touched, see `journal::resolve`
+    let journal_id = locked(lock, || {
+        journal::begin(
+            conn,
+            mod_id,
+            &report.target_path,
+            &to_copy,
+            &report.swapped_out,
+            now,
+        )
+    })?;
+    let result = apply(
+        conn,
+        lock,
+        journal_id,
+        &mut report,
+        folder,
+        game_mods_dir,
+        &target,
+        &to_copy,
+        chosen.as_ref(),
+        &display_name,
+        profile_id,
+        now,
+    );
+    if let Err(err) = &result {
+        warn!(
+            "[install] mod id={} failed, journal id={} left for recovery: {}",
+            mod_id, journal_id, err
+        );
+        let _writer = lock();
+        journal::abort(conn, journal_id, err.message());
+    }
+    result.map(|()| report)
+}
+
+// the journaled part of `install`: every step below is recorded before or as it happens.
+// The copy runs on its own; swapping it into place holds `lock` throughout, so two
+// installs never interleave their moves.
+#[allow(clippy::too_many_arguments)]
+fn apply(
+    conn: &mut Connection,
+    lock: Lock,
+    journal_id: i64,
+    report: &mut InstallReport,
+    folder: &Path,
+    game_mods_dir: &Path,
+    target: &Path,
+    to_copy: &[String],
+    chosen: Option<&HashSet<String>>,
+    display_name: &str,
+    profile_id: Option<i64>,
+    now: &str,
+) -> SResult<()> {
+    let mod_id = report.mod_id;
+    // copied next to the target first, so the game never sees a half-copied mod
+    let staging = journal::staging_dir(target, journal_id);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to clear '{}': {}", staging.display(), e))?;
+    }
+    for rel_path in to_copy {
+        let src = folder.join(rel_path);
+        let dest = staging.join(rel_path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        report.bytes_copied += fs::copy(&src, &dest)
+            .map_err(|e| format!("Failed to copy '{}': {}", src.display(), e))?;
+        report.files_copied += 1;
+        if report.files_copied.is_multiple_of(journal::PROGRESS_EVERY) {
+            locked(lock, || {
+                journal::progress(conn, journal_id, "staging", report.files_copied)
+            })?;
+        }
+    }
+    // an install with every file deselected still leaves an (empty) folder behind
+    fs::create_dir_all(&staging)
+        .map_err(|e| format!("Failed to create '{}': {}", staging.display(), e))?;
+
+    let _writer = lock();
+    journal::progress(conn, journal_id, "swapping", report.files_copied)?;
+    for sibling in report.swapped_out.iter() {
+        let tx = conn.transaction()?;
+        uninstall(&tx, *sibling, game_mods_dir, profile_id, now)?;
+        tx.commit()?;
+    }
+
+    // the previous copy is moved aside, not deleted, so a rollback can put it back
+    journal::progress(conn, journal_id, "clearing", report.files_copied)?;
+    let backup = if target.exists() {
+        let backup = journal::backup_dir(game_mods_dir, journal_id);
+        journal::set_backup(conn, journal_id, &backup)?;
+        if let Some(parent) = backup.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        fs::rename(target, &backup).map_err(|e| {
+            format!(
+                "Failed to move '{}' to '{}': {}",
+                target.display(),
+                backup.display(),
+                e
+            )
+        })?;
+        Some(backup)
+    } else {
+        None
+    };
+    journal::progress(conn, journal_id, "committing", report.files_copied)?;
+    fs::rename(&staging, target).map_err(|e| {
+        format!(
+            "Failed to move '{}' to '{}': {}",
+            staging.display(),
+            target.display(),
+            e
+        )
+    })?;
+
+    let tx = conn.transaction()?;
+    if let Some(chosen) = chosen {
+        store_selection(&tx, mod_id, chosen)?;
+    }
+    let before = history::snapshot(&tx, mod_id)?;
+    tx.execute(
+        r#"
+        UPDATE mods SET installed = 1, installed_at = ?2, target_path = ?3, updated_at = ?2
+        WHERE id = ?1
+        "#,
+        params![mod_id, now, report.target_path],
+    )?;
+    // fresh copy: forget drift results of the previous install
+    tx.execute("DELETE FROM install_checks WHERE mod_id = ?1", [mod_id])?;
+    history::log(
+        &tx,
+        "install",
+        &format!("Installed '{}'", display_name),
+        &[(mod_id, before)],
+        now,
+    )?;
+    record_event(
+        &tx,
+        mod_id,
+        "install",
+        Some(&report.target_path),
+        profile_id,
+        now,
+    )?;
+    stats::record_install(&tx, mod_id, now);
+    if let Some(group_id) = groups::group_of(&tx, mod_id)? {
+        groups::set_active(&tx, group_id, mod_id)?;
+    }
+    journal::finish(&tx, journal_id, report.files_copied, now)?;
+    tx.commit()?;
+    if let Some(backup) = backup {
+        journal::drop_backup(&backup);
+    }
+    Ok(())
+}
+
+/// Removes the installed copy of a mod from the game folder and clears its install state.
+/// A recorded install path outside `game_mods_dir` is refused rather than deleted.
+pub fn uninstall(
+    conn: &Connection,
+    mod_id: i64,
+    game_mods_dir: &Path,
+    profile_id: Option<i64>,
+    now: &str,
+) -> SResult<()> {
+    let Some(before) = history::snapshot(conn, mod_id)? else {
+        return Err(AppError::not_found("Mod", mod_id));
+    };
+    if let Some(target) = before.target_path.as_deref() {
+        let target = Path::new(target);
+        ensure_inside(game_mods_dir, target)?;
+        if target.exists() {
+            fs::remove_dir_all(target)
+                .map_err(|e| format!("Failed to remove '{}': {}", target.display(), e))?;
+        }
+    }
+    conn.execute(
+        r#"
+        UPDATE mods SET installed = 0, installed_at = NULL, target_path = NULL, updated_at = ?2
+        WHERE id = ?1
+        "#,
+        params![mod_id, now],
+    )?;
+    conn.execute("DELETE FROM install_checks WHERE mod_id = ?1", [mod_id])?;
+    let target_path = before.target_path.clone();
+    history::log(
+        conn,
+        "uninstall",
+        &format!("Uninstalled '{}'", before.display_name),
+        &[(mod_id, Some(before))],
+        now,
+    )?;
+    record_event(
+        conn,
+        mod_id,
+        "uninstall",
+        target_path.as_deref(),
+        profile_id,
+        now,
+    )?;
+    Ok(())
+}
+
+/// Refuses an install path that is not a folder below `game_mods_dir`: `..`, an absolute
+/// path recorded elsewhere, or a parent folder that links out of the game folder.
+fn ensure_inside(game_mods_dir: &Path, target: &Path) -> SResult<()> {
+    let outside = || {
+        AppError::invalid(format!(
+            "'{}' is not inside the game mods folder '{}'",
+            target.display(),
+            game_mods_dir.display()
+        ))
+    };
+    let rel = target.strip_prefix(game_mods_dir).map_err(|_| outside())?;
+    if rel.as_os_str().is_empty()
+        || !rel
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err(outside());
+    }
+    // the target itself may be a link (see `gamelinks`), the folders above it may not
+    if let (Some(parent), Ok(root)) = (target.parent(), game_mods_dir.canonicalize()) {
+        if let Ok(parent) = parent.canonicalize() {
+            if !parent.starts_with(&root) {
+                return Err(outside());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Records that the user accepted the flagged files of a mod so it can be installed.
+pub fn confirm_risk(conn: &Connection, mod_id: i64, now: &str) -> SResult<()> {
+    let n = conn.execute(
+        "UPDATE mods SET risk_confirmed_at = ?2 WHERE id = ?1 AND risk_flags IS NOT NULL",
+        params![mod_id, now],
+    )?;
+    if n == 0 {
+        return Err(format!("Mod with id={} has no flagged files", mod_id).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::shell;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecondInstanceEvent {
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+/// Brings the main window to the front.
+pub fn focus_main(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Runs in the first instance when the app is launched again: the new process exits
+/// and its arguments are handed to the frontend ("second-instance"). Install links
+/// among them reach `deeplink` through the plugin's deep-link forwarding.
+pub fn on_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    info!("[instance] second launch with {} argument(s)", args.len());
+    focus_main(app);
+    // the first argument is the executable
+    let args: Vec<String> = args.into_iter().skip(1).collect();
+    if args.is_empty() {
+        return;
+    }
+    // started from the Explorer menu: only the picked paths matter
+    let picked = shell::import_paths(&args);
+    if !picked.is_empty() {
+        shell::handle(app, picked);
+        return;
+    }
+    if let Err(err) = app.emit("second-instance", SecondInstanceEvent { args, cwd }) {
+        warn!("[instance] failed to forward arguments: {}", err);
+    }
+}
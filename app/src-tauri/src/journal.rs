@@ -0,0 +1,419 @@
+use crate::errors::AppError;
+use crate::history;
+use crate::install;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+// copied files between progress writes; a crash re-checks at most this many
+pub const PROGRESS_EVERY: usize = 25;
+
+/// An install that was written to the journal before it touched the game folder and never
+/// finished, offered on the next launch through `install_journal_resolve`.
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub mod_id: i64,
+    pub display_name: Option<String>,
+    pub target_path: String,
+    pub files: Vec<String>,
+    pub swapped_out: Vec<i64>,
+    pub status: String, // 'pending' | 'interrupted' | 'done' | 'rolled_back' | 'replayed'
+    pub stage: String,  // 'staging' | 'swapping' | 'clearing' | 'committing' (older: 'copying')
+    pub completed: usize,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Where the copy being replaced was moved while clearing, until the install commits.
+    pub backup_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalAction {
+    /// Removes what was copied, puts back the copy it replaced and reinstalls the
+    /// variants it swapped out.
+    #[default]
+    Rollback,
+    /// Runs the install again with the journaled files.
+    Replay,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JournalResolution {
+    pub journal_id: i64,
+    pub mod_id: i64,
+    /// Rollback only: journaled files found and removed.
+    pub files_removed: usize,
+    /// Rollback only: whether the replaced copy was moved back into place.
+    pub restored_backup: bool,
+    /// Rollback only: swapped-out variants installed again.
+    pub reinstalled: Vec<i64>,
+    /// Rollback only: variants that could not be reinstalled.
+    pub errors: Vec<String>,
+    /// Replay only.
+    pub report: Option<install::InstallReport>,
+}
+
+/// Records an install before any file is touched. Committed on its own so it survives
+/// the process dying halfway.
+pub fn begin(
+    conn: &Connection,
+    mod_id: i64,
+    target_path: &str,
+    files: &[String],
+    swapped_out: &[i64],
+    now: &str,
+) -> SResult<i64> {
+    conn.execute(
+        r#"
+        INSERT INTO install_journal
+          (mod_id, target_path, files_json, swapped_out_json, status, stage, started_at)
+        VALUES (?1, ?2, ?3, ?4, 'pending', 'staging', ?5)
+        "#,
+        params![
+            mod_id,
+            target_path,
+            serde_json::to_string(files)?,
+            serde_json::to_string(swapped_out)?,
+            now
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Where `apply` moves the copy an install replaces: next to the game mods folder, so the
+/// game does not load it twice.
+pub fn backup_dir(game_mods_dir: &Path, id: i64) -> PathBuf {
+    let name = game_mods_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mods".to_string());
+    game_mods_dir
+        .with_file_name(format!("{}.backup", name))
+        .join(id.to_string())
+}
+
+/// Where `apply` copies a mod before renaming it into place: a hidden sibling of the target,
+/// so it stays on the same drive and the rename cannot fail halfway.
+pub fn staging_dir(target: &Path, id: i64) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    target.with_file_name(format!(".{}.installing-{}", name, id))
+}
+
+/// Recorded before the move, so a crash in between still finds the backup.
+pub fn set_backup(conn: &Connection, id: i64, backup: &Path) -> SResult<()> {
+    conn.execute(
+        "UPDATE install_journal SET backup_path = ?2 WHERE id = ?1",
+        params![id, backup.to_string_lossy()],
+    )?;
+    Ok(())
+}
+
+/// Deletes a backup that is no longer needed, and the backup folder once it is empty.
+/// Failures are only logged: the install they belong to is already recorded.
+pub fn drop_backup(backup: &Path) {
+    if backup.exists() {
+        if let Err(err) = fs::remove_dir_all(backup) {
+            warn!(
+                "[journal] failed to remove backup '{}': {}",
+                backup.display(),
+                err
+            );
+            return;
+        }
+    }
+    if let Some(parent) = backup.parent() {
+        let _ = fs::remove_dir(parent);
+    }
+}
+
+pub fn progress(conn: &Connection, id: i64, stage: &str, completed: usize) -> SResult<()> {
+    conn.execute(
+        "UPDATE install_journal SET stage = ?2, completed = ?3 WHERE id = ?1",
+        params![id, stage, completed as i64],
+    )?;
+    Ok(())
+}
+
+/// Closes the entry; called inside the transaction that records the install.
+pub fn finish(conn: &Connection, id: i64, completed: usize, now: &str) -> SResult<()> {
+    conn.execute(
+        r#"
+        UPDATE install_journal SET status = 'done', stage = 'committing', completed = ?2,
+          finished_at = ?3
+        WHERE id = ?1
+        "#,
+        params![id, completed as i64, now],
+    )?;
+    Ok(())
+}
+
+/// Marks an install that failed after touching the game folder, so it is offered for
+/// rollback or replay like a crashed one.
+pub fn abort(conn: &Connection, id: i64, err: &str) {
+    if let Err(e) = conn.execute(
+        "UPDATE install_journal SET status = 'interrupted', error = ?2 WHERE id = ?1",
+        params![id, err],
+    ) {
+        warn!("[journal] failed to mark id={} interrupted: {}", id, e);
+    }
+}
+
+/// Entries still pending belong to a process that is gone; called once at startup.
+pub fn recover(conn: &Connection) -> SResult<usize> {
+    let n = conn.execute(
+        "UPDATE install_journal SET status = 'interrupted' WHERE status = 'pending'",
+        [],
+    )?;
+    if n > 0 {
+        warn!("[journal] {} install(s) were interrupted", n);
+    }
+    Ok(n)
+}
+
+const SELECT: &str = r#"
+    SELECT j.id, j.mod_id, m.display_name, j.target_path, j.files_json, j.swapped_out_json,
+           j.status, j.stage, j.completed, j.error, j.started_at, j.finished_at,
+           j.backup_path
+    FROM install_journal j
+    LEFT JOIN mods m ON m.id = j.mod_id
+"#;
+
+fn entry_from_row(r: &rusqlite::Row) -> rusqlite::Result<(JournalEntry, String, String)> {
+    Ok((
+        JournalEntry {
+            id: r.get(0)?,
+            mod_id: r.get(1)?,
+            display_name: r.get(2)?,
+            target_path: r.get(3)?,
+            files: Vec::new(),
+            swapped_out: Vec::new(),
+            status: r.get(6)?,
+            stage: r.get(7)?,
+            completed: r.get::<_, i64>(8)? as usize,
+            error: r.get(9)?,
+            started_at: r.get(10)?,
+            finished_at: r.get(11)?,
+            backup_path: r.get(12)?,
+        },
+        r.get(4)?,
+        r.get(5)?,
+    ))
+}
+
+fn parse(row: (JournalEntry, String, String)) -> SResult<JournalEntry> {
+    let (mut entry, files, swapped_out) = row;
+    entry.files = serde_json::from_str(&files)?;
+    entry.swapped_out = serde_json::from_str(&swapped_out)?;
+    Ok(entry)
+}
+
+pub fn incomplete(conn: &Connection) -> SResult<Vec<JournalEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE j.status = 'interrupted' ORDER BY j.id DESC",
+        SELECT
+    ))?;
+    let rows = stmt
+        .query_map([], entry_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter().map(parse).collect()
+}
+
+fn load(conn: &Connection, id: i64) -> SResult<JournalEntry> {
+    let row = conn
+        .query_row(&format!("{} WHERE j.id = ?1", SELECT), [id], entry_from_row)
+        .optional()?
+        .ok_or_else(|| AppError::not_found("Install journal", id))?;
+    parse(row)
+}
+
+fn set_status(conn: &Connection, id: i64, status: &str, now: &str) -> SResult<()> {
+    conn.execute(
+        "UPDATE install_journal SET status = ?2, finished_at = ?3 WHERE id = ?1",
+        params![id, status, now],
+    )?;
+    Ok(())
+}
+
+// removes the journaled files under `target` and the folders left empty
+fn remove_copied(target: &Path, files: &[String]) -> SResult<usize> {
+    let mut removed = 0;
+    let mut dirs = HashSet::new();
+    for rel in files {
+        let path = target.join(rel);
+        if path.is_file() {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+            removed += 1;
+        }
+        let mut parent = path.parent();
+        while let Some(dir) = parent.filter(|d| d.starts_with(target)) {
+            dirs.insert(dir.to_path_buf());
+            parent = dir.parent();
+        }
+    }
+    // deepest first; `remove_dir` leaves folders that still hold something
+    let mut dirs: Vec<_> = dirs.into_iter().collect();
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+    for dir in dirs {
+        let _ = fs::remove_dir(dir);
+    }
+    Ok(removed)
+}
+
+/// Rolls back or replays an interrupted install.
+pub fn resolve(
+    conn: &mut Connection,
+    id: i64,
+    action: JournalAction,
+    game_mods_dir: &Path,
+    now: &str,
+) -> SResult<JournalResolution> {
+    let entry = load(conn, id)?;
+    if entry.status != "interrupted" {
+        return Err(format!(
+            "Install journal id={} is {}, not interrupted",
+            id, entry.status
+        )
+        .into());
+    }
+    let mut resolution = JournalResolution {
+        journal_id: id,
+        mod_id: entry.mod_id,
+        ..Default::default()
+    };
+    // a copy that never made it into place, whichever way the entry is resolved
+    let staging = staging_dir(Path::new(&entry.target_path), id);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to remove '{}': {}", staging.display(), e))?;
+    }
+    match action {
+        JournalAction::Replay => {
+            info!(
+                "[journal] replaying id={} mod id={} ({} files)",
+                id,
+                entry.mod_id,
+                entry.files.len()
+            );
+            let report = install::install(
+                conn,
+                entry.mod_id,
+                Some(entry.files),
+                game_mods_dir,
+                None,
+                now,
+            )?;
+            set_status(conn, id, "replayed", now)?;
+            // the replay replaced the interrupted copy, not the one backed up before it
+            if let Some(backup) = entry.backup_path.as_deref() {
+                drop_backup(Path::new(backup));
+            }
+            resolution.report = Some(report);
+        }
+        JournalAction::Rollback => {
+            let target = Path::new(&entry.target_path);
+            // only 'committing' (or 'copying' in older entries, which copied straight into
+            // the target) can have put files at the target, after the previous copy moved
+            if matches!(entry.stage.as_str(), "copying" | "committing") {
+                resolution.files_removed = remove_copied(target, &entry.files)?;
+            }
+            let backup = entry
+                .backup_path
+                .as_deref()
+                .map(Path::new)
+                .filter(|b| b.is_dir());
+            if let Some(backup) = backup {
+                // anything left at the target came from the interrupted copy
+                if target.exists() {
+                    fs::remove_dir_all(target)
+                        .map_err(|e| format!("Failed to clear '{}': {}", target.display(), e))?;
+                }
+                fs::rename(backup, target).map_err(|e| {
+                    format!(
+                        "Failed to restore '{}' from '{}': {}",
+                        target.display(),
+                        backup.display(),
+                        e
+                    )
+                })?;
+                if let Some(parent) = backup.parent() {
+                    let _ = fs::remove_dir(parent);
+                }
+                resolution.restored_backup = true;
+            }
+            // entries from before backups existed cleared the previous copy for good
+            let lost = !matches!(entry.stage.as_str(), "swapping" | "staging")
+                && entry.backup_path.is_none();
+            let tx = conn.transaction()?;
+            // the install was not recorded, but a previous copy at the same place was
+            // cleared by it
+            if let Some(before) = history::snapshot(&tx, entry.mod_id)?
+                .filter(|s| lost && s.target_path.as_deref() == Some(entry.target_path.as_str()))
+            {
+                tx.execute(
+                    r#"
+                    UPDATE mods SET installed = 0, installed_at = NULL, target_path = NULL,
+                      updated_at = ?2
+                    WHERE id = ?1
+                    "#,
+                    params![entry.mod_id, now],
+                )?;
+                tx.execute(
+                    "DELETE FROM install_checks WHERE mod_id = ?1",
+                    [entry.mod_id],
+                )?;
+                history::log(
+                    &tx,
+                    "uninstall",
+                    &format!("Rolled back the install of '{}'", before.display_name),
+                    &[(entry.mod_id, Some(before))],
+                    now,
+                )?;
+                install::record_event(
+                    &tx,
+                    entry.mod_id,
+                    "uninstall",
+                    Some(&entry.target_path),
+                    None,
+                    now,
+                )?;
+            }
+            set_status(&tx, id, "rolled_back", now)?;
+            tx.commit()?;
+
+            // the variants uninstalled to make room, unless something else took their place
+            for sibling in entry.swapped_out.iter().copied() {
+                let installed = history::snapshot(conn, sibling)?.is_some_and(|s| s.installed);
+                if installed {
+                    continue;
+                }
+                match install::install(conn, sibling, None, game_mods_dir, None, now) {
+                    Ok(_) => resolution.reinstalled.push(sibling),
+                    Err(err) => {
+                        warn!("[journal] failed to reinstall id={}: {}", sibling, err);
+                        resolution.errors.push(format!("id={}: {}", sibling, err));
+                    }
+                }
+            }
+            info!(
+                "[journal] rolled back id={} mod id={} removed={} restored={} reinstalled={}",
+                id,
+                entry.mod_id,
+                resolution.files_removed,
+                resolution.restored_backup,
+                resolution.reinstalled.len()
+            );
+        }
+    }
+    Ok(resolution)
+}
@@ -0,0 +1,178 @@
+use crate::commands::load_settings;
+use crate::db;
+use crate::errors::AppError;
+use crate::opener;
+use crate::platform;
+use crate::profiles::{self, ProfileApplyReport};
+use crate::verify::{self, GameAuditEntry, GameAuditStatus};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+const STEAM_APP_ID: u32 = 2072450;
+
+/// A mod that was installed when the game was launched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayedMod {
+    pub id: i64,
+    pub display_name: String,
+    pub author: Option<String>,
+    pub target_path: Option<String>,
+    /// Status from the pre-launch audit; `None` when the mod has no manifest.
+    pub audit: Option<GameAuditStatus>,
+}
+
+/// What was active at a launch ("last played with"), for tracking down crashes.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameLaunch {
+    pub id: i64,
+    pub launched_at: String,
+    pub profile_id: Option<i64>,
+    pub method: String,
+    pub mods: Vec<PlayedMod>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GameLaunchReport {
+    pub launch: GameLaunch,
+    pub profile: Option<ProfileApplyReport>,
+    /// Installed mods the audit did not find intact; the game is launched anyway.
+    pub problems: Vec<GameAuditEntry>,
+}
+
+fn played_mods(conn: &Connection, audit: &[GameAuditEntry]) -> SResult<Vec<PlayedMod>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT id, display_name, author, target_path FROM mods
+            WHERE installed = 1 ORDER BY display_name
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(PlayedMod {
+            id: r.get(0)?,
+            display_name: r.get(1)?,
+            author: r.get(2)?,
+            target_path: r.get(3)?,
+            audit: None,
+        })
+    })?;
+    let mut mods: Vec<PlayedMod> = rows.collect::<Result<_, _>>()?;
+    for m in mods.iter_mut() {
+        m.audit = audit.iter().find(|a| a.mod_id == m.id).map(|a| a.status);
+    }
+    Ok(mods)
+}
+
+// the executable when there is one (Windows), else Steam
+fn start_game(configured_exe: Option<&str>) -> SResult<&'static str> {
+    if let Some(exe) = platform::game_executable(configured_exe) {
+        let mut cmd = Command::new(&exe);
+        if let Some(dir) = exe.parent() {
+            cmd.current_dir(dir);
+        }
+        cmd.spawn()
+            .map_err(|e| format!("Failed to start '{}': {}", exe.display(), e))?;
+        return Ok("exe");
+    }
+    opener::open(&format!("steam://rungameid/{}", STEAM_APP_ID))?;
+    Ok("steam")
+}
+
+/// Applies `profile_id` if given, audits the installed mods, starts the game and records
+/// what was installed. Audit problems are reported, not fatal.
+pub fn launch(
+    conn: &mut Connection,
+    profile_id: Option<i64>,
+    now: &str,
+) -> SResult<GameLaunchReport> {
+    let settings = load_settings(conn)?;
+    let profile = match profile_id {
+        Some(id) => {
+            let game_dir = settings
+                .game_mods_dir
+                .as_deref()
+                .ok_or_else(|| AppError::not_configured("game_mods_dir"))?;
+            let _writer = db::write_lock();
+            Some(profiles::apply(conn, id, Path::new(game_dir), now)?)
+        }
+        None => None,
+    };
+
+    let audit = verify::game_audit(conn, now)?;
+    let mods = played_mods(conn, &audit)?;
+    let method = start_game(settings.game_exe.as_deref())?;
+
+    let mods_json = serde_json::to_string(&mods)?;
+    let id = {
+        let _writer = db::write_lock();
+        conn.execute(
+            r#"
+            INSERT INTO game_launches (launched_at, profile_id, method, mods_json)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![now, profile_id, method, mods_json],
+        )?;
+        conn.last_insert_rowid()
+    };
+    let problems: Vec<GameAuditEntry> = audit
+        .into_iter()
+        .filter(|a| a.status != GameAuditStatus::Intact)
+        .collect();
+    if !problems.is_empty() {
+        warn!(
+            "[launch] {} installed mod(s) are not intact",
+            problems.len()
+        );
+    }
+    info!(
+        "[launch] started the game via {} with {} mod(s)",
+        method,
+        mods.len()
+    );
+    Ok(GameLaunchReport {
+        launch: GameLaunch {
+            id,
+            launched_at: now.to_string(),
+            profile_id,
+            method: method.to_string(),
+            mods,
+        },
+        profile,
+        problems,
+    })
+}
+
+/// Recorded launches, newest first.
+pub fn list(conn: &Connection, limit: usize) -> SResult<Vec<GameLaunch>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT id, launched_at, profile_id, method, mods_json FROM game_launches
+            ORDER BY id DESC LIMIT ?1
+            "#,
+    )?;
+    let rows = stmt.query_map([limit as i64], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, Option<i64>>(2)?,
+            r.get::<_, String>(3)?,
+            r.get::<_, String>(4)?,
+        ))
+    })?;
+    let mut out = Vec::new();
+    for row in rows {
+        let (id, launched_at, profile_id, method, mods_json) = row?;
+        out.push(GameLaunch {
+            id,
+            launched_at,
+            profile_id,
+            method,
+            mods: serde_json::from_str(&mods_json)?,
+        });
+    }
+    Ok(out)
+}
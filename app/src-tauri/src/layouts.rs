@@ -0,0 +1,181 @@
+use crate::platform;
+use crate::types::LibraryLayout;
+use glob::{MatchOptions, Pattern};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use walkdir::{DirEntry, WalkDir};
+
+// deepest folder level `Auto` looks at below a library root
+const AUTO_MAX_DEPTH: usize = 8;
+
+/// Spine exports that mark a folder as a mod: the skeleton (.skel or a .json next to an
+/// .atlas) and its texture atlas.
+const SPINE_EXTENSIONS: &[&str] = &["skel", "atlas"];
+
+/// Folders a rescan skips, as globs relative to the library root (`**/backup/**`).
+#[derive(Default)]
+pub struct Exclusions {
+    patterns: Vec<Pattern>,
+}
+
+impl Exclusions {
+    pub fn new(globs: &[String]) -> Result<Self, String> {
+        let patterns = globs
+            .iter()
+            .map(|g| g.trim())
+            .filter(|g| !g.is_empty())
+            .map(|g| Pattern::new(g).map_err(|e| format!("Invalid exclusion '{}': {}", g, e)))
+            .collect::<Result<_, _>>()?;
+        Ok(Exclusions { patterns })
+    }
+
+    /// True when a pattern matches the folder itself or everything inside it.
+    fn excludes(&self, root: &Path, entry: &DirEntry) -> bool {
+        if self.patterns.is_empty() || !entry.file_type().is_dir() {
+            return false;
+        }
+        let Ok(rel) = entry.path().strip_prefix(root) else {
+            return false;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        let inside = format!("{}/x", rel);
+        let options = MatchOptions {
+            case_sensitive: !platform::CASE_INSENSITIVE_PATHS,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        self.patterns
+            .iter()
+            .any(|p| p.matches_with(&rel, options) || p.matches_with(&inside, options))
+    }
+}
+
+pub struct FoundMod {
+    pub path: PathBuf,
+    /// Top-level folder below the root, for layouts that group mods by author.
+    pub author_folder: Option<String>,
+}
+
+/// True when `dir` directly contains Spine asset files.
+pub fn has_spine_assets(dir: &Path) -> bool {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries.filter_map(|e| e.ok()).any(|e| {
+                e.path()
+                    .extension()
+                    .map(|ext| {
+                        let ext = ext.to_string_lossy().to_lowercase();
+                        SPINE_EXTENSIONS.contains(&ext.as_str())
+                    })
+                    .unwrap_or(false)
+            })
+        })
+        .unwrap_or(false)
+}
+
+fn author_of(root: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(root).ok()?;
+    let mut comps = rel.components();
+    let first = comps.next()?;
+    // a mod directly below the root has no author folder
+    comps
+        .next()
+        .map(|_| first.as_os_str().to_string_lossy().to_string())
+}
+
+/// Lists the mod folders below `root` according to its layout, skipping excluded folders.
+/// Walk errors are collected in `errors` and do not stop the scan.
+pub fn discover(
+    root: &Path,
+    layout: LibraryLayout,
+    exclusions: &Exclusions,
+    errors: &mut Vec<walkdir::Error>,
+) -> Vec<FoundMod> {
+    let depth = match layout {
+        LibraryLayout::Flat => 1,
+        LibraryLayout::AuthorMod => 2,
+        LibraryLayout::AuthorCharacterMod => 3,
+        LibraryLayout::Auto => return discover_auto(root, exclusions, errors),
+    };
+    let mut found = Vec::new();
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(depth)
+        .into_iter()
+        .filter_entry(|e| !exclusions.excludes(root, e));
+    for entry in walker {
+        match entry {
+            Ok(e) if e.depth() == depth && e.file_type().is_dir() => found.push(FoundMod {
+                author_folder: author_of(root, e.path()),
+                path: e.into_path(),
+            }),
+            Ok(_) => {}
+            Err(err) => errors.push(err),
+        }
+    }
+    found
+}
+
+/// `discover`, walked again after a pause while it hits transient IO errors (a NAS
+/// dropping the connection, a disk spinning up). The last walk's errors are kept.
+pub fn discover_retrying(
+    root: &Path,
+    layout: LibraryLayout,
+    exclusions: &Exclusions,
+    errors: &mut Vec<walkdir::Error>,
+) -> Vec<FoundMod> {
+    let mut delays = platform::RETRY_BACKOFF_MS.iter();
+    loop {
+        let mut attempt = Vec::new();
+        let found = discover(root, layout, exclusions, &mut attempt);
+        let transient = attempt
+            .iter()
+            .any(|e| e.io_error().is_some_and(platform::is_transient));
+        match delays.next() {
+            Some(ms) if transient => {
+                warn!(
+                    "[layouts] transient errors walking '{}', retrying in {} ms",
+                    root.display(),
+                    ms
+                );
+                std::thread::sleep(std::time::Duration::from_millis(*ms));
+            }
+            _ => {
+                errors.extend(attempt);
+                return found;
+            }
+        }
+    }
+}
+
+// a folder holding Spine assets is a mod; its subfolders are not searched further
+fn discover_auto(
+    root: &Path,
+    exclusions: &Exclusions,
+    errors: &mut Vec<walkdir::Error>,
+) -> Vec<FoundMod> {
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(AUTO_MAX_DEPTH)
+        .into_iter()
+        .filter_entry(|e| !exclusions.excludes(root, e));
+    while let Some(entry) = walker.next() {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+        if !entry.file_type().is_dir() || !has_spine_assets(entry.path()) {
+            continue;
+        }
+        walker.skip_current_dir();
+        found.push(FoundMod {
+            author_folder: author_of(root, entry.path()),
+            path: entry.into_path(),
+        });
+    }
+    found
+}
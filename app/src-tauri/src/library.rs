@@ -0,0 +1,594 @@
+use crate::authors;
+use crate::commands;
+use crate::db;
+use crate::errors::AppError;
+use crate::groups;
+use crate::history;
+use crate::install;
+use crate::manifest;
+use crate::platform;
+use crate::profiles;
+use crate::roots;
+use crate::types::ModType;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+const EXPORT_VERSION: u32 = 1;
+
+/// A mod's curated metadata. Catalog assignments are stored by slug and folders by their
+/// path relative to the library root, so the file is portable between machines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedMod {
+    pub display_name: String,
+    pub folder_path: String,
+    #[serde(default)]
+    pub rel_path: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub download_url: Option<String>,
+    pub mod_type: ModType,
+    #[serde(default)]
+    pub character_slug: Option<String>,
+    #[serde(default)]
+    pub costume_slug: Option<String>,
+    #[serde(default)]
+    pub swap_source_character_slug: Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub group_active: bool,
+    /// Files installed from this mod; empty means all of them.
+    #[serde(default)]
+    pub install_selection: Vec<String>,
+    /// When the mod last changed on the machine that wrote the file, see
+    /// `MergeStrategy::Newest`.
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedAuthor {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub homepage_url: Option<String>,
+    #[serde(default)]
+    pub kofi_url: Option<String>,
+}
+
+/// A profile, with its mods given by their exported `folder_path`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedProfile {
+    pub name: String,
+    #[serde(default)]
+    pub mods: Vec<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryExport {
+    pub version: u32,
+    pub schema_version: i64,
+    pub exported_at: String,
+    /// The machine that wrote a shared library file, see `shared`.
+    #[serde(default)]
+    pub machine: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<ExportedAuthor>,
+    #[serde(default)]
+    pub mods: Vec<ExportedMod>,
+    /// Only in full exports; a shared library file leaves profiles to each machine.
+    #[serde(default)]
+    pub profiles: Vec<ExportedProfile>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Only add mods the library does not know yet.
+    #[default]
+    KeepExisting,
+    /// Fill fields that are empty locally.
+    FillEmpty,
+    /// The file wins for every field it sets.
+    Overwrite,
+    /// The file wins for mods it changed more recently than this library did.
+    Newest,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LibraryImportReport {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Mods whose folder exists neither at the exported path nor under a local root.
+    pub unmatched: Vec<String>,
+    pub authors: usize,
+    pub profiles: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LibraryExportSummary {
+    pub mods: usize,
+    pub authors: usize,
+    pub profiles: usize,
+}
+
+fn character_slug(conn: &Connection, id: Option<i64>) -> SResult<Option<String>> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    conn.query_row("SELECT slug FROM characters WHERE id = ?1", [id], |r| {
+        r.get(0)
+    })
+    .optional()
+    .map_err(AppError::from)
+}
+
+fn character_id(conn: &Connection, slug: Option<&str>) -> SResult<Option<i64>> {
+    let Some(slug) = slug else {
+        return Ok(None);
+    };
+    conn.query_row("SELECT id FROM characters WHERE slug = ?1", [slug], |r| {
+        r.get(0)
+    })
+    .optional()
+    .map_err(AppError::from)
+}
+
+fn costume_id(
+    conn: &Connection,
+    character: Option<&str>,
+    slug: Option<&str>,
+) -> SResult<Option<i64>> {
+    let (Some(character), Some(slug)) = (character, slug) else {
+        return Ok(None);
+    };
+    conn.query_row(
+        r#"
+        SELECT co.id FROM costumes co JOIN characters ch ON ch.id = co.character_id
+        WHERE ch.slug = ?1 AND co.slug = ?2
+        "#,
+        params![character, slug],
+        |r| r.get(0),
+    )
+    .optional()
+    .map_err(AppError::from)
+}
+
+pub fn export_to_path(conn: &Connection, path: &Path) -> SResult<LibraryExportSummary> {
+    let export = build_export(conn, None)?;
+    let json = serde_json::to_string_pretty(&export)?;
+    std::fs::write(path, json)
+        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    Ok(LibraryExportSummary {
+        mods: export.mods.len(),
+        authors: export.authors.len(),
+        profiles: export.profiles.len(),
+    })
+}
+
+/// The library's metadata; only mods in folders below `under` when given.
+pub fn build_export(conn: &Connection, under: Option<&str>) -> SResult<LibraryExport> {
+    let under = under.map(platform::path_key);
+    let authors: Vec<ExportedAuthor> = authors::list(conn)?
+        .into_iter()
+        .map(|a| ExportedAuthor {
+            name: a.name,
+            aliases: a.aliases,
+            homepage_url: a.homepage_url,
+            kofi_url: a.kofi_url,
+        })
+        .collect();
+
+    let mut folders: HashMap<i64, String> = HashMap::new();
+    let mods: Vec<ExportedMod> = {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT m.id, m.display_name, m.folder_path, m.rel_path, m.author, m.download_url,
+                       m.mod_type, m.character_id, co.slug, m.swap_source_character_id,
+                       g.name, g.active_mod_id = m.id, m.updated_at
+                FROM mods m
+                LEFT JOIN costumes co ON co.id = m.costume_id
+                LEFT JOIN mod_groups g ON g.id = m.group_id
+                ORDER BY m.id
+                "#,
+        )?;
+        let rows = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                ExportedMod {
+                    display_name: r.get(1)?,
+                    folder_path: r.get(2)?,
+                    rel_path: r.get(3)?,
+                    author: r.get(4)?,
+                    download_url: r.get(5)?,
+                    mod_type: ModType::from_str(&r.get::<_, String>(6)?),
+                    character_slug: None,
+                    costume_slug: r.get(8)?,
+                    swap_source_character_slug: None,
+                    group: r.get(10)?,
+                    group_active: r.get::<_, Option<bool>>(11)?.unwrap_or(false),
+                    install_selection: Vec::new(),
+                    updated_at: r.get(12)?,
+                },
+                r.get::<_, Option<i64>>(7)?,
+                r.get::<_, Option<i64>>(9)?,
+            ))
+        })?;
+        let rows: Vec<_> = rows.collect::<Result<_, _>>()?;
+        let mut out = Vec::with_capacity(rows.len());
+        for (id, mut m, character, swap_source) in rows {
+            if under.as_deref().is_some_and(|root| {
+                !platform::path_key(&m.folder_path).starts_with(&format!("{}/", root))
+            }) {
+                continue;
+            }
+            m.character_slug = character_slug(conn, character)?;
+            m.swap_source_character_slug = character_slug(conn, swap_source)?;
+            let mut selection: Vec<String> = install::selection(conn, id)?.into_iter().collect();
+            selection.sort();
+            m.install_selection = selection;
+            folders.insert(id, m.folder_path.clone());
+            out.push(m);
+        }
+        out
+    };
+    let profiles: Vec<ExportedProfile> = if under.is_some() {
+        Vec::new()
+    } else {
+        profiles::list(conn)?
+            .into_iter()
+            .map(|p| ExportedProfile {
+                name: p.name,
+                mods: p
+                    .mod_ids
+                    .iter()
+                    .filter_map(|id| folders.get(id).cloned())
+                    .collect(),
+                updated_at: Some(p.updated_at),
+            })
+            .collect()
+    };
+
+    Ok(LibraryExport {
+        version: EXPORT_VERSION,
+        schema_version: db::schema_version(conn)?,
+        exported_at: commands::now_iso(),
+        machine: None,
+        authors,
+        mods,
+        profiles,
+    })
+}
+
+// RFC 3339 with varying fraction digits does not sort as text
+fn is_later(a: &str, b: &str) -> bool {
+    match (
+        OffsetDateTime::parse(a, &Rfc3339),
+        OffsetDateTime::parse(b, &Rfc3339),
+    ) {
+        (Ok(a), Ok(b)) => a > b,
+        _ => a > b,
+    }
+}
+
+// the merged value of one field: the file's when `overwrite` and set, else the local one
+fn pick<T>(overwrite: bool, file: Option<T>, local: Option<T>) -> Option<T> {
+    if overwrite {
+        file.or(local)
+    } else {
+        local.or(file)
+    }
+}
+
+// the local folder an exported mod refers to: its exact path, else its root-relative path
+// under one of the local library roots
+fn locate(conn: &Connection, m: &ExportedMod) -> SResult<Option<String>> {
+    let exact = commands::normalize_path_string(&m.folder_path);
+    if Path::new(&exact).is_dir() {
+        return Ok(Some(exact));
+    }
+    let Some(rel) = m.rel_path.as_deref() else {
+        return Ok(None);
+    };
+    for root in roots::list(conn)? {
+        let candidate = Path::new(&root.path).join(rel);
+        if candidate.is_dir() {
+            return Ok(Some(commands::normalize_path_string(
+                &candidate.to_string_lossy(),
+            )));
+        }
+    }
+    Ok(None)
+}
+
+/// Merges an export into the library. Mods are matched by folder; exported mods whose
+/// folder is not on this machine are reported, not created. Journaled as one import.
+pub fn import_from_path(
+    conn: &mut Connection,
+    path: &Path,
+    strategy: MergeStrategy,
+) -> SResult<LibraryImportReport> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let export: LibraryExport = serde_json::from_str(&raw)?;
+    let report = import_export(conn, &export, strategy)?;
+    info!(
+        "[library] import '{}' added={} updated={} unchanged={} unmatched={}",
+        path.display(),
+        report.added,
+        report.updated,
+        report.unchanged,
+        report.unmatched.len()
+    );
+    Ok(report)
+}
+
+/// Merges parsed library metadata, see `import_from_path`.
+pub fn import_export(
+    conn: &mut Connection,
+    export: &LibraryExport,
+    strategy: MergeStrategy,
+) -> SResult<LibraryImportReport> {
+    if export.version > EXPORT_VERSION {
+        return Err(format!(
+            "Library export version {} is newer than supported ({})",
+            export.version, EXPORT_VERSION
+        )
+        .into());
+    }
+
+    let now = commands::now_iso();
+    let tx = conn.transaction()?;
+    let mut report = LibraryImportReport::default();
+
+    for a in export.authors.iter() {
+        let Some((id, name)) = authors::resolve(&tx, &a.name, &now)? else {
+            continue;
+        };
+        for alias in a.aliases.iter() {
+            tx.execute(
+                "INSERT OR IGNORE INTO author_aliases (alias, author) VALUES (?1, ?2)",
+                params![alias, name],
+            )?;
+        }
+        let links = authors::AuthorPatch {
+            name: None,
+            homepage_url: a.homepage_url.clone(),
+            kofi_url: a.kofi_url.clone(),
+        };
+        if strategy == MergeStrategy::Overwrite {
+            authors::update(&tx, id, &links)?;
+        } else {
+            tx.execute(
+                r#"
+                UPDATE authors SET
+                  homepage_url = COALESCE(homepage_url, ?2),
+                  kofi_url = COALESCE(kofi_url, ?3)
+                WHERE id = ?1
+                "#,
+                params![id, links.homepage_url, links.kofi_url],
+            )?;
+        }
+        report.authors += 1;
+    }
+
+    let mut group_ids: HashMap<String, i64> = HashMap::new();
+    let mut journal = Vec::new();
+    let mut seen = HashSet::new();
+    // exported folder -> local mod, for the profiles below
+    let mut located: HashMap<&str, i64> = HashMap::new();
+    for m in export.mods.iter() {
+        let Some(folder_path) = locate(&tx, m)? else {
+            report.unmatched.push(m.folder_path.clone());
+            continue;
+        };
+        if !seen.insert(folder_path.clone()) {
+            continue;
+        }
+        let before = history::snapshot_by_path(&tx, &folder_path)?;
+        let character = character_id(&tx, m.character_slug.as_deref())?;
+        let costume = costume_id(&tx, m.character_slug.as_deref(), m.costume_slug.as_deref())?;
+        let swap_source = character_id(&tx, m.swap_source_character_slug.as_deref())?;
+        // whether the file's values reach a mod the library already has
+        let merge = before.as_ref().is_some_and(|local| match strategy {
+            MergeStrategy::KeepExisting => false,
+            MergeStrategy::Newest => m
+                .updated_at
+                .as_deref()
+                .is_some_and(|t| is_later(t, &local.updated_at)),
+            MergeStrategy::FillEmpty | MergeStrategy::Overwrite => true,
+        });
+
+        let mod_id = match before.as_ref() {
+            None => {
+                tx.execute(
+                    r#"
+                    INSERT INTO mods (
+                      display_name, folder_path, author, download_url, mod_type, character_id,
+                      costume_id, swap_source_character_id, installed, created_at, updated_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?9)
+                    "#,
+                    params![
+                        m.display_name,
+                        folder_path,
+                        m.author,
+                        m.download_url,
+                        m.mod_type.to_string(),
+                        character,
+                        costume,
+                        swap_source,
+                        now
+                    ],
+                )?;
+                report.added += 1;
+                let id = tx.last_insert_rowid();
+                manifest::store_stat(&tx, id, Path::new(&folder_path))?;
+                id
+            }
+            Some(local) if !merge => {
+                report.unchanged += 1;
+                local.id
+            }
+            Some(local) => {
+                // Overwrite/Newest: file value when set; FillEmpty: local value when set
+                let overwrite = strategy != MergeStrategy::FillEmpty;
+                let author = pick(overwrite, m.author.clone(), local.author.clone());
+                let download_url = pick(
+                    overwrite,
+                    m.download_url.clone(),
+                    local.download_url.clone(),
+                );
+                // character and costume travel together, so a costume never lands on a
+                // different character
+                let local_empty = local.character_id.is_none() && local.costume_id.is_none();
+                let (character, costume) = match (character, local.character_id) {
+                    (Some(c), Some(l)) if c == l => {
+                        (Some(c), pick(overwrite, costume, local.costume_id))
+                    }
+                    (Some(c), _) if overwrite || local_empty => (Some(c), costume),
+                    _ => (local.character_id, local.costume_id),
+                };
+                let swap_source = pick(overwrite, swap_source, local.swap_source_character_id);
+                let mod_type = if overwrite || local.mod_type == "other" {
+                    m.mod_type.to_string()
+                } else {
+                    local.mod_type.clone()
+                };
+                let changed = author != local.author
+                    || download_url != local.download_url
+                    || character != local.character_id
+                    || costume != local.costume_id
+                    || swap_source != local.swap_source_character_id
+                    || mod_type != local.mod_type;
+                if changed {
+                    // the file's time, so the next sync does not see the mod as changed here
+                    let updated_at = match strategy {
+                        MergeStrategy::Newest => {
+                            m.updated_at.clone().unwrap_or_else(|| now.clone())
+                        }
+                        _ => now.clone(),
+                    };
+                    tx.execute(
+                        r#"
+                        UPDATE mods SET
+                          author = ?2, download_url = ?3, character_id = ?4, costume_id = ?5,
+                          swap_source_character_id = ?6, mod_type = ?7, updated_at = ?8
+                        WHERE id = ?1
+                        "#,
+                        params![
+                            local.id,
+                            author,
+                            download_url,
+                            character,
+                            costume,
+                            swap_source,
+                            mod_type,
+                            updated_at
+                        ],
+                    )?;
+                    report.updated += 1;
+                } else {
+                    report.unchanged += 1;
+                }
+                local.id
+            }
+        };
+        located.insert(m.folder_path.as_str(), mod_id);
+        if before.is_some() && !merge {
+            continue;
+        }
+        journal.push((mod_id, before));
+
+        if !m.install_selection.is_empty() {
+            install::store_selection(&tx, mod_id, &m.install_selection.iter().cloned().collect())?;
+        }
+        if let Some(name) = m.group.as_deref() {
+            let group_id = match group_ids.get(name) {
+                Some(id) => *id,
+                None => {
+                    let existing: Option<i64> = tx
+                        .query_row("SELECT id FROM mod_groups WHERE name = ?1", [name], |r| {
+                            r.get(0)
+                        })
+                        .optional()?;
+                    let id = match existing {
+                        Some(id) => id,
+                        None => groups::create(&tx, name, &[], &now)?,
+                    };
+                    group_ids.insert(name.to_string(), id);
+                    id
+                }
+            };
+            groups::set_group(&tx, Some(group_id), &[mod_id])?;
+            if m.group_active {
+                groups::set_active(&tx, group_id, mod_id)?;
+            }
+        }
+    }
+
+    for p in export.profiles.iter() {
+        let name = p.name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let local: Option<(i64, String)> = tx
+            .query_row(
+                "SELECT id, updated_at FROM profiles WHERE name = ?1 COLLATE NOCASE",
+                [name],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()?;
+        let replace = match local {
+            None => true,
+            Some((id, updated_at)) => match strategy {
+                MergeStrategy::KeepExisting => false,
+                MergeStrategy::FillEmpty => profiles::members(&tx, id)?.is_empty(),
+                MergeStrategy::Overwrite => true,
+                MergeStrategy::Newest => p
+                    .updated_at
+                    .as_deref()
+                    .is_some_and(|t| is_later(t, &updated_at)),
+            },
+        };
+        if !replace {
+            continue;
+        }
+        let members: Vec<i64> = p
+            .mods
+            .iter()
+            .filter_map(|f| located.get(f.as_str()).copied())
+            .collect();
+        let updated_at = match strategy {
+            MergeStrategy::Newest => p.updated_at.as_deref().unwrap_or(&now),
+            _ => &now,
+        };
+        profiles::store(&tx, name, &members, updated_at)?;
+        report.profiles += 1;
+    }
+
+    roots::assign(&tx)?;
+    authors::sync(&tx, &now)?;
+    history::log(
+        &tx,
+        "import",
+        &format!(
+            "Imported library metadata: {} new, {} updated",
+            report.added, report.updated
+        ),
+        &journal,
+        &now,
+    )?;
+    tx.commit()?;
+    Ok(report)
+}
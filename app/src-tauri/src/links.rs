@@ -0,0 +1,210 @@
+use crate::commands;
+use crate::db::{self, DbPool};
+use crate::errors::AppError;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::Url;
+use tracing::info;
+use ureq::ResponseExt;
+
+pub type SResult<T> = Result<T, AppError>;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REDIRECTS: u32 = 10;
+
+/// Result of checking one mod's `download_url`, also stored in `mod_links`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCheck {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub url: String,
+    /// 'ok' | 'dead' (the server says the file is gone) | 'error' (no answer, try again later)
+    pub status: String,
+    pub http_status: Option<u16>,
+    /// Where the redirects ended.
+    pub final_url: Option<String>,
+    /// Known hosts only: 'gamebanana', 'google_drive', 'mega', 'github', 'discord'.
+    pub host: Option<String>,
+    /// The file/project id on that host, e.g. the GameBanana mod id.
+    pub canonical_id: Option<String>,
+    pub error: Option<String>,
+    pub checked_at: String,
+}
+
+/// Recognizes the hosts BD2 mods are shared on and extracts a stable id from the URL:
+/// `gamebanana.com/mods/123456` -> ("gamebanana", "123456"), Drive file ids, MEGA file
+/// ids, GitHub `owner/repo`.
+pub fn canonical_id(url: &str) -> Option<(&'static str, String)> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.").to_lowercase();
+    let segments: Vec<&str> = parsed
+        .path_segments()
+        .map(|s| s.filter(|p| !p.is_empty()).collect())
+        .unwrap_or_default();
+    let query = |key: &str| {
+        parsed
+            .query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.to_string())
+    };
+    match host.as_str() {
+        "gamebanana.com" => match segments.as_slice() {
+            ["mods", id, ..] if id.chars().all(|c| c.is_ascii_digit()) => {
+                Some(("gamebanana", id.to_string()))
+            }
+            // direct file downloads: keep them apart from mod ids
+            ["dl", id, ..] | ["mmdl", id, ..] => Some(("gamebanana", format!("file:{}", id))),
+            _ => None,
+        },
+        "drive.google.com" | "docs.google.com" => match segments.as_slice() {
+            [.., "d", id, _] | [.., "d", id] => Some(("google_drive", id.to_string())),
+            _ => query("id").map(|id| ("google_drive", id)),
+        },
+        "mega.nz" | "mega.co.nz" => match segments.as_slice() {
+            ["file", id, ..] | ["folder", id, ..] => Some(("mega", id.to_string())),
+            _ => None,
+        },
+        "github.com" => match segments.as_slice() {
+            [owner, repo, ..] => Some((
+                "github",
+                format!("{}/{}", owner, repo.trim_end_matches(".git")),
+            )),
+            _ => None,
+        },
+        "cdn.discordapp.com" | "media.discordapp.net" => match segments.as_slice() {
+            ["attachments", channel, id, ..] => Some(("discord", format!("{}/{}", channel, id))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+struct Probe {
+    status: Option<u16>,
+    final_url: Option<String>,
+    error: Option<String>,
+}
+
+// HEAD first; some hosts (Drive, MEGA) refuse it, those get a GET whose body is not read
+fn probe(agent: &ureq::Agent, url: &str) -> Probe {
+    let mut result = agent.head(url).call();
+    if matches!(&result, Ok(resp) if matches!(resp.status().as_u16(), 403 | 405 | 501)) {
+        result = agent.get(url).call();
+    }
+    match result {
+        Ok(resp) => Probe {
+            status: Some(resp.status().as_u16()),
+            final_url: Some(resp.get_uri().to_string()),
+            error: None,
+        },
+        Err(err) => Probe {
+            status: None,
+            final_url: None,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+fn store(conn: &Connection, check: &LinkCheck) -> SResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO mod_links
+          (mod_id, url, status, http_status, final_url, host, canonical_id, checked_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        ON CONFLICT(mod_id) DO UPDATE SET
+          url = excluded.url,
+          status = excluded.status,
+          http_status = excluded.http_status,
+          final_url = excluded.final_url,
+          host = excluded.host,
+          canonical_id = excluded.canonical_id,
+          checked_at = excluded.checked_at
+        "#,
+        params![
+            check.mod_id,
+            check.url,
+            check.status,
+            check.http_status,
+            check.final_url,
+            check.host,
+            check.canonical_id,
+            check.checked_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Checks the download URL of `ids` (every mod with an http(s) URL when `None`) and records
+/// the results. 404/410 marks a link dead; network failures are reported as 'error' since
+/// the host may just be unreachable. No connection is held while requesting.
+pub fn validate(pool: &DbPool, ids: Option<Vec<i64>>) -> SResult<Vec<LinkCheck>> {
+    let targets: Vec<(i64, String, String)> = {
+        let conn = pool.get()?;
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT id, display_name, download_url FROM mods
+                WHERE download_url LIKE 'http://%' OR download_url LIKE 'https://%'
+                ORDER BY id
+                "#,
+        )?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    let targets: Vec<(i64, String, String)> = match ids {
+        Some(ids) => targets
+            .into_iter()
+            .filter(|(id, ..)| ids.contains(id))
+            .collect(),
+        None => targets,
+    };
+    info!("[links] validating {} download urls", targets.len());
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .max_redirects(MAX_REDIRECTS)
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let now = commands::now_iso();
+    let mut out = Vec::with_capacity(targets.len());
+    for (mod_id, display_name, url) in targets {
+        let probe = probe(&agent, &url);
+        let status = match probe.status {
+            Some(404 | 410) => "dead",
+            Some(code) if code < 400 => "ok",
+            _ => "error",
+        };
+        // the redirect target often carries the real id (short links, mirrors)
+        let known = probe
+            .final_url
+            .as_deref()
+            .and_then(canonical_id)
+            .or_else(|| canonical_id(&url));
+        out.push(LinkCheck {
+            mod_id,
+            display_name,
+            url,
+            status: status.to_string(),
+            http_status: probe.status,
+            final_url: probe.final_url,
+            host: known.as_ref().map(|(host, _)| host.to_string()),
+            canonical_id: known.map(|(_, id)| id),
+            error: probe.error,
+            checked_at: now.clone(),
+        });
+    }
+
+    let conn = pool.get()?;
+    let _guard = db::write_lock();
+    for check in out.iter() {
+        store(&conn, check)?;
+    }
+    info!(
+        "[links] ok={} dead={} error={}",
+        out.iter().filter(|c| c.status == "ok").count(),
+        out.iter().filter(|c| c.status == "dead").count(),
+        out.iter().filter(|c| c.status == "error").count()
+    );
+    Ok(out)
+}
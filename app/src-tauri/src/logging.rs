@@ -0,0 +1,102 @@
+use crate::db;
+use crate::errors::AppError;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+pub type SResult<T> = Result<T, AppError>;
+
+pub const DEFAULT_LEVEL: &str = "info";
+const LOG_FILE_PREFIX: &str = "mods-handler.log";
+const KEEP_LOG_FILES: usize = 7;
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+// flushes the file writer when dropped, so it lives as long as the process
+static GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+pub fn logs_dir() -> SResult<PathBuf> {
+    let dir = db::data_dir()?.join("logs");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn filter_for(level: &str) -> SResult<EnvFilter> {
+    let level = level.trim().to_lowercase();
+    match level.as_str() {
+        "error" | "warn" | "info" | "debug" | "trace" => {
+            // dependencies stay at warn so a debug log is about this app
+            EnvFilter::try_new(format!("warn,app={}", level))
+                .map_err(|e| AppError::from(e.to_string()))
+        }
+        _ => Err(AppError::invalid(format!("Unknown log level '{}'", level))),
+    }
+}
+
+pub fn validate_level(level: &str) -> SResult<()> {
+    filter_for(level).map(|_| ())
+}
+
+/// Logs to a daily rolling file in the app data dir (the only output in release builds,
+/// which have no console) and to stdout.
+pub fn init() {
+    let filter = filter_for(DEFAULT_LEVEL).expect("default log level is valid");
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let file_layer = match logs_dir().and_then(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .max_log_files(KEEP_LOG_FILES)
+            .build(dir)
+            .map_err(|e| AppError::from(e.to_string()))
+    }) {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = GUARD.set(guard);
+            Some(fmt::layer().with_ansi(false).with_writer(writer))
+        }
+        Err(err) => {
+            eprintln!("[logging] file logging disabled: {}", err);
+            None
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(fmt::layer())
+        .init();
+    let _ = FILTER.set(handle);
+}
+
+/// Applies the `log_level` setting ("error" | "warn" | "info" | "debug" | "trace").
+pub fn set_level(level: &str) -> SResult<()> {
+    let filter = filter_for(level)?;
+    if let Some(handle) = FILTER.get() {
+        handle.reload(filter).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// The last `lines` lines of the most recent log file.
+pub fn tail(lines: usize) -> SResult<Vec<String>> {
+    let dir = logs_dir()?;
+    let latest = fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path);
+    let Some(path) = latest else {
+        return Ok(Vec::new());
+    };
+    let text = fs::read_to_string(&path)?;
+    let all: Vec<&str> = text.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].iter().map(|l| l.to_string()).collect())
+}
@@ -1,9 +1,66 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod adopt;
+mod aliases;
+mod appupdate;
+mod archive;
+mod assets;
+mod audit;
+mod authors;
 mod catalog;
+mod cli;
 mod commands;
 mod db;
+mod deeplink;
+mod diskspace;
+mod downloads;
+mod duplicates;
+mod errors;
+mod events;
+mod favorites;
+mod gallery;
+mod gamelinks;
+mod gameupdate;
+mod groups;
+mod history;
+mod i18n;
+mod imports;
+mod infer;
+mod install;
+mod instance;
+mod journal;
+mod launch;
+mod layouts;
+mod library;
+mod links;
+mod logging;
+mod manifest;
+mod modinfo;
+mod opener;
+mod organize;
+mod platform;
+mod previews;
+mod profiles;
+mod removal;
+mod report;
+mod roots;
+mod safemode;
+mod sanitize;
+mod scheduler;
+mod settings;
+mod setup;
+mod shared;
+mod shell;
+mod stats;
+mod targets;
+mod thumbs;
 mod types;
+mod updates;
+mod validate;
+mod verify;
+mod wishlist;
+
+use tauri::Manager;
 
 #[tauri::command]
 fn app_version(app_handle: tauri::AppHandle) -> String {
@@ -11,28 +68,194 @@ fn app_version(app_handle: tauri::AppHandle) -> String {
 }
 
 fn main() {
+    logging::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
+        // must come first: a second launch hands over its arguments and exits here,
+        // before setup opens the database
+        .plugin(tauri_plugin_single_instance::init(
+            instance::on_second_instance,
+        ))
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .setup(|app| {
+            let pool = db::init_pool().expect("failed to open the mods database");
+            if let Ok(conn) = pool.get() {
+                let settings = commands::load_settings(&conn).unwrap_or_default();
+                if let Err(err) = logging::set_level(&settings.log_level) {
+                    tracing::warn!("[logging] {}", err);
+                }
+            }
+            let read_pool =
+                db::init_read_pool().expect("failed to open the mods database for reading");
+            if let Ok(dir) = app.path().resource_dir() {
+                commands::set_resource_dir(dir);
+            }
+            app.manage(pool.clone());
+            app.manage(read_pool);
+            deeplink::init(app.handle());
+            shell::init(app.handle());
+            verify::spawn_background(app.handle().clone(), pool.clone());
+            scheduler::spawn(app.handle().clone(), pool);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             app_version,
+            commands::app_check_update,
             commands::db_init,
+            commands::mod_types_list,
             commands::mods_add,
             commands::mods_list,
+            commands::mods_list_enriched,
+            commands::mods_grouped,
             commands::mod_preview_info,
+            commands::mods_compare,
+            commands::mods_set_rating,
+            commands::mods_set_favorite,
+            commands::mod_thumbnail,
+            commands::mods_attach_screenshot,
             commands::previews_generate_images,
             commands::previews_generate_videos,
+            commands::previews_generate_animated,
+            commands::previews_generate_for,
             commands::previews_cancel,
+            commands::previews_errors,
+            commands::previews_migrate,
+            commands::previews_verify,
+            commands::previews_extract_posters,
             commands::mods_set_installed,
+            commands::mods_mark_verified,
+            commands::mods_rename,
+            commands::mods_confirm_risk,
             commands::mods_purge_all,
+            commands::mods_build_manifest,
+            commands::mods_find_duplicates,
+            commands::mods_search_files,
+            commands::mods_merge_duplicates,
+            commands::installed_drift_list,
+            commands::game_audit,
+            commands::game_launch,
+            commands::game_launches_list,
+            commands::game_adopt_existing,
             commands::settings_get,
             commands::settings_set,
+            commands::settings_restore_backup,
             commands::paths_rescan,
+            commands::scan_errors_list,
             commands::mods_import_dry_run,
             commands::mods_import_commit,
+            commands::import_sessions_interrupted,
+            commands::import_resume,
+            commands::i18n_table,
             commands::catalog_import_from_file,
+            commands::catalog_force_sync,
+            commands::costume_assets_import_from_file,
+            commands::costume_asset_ids,
+            commands::catalog_diff_from_file,
+            commands::catalog_apply_diff,
             commands::catalog_list,
+            commands::catalog_merge_characters,
+            commands::catalog_merge_costumes,
+            commands::catalog_rename_character,
+            commands::catalog_rename_costume,
+            commands::catalog_fetch_images,
+            commands::costume_preview,
+            commands::library_add,
+            commands::library_remove,
+            commands::library_update,
             commands::library_author_dirs,
+            commands::library_stats,
+            commands::db_maintenance,
+            commands::mods_delete,
+            commands::db_backup,
+            commands::db_restore,
+            commands::library_roots_list,
+            commands::library_relocate,
+            commands::mods_audit,
+            commands::mods_audit_apply,
+            commands::mods_bulk_update,
+            commands::mods_reinfer,
+            commands::inference_test,
+            commands::mods_reinfer_apply,
+            commands::history_list,
+            commands::history_undo,
+            commands::mod_files_list,
+            commands::mods_install,
+            commands::mods_install_dry_run,
+            commands::game_scan_links,
+            commands::mods_install_favorites,
+            commands::mods_uninstall_favorites,
+            commands::usage_stats,
+            commands::usage_stats_purge,
+            commands::install_journal_incomplete,
+            commands::install_journal_resolve,
+            commands::mods_history,
+            commands::install_space_check,
+            commands::mod_groups_list,
+            commands::mod_groups_create,
+            commands::mod_groups_assign,
+            commands::mod_groups_set_active,
+            commands::mod_groups_delete,
+            commands::profiles_list,
+            commands::profile_save,
+            commands::profile_delete,
+            commands::profile_apply,
+            commands::mods_disable_all,
+            commands::mods_restore_last_state,
+            commands::authors_list,
+            commands::authors_merge,
+            commands::authors_update,
+            commands::mods_import_archive,
+            commands::downloads_list,
+            commands::download_enqueue,
+            commands::download_pause,
+            commands::download_resume,
+            commands::download_cancel,
+            commands::wishlist_list,
+            commands::wishlist_add,
+            commands::wishlist_remove,
+            commands::wishlist_mark_acquired,
+            commands::wishlist_enqueue,
+            commands::deep_links_pending,
+            commands::deep_link_confirm,
+            commands::deep_link_dismiss,
+            commands::shell_integration_status,
+            commands::shell_integration_install,
+            commands::shell_integration_uninstall,
+            commands::shell_imports_take,
+            commands::mods_check_updates,
+            commands::mods_validate_urls,
+            commands::mods_updates_list,
+            commands::mods_updates_dismiss,
+            commands::logs_tail,
+            commands::report_generate,
+            commands::logs_open_folder,
+            commands::preview_tool_check,
+            commands::setup_status,
+            commands::setup_apply,
+            commands::library_export,
+            commands::library_import,
+            commands::library_shared_status,
+            commands::library_shared_sync,
+            commands::mods_export,
+            commands::mod_open_folder,
+            commands::mod_open_url,
+            commands::mod_validate,
+            commands::install_targets_list,
+            commands::install_target_set,
+            commands::install_target_resolve,
+            commands::aliases_export,
+            commands::aliases_import,
+            commands::author_alias_suggestions,
+            commands::sanitize_preview,
+            commands::sanitize_apply,
+            commands::library_organize_preview,
+            commands::library_organize,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
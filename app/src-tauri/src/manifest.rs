@@ -0,0 +1,387 @@
+use crate::commands::load_settings;
+use crate::errors::AppError;
+use crate::validate;
+use glob::{MatchOptions, Pattern};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+pub type SResult<T> = Result<T, AppError>;
+
+// files the app itself writes into mod folders; they are not part of the mod content
+pub(crate) const GENERATED_FILES: &[&str] = &[
+    "preview.png",
+    "preview.mp4",
+    "preview.webm",
+    "preview.webp",
+    "preview.gif",
+];
+const GENERATED_DIRS: &[&str] = &[crate::gallery::GALLERY_DIR];
+
+/// Upper bound of `AppSettings::scan_threads`.
+pub const MAX_THREADS: u32 = 64;
+
+/// Pool hashing and measuring folders in parallel; `threads` 0 uses one per CPU. Disk
+/// bound work, so it is kept apart from rayon's global pool.
+pub fn worker_pool(threads: u32) -> SResult<rayon::ThreadPool> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.min(MAX_THREADS) as usize)
+        .thread_name(|i| format!("scan-{}", i))
+        .build()
+        .map_err(|e| AppError::from(e.to_string()))
+}
+
+/// Files left out of sizes, manifests and installs, as globs from
+/// `AppSettings::ignore_globs` (`*.psd`, `Thumbs.db`). A pattern matches the path
+/// relative to the mod folder or the bare file name, in any case.
+#[derive(Debug, Clone, Default)]
+pub struct Ignores {
+    patterns: Vec<Pattern>,
+}
+
+impl Ignores {
+    pub fn new(globs: &[String]) -> SResult<Self> {
+        let patterns = globs
+            .iter()
+            .map(|g| g.trim())
+            .filter(|g| !g.is_empty())
+            .map(|g| Pattern::new(g).map_err(|e| format!("Invalid ignore pattern '{}': {}", g, e)))
+            .collect::<Result<_, _>>()?;
+        Ok(Ignores { patterns })
+    }
+
+    pub fn from_settings(conn: &Connection) -> SResult<Self> {
+        Self::new(&load_settings(conn)?.ignore_globs)
+    }
+
+    /// `rel` is '/'-separated and relative to the mod folder.
+    pub fn ignores(&self, rel: &str) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+        let options = MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+        let name = rel.rsplit('/').next().unwrap_or(rel);
+        self.patterns
+            .iter()
+            .any(|p| p.matches_with(rel, options) || p.matches_with(name, options))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub rel_path: String, // always '/'-separated
+    pub size: u64,
+    pub mtime: i64, // seconds since epoch
+    pub hash: String,
+}
+
+pub fn hash_file(path: &Path) -> SResult<String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open '{}': {}", path.display(), e))?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+pub(crate) fn is_generated(rel: &Path) -> bool {
+    let mut comps = rel.components();
+    let first = comps
+        .next()
+        .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if comps.next().is_none() {
+        return GENERATED_FILES.contains(&first.as_str());
+    }
+    GENERATED_DIRS.contains(&first.as_str())
+}
+
+/// Walks a mod folder and hashes every content file that is not ignored.
+pub fn build(folder: &Path, ignores: &Ignores) -> SResult<Vec<FileEntry>> {
+    if !folder.is_dir() {
+        return Err(AppError::path_missing("Folder", folder));
+    }
+    let mut out = Vec::new();
+    for entry in WalkDir::new(folder).min_depth(1) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry
+            .path()
+            .strip_prefix(folder)
+            .map_err(|e| e.to_string())?;
+        let rel_path = rel.to_string_lossy().replace('\\', "/");
+        if is_generated(rel) || ignores.ignores(&rel_path) {
+            continue;
+        }
+        let meta = entry.metadata().map_err(|e| e.to_string())?;
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        out.push(FileEntry {
+            rel_path,
+            size: meta.len(),
+            mtime,
+            hash: hash_file(entry.path())?,
+        });
+    }
+    out.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    Ok(out)
+}
+
+/// Size, file count and a quick fingerprint of `folder` built from file names, sizes and
+/// mtimes (no content hashing), so a rescan can tell whether anything changed.
+pub struct FolderStat {
+    pub size_bytes: u64,
+    pub file_count: u64,
+    pub fingerprint: String,
+    /// Same as `validate::risk_flags`, collected on the same walk.
+    pub risk_flags: Vec<String>,
+}
+
+/// Stores the folder's size and file count on the mod row (what a rescan would record).
+/// A folder that does not exist leaves the row unmeasured.
+pub fn store_stat(conn: &Connection, mod_id: i64, folder: &Path) -> SResult<()> {
+    if !folder.is_dir() {
+        return Ok(());
+    }
+    let stat = folder_stat(folder, &Ignores::from_settings(conn)?);
+    conn.execute(
+        "UPDATE mods SET size_bytes = ?2, file_count = ?3 WHERE id = ?1",
+        params![mod_id, stat.size_bytes as i64, stat.file_count as i64],
+    )?;
+    Ok(())
+}
+
+/// Measures `folder`; ignored files count neither for the size nor the fingerprint.
+pub fn folder_stat(folder: &Path, ignores: &Ignores) -> FolderStat {
+    let mut size_bytes = 0u64;
+    let mut file_count = 0u64;
+    let mut listing: Vec<(String, u64, i64)> = Vec::new();
+    let mut risk_flags = Vec::new();
+    for entry in WalkDir::new(folder).min_depth(1).into_iter().flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(folder).unwrap_or(entry.path());
+        let rel_path = rel.to_string_lossy().replace('\\', "/");
+        // flagged even when ignored, like the import does
+        if validate::is_risky(rel) {
+            risk_flags.push(rel_path.clone());
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if ignores.ignores(&rel_path) {
+            continue;
+        }
+        size_bytes += meta.len();
+        file_count += 1;
+        // previews are written by the app and must not count as a change
+        if is_generated(rel) {
+            continue;
+        }
+        let rel = rel_path;
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        listing.push((rel, meta.len(), mtime));
+    }
+    listing.sort();
+    risk_flags.sort();
+    let mut hasher = blake3::Hasher::new();
+    for (rel, size, mtime) in listing.iter() {
+        hasher.update(format!("{}|{}|{}\n", rel, size, mtime).as_bytes());
+    }
+    FolderStat {
+        size_bytes,
+        file_count,
+        fingerprint: hasher.finalize().to_hex().to_string(),
+        risk_flags,
+    }
+}
+
+/// Replaces the stored manifest of a mod.
+pub fn store(conn: &Connection, mod_id: i64, entries: &[FileEntry], now: &str) -> SResult<()> {
+    conn.execute("DELETE FROM mod_files WHERE mod_id = ?1", params![mod_id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO mod_files (mod_id, rel_path, size, mtime, hash) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for f in entries {
+        stmt.execute(params![mod_id, f.rel_path, f.size as i64, f.mtime, f.hash])?;
+    }
+    conn.execute(
+        "UPDATE mods SET manifest_built_at = ?2 WHERE id = ?1",
+        params![mod_id, now],
+    )?;
+    Ok(())
+}
+
+pub fn load(conn: &Connection, mod_id: i64) -> SResult<Vec<FileEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT rel_path, size, mtime, hash FROM mod_files WHERE mod_id = ?1 ORDER BY rel_path",
+    )?;
+    let rows = stmt.query_map([mod_id], |r| {
+        Ok(FileEntry {
+            rel_path: r.get(0)?,
+            size: r.get::<_, i64>(1)? as u64,
+            mtime: r.get(2)?,
+            hash: r.get(3)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// `load` without the files the ignore list matches; manifests built before a pattern
+/// was added still hold them, and ignored files are never copied into the game folder.
+pub fn load_installable(conn: &Connection, mod_id: i64) -> SResult<Vec<FileEntry>> {
+    let ignores = Ignores::from_settings(conn)?;
+    let mut files = load(conn, mod_id)?;
+    files.retain(|f| !ignores.ignores(&f.rel_path));
+    Ok(files)
+}
+
+/// Builds and stores the manifest unless one exists already (or `force`).
+/// Returns true when a manifest was (re)built.
+pub fn ensure(
+    conn: &Connection,
+    mod_id: i64,
+    folder: &Path,
+    force: bool,
+    now: &str,
+) -> SResult<bool> {
+    if !force {
+        let built: Option<String> = conn.query_row(
+            "SELECT manifest_built_at FROM mods WHERE id = ?1",
+            [mod_id],
+            |r| r.get(0),
+        )?;
+        if built.is_some() {
+            return Ok(false);
+        }
+    }
+    let entries = build(folder, &Ignores::from_settings(conn)?)?;
+    store(conn, mod_id, &entries, now)?;
+    Ok(true)
+}
+
+/// The hashed files of a mod that has no manifest yet, left for the caller to `store`
+/// (`ensure` without the write, so hashing can run outside `db::write_lock`).
+pub fn build_missing(
+    conn: &Connection,
+    mod_id: i64,
+    folder: &Path,
+) -> SResult<Option<Vec<FileEntry>>> {
+    let built: Option<String> = conn.query_row(
+        "SELECT manifest_built_at FROM mods WHERE id = ?1",
+        [mod_id],
+        |r| r.get(0),
+    )?;
+    if built.is_some() {
+        return Ok(None);
+    }
+    Ok(Some(build(folder, &Ignores::from_settings(conn)?)?))
+}
+
+/// A mod with files matching a `search` pattern.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSearchHit {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub folder_path: String,
+    /// Matching paths, relative to the mod folder.
+    pub matches: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileSearchReport {
+    pub mods: Vec<FileSearchHit>,
+    /// Mods without a manifest, which could not be searched (`mods_build_manifest`).
+    pub unindexed: usize,
+}
+
+/// Mods whose manifest has a file matching `pattern`, case-insensitively. A pattern with
+/// `*`, `?` or `[` is a glob over the relative path (`**/cutscene_*.skel`), also tried on
+/// the bare file name; anything else matches as a substring.
+pub fn search(conn: &Connection, pattern: &str) -> SResult<FileSearchReport> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err(AppError::invalid("Search pattern is empty"));
+    }
+    let glob = if pattern.contains(['*', '?', '[']) {
+        Some(
+            glob::Pattern::new(pattern)
+                .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?,
+        )
+    } else {
+        None
+    };
+    let options = glob::MatchOptions {
+        case_sensitive: false,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let needle = pattern.to_lowercase();
+    let matches = |rel: &str| match &glob {
+        Some(glob) => {
+            let name = rel.rsplit('/').next().unwrap_or(rel);
+            glob.matches_with(rel, options) || glob.matches_with(name, options)
+        }
+        None => rel.to_lowercase().contains(&needle),
+    };
+
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT m.id, m.display_name, m.folder_path, f.rel_path
+            FROM mod_files f JOIN mods m ON m.id = f.mod_id
+            ORDER BY m.display_name COLLATE NOCASE, m.id, f.rel_path
+            "#,
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut report = FileSearchReport::default();
+    while let Some(r) = rows.next()? {
+        let rel: String = r.get(3)?;
+        if !matches(&rel) {
+            continue;
+        }
+        let mod_id: i64 = r.get(0)?;
+        match report.mods.last_mut() {
+            Some(hit) if hit.mod_id == mod_id => hit.matches.push(rel),
+            _ => report.mods.push(FileSearchHit {
+                mod_id,
+                display_name: r.get(1)?,
+                folder_path: r.get(2)?,
+                matches: vec![rel],
+            }),
+        }
+    }
+    report.unindexed = conn.query_row(
+        "SELECT COUNT(*) FROM mods WHERE id NOT IN (SELECT DISTINCT mod_id FROM mod_files)",
+        [],
+        |r| r.get::<_, i64>(0),
+    )? as usize;
+    Ok(report)
+}
@@ -0,0 +1,257 @@
+use crate::archive;
+use crate::commands::load_settings;
+use crate::errors::AppError;
+use crate::manifest;
+use crate::previews;
+use crate::types::ModType;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use walkdir::WalkDir;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Sidecar describing a mod, shipped inside exported packages.
+pub const FILE_NAME: &str = "modinfo.json";
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Portable mod metadata. Characters and costumes are referenced by catalog slug so the
+/// file means the same thing in every library.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModInfo {
+    #[serde(default)]
+    pub format_version: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub character: Option<String>,
+    #[serde(default)]
+    pub costume: Option<String>,
+    /// Replaced character of swap mods.
+    #[serde(default)]
+    pub swap_source: Option<String>,
+    #[serde(default)]
+    pub mod_type: Option<ModType>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub download_url: Option<String>,
+    /// Preview image inside the package, relative to the mod folder.
+    #[serde(default)]
+    pub preview: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// `<folder name>.zip` with the mod folder at its top level.
+    #[default]
+    Zip,
+    /// A plain copy of the mod folder.
+    Folder,
+}
+
+/// The row's metadata in sidecar form, with the mod's folder path.
+pub fn from_db(conn: &Connection, mod_id: i64) -> SResult<(ModInfo, String)> {
+    conn.query_row(
+        r#"
+        SELECT m.folder_path, m.display_name, m.author, ch.slug, co.slug, sw.slug,
+               m.mod_type, m.download_url
+        FROM mods m
+        LEFT JOIN characters ch ON ch.id = m.character_id
+        LEFT JOIN costumes co ON co.id = m.costume_id
+        LEFT JOIN characters sw ON sw.id = m.swap_source_character_id
+        WHERE m.id = ?1
+        "#,
+        [mod_id],
+        |r| {
+            Ok((
+                ModInfo {
+                    format_version: FORMAT_VERSION,
+                    name: r.get(1)?,
+                    author: r.get::<_, Option<String>>(2)?.filter(|a| a != "unknown"),
+                    character: r.get(3)?,
+                    costume: r.get(4)?,
+                    swap_source: r.get(5)?,
+                    mod_type: Some(ModType::from_str(&r.get::<_, String>(6)?)),
+                    version: None,
+                    download_url: r.get(7)?,
+                    preview: None,
+                },
+                r.get(0)?,
+            ))
+        },
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Mod", mod_id))
+}
+
+/// Packages a mod with a generated `modinfo.json` (and its preview image, wherever previews
+/// are stored). `dest` is a directory, or the zip file itself. Returns the written path.
+pub fn export(
+    conn: &Connection,
+    mod_id: i64,
+    dest: &Path,
+    format: ExportFormat,
+    version: Option<String>,
+) -> SResult<PathBuf> {
+    let (mut info, folder_path) = from_db(conn, mod_id)?;
+    let folder = Path::new(&folder_path);
+    if !folder.is_dir() {
+        return Err(AppError::path_missing("Mod folder", folder));
+    }
+    let name = folder
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Mod folder '{}' has no name", folder_path))?;
+    info.version = version.filter(|v| !v.trim().is_empty());
+
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for entry in WalkDir::new(folder).min_depth(1) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry
+            .path()
+            .strip_prefix(folder)
+            .map_err(|e| e.to_string())?;
+        if manifest::is_generated(rel) || rel == Path::new(FILE_NAME) {
+            continue;
+        }
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        files.push((entry.path().to_path_buf(), rel));
+    }
+    let storage = load_settings(conn)?.preview_storage;
+    let preview = previews::dir_for(storage, mod_id, &folder_path)?.join("preview.png");
+    if preview.is_file() {
+        files.push((preview, "preview.png".to_string()));
+        info.preview = Some("preview.png".to_string());
+    }
+    let json = serde_json::to_vec_pretty(&info)?;
+
+    match format {
+        ExportFormat::Zip => {
+            let out = if dest
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+            {
+                dest.to_path_buf()
+            } else {
+                dest.join(format!("{}.zip", name))
+            };
+            let files: Vec<(PathBuf, String)> = files
+                .into_iter()
+                .map(|(src, rel)| (src, format!("{}/{}", name, rel)))
+                .collect();
+            archive::pack(&out, &files, &[(format!("{}/{}", name, FILE_NAME), json)])?;
+            Ok(out)
+        }
+        ExportFormat::Folder => {
+            let out = dest.join(&name);
+            if out.exists() {
+                return Err(format!("'{}' already exists", out.display()).into());
+            }
+            fs::create_dir_all(&out)?;
+            for (src, rel) in files.iter() {
+                let target = out.join(rel);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(src, &target)
+                    .map_err(|e| format!("Failed to copy '{}': {}", src.display(), e))?;
+            }
+            fs::write(out.join(FILE_NAME), json)?;
+            Ok(out)
+        }
+    }
+}
+
+/// The folder's `modinfo.json`, if it has a readable one.
+pub fn read(folder: &Path) -> Option<ModInfo> {
+    let path = folder.join(FILE_NAME);
+    let json = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&json) {
+        Ok(info) => Some(info),
+        Err(err) => {
+            warn!(
+                "[modinfo] ignoring unreadable '{}': {}",
+                path.display(),
+                err
+            );
+            None
+        }
+    }
+}
+
+fn character_id(conn: &Connection, slug: Option<&str>) -> SResult<Option<i64>> {
+    let Some(slug) = slug else {
+        return Ok(None);
+    };
+    conn.query_row("SELECT id FROM characters WHERE slug = ?1", [slug], |r| {
+        r.get(0)
+    })
+    .optional()
+    .map_err(AppError::from)
+}
+
+/// Overwrites the row's metadata with what the sidecar declares (fields it leaves out,
+/// or slugs unknown to the catalog, keep their current value).
+pub fn apply(conn: &Connection, mod_id: i64, info: &ModInfo, now: &str) -> SResult<()> {
+    let character = character_id(conn, info.character.as_deref())?;
+    let costume: Option<i64> = match (character, info.costume.as_deref()) {
+        (Some(ch), Some(slug)) => conn
+            .query_row(
+                "SELECT id FROM costumes WHERE character_id = ?1 AND slug = ?2",
+                params![ch, slug],
+                |r| r.get(0),
+            )
+            .optional()?,
+        _ => None,
+    };
+    let swap_source = character_id(conn, info.swap_source.as_deref())?;
+    conn.execute(
+        r#"
+        UPDATE mods SET
+          author = COALESCE(?2, author),
+          character_id = COALESCE(?3, character_id),
+          costume_id = CASE WHEN ?3 IS NOT NULL THEN ?4 ELSE COALESCE(?4, costume_id) END,
+          swap_source_character_id = COALESCE(?5, swap_source_character_id),
+          mod_type = COALESCE(?6, mod_type),
+          download_url = COALESCE(?7, download_url),
+          updated_at = ?8
+        WHERE id = ?1
+        "#,
+        params![
+            mod_id,
+            info.author.as_deref().filter(|a| !a.trim().is_empty()),
+            character,
+            costume,
+            swap_source,
+            info.mod_type.map(|t| t.to_string()),
+            info.download_url,
+            now
+        ],
+    )?;
+    Ok(())
+}
+
+/// Rewrites the mod's `modinfo.json` from its row, keeping the sidecar's version and
+/// preview entries.
+pub fn write_back(conn: &Connection, mod_id: i64) -> SResult<()> {
+    let (mut info, folder_path) = from_db(conn, mod_id)?;
+    let folder = Path::new(&folder_path);
+    if !folder.is_dir() {
+        return Err(AppError::path_missing("Mod folder", folder));
+    }
+    if let Some(previous) = read(folder) {
+        info.version = previous.version;
+        info.preview = previous.preview;
+    }
+    let json = serde_json::to_vec_pretty(&info)?;
+    fs::write(folder.join(FILE_NAME), json).map_err(AppError::from)
+}
@@ -0,0 +1,28 @@
+use crate::errors::AppError;
+use std::path::Path;
+use std::process::Command;
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[cfg(target_os = "windows")]
+const OPENER: &str = "explorer";
+#[cfg(target_os = "macos")]
+const OPENER: &str = "open";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const OPENER: &str = "xdg-open";
+
+/// Opens a folder in the system file manager (or a URL in the browser).
+pub fn open(target: &str) -> SResult<()> {
+    Command::new(OPENER)
+        .arg(target)
+        .spawn()
+        .map_err(|e| format!("Failed to open '{}': {}", target, e))?;
+    Ok(())
+}
+
+pub fn open_folder(path: &Path) -> SResult<()> {
+    if !path.is_dir() {
+        return Err(AppError::path_missing("Folder", path));
+    }
+    open(&path.to_string_lossy())
+}
@@ -0,0 +1,468 @@
+use crate::commands::{self, load_settings, normalize_path_string};
+use crate::errors::AppError;
+use crate::platform;
+use crate::previews;
+use crate::roots;
+use crate::sanitize;
+use crate::shared;
+use crate::types::{AppSettings, ModType, SanitizeRules};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Default `organize_template`; `/` separates folders below the library root.
+pub const DEFAULT_TEMPLATE: &str = "{author}/{character} - {costume} - {type}";
+
+const UNKNOWN_AUTHOR: &str = "Unknown";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizeMove {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrganizeSkip {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrganizePlan {
+    pub moves: Vec<OrganizeMove>,
+    pub skipped: Vec<OrganizeSkip>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrganizeReport {
+    pub moved: Vec<i64>,
+    pub errors: Vec<String>,
+}
+
+struct Candidate {
+    id: i64,
+    display_name: String,
+    folder_path: String,
+    installed: bool,
+    root: Option<String>,
+    author: Option<String>,
+    character: Option<String>,
+    costume: Option<String>,
+    swap_source: Option<String>,
+    mod_type: ModType,
+}
+
+fn candidates(conn: &Connection) -> SResult<Vec<Candidate>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT m.id, m.display_name, m.folder_path, m.installed, r.path,
+                   COALESCE(a.name, m.author), ch.display_name, co.display_name,
+                   sw.display_name, m.mod_type
+            FROM mods m
+            LEFT JOIN library_roots r ON r.id = m.root_id
+            LEFT JOIN authors a ON a.id = m.author_id
+            LEFT JOIN characters ch ON ch.id = m.character_id
+            LEFT JOIN costumes co ON co.id = m.costume_id
+            LEFT JOIN characters sw ON sw.id = m.swap_source_character_id
+            ORDER BY m.id
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(Candidate {
+            id: r.get(0)?,
+            display_name: r.get(1)?,
+            folder_path: r.get(2)?,
+            installed: r.get::<_, i64>(3)? != 0,
+            root: r.get(4)?,
+            author: r.get(5)?,
+            character: r.get(6)?,
+            costume: r.get(7)?,
+            swap_source: r.get(8)?,
+            mod_type: ModType::from_str(&r.get::<_, String>(9)?),
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+// fills one folder name of the template; " - " pieces whose placeholders are all empty are
+// dropped, so a mod without a costume gets "Character - Type"
+fn render_segment(segment: &str, c: &Candidate) -> String {
+    let author = c
+        .author
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .unwrap_or(UNKNOWN_AUTHOR);
+    let values = [
+        ("{author}", author),
+        ("{character}", c.character.as_deref().unwrap_or("")),
+        ("{costume}", c.costume.as_deref().unwrap_or("")),
+        ("{swap_source}", c.swap_source.as_deref().unwrap_or("")),
+        ("{type}", c.mod_type.label()),
+        ("{name}", c.display_name.as_str()),
+    ];
+    segment
+        .split(" - ")
+        .map(|piece| {
+            values.iter().fold(piece.to_string(), |acc, (key, value)| {
+                acc.replace(key, value)
+            })
+        })
+        .map(|piece| piece.trim().to_string())
+        .filter(|piece| !piece.is_empty())
+        .collect::<Vec<_>>()
+        .join(" - ")
+}
+
+/// The folder `c` belongs in below its root, or `None` when the template leaves nothing.
+fn relative_target(template: &str, c: &Candidate, rules: &SanitizeRules) -> Option<PathBuf> {
+    let mut rel = PathBuf::new();
+    for segment in template.split('/').filter(|s| !s.trim().is_empty()) {
+        let name = render_segment(segment, c);
+        if name.is_empty() {
+            continue;
+        }
+        rel.push(sanitize::sanitize_name(&name, rules));
+    }
+    (!rel.as_os_str().is_empty()).then_some(rel)
+}
+
+// `template`, else the `organize_template` setting
+fn resolve_template(template: Option<&str>, settings: &AppSettings) -> SResult<String> {
+    let template = template
+        .map(str::to_string)
+        .unwrap_or(settings.organize_template.clone());
+    if !template.contains('{') {
+        return Err(format!("Template '{}' has no placeholders", template).into());
+    }
+    Ok(template)
+}
+
+// whether `to` is `wanted` itself or one of its " (n)" variants
+fn is_planned_target(to: &Path, wanted: &Path) -> bool {
+    if normalize_path_string(&to.to_string_lossy())
+        == normalize_path_string(&wanted.to_string_lossy())
+    {
+        return true;
+    }
+    let (Some(name), Some(wanted_name)) = (to.file_name(), wanted.file_name()) else {
+        return false;
+    };
+    let suffix = name
+        .to_string_lossy()
+        .strip_prefix(&format!("{} (", wanted_name.to_string_lossy()))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    suffix
+        && to
+            .parent()
+            .map(|p| normalize_path_string(&p.to_string_lossy()))
+            == wanted
+                .parent()
+                .map(|p| normalize_path_string(&p.to_string_lossy()))
+}
+
+/// Where each mod (all, or `ids`) would move to under `template` (the `organize_template`
+/// setting when `None`). Installed mods, mods outside a library root and missing folders
+/// are skipped; mods already in place are left out. Name clashes get " (n)".
+pub fn plan(
+    conn: &Connection,
+    ids: Option<&[i64]>,
+    template: Option<&str>,
+) -> SResult<OrganizePlan> {
+    let settings = load_settings(conn)?;
+    let template = resolve_template(template, &settings)?;
+
+    let mut out = OrganizePlan::default();
+    let mut taken: HashSet<String> = HashSet::new();
+    for c in candidates(conn)? {
+        if ids.is_some_and(|ids| !ids.contains(&c.id)) {
+            continue;
+        }
+        let skip = |reason: &str| OrganizeSkip {
+            mod_id: c.id,
+            display_name: c.display_name.clone(),
+            reason: reason.to_string(),
+        };
+        if c.installed {
+            out.skipped
+                .push(skip("Installed; uninstall it before moving"));
+            continue;
+        }
+        let Some(root) = c.root.as_deref() else {
+            out.skipped.push(skip("Not inside a library folder"));
+            continue;
+        };
+        let from = Path::new(&c.folder_path);
+        if !from.is_dir() {
+            out.skipped.push(skip("Folder is missing"));
+            continue;
+        }
+        let Some(rel) = relative_target(&template, &c, &settings.sanitize) else {
+            out.skipped.push(skip("Template gives an empty name"));
+            continue;
+        };
+
+        let wanted = Path::new(root).join(&rel);
+        if normalize_path_string(&wanted.to_string_lossy()) == normalize_path_string(&c.folder_path)
+        {
+            continue;
+        }
+        let mut target = wanted.clone();
+        let mut n = 2;
+        while target.exists() || taken.contains(&normalize_path_string(&target.to_string_lossy())) {
+            let name = wanted
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default();
+            target = wanted.with_file_name(format!("{} ({})", name, n));
+            n += 1;
+        }
+        let to = normalize_path_string(&target.to_string_lossy());
+        taken.insert(to.clone());
+        out.moves.push(OrganizeMove {
+            mod_id: c.id,
+            display_name: c.display_name,
+            from: normalize_path_string(&c.folder_path),
+            to,
+        });
+    }
+    info!(
+        "[organize] planned moves={} skipped={}",
+        out.moves.len(),
+        out.skipped.len()
+    );
+    Ok(out)
+}
+
+// removes the author folders a move left empty, up to (not including) the root
+fn prune_empty_parents(from: &Path, root: Option<&str>) {
+    let Some(root) = root else { return };
+    let root = Path::new(root);
+    let mut dir = from.parent();
+    while let Some(d) = dir {
+        if d == root || !d.starts_with(root) || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+/// Performs planned moves. Each is re-checked against the database first (still at
+/// `from`, not installed) and its `to` against the target `template` gives inside the
+/// mod's library root. The folders are moved, then every `folder_path` (including mods
+/// nested below a moved folder) is updated in one transaction; if that fails the folders
+/// are moved back.
+pub fn apply(
+    conn: &mut Connection,
+    moves: &[OrganizeMove],
+    template: Option<&str>,
+    now: &str,
+) -> SResult<OrganizeReport> {
+    let mut report = OrganizeReport::default();
+    let settings = load_settings(conn)?;
+    let template = resolve_template(template, &settings)?;
+    let current: Vec<Candidate> = candidates(conn)?;
+    let mut done: Vec<(&OrganizeMove, Option<String>)> = Vec::new();
+    for m in moves {
+        let Some(c) = current.iter().find(|c| c.id == m.mod_id) else {
+            report
+                .errors
+                .push(format!("id={}: mod no longer exists", m.mod_id));
+            continue;
+        };
+        if c.installed {
+            report
+                .errors
+                .push(format!("id={}: installed; uninstall it first", m.mod_id));
+            continue;
+        }
+        if normalize_path_string(&c.folder_path) != m.from {
+            report
+                .errors
+                .push(format!("id={}: moved since the plan was made", m.mod_id));
+            continue;
+        }
+        // the frontend only picks moves; where they go is decided here
+        let wanted = c.root.as_deref().and_then(|root| {
+            relative_target(&template, c, &settings.sanitize).map(|rel| Path::new(root).join(rel))
+        });
+        if !wanted.is_some_and(|wanted| is_planned_target(Path::new(&m.to), &wanted)) {
+            report.errors.push(format!(
+                "id={}: '{}' is not the planned target",
+                m.mod_id, m.to
+            ));
+            continue;
+        }
+        if let Err(err) = shared::ensure_writable(conn, Path::new(&m.from))
+            .and_then(|()| shared::ensure_writable(conn, Path::new(&m.to)))
+        {
+            report.errors.push(format!("id={}: {}", m.mod_id, err));
+            continue;
+        }
+        let to = Path::new(&m.to);
+        if to.exists() {
+            report
+                .errors
+                .push(format!("id={}: '{}' already exists", m.mod_id, m.to));
+            continue;
+        }
+        if let Some(parent) = to.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                report.errors.push(format!(
+                    "id={}: cannot create '{}': {}",
+                    m.mod_id,
+                    parent.display(),
+                    e
+                ));
+                continue;
+            }
+        }
+        match fs::rename(&m.from, to) {
+            Ok(()) => done.push((m, c.root.clone())),
+            Err(e) => report.errors.push(format!(
+                "id={}: failed to move '{}' -> '{}': {}",
+                m.mod_id, m.from, m.to, e
+            )),
+        }
+    }
+
+    let stored = (|| -> SResult<()> {
+        let tx = conn.transaction()?;
+        // in the order the folders moved, so nested mods follow each move as it happened
+        for (m, _) in done.iter() {
+            commands::repoint_folder(&tx, &m.from, &normalize_path_string(&m.to), now)?;
+        }
+        roots::assign(&tx)?;
+        tx.commit().map_err(AppError::from)
+    })();
+    if let Err(err) = stored {
+        for (m, _) in done.iter().rev() {
+            if let Err(e) = fs::rename(&m.to, &m.from) {
+                warn!(
+                    "[organize] could not move '{}' back to '{}': {}",
+                    m.to, m.from, e
+                );
+            }
+        }
+        return Err(format!("Database update failed, folders moved back: {}", err).into());
+    }
+
+    for (m, root) in done.iter() {
+        prune_empty_parents(Path::new(&m.from), root.as_deref());
+        report.moved.push(m.mod_id);
+    }
+    info!(
+        "[organize] moved={} errors={}",
+        report.moved.len(),
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+/// Sets a mod's display name and, with `rename_folder`, renames its folder to match
+/// (sanitized). The folder is renamed first and moved back if the database update fails;
+/// manifests are relative and cached previews keyed by id, so both follow. Installed mods
+/// keep their folder. Returns the mod's folder path afterwards.
+pub fn rename(
+    conn: &mut Connection,
+    id: i64,
+    new_name: &str,
+    rename_folder: bool,
+    now: &str,
+) -> SResult<String> {
+    let name = new_name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid("Name is empty"));
+    }
+    let (folder_path, installed): (String, bool) = conn
+        .query_row(
+            "SELECT folder_path, installed FROM mods WHERE id = ?1",
+            [id],
+            |r| Ok((r.get(0)?, r.get::<_, i64>(1)? != 0)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found("Mod", id))?;
+
+    let from = PathBuf::from(&folder_path);
+    let mut to = from.clone();
+    if rename_folder {
+        if installed {
+            return Err("Uninstall the mod before renaming its folder"
+                .to_string()
+                .into());
+        }
+        shared::ensure_writable(conn, &from)?;
+        let rules = load_settings(conn)?.sanitize;
+        let folder_name = sanitize::sanitize_name(name, &rules);
+        // whatever the rules let through, the result must stay a sibling folder
+        if !platform::is_single_component(&folder_name) {
+            return Err(AppError::invalid(format!(
+                "'{}' cannot be used as a folder name",
+                folder_name
+            )));
+        }
+        to = from.with_file_name(folder_name);
+    }
+    let moved = to != from;
+    if moved {
+        if !from.is_dir() {
+            return Err(AppError::path_missing("Folder", &from));
+        }
+        // a case-only rename is the same folder on Windows/macOS
+        if to.exists() && !to.to_string_lossy().eq_ignore_ascii_case(&folder_path) {
+            return Err(format!("'{}' already exists", to.display()).into());
+        }
+        fs::rename(&from, &to).map_err(|e| {
+            format!(
+                "Failed to rename '{}' -> '{}': {}",
+                from.display(),
+                to.display(),
+                e
+            )
+        })?;
+    }
+    let to_norm = normalize_path_string(&to.to_string_lossy());
+
+    let stored = (|| -> SResult<()> {
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE mods SET display_name = ?2, updated_at = ?3 WHERE id = ?1",
+            params![id, name, now],
+        )?;
+        if moved {
+            // the folder itself and any mod folder nested below it
+            let from_norm = normalize_path_string(&folder_path);
+            commands::repoint_folder(&tx, &from_norm, &to_norm, now)?;
+            roots::assign(&tx)?;
+            previews::refresh_flags(&tx, Some(&[id]))?;
+        }
+        tx.commit().map_err(AppError::from)
+    })();
+    if let Err(err) = stored {
+        if moved {
+            if let Err(e) = fs::rename(&to, &from) {
+                warn!(
+                    "[organize] could not rename '{}' back to '{}': {}",
+                    to.display(),
+                    from.display(),
+                    e
+                );
+            }
+        }
+        return Err(err);
+    }
+    info!(
+        "[organize] renamed id={} to '{}' folder='{}'",
+        id, name, to_norm
+    );
+    Ok(to_norm)
+}
@@ -0,0 +1,221 @@
+use directories::UserDirs;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+// Windows and macOS file systems ignore case by default, Linux (and the Steam Deck) don't
+pub(crate) const CASE_INSENSITIVE_PATHS: bool = cfg!(any(windows, target_os = "macos"));
+
+const GAME_FOLDER: &str = "steamapps/common/BrownDust II";
+const GAME_EXE: &str = "BrownDust II.exe";
+
+// the standalone launcher's install dir (inside a Proton prefix on Linux)
+const LAUNCHER_FOLDER: &str = "Neowiz/Browndust2";
+
+#[cfg(windows)]
+const STEAM_ROOTS: &[&str] = &[
+    "C:/Program Files (x86)/Steam",
+    "C:/Program Files/Steam",
+    "C:/SteamLibrary",
+    "D:/SteamLibrary",
+    "E:/SteamLibrary",
+];
+
+#[cfg(target_os = "linux")]
+const STEAM_ROOTS: &[&str] = &[
+    "~/.steam/steam",
+    "~/.local/share/Steam",
+    "~/.var/app/com.valvesoftware.Steam/.local/share/Steam",
+    // Steam Deck SD card
+    "/run/media/mmcblk0p1",
+];
+
+#[cfg(target_os = "macos")]
+const STEAM_ROOTS: &[&str] = &["~/Library/Application Support/Steam"];
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+const STEAM_ROOTS: &[&str] = &[];
+
+fn expand_home(p: &str) -> Option<PathBuf> {
+    match p.strip_prefix("~/") {
+        Some(rest) => Some(UserDirs::new()?.home_dir().join(rest)),
+        None => Some(PathBuf::from(p)),
+    }
+}
+
+/// Extra Steam library folders listed in `steamapps/libraryfolders.vdf`.
+fn library_folders(steam_root: &Path) -> Vec<PathBuf> {
+    let Ok(vdf) = fs::read_to_string(steam_root.join("steamapps/libraryfolders.vdf")) else {
+        return Vec::new();
+    };
+    vdf.lines()
+        .filter_map(|line| {
+            // "path"		"D:\\SteamLibrary"
+            let parts: Vec<&str> = line.split('"').collect();
+            (parts.len() >= 4 && parts[1] == "path")
+                .then(|| PathBuf::from(parts[3].replace("\\\\", "/")))
+        })
+        .collect()
+}
+
+/// Folders the game may be installed in on this OS, most likely first. On Linux this
+/// includes standalone launcher installs inside Proton prefixes.
+pub fn game_roots() -> Vec<PathBuf> {
+    let mut libraries: Vec<PathBuf> = Vec::new();
+    for root in STEAM_ROOTS.iter().filter_map(|r| expand_home(r)) {
+        if !root.is_dir() {
+            continue;
+        }
+        let extra = library_folders(&root);
+        libraries.push(root);
+        libraries.extend(extra);
+    }
+    libraries.dedup();
+
+    let mut out: Vec<PathBuf> = libraries.iter().map(|l| l.join(GAME_FOLDER)).collect();
+    if cfg!(windows) {
+        out.push(PathBuf::from("C:/").join(LAUNCHER_FOLDER));
+    }
+    if cfg!(target_os = "linux") {
+        for library in libraries.iter() {
+            let Ok(prefixes) = fs::read_dir(library.join("steamapps/compatdata")) else {
+                continue;
+            };
+            out.extend(
+                prefixes
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path().join("pfx/drive_c").join(LAUNCHER_FOLDER)),
+            );
+        }
+    }
+    out
+}
+
+/// The game executable: the configured one, else the first one found in `game_roots`.
+/// Only Windows runs it directly; elsewhere the game needs Steam's Proton.
+pub fn game_executable(configured: Option<&str>) -> Option<PathBuf> {
+    if let Some(path) = configured.map(str::trim).filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    if !cfg!(windows) {
+        return None;
+    }
+    find_game_exe()
+}
+
+/// The game executable in the first of `game_roots` that has one, on any OS (under Proton
+/// it is still the Windows binary).
+pub fn find_game_exe() -> Option<PathBuf> {
+    game_roots()
+        .into_iter()
+        .map(|root| root.join(GAME_EXE))
+        .find(|exe| exe.is_file())
+}
+
+/// The Java executable for the preview tool: the configured one, else `$JAVA_HOME`'s,
+/// else whatever `java` is on PATH.
+pub fn java_command(configured: Option<&str>) -> PathBuf {
+    if let Some(path) = configured.map(str::trim).filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    let exe = if cfg!(windows) { "java.exe" } else { "java" };
+    std::env::var_os("JAVA_HOME")
+        .map(|home| PathBuf::from(home).join("bin").join(exe))
+        .filter(|p| p.is_file())
+        .unwrap_or_else(|| PathBuf::from("java"))
+}
+
+/// '/'-separated without trailing separator or verbatim prefix (canonicalize adds `\\?\`
+/// on Windows). Backslashes are only separators on Windows.
+pub fn slashed(p: &str) -> String {
+    let mut s = if cfg!(windows) {
+        p.replace('\\', "/")
+    } else {
+        p.to_string()
+    };
+    if let Some(rest) = s.strip_prefix("//?/") {
+        s = match rest.strip_prefix("UNC/") {
+            Some(share) => format!("//{}", share),
+            None => rest.to_string(),
+        };
+    }
+    while s.ends_with('/') && s.len() > 1 {
+        s.pop();
+    }
+    s
+}
+
+/// True when `name` is one plain file or folder name, safe to join below a folder: no
+/// separator, `.`/`..`, root or drive. Backslashes are refused on every OS, since the
+/// same name may reach a Windows machine through a shared library.
+pub fn is_single_component(name: &str) -> bool {
+    !name.contains(['/', '\\'])
+        && matches!(
+            Path::new(name).components().collect::<Vec<_>>()[..],
+            [Component::Normal(_)]
+        )
+}
+
+/// Form of a path for equality/prefix checks: slashed, and case-folded where the OS
+/// ignores case. ASCII folding keeps byte offsets valid for slicing the original.
+pub fn path_key(p: &str) -> String {
+    let s = slashed(p);
+    if CASE_INSENSITIVE_PATHS {
+        s.to_ascii_lowercase()
+    } else {
+        s
+    }
+}
+
+/// Windows only: the `\\?\` (or `\\?\UNC\`) form of an absolute path, which lifts the
+/// 260-character MAX_PATH limit deep NAS libraries run into. Other paths are unchanged.
+pub fn long_path(p: &Path) -> PathBuf {
+    if !cfg!(windows) || !p.is_absolute() {
+        return p.to_path_buf();
+    }
+    let s = p.to_string_lossy().replace('/', "\\");
+    if s.starts_with("\\\\?\\") {
+        PathBuf::from(s)
+    } else if let Some(share) = s.strip_prefix("\\\\") {
+        PathBuf::from(format!("\\\\?\\UNC\\{}", share))
+    } else {
+        PathBuf::from(format!("\\\\?\\{}", s))
+    }
+}
+
+// delays between attempts of `retry_transient`
+pub(crate) const RETRY_BACKOFF_MS: &[u64] = &[200, 800, 2000];
+
+/// IO errors a network share or a waking disk produces and that may pass on their own.
+pub fn is_transient(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    if matches!(
+        err.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NetworkDown
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::HostUnreachable
+            | ErrorKind::ResourceBusy
+    ) {
+        return true;
+    }
+    // ERROR_BAD_NETPATH, ERROR_UNEXP_NET_ERR, ERROR_NETNAME_DELETED, ERROR_SEM_TIMEOUT
+    cfg!(windows) && matches!(err.raw_os_error(), Some(53 | 59 | 64 | 121))
+}
+
+/// Runs `op` again after a short, growing pause while it fails with a transient error.
+pub fn retry_transient<T>(mut op: impl FnMut() -> std::io::Result<T>) -> std::io::Result<T> {
+    let mut delays = RETRY_BACKOFF_MS.iter();
+    loop {
+        match op() {
+            Err(err) if is_transient(&err) => match delays.next() {
+                Some(ms) => std::thread::sleep(std::time::Duration::from_millis(*ms)),
+                None => return Err(err),
+            },
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,553 @@
+use crate::commands::{load_settings, save_settings};
+use crate::db;
+use crate::errors::AppError;
+use crate::manifest::{self, GENERATED_FILES};
+use crate::types::PreviewStorage;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreviewMigrationReport {
+    pub moved: usize,
+    /// Files left in place because the destination already had one.
+    pub skipped: usize,
+    /// Cache folders removed because their mod no longer exists.
+    pub pruned: usize,
+    pub errors: Vec<String>,
+}
+
+/// A preview made before the mod's files last changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct StalePreview {
+    pub mod_id: i64,
+    pub display_name: String,
+    /// 'image' | 'video' | 'animated'
+    pub kind: String,
+    pub preview_path: String,
+    /// Unix seconds.
+    pub preview_modified: i64,
+    /// The most recently modified content file, relative to the mod folder.
+    pub latest_file: Option<String>,
+    pub latest_modified: i64,
+    /// 'changed': the files differ from when the preview was generated;
+    /// 'newer_files': a file is newer than the preview (previews made before tracking).
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreviewVerifyReport {
+    /// Previews looked at.
+    pub checked: usize,
+    /// Mods whose folder is gone; their previews are left alone.
+    pub missing_folders: usize,
+    pub stale: Vec<StalePreview>,
+}
+
+// the files a preview kind may have been written as
+const KIND_FILES: &[(&str, &[&str])] = &[
+    ("image", &["preview.png"]),
+    ("video", &["preview.mp4", "preview.webm"]),
+    ("animated", &["preview.webp", "preview.gif"]),
+];
+
+/// Lines kept from a failed generator run; the cause is near the end.
+const MAX_ERROR_LINES: usize = 400;
+
+/// Output of the last failed generator run for one mod and preview kind, from `preview_errors`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewError {
+    pub mod_id: i64,
+    /// 'image' | 'video' | 'animated'
+    pub kind: String,
+    pub exit_code: Option<i32>,
+    pub message: String,
+    /// stdout and stderr interleaved as they arrived.
+    pub output: String,
+    pub failed_at: String,
+}
+
+pub fn record_error(conn: &Connection, error: &PreviewError) -> SResult<()> {
+    let lines: Vec<&str> = error.output.lines().collect();
+    let output = lines[lines.len().saturating_sub(MAX_ERROR_LINES)..].join("\n");
+    conn.execute(
+        r#"
+        INSERT INTO preview_errors (mod_id, kind, exit_code, message, output, failed_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(mod_id, kind) DO UPDATE SET
+          exit_code = excluded.exit_code,
+          message = excluded.message,
+          output = excluded.output,
+          failed_at = excluded.failed_at
+        "#,
+        params![
+            error.mod_id,
+            error.kind,
+            error.exit_code,
+            error.message,
+            output,
+            error.failed_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Forgets the failure once the same kind generates fine.
+pub fn clear_error(conn: &Connection, mod_id: i64, kind: &str) -> SResult<()> {
+    conn.execute(
+        "DELETE FROM preview_errors WHERE mod_id = ?1 AND kind = ?2",
+        params![mod_id, kind],
+    )?;
+    Ok(())
+}
+
+pub fn errors_for(conn: &Connection, mod_id: i64) -> SResult<Vec<PreviewError>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT mod_id, kind, exit_code, message, output, failed_at FROM preview_errors
+            WHERE mod_id = ?1 ORDER BY failed_at DESC
+            "#,
+    )?;
+    let rows = stmt.query_map([mod_id], |r| {
+        Ok(PreviewError {
+            mod_id: r.get(0)?,
+            kind: r.get(1)?,
+            exit_code: r.get(2)?,
+            message: r.get(3)?,
+            output: r.get(4)?,
+            failed_at: r.get(5)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+pub fn cache_root() -> SResult<PathBuf> {
+    db::data_dir()
+        .map(|d| d.join("previews"))
+        .map_err(|e| AppError::from(e.to_string()))
+}
+
+/// Removes the cache folder of a deleted mod. Ids are reused, so a leftover folder would
+/// show its previews on the next mod to get the id. Whatever the storage setting, since
+/// the folder may predate a switch.
+pub fn drop_cache(mod_id: i64) {
+    let Ok(dir) = cache_root().map(|root| root.join(mod_id.to_string())) else {
+        return;
+    };
+    if dir.is_dir() {
+        if let Err(err) = fs::remove_dir_all(&dir) {
+            warn!("[previews] failed to remove '{}': {}", dir.display(), err);
+        }
+    }
+}
+
+/// Folder holding a mod's `preview.*` files: the mod folder itself, or
+/// `<app data>/previews/<mod id>` so nothing generated ends up in the library (or the game).
+pub fn dir_for(storage: PreviewStorage, mod_id: i64, folder_path: &str) -> SResult<PathBuf> {
+    match storage {
+        PreviewStorage::ModFolder => Ok(PathBuf::from(folder_path)),
+        PreviewStorage::Cache => Ok(cache_root()?.join(mod_id.to_string())),
+    }
+}
+
+/// Whether `dir` holds a still preview and a video preview (mp4 or webm).
+pub fn flags_in(dir: &Path) -> (bool, bool) {
+    let has_image = dir.join("preview.png").is_file();
+    let has_video = dir.join("preview.mp4").is_file() || dir.join("preview.webm").is_file();
+    (has_image, has_video)
+}
+
+/// Re-reads which previews exist for `ids` (every mod when `None`) into the cached
+/// `has_image`/`has_video` columns. Returns how many rows changed.
+pub fn refresh_flags(conn: &Connection, ids: Option<&[i64]>) -> SResult<usize> {
+    let storage = load_settings(conn)?.preview_storage;
+    let mods: Vec<(i64, String, bool, bool)> = {
+        let mut stmt = conn.prepare("SELECT id, folder_path, has_image, has_video FROM mods")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut changed = 0;
+    for (id, folder_path, had_image, had_video) in mods {
+        if ids.is_some_and(|ids| !ids.contains(&id)) {
+            continue;
+        }
+        let flags = flags_in(&dir_for(storage, id, &folder_path)?);
+        if flags == (had_image, had_video) {
+            continue;
+        }
+        conn.execute(
+            "UPDATE mods SET has_image = ?2, has_video = ?3 WHERE id = ?1",
+            params![id, flags.0, flags.1],
+        )?;
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+/// Remembers what the mod folder looked like when a preview of `kind` was generated, so
+/// `verify` notices changes that keep old modification times (extracted archives).
+pub fn record_source(conn: &Connection, mod_id: i64, kind: &str, now: &str) -> SResult<()> {
+    let folder_path: String = conn.query_row(
+        "SELECT folder_path FROM mods WHERE id = ?1",
+        [mod_id],
+        |r| r.get(0),
+    )?;
+    let ignores = manifest::Ignores::from_settings(conn)?;
+    let fingerprint = manifest::folder_stat(Path::new(&folder_path), &ignores).fingerprint;
+    conn.execute(
+        r#"
+        INSERT INTO preview_sources (mod_id, kind, fingerprint, generated_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(mod_id, kind) DO UPDATE SET
+          fingerprint = excluded.fingerprint,
+          generated_at = excluded.generated_at
+        "#,
+        params![mod_id, kind, fingerprint, now],
+    )?;
+    Ok(())
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// the most recently modified content file of a mod folder; previews do not count
+fn latest_file(folder: &Path) -> Option<(String, i64)> {
+    WalkDir::new(folder)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(folder).ok()?;
+            if manifest::is_generated(rel) {
+                return None;
+            }
+            let modified = unix_secs(e.metadata().ok()?.modified().ok()?);
+            Some((rel.to_string_lossy().replace('\\', "/"), modified))
+        })
+        .max_by_key(|(_, modified)| *modified)
+}
+
+/// Finds previews of `ids` (every mod when `None`) older than the mod's files: by the
+/// fingerprint recorded at generation, or for older previews by modification time.
+/// Regenerating the stale ones is `previews_generate_for` with `overwrite`.
+pub fn verify(conn: &Connection, ids: Option<&[i64]>) -> SResult<PreviewVerifyReport> {
+    let storage = load_settings(conn)?.preview_storage;
+    let mods: Vec<(i64, String, String)> = {
+        let mut stmt =
+            conn.prepare("SELECT id, display_name, folder_path FROM mods ORDER BY id")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut recorded =
+        conn.prepare("SELECT fingerprint FROM preview_sources WHERE mod_id = ?1 AND kind = ?2")?;
+
+    let ignores = manifest::Ignores::from_settings(conn)?;
+    let mut report = PreviewVerifyReport::default();
+    for (id, display_name, folder_path) in mods {
+        if ids.is_some_and(|ids| !ids.contains(&id)) {
+            continue;
+        }
+        let folder = Path::new(&folder_path);
+        let dir = dir_for(storage, id, &folder_path)?;
+        let previews: Vec<(&str, PathBuf)> = KIND_FILES
+            .iter()
+            .filter_map(|(kind, names)| {
+                let path = names.iter().map(|n| dir.join(n)).find(|p| p.is_file())?;
+                Some((*kind, path))
+            })
+            .collect();
+        if previews.is_empty() {
+            continue;
+        }
+        if !folder.is_dir() {
+            report.missing_folders += 1;
+            continue;
+        }
+        let latest = latest_file(folder);
+        // computed once per mod, only when a preview has one recorded
+        let mut fingerprint: Option<String> = None;
+        for (kind, path) in previews {
+            report.checked += 1;
+            let preview_modified = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(unix_secs)
+                .unwrap_or(0);
+            let latest_modified = latest.as_ref().map(|(_, m)| *m).unwrap_or(0);
+            let source: Option<String> = recorded
+                .query_row(params![id, kind], |r| r.get(0))
+                .optional()?;
+            let reason = match source {
+                Some(source) => {
+                    let current = fingerprint
+                        .get_or_insert_with(|| manifest::folder_stat(folder, &ignores).fingerprint);
+                    (*current != source).then_some("changed")
+                }
+                None => (latest_modified > preview_modified).then_some("newer_files"),
+            };
+            if let Some(reason) = reason {
+                report.stale.push(StalePreview {
+                    mod_id: id,
+                    display_name: display_name.clone(),
+                    kind: kind.to_string(),
+                    preview_path: path.to_string_lossy().to_string(),
+                    preview_modified,
+                    latest_file: latest.as_ref().map(|(f, _)| f.clone()),
+                    latest_modified,
+                    reason: reason.to_string(),
+                });
+            }
+        }
+    }
+    info!(
+        "[previews] verified {} previews, {} stale",
+        report.checked,
+        report.stale.len()
+    );
+    Ok(report)
+}
+
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // rename fails across volumes (library on another drive than the app data)
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+/// Moves every existing preview to where `to` stores it, then switches the setting so
+/// lookups and generation follow. Cache folders of deleted mods are dropped on the way.
+pub fn migrate(conn: &Connection, to: PreviewStorage) -> SResult<PreviewMigrationReport> {
+    let mut settings = load_settings(conn)?;
+    let from = settings.preview_storage;
+    let mut report = PreviewMigrationReport::default();
+
+    let mods: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, folder_path FROM mods")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    if from != to {
+        for (id, folder_path) in mods.iter() {
+            let src = dir_for(from, *id, folder_path)?;
+            let dst = dir_for(to, *id, folder_path)?;
+            if to == PreviewStorage::ModFolder && !dst.is_dir() {
+                continue;
+            }
+            for name in GENERATED_FILES {
+                let file = src.join(name);
+                if !file.is_file() {
+                    continue;
+                }
+                if dst.join(name).exists() {
+                    report.skipped += 1;
+                    continue;
+                }
+                match move_file(&file, &dst.join(name)) {
+                    Ok(()) => report.moved += 1,
+                    Err(e) => report.errors.push(format!("{}: {}", file.display(), e)),
+                }
+            }
+            if from == PreviewStorage::Cache {
+                // only removes the folder once it is empty
+                let _ = fs::remove_dir(&src);
+            }
+        }
+        settings.preview_storage = to;
+        save_settings(conn, &settings)?;
+        refresh_flags(conn, None)?;
+    }
+
+    let known: HashSet<String> = mods.iter().map(|(id, _)| id.to_string()).collect();
+    if let Ok(entries) = fs::read_dir(cache_root()?) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if known.contains(&name) || !entry.path().is_dir() {
+                continue;
+            }
+            match fs::remove_dir_all(entry.path()) {
+                Ok(()) => report.pruned += 1,
+                Err(e) => warn!(
+                    "[previews] failed to prune '{}': {}",
+                    entry.path().display(),
+                    e
+                ),
+            }
+        }
+    }
+
+    info!(
+        "[previews] migrated {:?} -> {:?} moved={} skipped={} pruned={} errors={}",
+        from,
+        to,
+        report.moved,
+        report.skipped,
+        report.pruned,
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+/// Which frame of `preview.mp4`/`.webm` becomes the poster.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PosterFrame {
+    First,
+    /// Falls back to the first frame when the length cannot be read.
+    #[default]
+    Middle,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PosterSummary {
+    pub extracted: usize,
+    /// Mods without a video, or with a still of their own.
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+// `preview_sources` row marking a `preview.png` taken from the video, which image
+// generation replaces
+const POSTER_KIND: &str = "poster";
+
+/// Mods whose `preview.png` is a poster frame.
+pub fn poster_ids(conn: &Connection) -> SResult<HashSet<i64>> {
+    let mut stmt = conn.prepare("SELECT mod_id FROM preview_sources WHERE kind = ?1")?;
+    let rows = stmt.query_map([POSTER_KIND], |r| r.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Called when the generator wrote a real still over the poster.
+pub fn clear_poster(conn: &Connection, mod_id: i64) -> SResult<()> {
+    conn.execute(
+        "DELETE FROM preview_sources WHERE mod_id = ?1 AND kind = ?2",
+        params![mod_id, POSTER_KIND],
+    )?;
+    Ok(())
+}
+
+// seconds, from the "Duration: 00:00:05.03" line ffmpeg prints for its input
+fn video_seconds(ffmpeg: &Path, video: &Path) -> Option<f64> {
+    let output = Command::new(ffmpeg).arg("-i").arg(video).output().ok()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let duration = stderr.split("Duration: ").nth(1)?.split(',').next()?;
+    duration.trim().split(':').try_fold(0.0, |acc, part| {
+        Some(acc * 60.0 + part.parse::<f64>().ok()?)
+    })
+}
+
+/// Writes one frame of `video` to `out` as PNG with ffmpeg. The file appears only once
+/// complete, so the grid never shows half an image.
+pub fn extract_poster(ffmpeg: &Path, video: &Path, out: &Path, frame: PosterFrame) -> SResult<()> {
+    let at = match frame {
+        PosterFrame::First => 0.0,
+        PosterFrame::Middle => video_seconds(ffmpeg, video).map_or(0.0, |s| s / 2.0),
+    };
+    let tmp = out.with_extension("poster.tmp");
+    let output = Command::new(ffmpeg)
+        .args(["-v", "error", "-y", "-ss"])
+        .arg(format!("{:.3}", at))
+        .arg("-i")
+        .arg(video)
+        .args(["-frames:v", "1", "-f", "image2", "-c:v", "png"])
+        .arg(&tmp)
+        .output()
+        .map_err(|e| format!("Failed to run '{}': {}", ffmpeg.display(), e))?;
+    if !output.status.success() || !tmp.is_file() {
+        let _ = fs::remove_file(&tmp);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg could not read '{}': {}",
+            video.display(),
+            stderr.lines().last().unwrap_or("no output").trim()
+        )
+        .into());
+    }
+    fs::rename(&tmp, out)
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            format!("Failed to write '{}': {}", out.display(), e)
+        })
+        .map_err(AppError::from)
+}
+
+/// Gives mods (of `ids`, or all) that have a preview video but no still a `preview.png`
+/// taken from the video, marked so image generation still replaces it.
+pub fn extract_posters(
+    conn: &Connection,
+    ffmpeg: &Path,
+    ids: Option<&[i64]>,
+    frame: PosterFrame,
+    now: &str,
+) -> SResult<PosterSummary> {
+    let storage = load_settings(conn)?.preview_storage;
+    let mods: Vec<(i64, String)> = {
+        let mut stmt =
+            conn.prepare("SELECT id, folder_path FROM mods WHERE has_video = 1 ORDER BY id")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut summary = PosterSummary::default();
+    let mut extracted = Vec::new();
+    for (id, folder_path) in mods {
+        if ids.is_some_and(|ids| !ids.contains(&id)) {
+            continue;
+        }
+        let dir = dir_for(storage, id, &folder_path)?;
+        let still = dir.join("preview.png");
+        let video = ["preview.mp4", "preview.webm"]
+            .iter()
+            .map(|n| dir.join(n))
+            .find(|p| p.is_file());
+        let Some(video) = video.filter(|_| !still.exists()) else {
+            summary.skipped += 1;
+            continue;
+        };
+        match extract_poster(ffmpeg, &video, &still, frame) {
+            Ok(()) => {
+                summary.extracted += 1;
+                extracted.push(id);
+            }
+            Err(err) => {
+                warn!("[previews] poster of id={}: {}", id, err);
+                summary.errors.push(format!("id={}: {}", id, err));
+            }
+        }
+    }
+    // ffmpeg runs unlocked; only recording the posters queues behind other writers
+    let _writer = db::write_lock();
+    for id in extracted.iter() {
+        conn.execute(
+            r#"
+            INSERT INTO preview_sources (mod_id, kind, fingerprint, generated_at)
+            VALUES (?1, ?2, '', ?3)
+            ON CONFLICT(mod_id, kind) DO UPDATE SET generated_at = excluded.generated_at
+            "#,
+            params![id, POSTER_KIND, now],
+        )?;
+    }
+    refresh_flags(conn, Some(&extracted))?;
+    info!(
+        "[previews] posters extracted={} skipped={} errors={}",
+        summary.extracted,
+        summary.skipped,
+        summary.errors.len()
+    );
+    Ok(summary)
+}
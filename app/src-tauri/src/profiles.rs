@@ -0,0 +1,169 @@
+use crate::errors::AppError;
+use crate::install;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// A named set of mods to have installed ("streaming", "full"); applying it installs
+/// exactly those mods.
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub id: i64,
+    pub name: String,
+    pub mod_ids: Vec<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProfileApplyReport {
+    pub installed: Vec<i64>,
+    pub uninstalled: Vec<i64>,
+    pub errors: Vec<String>,
+}
+
+pub fn members(conn: &Connection, profile_id: i64) -> SResult<Vec<i64>> {
+    let mut stmt =
+        conn.prepare("SELECT mod_id FROM profile_mods WHERE profile_id = ?1 ORDER BY mod_id")?;
+    let rows = stmt.query_map([profile_id], |r| r.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+pub fn list(conn: &Connection) -> SResult<Vec<Profile>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, created_at, updated_at FROM profiles ORDER BY name COLLATE NOCASE",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, String>(2)?,
+            r.get::<_, String>(3)?,
+        ))
+    })?;
+    let mut out = Vec::new();
+    for row in rows {
+        let (id, name, created_at, updated_at) = row?;
+        out.push(Profile {
+            id,
+            name,
+            mod_ids: members(conn, id)?,
+            created_at,
+            updated_at,
+        });
+    }
+    Ok(out)
+}
+
+/// Resolves a profile given by name (case-insensitive) or id.
+pub fn find(conn: &Connection, name_or_id: &str) -> SResult<i64> {
+    let by_name: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM profiles WHERE name = ?1 COLLATE NOCASE",
+            [name_or_id.trim()],
+            |r| r.get(0),
+        )
+        .optional()?;
+    if let Some(id) = by_name {
+        return Ok(id);
+    }
+    let by_id: Option<i64> = match name_or_id.trim().parse::<i64>() {
+        Ok(id) => conn
+            .query_row("SELECT id FROM profiles WHERE id = ?1", [id], |r| r.get(0))
+            .optional()?,
+        Err(_) => None,
+    };
+    by_id
+        .ok_or_else(|| format!("No profile named '{}'", name_or_id))
+        .map_err(AppError::from)
+}
+
+/// Creates the profile, or replaces the mods of the one with the same name.
+pub fn save(conn: &mut Connection, name: &str, mod_ids: &[i64], now: &str) -> SResult<i64> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid("Profile name is empty"));
+    }
+    let tx = conn.transaction()?;
+    let id = store(&tx, name, mod_ids, now)?;
+    tx.commit()?;
+    Ok(id)
+}
+
+/// `save` inside the caller's transaction, for a name that is already checked.
+pub fn store(conn: &Connection, name: &str, mod_ids: &[i64], now: &str) -> SResult<i64> {
+    conn.execute(
+        r#"
+        INSERT INTO profiles (name, created_at, updated_at) VALUES (?1, ?2, ?2)
+        ON CONFLICT(name) DO UPDATE SET updated_at = excluded.updated_at
+        "#,
+        params![name, now],
+    )?;
+    let id: i64 = conn.query_row(
+        "SELECT id FROM profiles WHERE name = ?1 COLLATE NOCASE",
+        [name],
+        |r| r.get(0),
+    )?;
+    conn.execute("DELETE FROM profile_mods WHERE profile_id = ?1", [id])?;
+    for mod_id in mod_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO profile_mods (profile_id, mod_id) VALUES (?1, ?2)",
+            params![id, mod_id],
+        )?;
+    }
+    Ok(id)
+}
+
+pub fn delete(conn: &Connection, id: i64) -> SResult<()> {
+    conn.execute("DELETE FROM profiles WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Uninstalls the installed mods that are not in the profile, then installs its missing
+/// ones. A mod that fails is reported and the rest still applied.
+pub fn apply(
+    conn: &mut Connection,
+    id: i64,
+    game_mods_dir: &Path,
+    now: &str,
+) -> SResult<ProfileApplyReport> {
+    let wanted: HashSet<i64> = members(conn, id)?.into_iter().collect();
+    let installed: HashSet<i64> = {
+        let mut stmt = conn.prepare("SELECT id FROM mods WHERE installed = 1")?;
+        let rows = stmt.query_map([], |r| r.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut report = ProfileApplyReport::default();
+    let mut extra: Vec<i64> = installed.difference(&wanted).copied().collect();
+    extra.sort();
+    for mod_id in extra {
+        match install::uninstall(conn, mod_id, game_mods_dir, Some(id), now) {
+            Ok(()) => report.uninstalled.push(mod_id),
+            Err(err) => report.errors.push(format!("id={}: {}", mod_id, err)),
+        }
+    }
+    let mut missing: Vec<i64> = wanted.difference(&installed).copied().collect();
+    missing.sort();
+    for mod_id in missing {
+        match install::install(conn, mod_id, None, game_mods_dir, Some(id), now) {
+            Ok(_) => report.installed.push(mod_id),
+            Err(err) => report.errors.push(format!("id={}: {}", mod_id, err)),
+        }
+    }
+    for err in report.errors.iter() {
+        warn!("[profiles] apply id={}: {}", id, err);
+    }
+    info!(
+        "[profiles] applied id={}: installed={} uninstalled={} errors={}",
+        id,
+        report.installed.len(),
+        report.uninstalled.len(),
+        report.errors.len()
+    );
+    Ok(report)
+}
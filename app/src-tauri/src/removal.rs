@@ -0,0 +1,128 @@
+use crate::errors::AppError;
+use crate::install;
+use crate::previews;
+use crate::shared;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Removes a mod folder, sending it to the OS recycle bin unless `permanent` is set.
+/// Returns how the folder went away ("trash", "permanent" or "missing") for logging.
+pub fn remove_folder(path: &Path, permanent: bool) -> SResult<&'static str> {
+    if !path.exists() {
+        return Ok("missing");
+    }
+    if permanent {
+        fs::remove_dir_all(path)
+            .map_err(|e| format!("Failed to delete '{}': {}", path.display(), e))?;
+        return Ok("permanent");
+    }
+    trash::delete(path).map_err(|e| {
+        format!(
+            "Failed to move '{}' to the recycle bin: {}",
+            path.display(),
+            e
+        )
+    })?;
+    Ok("trash")
+}
+
+/// What happened to one mod of a purge.
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeResult {
+    pub mod_id: i64,
+    pub display_name: String,
+    /// "trash", "permanent" or "missing" when the folder was removed.
+    pub folder: Option<&'static str>,
+    /// The mod was installed and got removed from the game folder first.
+    pub uninstalled: bool,
+    /// Set when the mod was kept because its folder could not be removed.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PurgeReport {
+    pub deleted: Vec<i64>,
+    pub results: Vec<PurgeResult>,
+}
+
+/// Deletes the rows of `ids`, with `delete_files` removing their folders first (to the
+/// recycle bin unless `permanent`). Installed mods are uninstalled from `game_mods_dir`
+/// first, and kept when that is not configured. A mod whose folder cannot be removed keeps
+/// its row and the purge goes on with the rest. Every mod is committed on its own, so a
+/// failure halfway never brings back rows whose folders are already gone.
+pub fn purge(
+    conn: &mut Connection,
+    ids: &[i64],
+    delete_files: bool,
+    permanent: bool,
+    game_mods_dir: Option<&Path>,
+    now: &str,
+) -> SResult<PurgeReport> {
+    let mut report = PurgeReport::default();
+    for &id in ids {
+        let row: Option<(String, String, bool)> = conn
+            .query_row(
+                "SELECT folder_path, display_name, installed FROM mods WHERE id = ?1",
+                [id],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .optional()?;
+        let Some((folder_path, display_name, installed)) = row else {
+            continue;
+        };
+        let mut result = PurgeResult {
+            mod_id: id,
+            display_name,
+            folder: None,
+            uninstalled: false,
+            error: None,
+        };
+        if installed {
+            let uninstalled = match game_mods_dir {
+                Some(game_mods_dir) => conn.transaction().map_err(AppError::from).and_then(|tx| {
+                    install::uninstall(&tx, id, game_mods_dir, None, now)?;
+                    tx.commit().map_err(AppError::from)
+                }),
+                None => Err(AppError::not_configured("game_mods_dir")),
+            };
+            if let Err(err) = uninstalled {
+                warn!("[purge] id={} kept, still installed: {}", id, err);
+                result.error = Some(err.to_string());
+                report.results.push(result);
+                continue;
+            }
+            result.uninstalled = true;
+        }
+        if delete_files {
+            let removed = shared::ensure_writable(conn, Path::new(&folder_path))
+                .and_then(|()| remove_folder(Path::new(&folder_path), permanent));
+            match removed {
+                Ok(how) => result.folder = Some(how),
+                Err(err) => {
+                    warn!("[purge] id={} kept: {}", id, err);
+                    result.error = Some(err.to_string());
+                    report.results.push(result);
+                    continue;
+                }
+            }
+        }
+        if conn.execute("DELETE FROM mods WHERE id = ?1", [id])? > 0 {
+            previews::drop_cache(id);
+            report.deleted.push(id);
+        }
+        report.results.push(result);
+    }
+    info!(
+        "[purge] deleted {} of {} mods delete_files={} permanent={}",
+        report.deleted.len(),
+        ids.len(),
+        delete_files,
+        permanent
+    );
+    Ok(report)
+}
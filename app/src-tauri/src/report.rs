@@ -0,0 +1,179 @@
+use crate::commands::load_settings;
+use crate::errors::AppError;
+use crate::manifest;
+use crate::modinfo;
+use crate::types::ModType;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub type SResult<T> = Result<T, AppError>;
+
+// hashes are shortened in Markdown; the JSON keeps them whole
+const SHORT_HASH: usize = 12;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// A table to paste into Discord or an issue.
+    #[default]
+    Markdown,
+    Json,
+}
+
+/// One installed mod as shared in a report. Local paths are left out on purpose.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportMod {
+    pub name: String,
+    pub author: Option<String>,
+    /// From the mod's `modinfo.json`.
+    pub version: Option<String>,
+    pub character: Option<String>,
+    pub costume: Option<String>,
+    pub swap_source: Option<String>,
+    pub mod_type: String,
+    pub download_url: Option<String>,
+    pub installed_at: Option<String>,
+    pub file_count: usize,
+    /// blake3 over the manifest (path and hash of every file); `None` until one is built.
+    pub content_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledReport {
+    pub generated_at: String,
+    pub app_version: String,
+    pub game_version: Option<String>,
+    pub mods: Vec<ReportMod>,
+}
+
+// same input, same hash: the manifest is sorted by path
+fn content_hash(files: &[manifest::FileEntry]) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+    let mut hasher = blake3::Hasher::new();
+    for f in files {
+        hasher.update(format!("{}|{}\n", f.rel_path, f.hash).as_bytes());
+    }
+    Some(hasher.finalize().to_hex().to_string())
+}
+
+/// The installed mods with their metadata and content hashes, by name.
+pub fn installed(conn: &Connection, now: &str) -> SResult<InstalledReport> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT m.id, m.folder_path, m.display_name, m.author, ch.display_name,
+                   co.display_name, sw.display_name, m.mod_type, m.download_url,
+                   m.installed_at
+            FROM mods m
+            LEFT JOIN characters ch ON ch.id = m.character_id
+            LEFT JOIN costumes co ON co.id = m.costume_id
+            LEFT JOIN characters sw ON sw.id = m.swap_source_character_id
+            WHERE m.installed = 1
+            ORDER BY LOWER(m.display_name), m.id
+            "#,
+    )?;
+    let rows: Vec<(i64, String, ReportMod)> = stmt
+        .query_map([], |r| {
+            Ok((
+                r.get(0)?,
+                r.get(1)?,
+                ReportMod {
+                    name: r.get(2)?,
+                    author: r.get::<_, Option<String>>(3)?.filter(|a| a != "unknown"),
+                    version: None,
+                    character: r.get(4)?,
+                    costume: r.get(5)?,
+                    swap_source: r.get(6)?,
+                    mod_type: ModType::from_str(&r.get::<_, String>(7)?)
+                        .label()
+                        .to_string(),
+                    download_url: r.get(8)?,
+                    installed_at: r.get(9)?,
+                    file_count: 0,
+                    content_hash: None,
+                },
+            ))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut mods = Vec::with_capacity(rows.len());
+    for (id, folder_path, mut m) in rows {
+        let files = manifest::load(conn, id)?;
+        m.file_count = files.len();
+        m.content_hash = content_hash(&files);
+        m.version = modinfo::read(Path::new(&folder_path)).and_then(|info| info.version);
+        mods.push(m);
+    }
+    Ok(InstalledReport {
+        generated_at: now.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        game_version: load_settings(conn)?.game_version,
+        mods,
+    })
+}
+
+// table cells must not break the row
+fn cell(value: Option<&str>) -> String {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(v) => v.replace('|', "\\|").replace('\n', " "),
+        None => "-".to_string(),
+    }
+}
+
+pub fn to_markdown(report: &InstalledReport) -> String {
+    let mut out = format!(
+        "# Installed mods ({})\n\nApp {}, game {}, generated {}\n\n",
+        report.mods.len(),
+        report.app_version,
+        report.game_version.as_deref().unwrap_or("unknown"),
+        report.generated_at
+    );
+    if report.mods.is_empty() {
+        out.push_str("No mods installed.\n");
+        return out;
+    }
+    out.push_str("| Name | Author | Version | Character | Type | Link | Files | Hash |\n");
+    out.push_str("|---|---|---|---|---|---|---|---|\n");
+    for m in report.mods.iter() {
+        let character = match (&m.swap_source, &m.character, &m.costume) {
+            (Some(src), Some(ch), co) => match co {
+                Some(co) => format!("{} → {} ({})", src, ch, co),
+                None => format!("{} → {}", src, ch),
+            },
+            (_, Some(ch), Some(co)) => format!("{} ({})", ch, co),
+            (_, ch, _) => ch.clone().unwrap_or_default(),
+        };
+        let link = m
+            .download_url
+            .as_deref()
+            .filter(|u| u.starts_with("http://") || u.starts_with("https://"))
+            .map(|u| format!("<{}>", u));
+        let hash = m
+            .content_hash
+            .as_deref()
+            .map(|h| format!("`{}`", &h[..SHORT_HASH.min(h.len())]));
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+            cell(Some(&m.name)),
+            cell(m.author.as_deref()),
+            cell(m.version.as_deref()),
+            cell(Some(&character)),
+            cell(Some(&m.mod_type)),
+            cell(link.as_deref()),
+            m.file_count,
+            cell(hash.as_deref())
+        ));
+    }
+    out
+}
+
+/// The installed-mods report as text in `format`.
+pub fn generate(conn: &Connection, format: ReportFormat, now: &str) -> SResult<String> {
+    let report = installed(conn, now)?;
+    match format {
+        ReportFormat::Markdown => Ok(to_markdown(&report)),
+        ReportFormat::Json => serde_json::to_string_pretty(&report).map_err(AppError::from),
+    }
+}
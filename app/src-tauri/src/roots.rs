@@ -0,0 +1,167 @@
+use crate::commands;
+use crate::errors::AppError;
+use crate::platform::{path_key, slashed};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::Path;
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryRoot {
+    pub id: i64,
+    pub path: String,
+    pub mod_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelocateReport {
+    pub root_id: i64,
+    pub old_root: String,
+    pub new_root: String,
+    pub updated: usize,
+    // folders that are still missing under the new root
+    pub missing: Vec<String>,
+}
+
+// stored and user-typed paths compare alike (see `platform::path_key`)
+fn relative_to(root: &str, folder_path: &str) -> Option<String> {
+    let root = path_key(root);
+    let folder = slashed(folder_path);
+    if !path_key(&folder).starts_with(&root) {
+        return None;
+    }
+    let rest = folder[root.len()..].strip_prefix('/')?;
+    (!rest.is_empty()).then(|| rest.to_string())
+}
+
+fn find(conn: &Connection, path: &str) -> SResult<Option<i64>> {
+    let wanted = path_key(path);
+    let mut stmt = conn.prepare("SELECT id, path FROM library_roots")?;
+    let rows = stmt.query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (id, root) = row?;
+        if path_key(&root) == wanted {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
+/// Registers a library root (idempotent) and returns its id.
+pub fn register(conn: &Connection, path: &str) -> SResult<i64> {
+    if let Some(id) = find(conn, path)? {
+        return Ok(id);
+    }
+    conn.execute("INSERT INTO library_roots (path) VALUES (?1)", [path])?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Links mods that are not yet attached to a root to the registered root containing them
+/// and records their path relative to it.
+pub fn assign(conn: &Connection) -> SResult<usize> {
+    let roots: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, path FROM library_roots")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    if roots.is_empty() {
+        return Ok(0);
+    }
+
+    let pending: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, folder_path FROM mods WHERE root_id IS NULL")?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut assigned = 0usize;
+    for (mod_id, folder_path) in pending {
+        // nested roots: the longest (most specific) root wins
+        let best = roots
+            .iter()
+            .filter_map(|(root_id, root)| {
+                relative_to(root, &folder_path).map(|rel| (root.len(), *root_id, rel))
+            })
+            .max_by_key(|(len, _, _)| *len);
+        if let Some((_, root_id, rel)) = best {
+            conn.execute(
+                "UPDATE mods SET root_id = ?2, rel_path = ?3 WHERE id = ?1",
+                params![mod_id, root_id, rel],
+            )?;
+            assigned += 1;
+        }
+    }
+    Ok(assigned)
+}
+
+pub fn list(conn: &Connection) -> SResult<Vec<LibraryRoot>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT r.id, r.path, COUNT(m.id)
+            FROM library_roots r LEFT JOIN mods m ON m.root_id = r.id
+            GROUP BY r.id ORDER BY r.path
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(LibraryRoot {
+            id: r.get(0)?,
+            path: r.get(1)?,
+            mod_count: r.get::<_, i64>(2)? as usize,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Points the root at `new_root` and rewrites `folder_path` of every mod below it from its
+/// stored relative path. Missing folders are reported, not deleted.
+pub fn relocate(conn: &mut Connection, old_root: &str, new_root: &str) -> SResult<RelocateReport> {
+    if !Path::new(new_root).is_dir() {
+        return Err(format!("New library root '{}' is not a folder", new_root).into());
+    }
+    let tx = conn.transaction()?;
+    // older rows may predate root tracking; attach them first so they move too
+    let root_id = register(&tx, old_root)?;
+    assign(&tx)?;
+
+    let previous: Option<String> = tx
+        .query_row(
+            "SELECT path FROM library_roots WHERE id = ?1",
+            [root_id],
+            |r| r.get(0),
+        )
+        .optional()?;
+    tx.execute(
+        "UPDATE library_roots SET path = ?2 WHERE id = ?1",
+        params![root_id, new_root],
+    )?;
+
+    let members: Vec<(i64, String)> = {
+        let mut stmt = tx.prepare("SELECT id, rel_path FROM mods WHERE root_id = ?1")?;
+        let rows = stmt.query_map([root_id], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut updated = 0usize;
+    let mut missing = Vec::new();
+    for (mod_id, rel) in members {
+        let folder = Path::new(new_root).join(&rel);
+        let folder_path = commands::normalize_path_string(&folder.to_string_lossy());
+        if !folder.is_dir() {
+            missing.push(folder_path.clone());
+        }
+        updated += tx.execute(
+            "UPDATE mods SET folder_path = ?2 WHERE id = ?1",
+            params![mod_id, folder_path],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(RelocateReport {
+        root_id,
+        old_root: previous.unwrap_or_else(|| old_root.to_string()),
+        new_root: new_root.to_string(),
+        updated,
+        missing,
+    })
+}
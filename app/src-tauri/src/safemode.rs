@@ -0,0 +1,209 @@
+use crate::errors::AppError;
+use crate::install;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisableMethod {
+    /// Every installed mod is uninstalled.
+    Uninstall,
+    /// The game's mods folder is renamed to `<name>.disabled`; nothing is deleted.
+    RenameFolder,
+}
+
+impl DisableMethod {
+    fn as_str(self) -> &'static str {
+        match self {
+            DisableMethod::Uninstall => "uninstall",
+            DisableMethod::RenameFolder => "rename_folder",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "rename_folder" => DisableMethod::RenameFolder,
+            _ => DisableMethod::Uninstall,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SafeModeReport {
+    pub state_id: i64,
+    /// Mods uninstalled (disable) or reinstalled (restore); empty for a folder rename.
+    pub mod_ids: Vec<i64>,
+    pub errors: Vec<String>,
+}
+
+fn disabled_path(game_mods_dir: &Path) -> PathBuf {
+    let name = game_mods_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mods".to_string());
+    game_mods_dir.with_file_name(format!("{}.disabled", name))
+}
+
+fn installed_ids(conn: &Connection) -> SResult<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT id FROM mods WHERE installed = 1 ORDER BY id")?;
+    let rows = stmt.query_map([], |r| r.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Turns every mod off and records what was on so `restore_last` can bring it back.
+/// With `Uninstall` a mod that fails is reported and the rest still removed.
+pub fn disable_all(
+    conn: &Connection,
+    game_mods_dir: &Path,
+    method: DisableMethod,
+    now: &str,
+) -> SResult<SafeModeReport> {
+    let active: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM safe_mode_states WHERE restored_at IS NULL",
+            [],
+            |r| r.get(0),
+        )
+        .optional()?;
+    if active.is_some() {
+        return Err("Mods are already disabled; restore them first"
+            .to_string()
+            .into());
+    }
+
+    let installed = installed_ids(conn)?;
+    let mut report = SafeModeReport::default();
+    match method {
+        DisableMethod::Uninstall => {
+            for mod_id in installed.iter() {
+                match install::uninstall(conn, *mod_id, game_mods_dir, None, now) {
+                    Ok(()) => report.mod_ids.push(*mod_id),
+                    Err(err) => report.errors.push(format!("id={}: {}", mod_id, err)),
+                }
+            }
+        }
+        DisableMethod::RenameFolder => {
+            let disabled = disabled_path(game_mods_dir);
+            if !game_mods_dir.is_dir() {
+                return Err(AppError::path_missing("Game mods folder", game_mods_dir));
+            }
+            if disabled.exists() {
+                return Err(format!("'{}' already exists", disabled.display()).into());
+            }
+            fs::rename(game_mods_dir, &disabled).map_err(|e| {
+                format!(
+                    "Failed to rename '{}' to '{}': {}",
+                    game_mods_dir.display(),
+                    disabled.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    // only what was actually turned off needs restoring
+    let recorded = match method {
+        DisableMethod::Uninstall => &report.mod_ids,
+        DisableMethod::RenameFolder => &installed,
+    };
+    let mod_ids_json = serde_json::to_string(recorded)?;
+    conn.execute(
+        r#"
+        INSERT INTO safe_mode_states (created_at, method, game_mods_dir, mod_ids_json)
+        VALUES (?1, ?2, ?3, ?4)
+        "#,
+        params![
+            now,
+            method.as_str(),
+            game_mods_dir.to_string_lossy(),
+            mod_ids_json
+        ],
+    )?;
+    report.state_id = conn.last_insert_rowid();
+
+    for err in report.errors.iter() {
+        warn!("[safemode] disable: {}", err);
+    }
+    info!(
+        "[safemode] disabled {} mod(s) via {}",
+        installed.len(),
+        method.as_str()
+    );
+    Ok(report)
+}
+
+/// Undoes the latest `disable_all`: reinstalls the mods it removed or renames the folder back.
+pub fn restore_last(conn: &mut Connection, now: &str) -> SResult<SafeModeReport> {
+    let state: Option<(i64, String, String, String)> = conn
+        .query_row(
+            r#"
+            SELECT id, method, game_mods_dir, mod_ids_json FROM safe_mode_states
+            WHERE restored_at IS NULL ORDER BY id DESC LIMIT 1
+            "#,
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .optional()?;
+    let Some((state_id, method, game_mods_dir, mod_ids_json)) = state else {
+        return Err("No disabled state to restore".into());
+    };
+    let game_mods_dir = PathBuf::from(game_mods_dir);
+    let mod_ids: Vec<i64> = serde_json::from_str(&mod_ids_json)?;
+
+    let mut report = SafeModeReport {
+        state_id,
+        ..Default::default()
+    };
+    match DisableMethod::parse(&method) {
+        DisableMethod::Uninstall => {
+            for mod_id in mod_ids {
+                match install::install(conn, mod_id, None, &game_mods_dir, None, now) {
+                    Ok(_) => report.mod_ids.push(mod_id),
+                    Err(err) => report.errors.push(format!("id={}: {}", mod_id, err)),
+                }
+            }
+        }
+        DisableMethod::RenameFolder => {
+            let disabled = disabled_path(&game_mods_dir);
+            if !disabled.is_dir() {
+                return Err(AppError::path_missing("Disabled mods folder", &disabled));
+            }
+            // a game update may have recreated an empty mods folder meanwhile
+            if game_mods_dir.exists() {
+                fs::remove_dir(&game_mods_dir).map_err(|_| {
+                    format!(
+                        "'{}' exists again and is not empty; move its contents away first",
+                        game_mods_dir.display()
+                    )
+                })?;
+            }
+            fs::rename(&disabled, &game_mods_dir).map_err(|e| {
+                format!(
+                    "Failed to rename '{}' back to '{}': {}",
+                    disabled.display(),
+                    game_mods_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    conn.execute(
+        "UPDATE safe_mode_states SET restored_at = ?2 WHERE id = ?1",
+        params![state_id, now],
+    )?;
+    for err in report.errors.iter() {
+        warn!("[safemode] restore: {}", err);
+    }
+    info!(
+        "[safemode] restored state id={} ({} mod(s) reinstalled)",
+        state_id,
+        report.mod_ids.len()
+    );
+    Ok(report)
+}
@@ -0,0 +1,134 @@
+use crate::errors::AppError;
+use crate::types::SanitizeRules;
+use deunicode::deunicode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// characters rejected by Windows (and therefore by the game loader on Windows)
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamePlan {
+    pub from: String,
+    pub to: String,
+    // set when the sanitized name collides with an existing entry and a suffix was added
+    pub deduplicated: bool,
+}
+
+/// Applies `rules` to a single file or folder name. Never returns an empty string.
+pub fn sanitize_name(name: &str, rules: &SanitizeRules) -> String {
+    let mut out = if rules.ascii_only {
+        deunicode(name)
+    } else {
+        name.to_string()
+    };
+
+    if rules.replace_illegal {
+        out = out
+            .chars()
+            .map(|c| {
+                if ILLEGAL_CHARS.contains(&c) || c.is_control() {
+                    rules.replacement.clone()
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect();
+    }
+
+    if rules.collapse_whitespace {
+        out = out.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    if rules.trim_trailing_dots_spaces {
+        out = out.trim_end_matches(['.', ' ']).trim_start().to_string();
+    }
+
+    if let Some(max) = rules.max_length.filter(|m| *m > 0) {
+        if out.chars().count() > max {
+            out = out.chars().take(max).collect();
+            if rules.trim_trailing_dots_spaces {
+                out = out.trim_end_matches(['.', ' ']).to_string();
+            }
+        }
+    }
+
+    let stem = out.split('.').next().unwrap_or("").to_lowercase();
+    if RESERVED_NAMES.contains(&stem.as_str()) {
+        out.push_str(&rules.replacement);
+    }
+
+    if out.is_empty() {
+        out = rules.replacement.clone();
+        if out.is_empty() {
+            out = "_".to_string();
+        }
+    }
+    out
+}
+
+/// Picks a sibling path for `name` under `parent` that does not exist yet, appending " (n)".
+fn free_target(parent: &Path, name: &str, original: &Path) -> (PathBuf, bool) {
+    let candidate = parent.join(name);
+    if !candidate.exists() || candidate == original {
+        return (candidate, false);
+    }
+    let mut n = 2;
+    loop {
+        let candidate = parent.join(format!("{} ({})", name, n));
+        if !candidate.exists() {
+            return (candidate, true);
+        }
+        n += 1;
+    }
+}
+
+/// Plans the rename of a single path. Returns `None` when the name is already clean.
+pub fn plan_for_path(path: &Path, rules: &SanitizeRules) -> Option<RenamePlan> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let clean = sanitize_name(&name, rules);
+    if clean == name {
+        return None;
+    }
+    let parent = path.parent()?;
+    let (target, deduplicated) = free_target(parent, &clean, path);
+    Some(RenamePlan {
+        from: path.to_string_lossy().to_string(),
+        to: target.to_string_lossy().to_string(),
+        deduplicated,
+    })
+}
+
+/// Dry-run over the direct children of `dir` (mod folders of an author dir, or author dirs
+/// of a library root).
+pub fn plan_for_children(dir: &Path, rules: &SanitizeRules) -> Result<Vec<RenamePlan>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read dir '{}': {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+        .map(|e| e.path())
+        .collect();
+    paths.sort();
+    Ok(paths
+        .iter()
+        .filter_map(|p| plan_for_path(p, rules))
+        .collect())
+}
+
+/// Performs a planned rename on disk.
+pub fn apply_plan(plan: &RenamePlan) -> Result<(), AppError> {
+    let to = Path::new(&plan.to);
+    if to.exists() {
+        return Err(format!("Target '{}' already exists", plan.to).into());
+    }
+    fs::rename(&plan.from, to)
+        .map_err(|e| format!("Failed to rename '{}' -> '{}': {}", plan.from, plan.to, e))
+        .map_err(AppError::from)
+}
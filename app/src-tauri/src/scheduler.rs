@@ -0,0 +1,138 @@
+use crate::appupdate;
+use crate::commands;
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::gameupdate;
+use crate::types::ScanSummary;
+use crate::updates;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+// settings are re-read on every tick, so changes apply without a restart
+const TICK: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoRescanEvent {
+    pub summary: ScanSummary,
+    pub new_mods: usize,
+}
+
+fn mod_count(conn: &Connection) -> Result<usize, AppError> {
+    conn.query_row("SELECT COUNT(*) FROM mods", [], |r| r.get::<_, i64>(0))
+        .map(|n| n as usize)
+        .map_err(AppError::from)
+}
+
+fn due(last: Instant, every: Duration) -> bool {
+    !every.is_zero() && last.elapsed() >= every
+}
+
+// once per launch, when `check_app_update` is set
+async fn check_app_update(app: &AppHandle, pool: &DbPool) {
+    match commands::with_conn(pool, |conn| commands::load_settings(conn)).await {
+        Ok(settings) if settings.check_app_update => {}
+        Ok(_) => return,
+        Err(err) => {
+            warn!("[scheduler] failed to load settings: {}", err);
+            return;
+        }
+    }
+    let current = app.package_info().version.to_string();
+    let result = tauri::async_runtime::spawn_blocking(move || appupdate::check(&current))
+        .await
+        .map_err(|e| AppError::from(e.to_string()))
+        .and_then(|r| r);
+    match result {
+        Ok(update) if update.update_available => {
+            if let Err(err) = app.emit("app-update", update) {
+                warn!("[scheduler] failed to emit app update event: {}", err);
+            }
+        }
+        Ok(_) => {}
+        Err(err) => warn!("[scheduler] app update check failed: {}", err),
+    }
+}
+
+/// Runs the periodic library rescan (`auto_rescan_minutes`) and download update check
+/// (`auto_update_check_hours`); 0 disables either. Emits `auto-rescan` when new mods
+/// appeared and `mod-updates` when new updates were found. Every tick (starting right
+/// away) also looks for a game update and emits `game-updated` after auditing the mods.
+/// Before the first tick a newer app release is looked for (`check_app_update`).
+pub fn spawn(app: AppHandle, pool: DbPool) {
+    tauri::async_runtime::spawn(async move {
+        check_app_update(&app, &pool).await;
+        let mut ticker = tokio::time::interval(TICK);
+        let mut last_rescan = Instant::now();
+        let mut last_update_check = Instant::now();
+        loop {
+            ticker.tick().await;
+            let settings =
+                match commands::with_conn(&pool, |conn| commands::load_settings(conn)).await {
+                    Ok(s) => s,
+                    Err(err) => {
+                        warn!("[scheduler] failed to load settings: {}", err);
+                        continue;
+                    }
+                };
+
+            let result =
+                commands::with_conn(&pool, |conn| gameupdate::check(conn, &commands::now_iso()))
+                    .await;
+            match result {
+                Ok(Some(event)) => {
+                    if let Err(err) = app.emit("game-updated", event) {
+                        warn!("[scheduler] failed to emit game update event: {}", err);
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => warn!("[scheduler] game update check failed: {}", err),
+            }
+
+            let rescan_every = Duration::from_secs(settings.auto_rescan_minutes as u64 * 60);
+            if due(last_rescan, rescan_every) {
+                last_rescan = Instant::now();
+                // takes the write lock itself, only around its writes
+                let result = commands::with_conn(&pool, |conn| {
+                    let before = mod_count(conn)?;
+                    let summary = commands::rescan(conn, None)?;
+                    Ok((summary, mod_count(conn)?.saturating_sub(before)))
+                })
+                .await;
+                match result {
+                    Ok((summary, new_mods)) => {
+                        info!("[scheduler] auto rescan new_mods={}", new_mods);
+                        if new_mods > 0 {
+                            let event = AutoRescanEvent { summary, new_mods };
+                            if let Err(err) = app.emit("auto-rescan", event) {
+                                warn!("[scheduler] failed to emit rescan event: {}", err);
+                            }
+                        }
+                    }
+                    Err(err) => warn!("[scheduler] auto rescan failed: {}", err),
+                }
+            }
+
+            let check_every = Duration::from_secs(settings.auto_update_check_hours as u64 * 3600);
+            if due(last_update_check, check_every) {
+                last_update_check = Instant::now();
+                let pool = pool.clone();
+                let result = tauri::async_runtime::spawn_blocking(move || updates::check(&pool))
+                    .await
+                    .map_err(|e| AppError::from(e.to_string()))
+                    .and_then(|r| r);
+                match result {
+                    Ok(report) if report.new_updates > 0 => {
+                        if let Err(err) = app.emit("mod-updates", report) {
+                            warn!("[scheduler] failed to emit updates event: {}", err);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => warn!("[scheduler] update check failed: {}", err),
+                }
+            }
+        }
+    });
+}
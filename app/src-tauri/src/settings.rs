@@ -0,0 +1,292 @@
+use crate::errors::AppError;
+use crate::layouts::Exclusions;
+use crate::logging;
+use crate::manifest;
+use crate::platform;
+use crate::types::{AppSettings, LibraryDir, PreviewStorage};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Shape version of the stored `app_settings` blob; bump it with a step in `migrate`.
+pub const VERSION: u32 = 2;
+
+const KEY: &str = "app_settings";
+// the blob as it was before the last save, see `restore_backup`
+const BACKUP_KEY: &str = "app_settings_backup";
+
+fn read(conn: &Connection, key: &str) -> SResult<Option<String>> {
+    conn.query_row(
+        "SELECT value_json FROM settings WHERE key = ?1",
+        [key],
+        |r| r.get(0),
+    )
+    .optional()
+    .map_err(AppError::from)
+}
+
+fn write(conn: &Connection, key: &str, json: &str) -> SResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO settings(key, value_json)
+        VALUES (?1, ?2)
+        ON CONFLICT(key) DO UPDATE SET value_json = excluded.value_json
+        "#,
+        params![key, json],
+    )?;
+    Ok(())
+}
+
+/// Upgrades an older settings blob in place, one version at a time.
+fn migrate(value: &mut Value) -> SResult<()> {
+    let Some(obj) = value.as_object_mut() else {
+        return Err("Stored settings are not a JSON object".into());
+    };
+    let version = obj.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > VERSION as u64 {
+        return Err(format!(
+            "Stored settings are version {}, newer than this app understands ({})",
+            version, VERSION
+        )
+        .into());
+    }
+    if version < 1 {
+        // unversioned blobs: a single `library_dir`, or `library_dirs` saved as a string
+        if let Some(Value::String(dir)) = obj.remove("library_dir") {
+            obj.entry("library_dirs")
+                .or_insert_with(|| Value::Array(vec![Value::String(dir)]));
+        }
+        if let Some(Value::String(dir)) = obj.get("library_dirs").cloned() {
+            obj.insert(
+                "library_dirs".into(),
+                Value::Array(vec![Value::String(dir)]),
+            );
+        }
+        obj.entry("library_dirs")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        obj.insert("version".into(), Value::from(1));
+    }
+    if version < 2 {
+        // library dirs became entries and absorbed the per-dir `library_layouts` map
+        let layouts = obj
+            .remove("library_layouts")
+            .and_then(|v| v.as_object().cloned())
+            .unwrap_or_default();
+        if let Some(Value::Array(dirs)) = obj.get_mut("library_dirs") {
+            for dir in dirs.iter_mut() {
+                if let Value::String(path) = dir {
+                    let mut entry = serde_json::Map::new();
+                    if let Some(layout) = layouts.get(path.as_str()) {
+                        entry.insert("layout".into(), layout.clone());
+                    }
+                    entry.insert("path".into(), Value::String(std::mem::take(path)));
+                    *dir = Value::Object(entry);
+                }
+            }
+        }
+        obj.insert("version".into(), Value::from(2));
+    }
+    Ok(())
+}
+
+/// Checks values serde accepts but the app can't use. Returns every problem at once.
+pub fn validate(s: &AppSettings) -> SResult<()> {
+    let mut problems = Vec::new();
+    if s.library_dirs.iter().any(|d| d.path.trim().is_empty()) {
+        problems.push("library folders must not be empty".to_string());
+    }
+    let mut seen = HashSet::new();
+    for dir in s.library_dirs.iter() {
+        if !seen.insert(platform::path_key(&dir.path)) {
+            problems.push(format!("library folder '{}' is listed twice", dir.path));
+        }
+        if let Err(err) = Exclusions::new(&dir.exclude_globs) {
+            problems.push(err.to_string());
+        }
+    }
+    if let Err(err) = manifest::Ignores::new(&s.ignore_globs) {
+        problems.push(err.to_string());
+    }
+    if s.game_mods_dir
+        .as_deref()
+        .is_some_and(|d| d.trim().is_empty())
+    {
+        problems.push("game mods folder must not be empty".to_string());
+    }
+    if let Err(err) = logging::validate_level(&s.log_level) {
+        problems.push(err.to_string());
+    }
+    if !(0.0..=1.0).contains(&s.infer_min_confidence) {
+        problems.push("inference confidence threshold must be between 0 and 1".to_string());
+    }
+    let w = &s.infer_weights;
+    if [w.costume_min_score, w.fuzzy_weight, w.costume_name_weight]
+        .iter()
+        .any(|v| !(0.0..=1.0).contains(v))
+    {
+        problems.push("inference weights must be between 0 and 1".to_string());
+    }
+    if s.preview_animated.seconds == 0 || s.preview_animated.seconds > 30 {
+        problems.push("animated preview length must be 1-30 seconds".to_string());
+    }
+    if s.preview_animated.fps == 0 || s.preview_animated.fps > 60 {
+        problems.push("animated preview fps must be 1-60".to_string());
+    }
+    let video = &s.preview_video;
+    if video.seconds == 0 || video.seconds > 60 {
+        problems.push("preview video length must be 1-60 seconds".to_string());
+    }
+    if video.fps == 0 || video.fps > 60 {
+        problems.push("preview video fps must be 1-60".to_string());
+    }
+    if video.max_size.is_some_and(|px| px < 64) {
+        problems.push("preview video size cap must be at least 64 pixels".to_string());
+    }
+    if s.preview_storage == PreviewStorage::ModFolder && s.library_dirs.iter().any(|d| d.read_only)
+    {
+        problems.push(
+            "previews must be kept in the app cache while a library folder is read-only"
+                .to_string(),
+        );
+    }
+    if s.scan_threads > manifest::MAX_THREADS {
+        problems.push(format!(
+            "scan threads must be at most {}",
+            manifest::MAX_THREADS
+        ));
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::invalid(format!(
+            "Invalid settings: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+/// Loads the stored settings (defaults when none were saved yet), migrating older shapes.
+/// A blob that can't be read is reported instead of being replaced by defaults.
+pub fn load(conn: &Connection) -> SResult<AppSettings> {
+    let Some(json) = read(conn, KEY)? else {
+        return Ok(AppSettings::default());
+    };
+    let mut value: Value =
+        serde_json::from_str(&json).map_err(|e| format!("Stored settings are corrupt: {}", e))?;
+    migrate(&mut value)?;
+    serde_json::from_value(value)
+        .map_err(|e| format!("Stored settings are invalid: {}", e))
+        .map_err(AppError::from)
+}
+
+/// Validates and stores the settings, keeping the previous blob as a backup.
+pub fn save(conn: &Connection, settings: &AppSettings) -> SResult<()> {
+    validate(settings)?;
+    let mut settings = settings.clone();
+    settings.version = VERSION;
+    let json = serde_json::to_string(&settings)?;
+    if let Some(previous) = read(conn, KEY)? {
+        if previous != json {
+            write(conn, BACKUP_KEY, &previous)?;
+        }
+    }
+    write(conn, KEY, &json)
+}
+
+/// Stores the detected game version without rewriting the rest of the settings, so a
+/// background check cannot undo a save made since it loaded them. Skips the backup `save`
+/// keeps, which is for the user's own changes.
+pub fn set_game_version(conn: &Connection, version: &str) -> SResult<()> {
+    let Some(json) = read(conn, KEY)? else {
+        let settings = AppSettings {
+            game_version: Some(version.to_string()),
+            ..Default::default()
+        };
+        return save(conn, &settings);
+    };
+    let mut value: Value =
+        serde_json::from_str(&json).map_err(|e| format!("Stored settings are corrupt: {}", e))?;
+    let Some(fields) = value.as_object_mut() else {
+        return Err("Stored settings are not an object".to_string().into());
+    };
+    fields.insert("game_version".to_string(), Value::from(version));
+    write(conn, KEY, &value.to_string())
+}
+
+/// Puts the settings saved before the last change back in place and returns them.
+pub fn restore_backup(conn: &Connection) -> SResult<AppSettings> {
+    let json = read(conn, BACKUP_KEY)?.ok_or_else(|| "No settings backup available".to_string())?;
+    let mut value: Value =
+        serde_json::from_str(&json).map_err(|e| format!("Settings backup is corrupt: {}", e))?;
+    migrate(&mut value)?;
+    let settings: AppSettings =
+        serde_json::from_value(value).map_err(|e| format!("Settings backup is invalid: {}", e))?;
+    if let Err(err) = validate(&settings) {
+        warn!(
+            "[settings] restoring a backup that fails validation: {}",
+            err
+        );
+    }
+    let current = read(conn, KEY)?;
+    write(conn, KEY, &json)?;
+    if let Some(current) = current {
+        write(conn, BACKUP_KEY, &current)?;
+    }
+    info!("[settings] restored the previous settings");
+    Ok(settings)
+}
+
+fn find_dir(settings: &AppSettings, path: &str) -> SResult<usize> {
+    let key = platform::path_key(path);
+    settings
+        .library_dirs
+        .iter()
+        .position(|d| platform::path_key(&d.path) == key)
+        .ok_or_else(|| format!("'{}' is not a library folder", path))
+        .map_err(AppError::from)
+}
+
+/// Adds a library folder (an existing entry for the same path is replaced).
+pub fn library_add(conn: &Connection, mut dir: LibraryDir) -> SResult<Vec<LibraryDir>> {
+    let mut settings = load(conn)?;
+    dir.path = crate::commands::normalize_path_string(&dir.path);
+    match find_dir(&settings, &dir.path) {
+        Ok(i) => settings.library_dirs[i] = dir,
+        Err(_) => settings.library_dirs.push(dir),
+    }
+    save(conn, &settings)?;
+    Ok(settings.library_dirs)
+}
+
+/// Drops a library folder from the settings; its mods stay registered.
+pub fn library_remove(conn: &Connection, path: &str) -> SResult<Vec<LibraryDir>> {
+    let mut settings = load(conn)?;
+    let i = find_dir(&settings, path)?;
+    settings.library_dirs.remove(i);
+    save(conn, &settings)?;
+    Ok(settings.library_dirs)
+}
+
+/// Replaces the entry for `path`; `dir.path` may point it at a new location.
+pub fn library_update(
+    conn: &Connection,
+    path: &str,
+    mut dir: LibraryDir,
+) -> SResult<Vec<LibraryDir>> {
+    let mut settings = load(conn)?;
+    let i = find_dir(&settings, path)?;
+    dir.path = crate::commands::normalize_path_string(&dir.path);
+    // pointing it at another entry's folder would leave two entries for one path
+    if find_dir(&settings, &dir.path).is_ok_and(|j| j != i) {
+        return Err(AppError::invalid(format!(
+            "'{}' is already a library folder",
+            dir.path
+        )));
+    }
+    settings.library_dirs[i] = dir;
+    save(conn, &settings)?;
+    Ok(settings.library_dirs)
+}
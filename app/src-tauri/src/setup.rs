@@ -0,0 +1,232 @@
+use crate::catalog;
+use crate::commands;
+use crate::errors::AppError;
+use crate::platform;
+use crate::roots;
+use crate::types::{AppSettings, LibraryDir};
+use directories::UserDirs;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+const DEFAULT_LIBRARY_NAME: &str = "BrownDust2 Mods";
+
+// mod folders below a game root, most specific first
+const GAME_MOD_SUBDIRS: &[&str] = &["BepInEx/plugins/BrownDustX/mods", "Mods", "mods"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupStatus {
+    pub has_library_dirs: bool,
+    pub has_game_dir: bool,
+    pub has_catalog: bool,
+    pub java_available: bool,
+    pub preview_tool_available: bool,
+    /// Keys of the unmet requirements: "library_dirs" | "game_dir" | "catalog" | "java"
+    /// | "preview_tool". Java and the preview tool are only needed for previews.
+    pub missing: Vec<String>,
+    /// True when everything but the optional preview requirements is in place.
+    pub ready: bool,
+    pub detected_game_dir: Option<String>,
+    pub default_library_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SetupPlan {
+    /// Creates this folder (or the default library folder when empty) and adds it to the
+    /// library dirs.
+    pub create_library: bool,
+    pub library_dir: Option<String>,
+    /// Sets the game mods dir to the detected one (when none is configured).
+    pub detect_game: bool,
+    /// Imports the bundled catalog.
+    pub seed_catalog: bool,
+}
+
+fn default_library_dir() -> Option<PathBuf> {
+    let dirs = UserDirs::new()?;
+    let base = dirs.document_dir().unwrap_or(dirs.home_dir());
+    Some(base.join(DEFAULT_LIBRARY_NAME))
+}
+
+fn detect_game_mods_dir() -> Option<PathBuf> {
+    platform::game_roots()
+        .into_iter()
+        .filter(|root| root.is_dir())
+        .flat_map(|root| GAME_MOD_SUBDIRS.iter().map(move |sub| root.join(sub)))
+        .find(|dir| dir.is_dir())
+}
+
+/// Whether previews can be generated: Java runs and the generator jar is found.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewToolCheck {
+    pub java_path: String,
+    /// First line of `java -version`, e.g. `openjdk version "21.0.2" 2024-01-16`.
+    pub java_version: Option<String>,
+    pub java_error: Option<String>,
+    pub tool_path: Option<String>,
+    pub tool_error: Option<String>,
+    pub ready: bool,
+}
+
+impl PreviewToolCheck {
+    /// The jar to run, or the first problem as a user-facing error.
+    pub fn jar(&self) -> SResult<PathBuf> {
+        let problem = match (&self.java_error, &self.tool_error) {
+            (Some(err), _) => Some(("java_path", err)),
+            (None, Some(err)) => Some(("preview_tool_path", err)),
+            (None, None) => None,
+        };
+        if let Some((setting, err)) = problem {
+            return Err(AppError::NotConfigured {
+                message: err.clone(),
+                setting: Some(setting.to_string()),
+            });
+        }
+        self.tool_path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or_else(|| AppError::not_configured("preview_tool_path"))
+    }
+}
+
+// `java -version` prints to stderr
+fn java_version(java: &Path) -> SResult<String> {
+    let output = Command::new(java)
+        .arg("-version")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| {
+            format!(
+                "Java could not be started ('{}': {}); install Java or set java_path in settings",
+                java.display(),
+                e
+            )
+        })?;
+    let text = String::from_utf8_lossy(&output.stderr).to_string()
+        + &String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!("'{}' -version failed: {}", java.display(), text.trim()).into());
+    }
+    Ok(text.lines().next().unwrap_or_default().trim().to_string())
+}
+
+pub fn preview_tool_check(settings: &AppSettings) -> PreviewToolCheck {
+    let java = platform::java_command(settings.java_path.as_deref());
+    let (java_version, java_error) = match java_version(&java) {
+        Ok(v) => (Some(v), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+    let (tool_path, tool_error) =
+        match commands::locate_preview_tool(settings.preview_tool_path.as_deref()) {
+            Ok(p) => (Some(p.to_string_lossy().to_string()), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+    PreviewToolCheck {
+        java_path: java.to_string_lossy().to_string(),
+        ready: java_error.is_none() && tool_error.is_none(),
+        java_version,
+        java_error,
+        tool_path,
+        tool_error,
+    }
+}
+
+pub fn status(conn: &Connection) -> SResult<SetupStatus> {
+    let settings = commands::load_settings(conn)?;
+    let has_library_dirs = settings
+        .library_dirs
+        .iter()
+        .any(|dir| dir.enabled && Path::new(&dir.path).is_dir());
+    let has_game_dir = settings
+        .game_mods_dir
+        .as_deref()
+        .map(|dir| Path::new(dir).is_dir())
+        .unwrap_or(false);
+    let characters: i64 = conn.query_row("SELECT COUNT(*) FROM characters", [], |r| r.get(0))?;
+    let has_catalog = characters > 0;
+    let preview = preview_tool_check(&settings);
+    let java_available = preview.java_error.is_none();
+    let preview_tool_available = preview.tool_error.is_none();
+
+    let missing: Vec<String> = [
+        ("library_dirs", has_library_dirs),
+        ("game_dir", has_game_dir),
+        ("catalog", has_catalog),
+        ("java", java_available),
+        ("preview_tool", preview_tool_available),
+    ]
+    .iter()
+    .filter(|(_, ok)| !ok)
+    .map(|(key, _)| key.to_string())
+    .collect();
+
+    Ok(SetupStatus {
+        has_library_dirs,
+        has_game_dir,
+        has_catalog,
+        java_available,
+        preview_tool_available,
+        missing,
+        ready: has_library_dirs && has_game_dir && has_catalog,
+        detected_game_dir: if has_game_dir {
+            None
+        } else {
+            detect_game_mods_dir().map(|d| commands::normalize_path_string(&d.to_string_lossy()))
+        },
+        default_library_dir: default_library_dir().map(|d| d.to_string_lossy().to_string()),
+    })
+}
+
+/// Runs the requested onboarding steps and returns the updated status.
+pub fn apply(conn: &mut Connection, plan: &SetupPlan) -> SResult<SetupStatus> {
+    let mut settings = commands::load_settings(conn)?;
+
+    if plan.create_library {
+        let dir = match plan.library_dir.as_deref().map(str::trim) {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => default_library_dir()
+                .ok_or_else(|| "Could not determine a default library folder".to_string())?,
+        };
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        let dir = commands::normalize_path_string(&dir.to_string_lossy());
+        let key = platform::path_key(&dir);
+        if !settings
+            .library_dirs
+            .iter()
+            .any(|d| platform::path_key(&d.path) == key)
+        {
+            settings.library_dirs.push(LibraryDir::new(dir.clone()));
+        }
+        roots::register(conn, &dir)?;
+        info!("[setup] library folder '{}'", dir);
+    }
+
+    if plan.detect_game && settings.game_mods_dir.is_none() {
+        match detect_game_mods_dir() {
+            Some(dir) => {
+                let dir = commands::normalize_path_string(&dir.to_string_lossy());
+                info!("[setup] detected game mods folder '{}'", dir);
+                settings.game_mods_dir = Some(dir);
+            }
+            None => info!("[setup] game mods folder not found"),
+        }
+    }
+    commands::save_settings(conn, &settings)?;
+
+    if plan.seed_catalog {
+        let report = catalog::sync_builtin(conn)?;
+        info!(
+            "[setup] catalog seeded characters={} costumes={}",
+            report.characters, report.costumes
+        );
+    }
+
+    status(conn)
+}
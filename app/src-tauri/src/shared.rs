@@ -0,0 +1,273 @@
+use crate::commands::load_settings;
+use crate::errors::AppError;
+use crate::library::{self, LibraryExport, LibraryImportReport, MergeStrategy};
+use crate::platform;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Metadata file kept in the root of every shared library folder.
+pub const SHARED_FILE: &str = "bd2mods-library.json";
+// `exported_at` of the shared file last merged or written, by library folder
+const SEEN_KEY: &str = "shared_library_seen";
+// held by the machine reading and rewriting the shared file
+const LOCK_FILE: &str = "bd2mods-library.json.lock";
+// a lock this old was left by a machine that stopped mid-sync
+const LOCK_STALE: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedFolderStatus {
+    pub path: String,
+    pub read_only: bool,
+    pub file_exists: bool,
+    /// Who wrote the file and when.
+    pub machine: Option<String>,
+    pub exported_at: Option<String>,
+    /// The file changed since this machine last synced.
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedFolderSync {
+    pub path: String,
+    /// Set when the file had changes from another machine.
+    pub pulled: Option<LibraryImportReport>,
+    /// Mods written to the file; `None` for read-only folders.
+    pub pushed: Option<usize>,
+    pub error: Option<String>,
+}
+
+/// This machine's name, written into the shared files.
+pub fn machine_name() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.trim().is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn read_seen(conn: &Connection) -> SResult<BTreeMap<String, String>> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT value_json FROM settings WHERE key = ?1",
+            [SEEN_KEY],
+            |r| r.get(0),
+        )
+        .optional()?;
+    Ok(json
+        .and_then(|j| serde_json::from_str(&j).ok())
+        .unwrap_or_default())
+}
+
+fn write_seen(conn: &Connection, seen: &BTreeMap<String, String>) -> SResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO settings(key, value_json) VALUES (?1, ?2)
+        ON CONFLICT(key) DO UPDATE SET value_json = excluded.value_json
+        "#,
+        params![SEEN_KEY, serde_json::to_string(seen)?],
+    )?;
+    Ok(())
+}
+
+fn read_file(dir: &Path) -> SResult<Option<LibraryExport>> {
+    let path = dir.join(SHARED_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|e| format!("'{}' is invalid: {}", path.display(), e))
+        .map_err(AppError::from)
+}
+
+// written next to the target and renamed over it, so the other machine never reads half
+// a file
+fn write_file(dir: &Path, export: &LibraryExport) -> SResult<()> {
+    let path = dir.join(SHARED_FILE);
+    let tmp = dir.join(format!("{}.{}.tmp", SHARED_FILE, machine_name()));
+    let json = serde_json::to_string_pretty(export)?;
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write '{}': {}", tmp.display(), e))?;
+    platform::retry_transient(|| fs::rename(&tmp, &path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            format!("Failed to replace '{}': {}", path.display(), e)
+        })
+        .map_err(AppError::from)
+}
+
+// removed again when dropped
+struct SyncLock(PathBuf);
+
+impl SyncLock {
+    fn acquire(dir: &Path) -> SResult<SyncLock> {
+        let path = dir.join(LOCK_FILE);
+        for _ in 0..2 {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    let _ = file.write_all(machine_name().as_bytes());
+                    return Ok(SyncLock(path));
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|t| t.elapsed().ok());
+                    if age.is_some_and(|age| age > LOCK_STALE) {
+                        warn!("[shared] taking over stale lock '{}'", path.display());
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    let holder = fs::read_to_string(&path).unwrap_or_default();
+                    let holder = if holder.trim().is_empty() {
+                        "another machine"
+                    } else {
+                        holder.trim()
+                    };
+                    return Err(format!("'{}' is being synced by {}", dir.display(), holder).into());
+                }
+                Err(err) => {
+                    return Err(format!("Failed to create '{}': {}", path.display(), err).into())
+                }
+            }
+        }
+        Err(format!("Failed to lock '{}'", path.display()).into())
+    }
+}
+
+impl Drop for SyncLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// The library folder holding `folder`, when it is read-only.
+pub fn read_only_root(conn: &Connection, folder: &Path) -> SResult<Option<String>> {
+    let key = platform::path_key(&folder.to_string_lossy());
+    Ok(load_settings(conn)?
+        .library_dirs
+        .into_iter()
+        .filter(|d| d.read_only)
+        .map(|d| d.path)
+        .find(|root| {
+            let root = platform::path_key(root);
+            key == root || key.starts_with(&format!("{}/", root))
+        }))
+}
+
+/// Refuses changes to the files of a read-only library folder.
+pub fn ensure_writable(conn: &Connection, folder: &Path) -> SResult<()> {
+    match read_only_root(conn, folder)? {
+        Some(root) => Err(format!(
+            "'{}' is in the read-only library folder '{}'",
+            folder.display(),
+            root
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+pub fn status(conn: &Connection) -> SResult<Vec<SharedFolderStatus>> {
+    let seen = read_seen(conn)?;
+    let mut out = Vec::new();
+    for dir in load_settings(conn)?
+        .library_dirs
+        .into_iter()
+        .filter(|d| d.shared)
+    {
+        let file = read_file(Path::new(&dir.path)).unwrap_or_else(|err| {
+            warn!("[shared] {}", err);
+            None
+        });
+        let exported_at = file.as_ref().map(|f| f.exported_at.clone());
+        out.push(SharedFolderStatus {
+            changed: exported_at.is_some()
+                && exported_at.as_ref() != seen.get(&platform::path_key(&dir.path)),
+            file_exists: file.is_some(),
+            machine: file.and_then(|f| f.machine),
+            exported_at,
+            read_only: dir.read_only,
+            path: dir.path,
+        });
+    }
+    Ok(out)
+}
+
+/// Syncs every enabled shared library folder: merges the file when another machine
+/// changed it since the last sync (the side that changed a mod last wins), then writes this
+/// machine's metadata back unless the folder is read-only. Writable folders are locked
+/// from the read to the write, so two machines syncing at once do not drop each other's
+/// changes. Failures are reported per folder.
+pub fn sync(conn: &mut Connection) -> SResult<Vec<SharedFolderSync>> {
+    let dirs: Vec<_> = load_settings(conn)?
+        .library_dirs
+        .into_iter()
+        .filter(|d| d.shared && d.enabled)
+        .collect();
+    let mut seen = read_seen(conn)?;
+    let mut out = Vec::new();
+    for dir in dirs {
+        let key = platform::path_key(&dir.path);
+        let mut result = SharedFolderSync {
+            path: dir.path.clone(),
+            pulled: None,
+            pushed: None,
+            error: None,
+        };
+        let synced = (|| -> SResult<()> {
+            let root = Path::new(&dir.path);
+            if !root.is_dir() {
+                return Err(
+                    format!("Shared library folder '{}' is not reachable", dir.path).into(),
+                );
+            }
+            let _lock = if dir.read_only {
+                None
+            } else {
+                Some(SyncLock::acquire(root)?)
+            };
+            if let Some(file) = read_file(root)? {
+                if seen.get(&key) != Some(&file.exported_at) {
+                    result.pulled =
+                        Some(library::import_export(conn, &file, MergeStrategy::Newest)?);
+                    seen.insert(key.clone(), file.exported_at);
+                }
+            }
+            if !dir.read_only {
+                let mut export = library::build_export(conn, Some(&dir.path))?;
+                export.machine = Some(machine_name());
+                write_file(root, &export)?;
+                result.pushed = Some(export.mods.len());
+                seen.insert(key.clone(), export.exported_at);
+            }
+            Ok(())
+        })();
+        if let Err(err) = synced {
+            warn!("[shared] sync of '{}' failed: {}", dir.path, err);
+            result.error = Some(err.to_string());
+        }
+        out.push(result);
+    }
+    write_seen(conn, &seen)?;
+    if !out.is_empty() {
+        info!(
+            "[shared] synced {} folder(s), {} pulled",
+            out.len(),
+            out.iter().filter(|r| r.pulled.is_some()).count()
+        );
+    }
+    Ok(out)
+}
@@ -0,0 +1,162 @@
+use crate::errors::AppError;
+use crate::instance;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Argument the Explorer menu entry starts the app with, followed by the selected path.
+/// It opens the window (see `cli::is_headless_flag`); a running instance receives it
+/// through `instance::on_second_instance`.
+pub const IMPORT_FLAG: &str = "--open-import";
+
+const MENU_LABEL: &str = "Import into BD2 Mods Handler";
+const VERB: &str = "BD2ModsImport";
+// per-user keys, so no elevation is needed; folders and the archives `archive` extracts
+const MENU_KEYS: &[&str] = &[
+    r"HKCU\Software\Classes\Directory\shell\BD2ModsImport",
+    r"HKCU\Software\Classes\SystemFileAssociations\.zip\shell\BD2ModsImport",
+];
+
+/// A path picked in Explorer, waiting for the frontend to run it through the import flow.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRequest {
+    pub id: u64,
+    pub path: String,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShellIntegrationStatus {
+    pub supported: bool,
+    pub installed: bool,
+}
+
+// kept until the frontend takes them, so a path that arrives with the first launch
+// (before anything listens) is not lost
+fn pending() -> &'static Mutex<Vec<ImportRequest>> {
+    static PENDING: OnceLock<Mutex<Vec<ImportRequest>>> = OnceLock::new();
+    PENDING.get_or_init(Default::default)
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The paths following `IMPORT_FLAG` in `args`.
+pub fn import_paths(args: &[String]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        if arg == IMPORT_FLAG {
+            if let Some(path) = it.next().filter(|p| !p.trim().is_empty()) {
+                out.push(path.clone());
+            }
+        }
+    }
+    out
+}
+
+/// Queues picked paths and tells the frontend ("shell-import").
+pub fn handle(app: &AppHandle, paths: Vec<String>) {
+    if paths.is_empty() {
+        return;
+    }
+    let requests: Vec<ImportRequest> = paths
+        .into_iter()
+        .map(|path| ImportRequest {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            is_dir: Path::new(&path).is_dir(),
+            path,
+        })
+        .collect();
+    info!("[shell] {} path(s) sent from Explorer", requests.len());
+    pending()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .extend(requests.iter().cloned());
+    instance::focus_main(app);
+    if let Err(err) = app.emit("shell-import", requests) {
+        warn!("[shell] failed to emit import request: {}", err);
+    }
+}
+
+/// Picks up the path the app was started with from the menu.
+pub fn init(app: &AppHandle) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    handle(app, import_paths(&args));
+}
+
+/// Removes and returns every pending request.
+pub fn take_all() -> Vec<ImportRequest> {
+    std::mem::take(&mut *pending().lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+fn reg(args: &[&str]) -> SResult<bool> {
+    let output = Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run reg.exe: {}", e))?;
+    Ok(output.status.success())
+}
+
+fn ensure_supported() -> SResult<()> {
+    if cfg!(windows) {
+        Ok(())
+    } else {
+        Err("The Explorer menu entry is only available on Windows"
+            .to_string()
+            .into())
+    }
+}
+
+pub fn status() -> ShellIntegrationStatus {
+    let installed = cfg!(windows)
+        && MENU_KEYS
+            .iter()
+            .all(|key| reg(&["query", key]).unwrap_or(false));
+    ShellIntegrationStatus {
+        supported: cfg!(windows),
+        installed,
+    }
+}
+
+/// Adds the "Import into BD2 Mods Handler" entry for folders and .zip files, starting
+/// `exe` with `IMPORT_FLAG`.
+pub fn install(exe: &Path) -> SResult<()> {
+    ensure_supported()?;
+    let exe = exe.to_string_lossy();
+    let command = format!("\"{}\" {} \"%1\"", exe, IMPORT_FLAG);
+    for key in MENU_KEYS {
+        let command_key = format!(r"{}\command", key);
+        let steps: [&[&str]; 3] = [
+            &["add", key, "/ve", "/d", MENU_LABEL, "/f"],
+            &["add", key, "/v", "Icon", "/d", &exe, "/f"],
+            &["add", &command_key, "/ve", "/d", &command, "/f"],
+        ];
+        for step in steps {
+            if !reg(step)? {
+                // leave nothing half-registered
+                let _ = uninstall();
+                return Err(format!("Failed to write the registry key '{}'", key).into());
+            }
+        }
+    }
+    info!("[shell] registered the {} menu entry for {}", VERB, exe);
+    Ok(())
+}
+
+pub fn uninstall() -> SResult<()> {
+    ensure_supported()?;
+    for key in MENU_KEYS {
+        // a missing key is fine
+        if reg(&["query", key])? && !reg(&["delete", key, "/f"])? {
+            return Err(format!("Failed to remove the registry key '{}'", key).into());
+        }
+    }
+    info!("[shell] removed the {} menu entry", VERB);
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use crate::commands::load_settings;
+use crate::errors::AppError;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+const DEFAULT_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageCount {
+    pub id: i64,
+    /// `None` when the mod or character is gone.
+    pub name: Option<String>,
+    pub count: u64,
+    pub last_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewTiming {
+    pub kind: String,
+    pub generated: u64,
+    pub total_ms: u64,
+    pub average_ms: u64,
+}
+
+/// What `usage_stats` shows. Everything stays in the local database.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageStats {
+    pub enabled: bool,
+    /// When the oldest counter still kept started.
+    pub since: Option<String>,
+    pub installs: u64,
+    pub top_mods: Vec<UsageCount>,
+    pub top_characters: Vec<UsageCount>,
+    pub previews: Vec<PreviewTiming>,
+}
+
+fn bump(conn: &Connection, kind: &str, key: &str, ms: u64, now: &str) -> SResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO usage_stats (kind, key, count, total_ms, first_at, last_at)
+        VALUES (?1, ?2, 1, ?3, ?4, ?4)
+        ON CONFLICT(kind, key) DO UPDATE SET
+          count = count + 1, total_ms = total_ms + excluded.total_ms, last_at = excluded.last_at
+        "#,
+        params![kind, key, ms as i64, now],
+    )?;
+    Ok(())
+}
+
+fn enabled(conn: &Connection) -> bool {
+    load_settings(conn).is_ok_and(|s| s.usage_stats)
+}
+
+/// Counts an install of the mod and of its character. A no-op unless `usage_stats` is on;
+/// failures are only logged, statistics never fail an install.
+pub fn record_install(conn: &Connection, mod_id: i64, now: &str) {
+    if !enabled(conn) {
+        return;
+    }
+    let recorded = (|| -> SResult<()> {
+        bump(conn, "install", &mod_id.to_string(), 0, now)?;
+        let character_id: Option<i64> = conn.query_row(
+            "SELECT character_id FROM mods WHERE id = ?1",
+            [mod_id],
+            |r| r.get(0),
+        )?;
+        if let Some(character_id) = character_id {
+            bump(conn, "character", &character_id.to_string(), 0, now)?;
+        }
+        Ok(())
+    })();
+    if let Err(err) = recorded {
+        warn!("[stats] failed to count install of id={}: {}", mod_id, err);
+    }
+}
+
+/// Adds a successful preview generation of `kind` that took `ms`.
+pub fn record_preview(conn: &Connection, kind: &str, ms: u64, now: &str) {
+    if !enabled(conn) {
+        return;
+    }
+    if let Err(err) = bump(conn, "preview", kind, ms, now) {
+        warn!("[stats] failed to record {} preview time: {}", kind, err);
+    }
+}
+
+fn top(conn: &Connection, sql: &str, limit: usize) -> SResult<Vec<UsageCount>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([limit as i64], |r| {
+        Ok(UsageCount {
+            id: r.get(0)?,
+            name: r.get(1)?,
+            count: r.get::<_, i64>(2)? as u64,
+            last_at: r.get(3)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// The counters, with the `limit` (10 by default) most installed mods and characters.
+pub fn usage(conn: &Connection, limit: Option<usize>) -> SResult<UsageStats> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let (installs, since): (i64, Option<String>) = conn.query_row(
+        r#"
+            SELECT COALESCE(SUM(CASE WHEN kind = 'install' THEN count END), 0), MIN(first_at)
+            FROM usage_stats
+            "#,
+        [],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )?;
+    let top_mods = top(
+        conn,
+        r#"
+        SELECT CAST(s.key AS INTEGER), m.display_name, s.count, s.last_at
+        FROM usage_stats s LEFT JOIN mods m ON m.id = CAST(s.key AS INTEGER)
+        WHERE s.kind = 'install'
+        ORDER BY s.count DESC, s.last_at DESC LIMIT ?1
+        "#,
+        limit,
+    )?;
+    let top_characters = top(
+        conn,
+        r#"
+        SELECT CAST(s.key AS INTEGER), c.display_name, s.count, s.last_at
+        FROM usage_stats s LEFT JOIN characters c ON c.id = CAST(s.key AS INTEGER)
+        WHERE s.kind = 'character'
+        ORDER BY s.count DESC, s.last_at DESC LIMIT ?1
+        "#,
+        limit,
+    )?;
+    let previews = {
+        let mut stmt = conn.prepare(
+            "SELECT key, count, total_ms FROM usage_stats WHERE kind = 'preview' ORDER BY key",
+        )?;
+        let rows = stmt.query_map([], |r| {
+            let generated = r.get::<_, i64>(1)? as u64;
+            let total_ms = r.get::<_, i64>(2)? as u64;
+            Ok(PreviewTiming {
+                kind: r.get(0)?,
+                generated,
+                total_ms,
+                average_ms: total_ms / generated.max(1),
+            })
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+    Ok(UsageStats {
+        enabled: enabled(conn),
+        since,
+        installs: installs as u64,
+        top_mods,
+        top_characters,
+        previews,
+    })
+}
+
+/// Deletes every counter; returns how many were kept.
+pub fn purge(conn: &Connection) -> SResult<usize> {
+    let n = conn.execute("DELETE FROM usage_stats", [])?;
+    info!("[stats] purged {} counters", n);
+    Ok(n)
+}
@@ -0,0 +1,169 @@
+use crate::assets;
+use crate::errors::AppError;
+use crate::types::ModType;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Where mods go below the game mods folder when no rule matches: their own folder name.
+pub const DEFAULT_PATTERN: &str = "{folder}";
+
+/// A rule mapping a character (optionally narrowed to a costume and/or mod type) to the
+/// game subfolder its mods must be installed into. Patterns may use `{character}`,
+/// `{costume}` (slugs), `{asset}` (the costume's game asset id, see `assets`), `{type}`
+/// and `{folder}` (the mod's folder name).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallTarget {
+    pub character_id: i64,
+    #[serde(default)]
+    pub costume_id: Option<i64>,
+    #[serde(default)]
+    pub mod_type: Option<ModType>,
+    pub path_pattern: String,
+}
+
+pub fn list(conn: &Connection) -> SResult<Vec<InstallTarget>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT character_id, costume_id, mod_type, path_pattern FROM install_targets
+            ORDER BY character_id, costume_id, mod_type
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(InstallTarget {
+            character_id: r.get(0)?,
+            costume_id: r.get(1)?,
+            mod_type: r
+                .get::<_, Option<String>>(2)?
+                .map(|t| ModType::from_str(&t)),
+            path_pattern: r.get(3)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Replaces the rule for the (character, costume, mod type) scope; an empty pattern
+/// removes it.
+pub fn set(conn: &Connection, target: &InstallTarget) -> SResult<()> {
+    let mod_type = target.mod_type.map(|t| t.to_string());
+    conn.execute(
+        r#"
+        DELETE FROM install_targets
+        WHERE character_id = ?1 AND costume_id IS ?2 AND mod_type IS ?3
+        "#,
+        params![target.character_id, target.costume_id, mod_type],
+    )?;
+    let pattern = target.path_pattern.trim();
+    if pattern.is_empty() {
+        return Ok(());
+    }
+    check_pattern(pattern)?;
+    conn.execute(
+        r#"
+        INSERT INTO install_targets (character_id, costume_id, mod_type, path_pattern)
+        VALUES (?1, ?2, ?3, ?4)
+        "#,
+        params![target.character_id, target.costume_id, mod_type, pattern],
+    )?;
+    Ok(())
+}
+
+// the expanded path must stay below the game mods folder
+fn check_pattern(path: &str) -> SResult<()> {
+    let escapes = Path::new(path)
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)));
+    if escapes || path.is_empty() {
+        return Err(format!(
+            "Install path '{}' must be relative to the game mods folder",
+            path
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// The folder (relative to the game mods folder) a mod is installed into: the most
+/// specific matching rule (costume over character, a typed rule over an untyped one),
+/// or the mod's own folder name.
+pub fn resolve(conn: &Connection, mod_id: i64) -> SResult<PathBuf> {
+    let (folder_path, character_id, costume_id, mod_type, character_slug, costume_slug): (
+        String,
+        Option<i64>,
+        Option<i64>,
+        String,
+        Option<String>,
+        Option<String>,
+    ) = conn
+        .query_row(
+            r#"
+            SELECT m.folder_path, m.character_id, m.costume_id, m.mod_type, ch.slug, co.slug
+            FROM mods m
+            LEFT JOIN characters ch ON ch.id = m.character_id
+            LEFT JOIN costumes co ON co.id = m.costume_id
+            WHERE m.id = ?1
+            "#,
+            [mod_id],
+            |r| {
+                Ok((
+                    r.get(0)?,
+                    r.get(1)?,
+                    r.get(2)?,
+                    r.get(3)?,
+                    r.get(4)?,
+                    r.get(5)?,
+                ))
+            },
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found("Mod", mod_id))?;
+
+    let pattern: Option<String> = match character_id {
+        None => None,
+        Some(character_id) => conn
+            .query_row(
+                r#"
+                SELECT path_pattern FROM install_targets
+                WHERE character_id = ?1
+                  AND (costume_id IS NULL OR costume_id = ?2)
+                  AND (mod_type IS NULL OR mod_type = ?3)
+                ORDER BY costume_id IS NULL, mod_type IS NULL
+                LIMIT 1
+                "#,
+                params![character_id, costume_id, mod_type],
+                |r| r.get(0),
+            )
+            .optional()?,
+    };
+    let pattern = pattern.unwrap_or_else(|| DEFAULT_PATTERN.to_string());
+
+    let folder = Path::new(&folder_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Mod folder '{}' has no name", folder_path))?;
+    if pattern.contains("{costume}") && costume_slug.is_none() {
+        return Err("This mod's install path needs a costume; assign one first"
+            .to_string()
+            .into());
+    }
+    let asset = match costume_id.filter(|_| pattern.contains("{asset}")) {
+        Some(costume_id) => assets::primary_asset_id(conn, costume_id)?,
+        None => None,
+    };
+    if pattern.contains("{asset}") && asset.is_none() {
+        return Err(
+            "This mod's install path needs the costume's game asset id; import an asset mapping first"
+                .to_string().into(),
+        );
+    }
+    let path = pattern
+        .replace("{character}", character_slug.as_deref().unwrap_or_default())
+        .replace("{costume}", costume_slug.as_deref().unwrap_or_default())
+        .replace("{asset}", asset.as_deref().unwrap_or_default())
+        .replace("{type}", &mod_type)
+        .replace("{folder}", &folder);
+    check_pattern(&path)?;
+    Ok(PathBuf::from(path))
+}
@@ -0,0 +1,118 @@
+use crate::db;
+use crate::errors::AppError;
+use crate::gallery;
+use image::imageops::FilterType;
+use image::ImageFormat;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{debug, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// Edge lengths the grid asks for; thumbnails fit inside a square of this size.
+pub const SIZES: [u32; 2] = [256, 512];
+
+fn thumbs_dir() -> SResult<PathBuf> {
+    db::data_dir()
+        .map(|d| d.join("thumbs"))
+        .map_err(|e| AppError::from(e.to_string()))
+}
+
+/// The image a thumbnail is made from: the generated preview, else the first screenshot.
+fn source_image(folder: &Path, preview_dir: &Path) -> Option<PathBuf> {
+    let preview = preview_dir.join("preview.png");
+    if preview.is_file() {
+        return Some(preview);
+    }
+    gallery::list(folder).into_iter().next()
+}
+
+// changes whenever the source is replaced or touched, which invalidates older thumbnails
+fn source_key(source: &Path, size: u32) -> SResult<String> {
+    let meta = fs::metadata(source)?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(source.to_string_lossy().as_bytes());
+    hasher.update(&meta.len().to_le_bytes());
+    hasher.update(&mtime.to_le_bytes());
+    hasher.update(&size.to_le_bytes());
+    Ok(hasher.finalize().to_hex()[..16].to_string())
+}
+
+/// Returns `<app data>/thumbs/<id>-<size>-<key>.webp` for the mod, creating it on first
+/// request and whenever the source image changed (stale ones are removed). `None` when the
+/// mod has no preview or screenshot yet. `blurred` thumbnails (`<id>-<size>b-...`) are the
+/// stand-ins shown for gated NSFW mods.
+pub fn ensure(
+    mod_id: i64,
+    folder: &Path,
+    preview_dir: &Path,
+    size: u32,
+    blurred: bool,
+) -> SResult<Option<PathBuf>> {
+    if !SIZES.contains(&size) {
+        return Err(format!(
+            "Unsupported thumbnail size {} (expected one of {:?})",
+            size, SIZES
+        )
+        .into());
+    }
+    let Some(source) = source_image(folder, preview_dir) else {
+        return Ok(None);
+    };
+    let dir = thumbs_dir()?;
+    let prefix = format!("{}-{}{}-", mod_id, size, if blurred { "b" } else { "" });
+    let path = dir.join(format!("{}{}.webp", prefix, source_key(&source, size)?));
+    if path.is_file() {
+        return Ok(Some(path));
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                if let Err(err) = fs::remove_file(entry.path()) {
+                    warn!(
+                        "[thumbs] failed to remove stale '{}': {}",
+                        entry.path().display(),
+                        err
+                    );
+                }
+            }
+        }
+    }
+
+    let img = image::open(&source)
+        .map_err(|e| format!("Failed to read image '{}': {}", source.display(), e))?;
+    let thumb = if img.width() > size || img.height() > size {
+        img.resize(size, size, FilterType::Lanczos3)
+    } else {
+        img
+    };
+    // strong enough that nothing but the colours survives
+    let thumb = if blurred {
+        thumb.blur(size as f32 / 16.0)
+    } else {
+        thumb
+    };
+    // write then rename so a concurrent request never sees a half-written file
+    let tmp = path.with_extension("tmp");
+    thumb
+        .save_with_format(&tmp, ImageFormat::WebP)
+        .map_err(|e| format!("Failed to write thumbnail '{}': {}", tmp.display(), e))?;
+    fs::rename(&tmp, &path)?;
+    debug!(
+        "[thumbs] mod id={} size={} blurred={} source='{}'",
+        mod_id,
+        size,
+        blurred,
+        source.display()
+    );
+    Ok(Some(path))
+}
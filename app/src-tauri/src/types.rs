@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Kind of content a mod replaces. The string forms are stored in `mods.mod_type` and must
+/// stay in sync with the CHECK constraint in `db::migrate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ModType {
     Idle,
@@ -14,8 +18,26 @@ pub enum ModType {
     Other,
 }
 
-impl ToString for ModType {
-    fn to_string(&self) -> String {
+impl fmt::Display for ModType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ModType {
+    pub const ALL: [ModType; 9] = [
+        ModType::Idle,
+        ModType::Cutscene,
+        ModType::Date,
+        ModType::History,
+        ModType::Minigame,
+        ModType::Swap,
+        ModType::Battle,
+        ModType::Ui,
+        ModType::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
         match self {
             ModType::Idle => "idle",
             ModType::Cutscene => "cutscene",
@@ -27,24 +49,37 @@ impl ToString for ModType {
             ModType::Ui => "ui",
             ModType::Other => "other",
         }
-        .to_string()
     }
-}
 
-impl ModType {
-    pub fn from_str(s: &str) -> Self {
-        match s {
-            "idle" => ModType::Idle,
-            "cutscene" => ModType::Cutscene,
-            "date" => ModType::Date,
-            "history" => ModType::History,
-            "minigame" => ModType::Minigame,
-            "swap" => ModType::Swap,
-            "battle" => ModType::Battle,
-            "ui" => ModType::Ui,
-            _ => ModType::Other,
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModType::Idle => "Idle",
+            ModType::Cutscene => "Cutscene",
+            ModType::Date => "Date",
+            ModType::History => "History",
+            ModType::Minigame => "Minigame",
+            ModType::Swap => "Character swap",
+            ModType::Battle => "Battle",
+            ModType::Ui => "UI",
+            ModType::Other => "Other",
         }
     }
+
+    /// Lenient parse: unknown strings map to `Other`.
+    pub fn from_str(s: &str) -> Self {
+        ModType::ALL
+            .iter()
+            .copied()
+            .find(|t| t.as_str() == s)
+            .unwrap_or(ModType::Other)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModTypeInfo {
+    pub value: ModType,
+    pub label: String,
+    pub aliases: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +91,11 @@ pub struct NewMod {
     pub character_id: Option<i64>,
     pub costume_id: Option<i64>,
     pub mod_type: ModType,
+    #[serde(default)]
+    pub swap_source_character_id: Option<i64>,
+    /// Inferred from the display name when not given.
+    #[serde(default)]
+    pub nsfw: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,46 +108,560 @@ pub struct ModRow {
     pub character_id: Option<i64>,
     pub costume_id: Option<i64>,
     pub mod_type: ModType,
+    pub swap_source_character_id: Option<i64>,
     pub installed: bool,
     pub installed_at: Option<String>,
     pub target_path: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    pub validation_status: Option<String>,
+    /// Folder size and file count from the last import/rescan; `None` until measured.
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
+    #[serde(default)]
+    pub file_count: Option<u64>,
+    #[serde(default)]
+    pub nsfw: bool,
+    /// Game version the mod was last confirmed working with, see `mods_mark_verified`.
+    #[serde(default)]
+    pub verified_game_version: Option<String>,
+    #[serde(default)]
+    pub verified_at: Option<String>,
+    /// Executables or scripts found in the folder, see `validate::risk_flags`. Installing
+    /// needs `mods_confirm_risk` first.
+    #[serde(default)]
+    pub risk_flags: Vec<String>,
+    #[serde(default)]
+    pub risk_confirmed_at: Option<String>,
+    /// The user's 1-5 stars, see `mods_set_rating`.
+    #[serde(default)]
+    pub rating: Option<u8>,
+    /// Installed together by `mods_install_favorites`, see `mods_set_favorite`.
+    #[serde(default)]
+    pub favorite: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A `ModRow` with the names the list shows and the cached preview flags, so the list
+/// needs no follow-up call per row.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModListItem {
+    #[serde(flatten)]
+    pub row: ModRow,
+    pub character_name: Option<String>,
+    pub costume_name: Option<String>,
+    pub swap_source_character_name: Option<String>,
+    /// From the `has_image`/`has_video` columns, refreshed by the preview subsystem.
+    pub has_image: bool,
+    pub has_video: bool,
+}
+
+/// Partial edit for `mods_bulk_update`; `None` leaves the field unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModPatch {
+    pub author: Option<String>,
+    pub download_url: Option<String>,
+    pub character_id: Option<i64>,
+    pub costume_id: Option<i64>,
+    pub swap_source_character_id: Option<i64>,
+    pub mod_type: Option<ModType>,
+    pub nsfw: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModSort {
+    #[default]
+    Name,
+    SizeDesc,
+    SizeAsc,
+}
+
+/// What `mods_grouped` groups the list by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModGroupBy {
+    #[default]
+    Character,
+    Costume,
+    Author,
+    ModType,
+}
+
+/// One collapsible section of the grouped list.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModSection {
+    /// Character or costume id, author name or mod type; `None` for mods without one.
+    pub key: Option<String>,
+    pub label: Option<String>,
+    pub count: usize,
+    pub installed: usize,
+    /// Still preview of the group's first mod that has one and may show it.
+    pub preview_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModFilter {
     pub character_id: Option<i64>,
     pub costume_id: Option<i64>,
     pub author: Option<String>,
     pub q: Option<String>, // free text
+    /// Matches swap mods replacing this character.
+    #[serde(default)]
+    pub swap_source_character_id: Option<i64>,
+    #[serde(default)]
+    pub author_id: Option<i64>,
+    /// Only mods larger than this many MB.
+    #[serde(default)]
+    pub min_size_mb: Option<u64>,
+    #[serde(default)]
+    pub sort: ModSort,
+    /// Only mods not verified with the current game version (all of them while the
+    /// version is unknown).
+    #[serde(default)]
+    pub untested: bool,
+    /// `true`: only mods with a preview image or video; `false`: only those without.
+    #[serde(default)]
+    pub has_preview: Option<bool>,
+    /// Only mods with no character assigned.
+    #[serde(default)]
+    pub missing_character: bool,
+    #[serde(default)]
+    pub validation_status: Option<crate::validate::ValidationStatus>,
+    #[serde(default)]
+    pub installed: Option<bool>,
+    #[serde(default)]
+    pub favorite: Option<bool>,
+}
+
+/// Which mods `mods_purge_all` removes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PurgeScope {
+    #[default]
+    All,
+    Author {
+        author_id: i64,
+    },
+    /// The mods `mods_list` shows for this filter.
+    Filter {
+        filter: ModFilter,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
-    pub library_dirs: Vec<String>,
+    /// Shape version, see `settings::VERSION`; 0 for blobs saved before versioning.
+    #[serde(default)]
+    pub version: u32,
+    pub library_dirs: Vec<LibraryDir>,
     pub game_mods_dir: Option<String>,
     pub install_strategy: Option<String>, // "copy" | "symlink" (later)
     pub last_library_pick: Option<String>,
+    #[serde(default)]
+    pub sanitize: SanitizeRules,
+    #[serde(default = "default_true")]
+    pub background_verify: bool,
+    #[serde(default = "default_verify_files_per_minute")]
+    pub verify_files_per_minute: u32,
+    /// Skip the recycle bin when deleting mod folders (for large cleanups).
+    #[serde(default)]
+    pub permanent_delete: bool,
+    /// Background library rescan interval in minutes (0 = off).
+    #[serde(default)]
+    pub auto_rescan_minutes: u32,
+    /// Background download update check interval in hours (0 = off).
+    #[serde(default)]
+    pub auto_update_check_hours: u32,
+    /// "error" | "warn" | "info" | "debug" | "trace"
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    #[serde(default)]
+    pub preview_animated: AnimatedPreviewSettings,
+    #[serde(default)]
+    pub preview_video: VideoPreviewSettings,
+    /// Where generated previews live; change it through `previews_migrate`.
+    #[serde(default)]
+    pub preview_storage: PreviewStorage,
+    /// Inferred characters scoring below this (0..1) are left unassigned on import.
+    #[serde(default = "default_infer_min_confidence")]
+    pub infer_min_confidence: f32,
+    /// Score weights of character/costume inference; try them with `inference_test`.
+    #[serde(default)]
+    pub infer_weights: InferWeights,
+    /// Java used for previews; `$JAVA_HOME` or `java` on PATH when unset.
+    #[serde(default)]
+    pub java_path: Option<String>,
+    /// The preview generator jar (or a folder holding it); the bundled one when unset.
+    #[serde(default)]
+    pub preview_tool_path: Option<String>,
+    /// ffmpeg used for poster frames of preview videos; the bundled one (`tools/`), else
+    /// `ffmpeg` on PATH, when unset.
+    #[serde(default)]
+    pub ffmpeg_path: Option<String>,
+    /// Game executable `game_launch` runs; the detected one, else Steam, when unset.
+    #[serde(default)]
+    pub game_exe: Option<String>,
+    /// Installs and archive extractions that would leave less than this many MB free on
+    /// the target volume are refused.
+    #[serde(default = "default_min_free_space_mb")]
+    pub min_free_space_mb: u64,
+    /// Fingerprint of the game executable and its data folder when last seen, see
+    /// `gameupdate`.
+    #[serde(default)]
+    pub game_version: Option<String>,
+    /// Gate NSFW mods: left out of listings, or listed with blurred previews when
+    /// `blur_previews` is also set. For streaming the app.
+    #[serde(default)]
+    pub hide_nsfw: bool,
+    #[serde(default)]
+    pub blur_previews: bool,
+    /// Folder naming `library_organize` moves mods into, below their library root; see
+    /// `organize::DEFAULT_TEMPLATE` for the placeholders.
+    #[serde(default = "default_organize_template")]
+    pub organize_template: String,
+    /// Threads hashing and measuring mod folders in parallel; 0 uses one per CPU.
+    #[serde(default)]
+    pub scan_threads: u32,
+    /// Look for a newer app release on launch; emits `app-update` when there is one.
+    #[serde(default = "default_true")]
+    pub check_app_update: bool,
+    /// Keep local usage counts for `usage_stats`; they never leave this machine.
+    #[serde(default)]
+    pub usage_stats: bool,
+    /// Files (globs over the path in the mod folder or the file name) that scans,
+    /// manifests and installs leave out, see `manifest::Ignores`.
+    #[serde(default = "default_ignore_globs")]
+    pub ignore_globs: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_verify_files_per_minute() -> u32 {
+    4
+}
+
+fn default_infer_min_confidence() -> f32 {
+    crate::infer::DEFAULT_MIN_CONFIDENCE
+}
+
+fn default_min_free_space_mb() -> u64 {
+    1000
+}
+
+fn default_organize_template() -> String {
+    crate::organize::DEFAULT_TEMPLATE.to_string()
+}
+
+fn default_ignore_globs() -> Vec<String> {
+    ["*.psd", "*.zip", "Thumbs.db", "desktop.ini", ".DS_Store"]
+        .iter()
+        .map(|g| g.to_string())
+        .collect()
+}
+
+fn default_log_level() -> String {
+    crate::logging::DEFAULT_LEVEL.to_string()
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            version: crate::settings::VERSION,
             library_dirs: vec![],
             game_mods_dir: None,
             install_strategy: Some("copy".into()),
             last_library_pick: None,
+            sanitize: SanitizeRules::default(),
+            background_verify: true,
+            verify_files_per_minute: default_verify_files_per_minute(),
+            permanent_delete: false,
+            auto_rescan_minutes: 0,
+            auto_update_check_hours: 0,
+            log_level: default_log_level(),
+            preview_animated: AnimatedPreviewSettings::default(),
+            preview_video: VideoPreviewSettings::default(),
+            preview_storage: PreviewStorage::default(),
+            infer_min_confidence: default_infer_min_confidence(),
+            infer_weights: InferWeights::default(),
+            java_path: None,
+            preview_tool_path: None,
+            ffmpeg_path: None,
+            game_exe: None,
+            min_free_space_mb: default_min_free_space_mb(),
+            game_version: None,
+            hide_nsfw: false,
+            blur_previews: false,
+            organize_template: default_organize_template(),
+            scan_threads: 0,
+            check_app_update: true,
+            usage_stats: false,
+            ignore_globs: default_ignore_globs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewStorage {
+    /// `preview.*` next to the mod files (legacy behaviour).
+    #[default]
+    ModFolder,
+    /// The app data dir, see `previews::dir_for`.
+    Cache,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimatedFormat {
+    #[default]
+    Webp,
+    Gif,
+}
+
+impl AnimatedFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            AnimatedFormat::Webp => "preview.webp",
+            AnimatedFormat::Gif => "preview.gif",
+        }
+    }
+}
+
+/// Output of animated previews, which autoplay in the grid unlike mp4 videos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimatedPreviewSettings {
+    #[serde(default)]
+    pub format: AnimatedFormat,
+    #[serde(default = "default_animated_seconds")]
+    pub seconds: u32,
+    #[serde(default = "default_animated_fps")]
+    pub fps: u32,
+}
+
+fn default_animated_seconds() -> u32 {
+    3
+}
+
+fn default_animated_fps() -> u32 {
+    15
+}
+
+impl Default for AnimatedPreviewSettings {
+    fn default() -> Self {
+        Self {
+            format: AnimatedFormat::default(),
+            seconds: default_animated_seconds(),
+            fps: default_animated_fps(),
+        }
+    }
+}
+
+/// How the generator loops the animation in `preview.mp4`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoLoop {
+    /// The generator decides from the animation.
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl VideoLoop {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VideoLoop::Auto => "auto",
+            VideoLoop::On => "on",
+            VideoLoop::Off => "off",
+        }
+    }
+}
+
+/// Output of `preview.mp4`; `previews_generate_videos` can override each value per run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoPreviewSettings {
+    #[serde(default = "default_video_seconds")]
+    pub seconds: u32,
+    #[serde(default = "default_video_fps")]
+    pub fps: u32,
+    #[serde(default)]
+    pub loop_mode: VideoLoop,
+    /// Longest side of the video in pixels; the generator's own size when unset.
+    #[serde(default)]
+    pub max_size: Option<u32>,
+}
+
+fn default_video_seconds() -> u32 {
+    5
+}
+
+fn default_video_fps() -> u32 {
+    30
+}
+
+impl Default for VideoPreviewSettings {
+    fn default() -> Self {
+        Self {
+            seconds: default_video_seconds(),
+            fps: default_video_fps(),
+            loop_mode: VideoLoop::default(),
+            max_size: None,
+        }
+    }
+}
+
+/// Per-run changes to `VideoPreviewSettings`, e.g. longer clips for cutscene mods.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct VideoPreviewOverrides {
+    pub seconds: Option<u32>,
+    pub fps: Option<u32>,
+    pub loop_mode: Option<VideoLoop>,
+    pub max_size: Option<u32>,
+}
+
+impl VideoPreviewSettings {
+    pub fn with(self, o: VideoPreviewOverrides) -> Self {
+        Self {
+            seconds: o.seconds.unwrap_or(self.seconds),
+            fps: o.fps.unwrap_or(self.fps),
+            loop_mode: o.loop_mode.unwrap_or(self.loop_mode),
+            max_size: o.max_size.or(self.max_size),
+        }
+    }
+}
+
+/// Tuning of `infer::Catalog` scoring, all 0..1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InferWeights {
+    /// Lowest score at which a costume is picked for the chosen character.
+    pub costume_min_score: f32,
+    /// Factor on typo-tolerant matches, so they rank below names found word for word.
+    pub fuzzy_weight: f32,
+    /// Score a character gets when only one of its costumes is named in full.
+    pub costume_name_weight: f32,
+}
+
+impl Default for InferWeights {
+    fn default() -> Self {
+        Self {
+            costume_min_score: crate::infer::DEFAULT_COSTUME_MIN_SCORE,
+            fuzzy_weight: crate::infer::DEFAULT_FUZZY_WEIGHT,
+            costume_name_weight: crate::infer::DEFAULT_COSTUME_NAME_WEIGHT,
+        }
+    }
+}
+
+/// A library folder and how rescans treat it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryDir {
+    pub path: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Disabled folders are kept in the list but not scanned or imported into.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Globs relative to `path`, '/'-separated (e.g. `**/backup/**`); matching folders
+    /// are skipped by rescans.
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    #[serde(default)]
+    pub layout: LibraryLayout,
+    /// Used from several machines (a NAS share): metadata syncs through
+    /// `shared::SHARED_FILE` in the folder, while installs stay in each machine's database.
+    #[serde(default)]
+    pub shared: bool,
+    /// Nothing in the folder is renamed, moved or deleted from this machine, and sync
+    /// only reads the shared metadata.
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl LibraryDir {
+    pub fn new(path: String) -> Self {
+        LibraryDir {
+            path,
+            label: None,
+            enabled: true,
+            exclude_globs: Vec::new(),
+            layout: LibraryLayout::default(),
+            shared: false,
+            read_only: false,
+        }
+    }
+}
+
+/// How mod folders are arranged below a library dir.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryLayout {
+    /// `root/Author/Mod`
+    #[default]
+    AuthorMod,
+    /// `root/Mod`
+    Flat,
+    /// `root/Author/Character/Mod`
+    AuthorCharacterMod,
+    /// Any depth; a folder containing Spine assets is a mod.
+    Auto,
+}
+
+/// Folder/file name clean-up applied on import, move and rename.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SanitizeRules {
+    pub apply_on_import: bool,
+    pub replace_illegal: bool, // <>:"/\|?* and control characters
+    pub replacement: String,
+    pub trim_trailing_dots_spaces: bool,
+    pub collapse_whitespace: bool,
+    pub ascii_only: bool,
+    pub max_length: Option<usize>,
+}
+
+impl Default for SanitizeRules {
+    fn default() -> Self {
+        Self {
+            apply_on_import: false,
+            replace_illegal: true,
+            replacement: "_".into(),
+            trim_trailing_dots_spaces: true,
+            collapse_whitespace: true,
+            ascii_only: false,
+            max_length: Some(120),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanSummary {
+    pub run_id: i64,
     pub scanned_dirs: usize,
     pub discovered_mods: usize,
-    pub upserts: usize,
+    pub upserts: usize, // added + changed
+    #[serde(default)]
+    pub added: usize,
+    #[serde(default)]
+    pub changed: usize,
+    #[serde(default)]
+    pub unchanged: usize,
+    /// Registered mods whose folder no longer exists.
+    #[serde(default)]
+    pub missing: usize,
     pub errors: usize,
+    /// The paths behind `errors`, also kept in `scan_errors` for `scan_errors_list`.
+    #[serde(default)]
+    pub failed: Vec<ScanError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanError {
+    pub path: String,
+    pub kind: String,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,7 +673,26 @@ pub struct DraftMod {
     pub mod_type: ModType,
     pub character_id: Option<i64>,
     pub costume_id: Option<i64>,
+    #[serde(default)]
+    pub swap_source_character_id: Option<i64>,
     pub infer_confidence: f32,
+    /// Up to three best character/costume matches, for the UI to offer as choices.
+    #[serde(default)]
+    pub infer_candidates: Vec<crate::infer::InferCandidate>,
+    /// "ok" | "incomplete" | "no_assets", see `validate::validate`.
+    #[serde(default)]
+    pub validation_status: Option<String>,
+    #[serde(default)]
+    pub validation_problems: Vec<String>,
+    /// `validation_problems` for translation; not sent back.
+    #[serde(default, skip_deserializing)]
+    pub validation_messages: Vec<crate::i18n::Message>,
+    /// The folder name carries an NSFW keyword, see `infer::looks_nsfw`.
+    #[serde(default)]
+    pub nsfw: bool,
+    /// Executables or scripts in the folder, see `validate::risk_flags`.
+    #[serde(default)]
+    pub risk_flags: Vec<String>,
 }
 
 // Database helpers for catalog data
@@ -130,14 +703,17 @@ pub fn upsert_character(
     tx: &Transaction<'_>,
     slug: &str,
     display_name: &str,
+    image_url: Option<&str>,
 ) -> Result<i64, Error> {
     tx.execute(
         r#"
-        INSERT INTO characters (slug, display_name)
-        VALUES (?1, ?2)
-        ON CONFLICT(slug) DO UPDATE SET display_name = excluded.display_name
+        INSERT INTO characters (slug, display_name, image_url)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(slug) DO UPDATE SET
+          display_name = excluded.display_name,
+          image_url = COALESCE(excluded.image_url, characters.image_url)
         "#,
-        params![slug, display_name],
+        params![slug, display_name, image_url],
     )?;
     tx.query_row(
         "SELECT id FROM characters WHERE slug = ?1",
@@ -152,14 +728,17 @@ pub fn upsert_costume(
     character_id: i64,
     slug: &str,
     display_name: &str,
+    image_url: Option<&str>,
 ) -> Result<i64, Error> {
     tx.execute(
         r#"
-        INSERT INTO costumes (character_id, slug, display_name)
-        VALUES (?1, ?2, ?3)
-        ON CONFLICT(character_id, slug) DO UPDATE SET display_name = excluded.display_name
+        INSERT INTO costumes (character_id, slug, display_name, image_url)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(character_id, slug) DO UPDATE SET
+          display_name = excluded.display_name,
+          image_url = COALESCE(excluded.image_url, costumes.image_url)
         "#,
-        params![character_id, slug, display_name],
+        params![character_id, slug, display_name, image_url],
     )?;
     tx.query_row(
         "SELECT id FROM costumes WHERE character_id = ?1 AND slug = ?2",
@@ -168,6 +747,19 @@ pub fn upsert_costume(
     )
 }
 
+/// Adds a reference image URL to a costume; known URLs keep their cached file.
+pub fn upsert_costume_reference(
+    tx: &Transaction<'_>,
+    costume_id: i64,
+    url: &str,
+) -> Result<(), Error> {
+    tx.execute(
+        "INSERT OR IGNORE INTO costume_references (costume_id, url) VALUES (?1, ?2)",
+        params![costume_id, url],
+    )?;
+    Ok(())
+}
+
 /// Inserts an alias for a character or costume entity. `entity_type` should be "character" or "costume".
 pub fn upsert_alias(
     tx: &Transaction<'_>,
@@ -188,12 +780,60 @@ pub fn upsert_alias(
     Ok(())
 }
 
+/// Stores the name of a character or costume in language `lang`, replacing the previous one.
+pub fn upsert_localized_name(
+    tx: &Transaction<'_>,
+    entity_type: &str,
+    entity_id: i64,
+    lang: &str,
+    name: &str,
+) -> Result<(), Error> {
+    let (lang, name) = (lang.trim().to_lowercase(), name.trim());
+    if lang.is_empty() || name.is_empty() {
+        return Ok(());
+    }
+    let sql = match entity_type {
+        "character" => {
+            r#"
+            INSERT INTO character_names (character_id, lang, name) VALUES (?1, ?2, ?3)
+            ON CONFLICT(character_id, lang) DO UPDATE SET name = excluded.name
+            "#
+        }
+        _ => {
+            r#"
+            INSERT INTO costume_names (costume_id, lang, name) VALUES (?1, ?2, ?3)
+            ON CONFLICT(costume_id, lang) DO UPDATE SET name = excluded.name
+            "#
+        }
+    };
+    tx.execute(sql, params![entity_id, lang, name])?;
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CatalogCostume {
     pub slug: String,
     pub display_name: String,
     #[serde(default)]
     pub aliases: Vec<String>,
+    /// Names from the language-specific wiki pages, by language code ("ko", "ja").
+    #[serde(default)]
+    pub names: BTreeMap<String, String>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+    /// Vanilla in-game art of the costume, shown next to mod previews.
+    #[serde(default)]
+    pub reference_images: Vec<String>,
+    #[serde(default)]
+    pub install_targets: Vec<CatalogInstallTarget>,
+}
+
+/// Game subfolder for mods of a character/costume, see `targets::InstallTarget`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogInstallTarget {
+    #[serde(default)]
+    pub mod_type: Option<ModType>,
+    pub path: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -202,12 +842,99 @@ pub struct CatalogCharacter {
     pub display_name: String,
     #[serde(default)]
     pub aliases: Vec<String>,
+    /// Names from the language-specific wiki pages, by language code ("ko", "ja").
+    #[serde(default)]
+    pub names: BTreeMap<String, String>,
     #[serde(default)]
     pub costumes: Vec<CatalogCostume>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub install_targets: Vec<CatalogInstallTarget>,
+}
+
+/// A costume's vanilla art for the compare view, see `catalog::costume_preview`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostumePreview {
+    pub costume_id: i64,
+    pub character_id: i64,
+    pub display_name: String,
+    pub character_name: String,
+    /// The cached catalog icon.
+    pub thumbnail_path: Option<String>,
+    pub references: Vec<CostumeReference>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostumeReference {
+    pub url: String,
+    /// `None` until `catalog_fetch_images` downloaded it.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageFetchReport {
+    pub downloaded: usize,
+    pub skipped: usize,
+    pub errors: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CatalogReport {
     pub characters: usize,
     pub costumes: usize,
+    #[serde(default)]
+    pub removed_characters: usize,
+    #[serde(default)]
+    pub removed_costumes: usize,
+}
+
+/// A single catalog entry touched by a diff. `character_slug` is always set; `costume_slug`
+/// is only set for costume entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogDiffEntry {
+    pub character_slug: String,
+    pub costume_slug: Option<String>,
+    pub display_name: String,
+    pub previous_display_name: Option<String>,
+    pub linked_mods: usize,
+}
+
+/// Structured preview of what applying a catalog source would change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogDiff {
+    pub new_characters: Vec<CatalogDiffEntry>,
+    pub renamed_characters: Vec<CatalogDiffEntry>,
+    pub missing_characters: Vec<CatalogDiffEntry>,
+    pub new_costumes: Vec<CatalogDiffEntry>,
+    pub renamed_costumes: Vec<CatalogDiffEntry>,
+    pub missing_costumes: Vec<CatalogDiffEntry>,
+    pub unchanged: usize,
+    // the parsed source, so the diff can be applied without re-reading it
+    pub source: Vec<CatalogCharacter>,
+}
+
+/// Result of merging one catalog character or costume into another.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatalogMergeReport {
+    /// Mods re-pointed at the surviving entry.
+    pub mod_ids: Vec<i64>,
+    pub aliases_moved: usize,
+    /// Costumes moved to the surviving character.
+    pub costumes_moved: usize,
+    /// Costumes folded into a same-slug costume of the surviving character.
+    pub costumes_merged: usize,
+}
+
+/// A mod whose catalog references point nowhere: rows deleted while foreign keys were off
+/// (older databases, library imports) or a costume that belongs to another character.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedMod {
+    pub id: i64,
+    pub display_name: String,
+    pub character_id: Option<i64>,
+    pub costume_id: Option<i64>,
+    pub swap_source_character_id: Option<i64>,
+    /// 'missing_character' | 'missing_costume' | 'missing_swap_source' | 'costume_mismatch'
+    pub reason: String,
 }
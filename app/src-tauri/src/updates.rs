@@ -0,0 +1,218 @@
+use crate::commands;
+use crate::db::{self, DbPool};
+use crate::errors::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModUpdate {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub download_url: String,
+    pub checked_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateCheckReport {
+    pub checked: usize,
+    pub failed: usize,
+    pub new_updates: usize,
+    pub updates: Vec<ModUpdate>,
+}
+
+// what the server says about the file behind a download URL
+#[derive(Debug, Clone, PartialEq)]
+struct RemoteState {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_length: Option<i64>,
+}
+
+impl RemoteState {
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none() && self.content_length.is_none()
+    }
+
+    // compares only what both sides know; servers do not always send every header
+    fn differs_from(&self, other: &RemoteState) -> bool {
+        fn changed<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+            matches!((a, b), (Some(a), Some(b)) if a != b)
+        }
+        changed(&self.etag, &other.etag)
+            || changed(&self.last_modified, &other.last_modified)
+            || changed(&self.content_length, &other.content_length)
+    }
+}
+
+fn probe(agent: &ureq::Agent, url: &str) -> SResult<RemoteState> {
+    let resp = agent
+        .head(url)
+        .call()
+        .map_err(|e| AppError::network(url, e))?;
+    let header = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    Ok(RemoteState {
+        etag: header("etag"),
+        last_modified: header("last-modified"),
+        content_length: header("content-length").and_then(|v| v.parse().ok()),
+    })
+}
+
+// the recorded baseline and whether an update is already flagged
+fn stored(conn: &Connection, mod_id: i64) -> SResult<Option<(RemoteState, bool)>> {
+    conn.query_row(
+        r#"
+        SELECT etag, last_modified, content_length, update_available
+        FROM mod_remote_state WHERE mod_id = ?1
+        "#,
+        [mod_id],
+        |r| {
+            Ok((
+                RemoteState {
+                    etag: r.get(0)?,
+                    last_modified: r.get(1)?,
+                    content_length: r.get(2)?,
+                },
+                r.get::<_, i64>(3)? != 0,
+            ))
+        },
+    )
+    .optional()
+    .map_err(AppError::from)
+}
+
+pub fn list(conn: &Connection) -> SResult<Vec<ModUpdate>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT m.id, m.display_name, m.download_url, s.checked_at
+            FROM mod_remote_state s JOIN mods m ON m.id = s.mod_id
+            WHERE s.update_available = 1
+            ORDER BY LOWER(m.display_name)
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(ModUpdate {
+            mod_id: r.get(0)?,
+            display_name: r.get(1)?,
+            download_url: r.get(2)?,
+            checked_at: r.get(3)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Asks the server behind every mod's download URL whether the file changed (ETag,
+/// Last-Modified or size) since it was first seen. The first check only records a
+/// baseline; links `links::validate` found dead are skipped. No connection is held
+/// during the network requests.
+pub fn check(pool: &DbPool) -> SResult<UpdateCheckReport> {
+    let targets: Vec<(i64, String)> = {
+        let conn = pool.get()?;
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT m.id, m.download_url FROM mods m
+                LEFT JOIN mod_links l ON l.mod_id = m.id AND l.url = m.download_url
+                WHERE (m.download_url LIKE 'http://%' OR m.download_url LIKE 'https://%')
+                  AND l.status IS NOT 'dead'
+                "#,
+        )?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    info!("[updates] checking {} download urls", targets.len());
+
+    // a host that never answers must not hold up the others
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into();
+    let mut failed = 0usize;
+    let mut results = Vec::new();
+    for (mod_id, url) in targets {
+        match probe(&agent, &url) {
+            Ok(state) if !state.is_empty() => results.push((mod_id, state)),
+            Ok(_) => info!(
+                "[updates] no cache headers for mod id={} url='{}'",
+                mod_id, url
+            ),
+            Err(err) => {
+                info!("[updates] mod id={} {}", mod_id, err);
+                failed += 1;
+            }
+        }
+    }
+
+    let conn = pool.get()?;
+    let _guard = db::write_lock();
+    let now = commands::now_iso();
+    let mut new_updates = 0usize;
+    for (mod_id, state) in results.iter() {
+        match stored(&conn, *mod_id)? {
+            None => {
+                conn.execute(
+                    r#"
+                    INSERT INTO mod_remote_state
+                      (mod_id, etag, last_modified, content_length, checked_at, update_available)
+                    VALUES (?1, ?2, ?3, ?4, ?5, 0)
+                    "#,
+                    params![
+                        mod_id,
+                        state.etag,
+                        state.last_modified,
+                        state.content_length,
+                        now
+                    ],
+                )?;
+            }
+            Some((baseline, flagged)) => {
+                // the baseline stays until the update is dismissed
+                let changed = state.differs_from(&baseline);
+                if changed && !flagged {
+                    new_updates += 1;
+                }
+                conn.execute(
+                    r#"
+                    UPDATE mod_remote_state SET checked_at = ?2, update_available = ?3
+                    WHERE mod_id = ?1
+                    "#,
+                    params![mod_id, now, (flagged || changed) as i64],
+                )?;
+            }
+        }
+    }
+    let updates = list(&conn)?;
+    warn!(
+        "[updates] checked={} failed={} new_updates={} pending={}",
+        results.len(),
+        failed,
+        new_updates,
+        updates.len()
+    );
+    Ok(UpdateCheckReport {
+        checked: results.len(),
+        failed,
+        new_updates,
+        updates,
+    })
+}
+
+/// Accepts the current remote file as the new baseline for `ids`.
+pub fn dismiss(conn: &Connection, ids: &[i64]) -> SResult<usize> {
+    let mut cleared = 0usize;
+    for id in ids {
+        // the next check re-records the baseline
+        cleared += conn.execute("DELETE FROM mod_remote_state WHERE mod_id = ?1", [id])?;
+    }
+    Ok(cleared)
+}
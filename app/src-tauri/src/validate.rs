@@ -0,0 +1,181 @@
+use crate::i18n::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+// how deep below the mod folder asset files are looked for
+const MAX_DEPTH: usize = 4;
+
+// mods are Spine skeletons, textures and audio; anything that runs has no business there
+const RISKY_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "scr", "com", "msi", "bat", "cmd", "ps1", "vbs", "vbe", "js", "jse", "wsf",
+    "hta", "lnk", "reg", "jar", "py", "sh", "so", "dylib",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationStatus {
+    Ok,
+    /// Spine files are present but something they need is missing.
+    Incomplete,
+    /// No Spine skeleton or atlas at all.
+    NoAssets,
+}
+
+impl ValidationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationStatus::Ok => "ok",
+            ValidationStatus::Incomplete => "incomplete",
+            ValidationStatus::NoAssets => "no_assets",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Validation {
+    pub status: ValidationStatus,
+    pub problems: Vec<String>,
+    /// `problems` for translation, in the same order.
+    #[serde(default, skip_deserializing)]
+    pub messages: Vec<Message>,
+}
+
+// texture pages named in an .atlas: the non-indented lines ending in an image extension
+fn atlas_pages(atlas: &Path) -> Vec<String> {
+    let Ok(text) = fs::read_to_string(atlas) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter(|l| !l.starts_with(char::is_whitespace))
+        .map(str::trim)
+        .filter(|l| {
+            let lower = l.to_lowercase();
+            lower.ends_with(".png") || lower.ends_with(".webp") || lower.ends_with(".jpg")
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Checks that every Spine skeleton (.skel or .json) in a mod folder has its .atlas and
+/// that every atlas has a skeleton and the texture pages it lists.
+pub fn validate(folder: &Path) -> Validation {
+    // per directory and file stem: the Spine extensions present
+    let mut assets: BTreeMap<(String, String), BTreeSet<String>> = BTreeMap::new();
+    for entry in WalkDir::new(folder)
+        .max_depth(MAX_DEPTH)
+        .into_iter()
+        .flatten()
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !matches!(ext.as_str(), "skel" | "json" | "atlas") {
+            continue;
+        }
+        let dir = path
+            .parent()
+            .and_then(|p| p.strip_prefix(folder).ok())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        assets.entry((dir, stem)).or_default().insert(ext);
+    }
+
+    let has_atlas = assets.values().any(|exts| exts.contains("atlas"));
+    let has_skel = assets.values().any(|exts| exts.contains("skel"));
+    if !has_atlas && !has_skel {
+        let message = Message::new("validate.no_assets");
+        return Validation {
+            status: ValidationStatus::NoAssets,
+            problems: vec![message.to_string()],
+            messages: vec![message],
+        };
+    }
+
+    let mut problems = Vec::new();
+    for ((dir, stem), exts) in assets.iter() {
+        let name = |ext: &str| {
+            if dir.is_empty() {
+                format!("{}.{}", stem, ext)
+            } else {
+                format!("{}/{}.{}", dir, stem, ext)
+            }
+        };
+        let skeleton = exts.contains("skel") || exts.contains("json");
+        if exts.contains("atlas") {
+            if !skeleton {
+                problems.push(
+                    Message::new("validate.atlas_without_skeleton").with("file", name("atlas")),
+                );
+            }
+            let atlas_dir = folder.join(dir);
+            for page in atlas_pages(&atlas_dir.join(format!("{}.atlas", stem))) {
+                if !atlas_dir.join(&page).is_file() {
+                    problems.push(
+                        Message::new("validate.missing_page")
+                            .with("file", name("atlas"))
+                            .with("page", page),
+                    );
+                }
+            }
+        } else if exts.contains("skel") {
+            problems
+                .push(Message::new("validate.skeleton_without_atlas").with("file", name("skel")));
+        }
+        // a lone .json is usually not a skeleton (configs, metadata), so it is not reported
+    }
+
+    Validation {
+        status: if problems.is_empty() {
+            ValidationStatus::Ok
+        } else {
+            ValidationStatus::Incomplete
+        },
+        problems: problems.iter().map(Message::to_string).collect(),
+        messages: problems,
+    }
+}
+
+/// Whether a file is an executable, library or script by its extension.
+pub fn is_risky(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    RISKY_EXTENSIONS.contains(&ext.as_str())
+}
+
+/// `risk_flags` as stored on the mod row: NULL for a clean mod.
+pub fn risk_flags_json(flags: &[String]) -> Option<String> {
+    (!flags.is_empty()).then(|| serde_json::to_string(flags).unwrap_or_default())
+}
+
+/// Files in a mod folder (at any depth) that are executables, libraries or scripts, as
+/// '/'-separated paths relative to the folder. Empty for a clean mod.
+pub fn risk_flags(folder: &Path) -> Vec<String> {
+    let mut out = Vec::new();
+    for entry in WalkDir::new(folder).min_depth(1).into_iter().flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if !is_risky(path) {
+            continue;
+        }
+        let rel = path.strip_prefix(folder).unwrap_or(path);
+        out.push(rel.to_string_lossy().replace('\\', "/"));
+    }
+    out.sort();
+    out
+}
@@ -0,0 +1,267 @@
+use crate::commands;
+use crate::db::{self, DbPool};
+use crate::errors::AppError;
+use crate::install;
+use crate::manifest;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+pub type SResult<T> = Result<T, AppError>;
+
+// how long to wait before retrying when disabled or when nothing is installed
+const IDLE_SLEEP: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftEvent {
+    pub mod_id: i64,
+    pub rel_path: String,
+    pub status: String, // 'modified' | 'missing'
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftRow {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub target_path: Option<String>,
+    pub rel_path: String,
+    pub status: String,
+    pub checked_at: String,
+}
+
+/// Starts the low-priority verifier. Each tick re-hashes a single installed file (the one
+/// checked longest ago) and compares it with the mod's manifest.
+pub fn spawn_background(app: AppHandle, pool: DbPool) {
+    thread::spawn(move || loop {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(err) => {
+                info!("[verify] no connection available: {}", err);
+                thread::sleep(IDLE_SLEEP);
+                continue;
+            }
+        };
+        let settings = commands::load_settings(&conn).unwrap_or_default();
+        if !settings.background_verify || settings.verify_files_per_minute == 0 {
+            drop(conn);
+            thread::sleep(IDLE_SLEEP);
+            continue;
+        }
+        let pause = Duration::from_secs_f64(60.0 / settings.verify_files_per_minute as f64);
+        let result = tick(&conn);
+        // Hand the connection back before sleeping so commands are not starved.
+        drop(conn);
+        match result {
+            Ok(Some(event)) => {
+                info!(
+                    "[verify] drift detected mod id={} file='{}' status={}",
+                    event.mod_id, event.rel_path, event.status
+                );
+                if let Err(err) = app.emit("install-drift", event) {
+                    warn!("[verify] failed to emit drift event: {}", err);
+                }
+                thread::sleep(pause);
+            }
+            Ok(None) => thread::sleep(pause),
+            Err(err) => {
+                warn!("[verify] tick failed: {}", err);
+                thread::sleep(IDLE_SLEEP);
+            }
+        }
+    });
+}
+
+/// Verifies the least recently checked installed file. Returns an event when it drifted.
+fn tick(conn: &Connection) -> SResult<Option<DriftEvent>> {
+    let next: Option<(i64, String, String, String)> = conn
+        .query_row(
+            r#"
+            SELECT m.id, m.target_path, f.rel_path, f.hash
+            FROM mods m
+            JOIN mod_files f ON f.mod_id = m.id
+            LEFT JOIN install_checks c ON c.mod_id = f.mod_id AND c.rel_path = f.rel_path
+            WHERE m.installed = 1 AND m.target_path IS NOT NULL
+              AND (NOT EXISTS (SELECT 1 FROM install_selections s WHERE s.mod_id = m.id)
+                   OR EXISTS (SELECT 1 FROM install_selections s
+                              WHERE s.mod_id = m.id AND s.rel_path = f.rel_path))
+            ORDER BY c.checked_at IS NOT NULL, c.checked_at ASC
+            LIMIT 1
+            "#,
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .optional()?;
+    let Some((mod_id, target, rel_path, expected)) = next else {
+        return Ok(None);
+    };
+
+    let installed = Path::new(&target).join(&rel_path);
+    let status = if !installed.is_file() {
+        "missing"
+    } else if manifest::hash_file(&installed)? != expected {
+        "modified"
+    } else {
+        "ok"
+    };
+
+    let previous: Option<String> = conn
+        .query_row(
+            "SELECT status FROM install_checks WHERE mod_id = ?1 AND rel_path = ?2",
+            params![mod_id, rel_path],
+            |r| r.get(0),
+        )
+        .optional()?;
+    let _writer = db::write_lock();
+    conn.execute(
+        r#"
+        INSERT INTO install_checks (mod_id, rel_path, status, checked_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(mod_id, rel_path) DO UPDATE SET
+          status = excluded.status,
+          checked_at = excluded.checked_at
+        "#,
+        params![mod_id, rel_path, status, commands::now_iso()],
+    )?;
+
+    // only report transitions so a drifted file isn't announced on every pass
+    if status != "ok" && previous.as_deref() != Some(status) {
+        return Ok(Some(DriftEvent {
+            mod_id,
+            rel_path,
+            status: status.to_string(),
+        }));
+    }
+    Ok(None)
+}
+
+/// Installed files whose last background check found them modified or missing.
+pub fn drift_list(conn: &Connection) -> SResult<Vec<DriftRow>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT m.id, m.display_name, m.target_path, c.rel_path, c.status, c.checked_at
+            FROM install_checks c
+            JOIN mods m ON m.id = c.mod_id
+            WHERE c.status != 'ok' AND m.installed = 1
+            ORDER BY m.display_name, c.rel_path
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(DriftRow {
+            mod_id: r.get(0)?,
+            display_name: r.get(1)?,
+            target_path: r.get(2)?,
+            rel_path: r.get(3)?,
+            status: r.get(4)?,
+            checked_at: r.get(5)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameAuditStatus {
+    Intact,
+    /// Some installed files differ from the mod or are gone.
+    Modified,
+    /// None of the installed files are left (e.g. wiped by a game patch).
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GameAuditEntry {
+    pub mod_id: i64,
+    pub display_name: String,
+    pub target_path: String,
+    pub status: GameAuditStatus,
+    pub files_checked: usize,
+    pub files_modified: usize,
+    pub files_missing: usize,
+    /// Set when a repair was requested and the reinstall succeeded.
+    pub repaired: bool,
+    pub error: Option<String>,
+}
+
+/// Checks every file of every installed mod against its manifest (all at once, unlike the
+/// background verifier) and records the results in `install_checks`. Only mods whose
+/// manifest is known are audited.
+pub fn game_audit(conn: &Connection, now: &str) -> SResult<Vec<GameAuditEntry>> {
+    let mods: Vec<(i64, String, String)> = {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT id, display_name, target_path FROM mods
+                WHERE installed = 1 AND target_path IS NOT NULL
+                ORDER BY display_name
+                "#,
+        )?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut out = Vec::new();
+    for (mod_id, display_name, target_path) in mods {
+        let selected = install::selection(conn, mod_id)?;
+        let files: Vec<manifest::FileEntry> = manifest::load_installable(conn, mod_id)?
+            .into_iter()
+            .filter(|f| selected.is_empty() || selected.contains(&f.rel_path))
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+
+        let target = Path::new(&target_path);
+        let mut results = Vec::with_capacity(files.len());
+        for f in files.iter() {
+            let installed = target.join(&f.rel_path);
+            let status = if !installed.is_file() {
+                "missing"
+            } else if manifest::hash_file(&installed)? != f.hash {
+                "modified"
+            } else {
+                "ok"
+            };
+            results.push((f.rel_path.as_str(), status));
+        }
+        let files_missing = results.iter().filter(|(_, s)| *s == "missing").count();
+        let files_modified = results.iter().filter(|(_, s)| *s == "modified").count();
+
+        {
+            let _writer = db::write_lock();
+            let mut stmt = conn.prepare(
+                r#"
+                    INSERT INTO install_checks (mod_id, rel_path, status, checked_at)
+                    VALUES (?1, ?2, ?3, ?4)
+                    ON CONFLICT(mod_id, rel_path) DO UPDATE SET
+                      status = excluded.status,
+                      checked_at = excluded.checked_at
+                    "#,
+            )?;
+            for (rel_path, status) in results.iter() {
+                stmt.execute(params![mod_id, rel_path, status, now])?;
+            }
+        }
+
+        out.push(GameAuditEntry {
+            mod_id,
+            display_name,
+            target_path,
+            status: if files_missing == files.len() {
+                GameAuditStatus::Missing
+            } else if files_missing + files_modified > 0 {
+                GameAuditStatus::Modified
+            } else {
+                GameAuditStatus::Intact
+            },
+            files_checked: files.len(),
+            files_modified,
+            files_missing,
+            repaired: false,
+            error: None,
+        });
+    }
+    Ok(out)
+}
@@ -0,0 +1,172 @@
+use crate::downloads;
+use crate::errors::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tracing::info;
+
+pub type SResult<T> = Result<T, AppError>;
+
+/// A mod noted for later: a link and a name, until it is downloaded and imported.
+#[derive(Debug, Clone, Serialize)]
+pub struct WishlistItem {
+    pub id: i64,
+    pub url: String,
+    pub name: String,
+    pub author: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+    /// Set once enqueued through `enqueue`.
+    pub download_id: Option<i64>,
+    /// The download's status while it runs, see `downloads::Download`.
+    pub download_status: Option<String>,
+    /// The imported mod; set by hand or when the linked download finishes.
+    pub mod_id: Option<i64>,
+    pub acquired_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WishlistEnqueueReport {
+    pub download_ids: Vec<i64>,
+    pub errors: Vec<String>,
+}
+
+pub fn add(
+    conn: &Connection,
+    url: &str,
+    name: &str,
+    author: Option<String>,
+    note: Option<String>,
+    now: &str,
+) -> SResult<i64> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err(AppError::invalid("URL is empty"));
+    }
+    let name = match name.trim() {
+        "" => url,
+        n => n,
+    };
+    let existing: Option<i64> = conn
+        .query_row("SELECT id FROM wishlist WHERE url = ?1", [url], |r| {
+            r.get(0)
+        })
+        .optional()?;
+    if existing.is_some() {
+        return Err(format!("'{}' is already on the wishlist", url).into());
+    }
+    conn.execute(
+        "INSERT INTO wishlist (url, name, author, note, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![url, name, author, note, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Open items first (newest on top), acquired ones after.
+pub fn list(conn: &Connection) -> SResult<Vec<WishlistItem>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT w.id, w.url, w.name, w.author, w.note, w.created_at, w.download_id,
+                   d.status, w.mod_id, w.acquired_at
+            FROM wishlist w
+            LEFT JOIN downloads d ON d.id = w.download_id
+            ORDER BY w.acquired_at IS NOT NULL, w.id DESC
+            "#,
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(WishlistItem {
+            id: r.get(0)?,
+            url: r.get(1)?,
+            name: r.get(2)?,
+            author: r.get(3)?,
+            note: r.get(4)?,
+            created_at: r.get(5)?,
+            download_id: r.get(6)?,
+            download_status: r.get(7)?,
+            mod_id: r.get(8)?,
+            acquired_at: r.get(9)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+pub fn remove(conn: &Connection, id: i64) -> SResult<bool> {
+    let n = conn.execute("DELETE FROM wishlist WHERE id = ?1", [id])?;
+    Ok(n > 0)
+}
+
+/// Links the item to the library mod it became.
+pub fn mark_acquired(conn: &Connection, id: i64, mod_id: i64, now: &str) -> SResult<()> {
+    let exists: Option<i64> = conn
+        .query_row("SELECT id FROM mods WHERE id = ?1", [mod_id], |r| r.get(0))
+        .optional()?;
+    if exists.is_none() {
+        return Err(AppError::not_found("Mod", mod_id));
+    }
+    let n = conn.execute(
+        "UPDATE wishlist SET mod_id = ?2, acquired_at = ?3 WHERE id = ?1",
+        params![id, mod_id, now],
+    )?;
+    if n == 0 {
+        return Err(AppError::not_found("Wishlist item", id));
+    }
+    Ok(())
+}
+
+/// Called when a download finished importing: items waiting on it are acquired.
+pub fn download_imported(
+    conn: &Connection,
+    download_id: i64,
+    mod_id: i64,
+    now: &str,
+) -> SResult<usize> {
+    conn.execute(
+        r#"
+        UPDATE wishlist SET mod_id = ?2, acquired_at = ?3
+        WHERE download_id = ?1 AND acquired_at IS NULL
+        "#,
+        params![download_id, mod_id, now],
+    )
+    .map_err(AppError::from)
+}
+
+/// Queues downloads for `ids` (every open item when `None`). Acquired items and those
+/// whose download is still queued or running are skipped; non-http links fail on their
+/// own without stopping the rest. The caller starts the returned downloads.
+pub fn enqueue(conn: &Connection, ids: Option<&[i64]>) -> SResult<WishlistEnqueueReport> {
+    let open: Vec<(i64, String, Option<String>)> = {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT w.id, w.url, w.author FROM wishlist w
+                LEFT JOIN downloads d ON d.id = w.download_id
+                WHERE w.acquired_at IS NULL
+                  AND (d.id IS NULL OR d.status IN ('failed', 'cancelled'))
+                ORDER BY w.id
+                "#,
+        )?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut report = WishlistEnqueueReport::default();
+    for (id, url, author) in open {
+        if ids.is_some_and(|ids| !ids.contains(&id)) {
+            continue;
+        }
+        match downloads::enqueue(conn, &url, author) {
+            Ok(download_id) => {
+                conn.execute(
+                    "UPDATE wishlist SET download_id = ?2 WHERE id = ?1",
+                    params![id, download_id],
+                )?;
+                report.download_ids.push(download_id);
+            }
+            Err(err) => report.errors.push(format!("id={}: {}", id, err)),
+        }
+    }
+    info!(
+        "[wishlist] enqueued {} download(s), {} failed",
+        report.download_ids.len(),
+        report.errors.len()
+    );
+    Ok(report)
+}
@@ -44,8 +44,17 @@ type ModRow = {
   created_at: string;
   updated_at: string;
 };
+type LibraryDir = {
+  path: string;
+  label?: string | null;
+  enabled: boolean;
+  exclude_globs?: string[];
+  layout?: string;
+  shared?: boolean;
+  read_only?: boolean;
+};
 type AppSettings = {
-  library_dirs: string[];
+  library_dirs: LibraryDir[];
   game_mods_dir?: string | null;
   install_strategy?: string | null;
   last_library_pick?: string | null;
@@ -192,7 +201,7 @@ export default function App() {
     invoke<string>("app_version")
       .then(setVersion)
       .catch(() => setVersion("dev"));
-    invoke<string>("db_init").catch(console.error);
+    invoke("db_init").catch(console.error);
     loadSettings();
   }, []);
 
@@ -228,16 +237,44 @@ export default function App() {
       return;
     }
     console.log("[settings] selected library folder", picked);
-    const next = {
-      ...settings,
-      last_library_pick: picked,
-      library_dirs: Array.from(
-        new Set([...(settings.library_dirs || []), picked]),
-      ),
-    };
-    console.log("[settings] computed next settings", next);
-    await saveSettings(next);
-    console.log("[settings] library directories", next.library_dirs);
+    try {
+      const dirs = await invoke<LibraryDir[]>("library_add", {
+        dir: { path: picked },
+      });
+      console.log("[settings] library directories", dirs);
+      await saveSettings({
+        ...settings,
+        library_dirs: dirs,
+        last_library_pick: picked,
+      });
+    } catch (err) {
+      console.error("[settings] failed to add library folder", err);
+    }
+    await loadSettings();
+  }
+
+  async function removeLibDir(dir: LibraryDir) {
+    try {
+      const dirs = await invoke<LibraryDir[]>("library_remove", {
+        path: dir.path,
+      });
+      console.log("[settings] library directories", dirs);
+    } catch (err) {
+      console.error("[settings] failed to remove library folder", err);
+    }
+    await loadSettings();
+  }
+
+  async function toggleLibDir(dir: LibraryDir) {
+    try {
+      const dirs = await invoke<LibraryDir[]>("library_update", {
+        path: dir.path,
+        dir: { ...dir, enabled: !dir.enabled },
+      });
+      console.log("[settings] library directories", dirs);
+    } catch (err) {
+      console.error("[settings] failed to update library folder", err);
+    }
     await loadSettings();
   }
 
@@ -441,7 +478,7 @@ export default function App() {
       console.log("[settings] bulk scan already running");
       return;
     }
-    const libs = settings.library_dirs || [];
+    const libs = (settings.library_dirs || []).filter((d) => d.enabled);
     if (libs.length === 0) {
       alert("Add at least one mods folder first.");
       return;
@@ -453,7 +490,7 @@ export default function App() {
       for (const dir of libs) {
         try {
           const authors = await invoke<AuthorFolder[]>("library_author_dirs", {
-            libRoot: dir,
+            libRoot: dir.path,
           });
           for (const author of authors) {
             if (seenFolders.has(author.folder_path)) continue;
@@ -514,8 +551,8 @@ export default function App() {
     if (purgeBusy) return;
     setPurgeBusy(true);
     try {
-      const removed = await invoke<number>("mods_purge_all");
-      console.log(`[settings] purge removed ${removed} mods`);
+      const report = await invoke<{ deleted: number[] }>("mods_purge_all");
+      console.log(`[settings] purge removed ${report.deleted.length} mods`);
       refresh();
       setPurgeConfirmOpen(false);
     } catch (err) {
@@ -954,6 +991,8 @@ export default function App() {
         onOpenChange={setSettingsOpen}
         settings={settings}
         onAddLibraryDir={addLibDir}
+        onRemoveLibraryDir={removeLibDir}
+        onToggleLibraryDir={toggleLibDir}
         onPickGameDir={pickGameDir}
         onScanLibraryDirs={startBulkImportFromLibraries}
         onScanGameMods={() =>
@@ -9,8 +9,14 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Separator } from "@/components/ui/separator";
 
+type LibraryDirData = {
+  path: string;
+  label?: string | null;
+  enabled: boolean;
+};
+
 type SettingsData = {
-  library_dirs: string[];
+  library_dirs: LibraryDirData[];
   game_mods_dir?: string | null;
   install_strategy?: string | null;
   last_library_pick?: string | null;
@@ -33,6 +39,8 @@ type Props = {
   onOpenChange: (open: boolean) => void;
   settings: SettingsData;
   onAddLibraryDir: () => void;
+  onRemoveLibraryDir: (dir: LibraryDirData) => void;
+  onToggleLibraryDir: (dir: LibraryDirData) => void;
   onPickGameDir: () => void;
   onScanLibraryDirs: () => void;
   onScanGameMods: () => void;
@@ -55,6 +63,8 @@ export default function SettingsDialog({
   onOpenChange,
   settings,
   onAddLibraryDir,
+  onRemoveLibraryDir,
+  onToggleLibraryDir,
   onPickGameDir,
   onScanLibraryDirs,
   onScanGameMods,
@@ -95,7 +105,31 @@ export default function SettingsDialog({
               {libraries.length === 0 ? (
                 <Input readOnly value="" placeholder="No folder selected yet" />
               ) : (
-                libraries.map((dir) => <Input key={dir} readOnly value={dir} />)
+                libraries.map((dir) => (
+                  <div key={dir.path} className="flex items-center gap-2">
+                    <Input
+                      readOnly
+                      value={
+                        dir.label ? `${dir.label} (${dir.path})` : dir.path
+                      }
+                      className={dir.enabled ? "" : "opacity-50"}
+                    />
+                    <Button
+                      size="sm"
+                      variant="outline"
+                      onClick={() => onToggleLibraryDir(dir)}
+                    >
+                      {dir.enabled ? "Disable" : "Enable"}
+                    </Button>
+                    <Button
+                      size="sm"
+                      variant="outline"
+                      onClick={() => onRemoveLibraryDir(dir)}
+                    >
+                      Remove
+                    </Button>
+                  </div>
+                ))
               )}
               <div className="flex gap-2">
                 <Button size="sm" onClick={onAddLibraryDir}>
@@ -105,7 +139,10 @@ export default function SettingsDialog({
                   size="sm"
                   variant="outline"
                   onClick={onScanLibraryDirs}
-                  disabled={scanLibraryDisabled || libraries.length === 0}
+                  disabled={
+                    scanLibraryDisabled ||
+                    !libraries.some((dir) => dir.enabled)
+                  }
                 >
                   Scan
                 </Button>